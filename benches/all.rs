@@ -36,12 +36,12 @@ fn bench_insert<T: Default + Item + native_db::ToInput>(
             BenchmarkId::new(name, bench_display.display_n_by_tranaction()),
             |b| {
                 b.iter_custom(|iters| {
-                    let mut native_db = NativeDBBenchDatabase::setup();
-                    native_db.set_mode(mode);
-                    let native_db = native_db.db();
+                    let mut native_db_bench = NativeDBBenchDatabase::setup();
+                    native_db_bench.set_mode(mode);
+                    let native_db = native_db_bench.db();
                     let start = std::time::Instant::now();
                     let mut native_db = native_db.rw_transaction().unwrap();
-                    native_db.set_two_phase_commit(true);
+                    native_db_bench.apply_mode(&mut native_db);
                     let mut count = 0;
                     for _ in 0..iters {
                         let mut item = T::default();
@@ -309,7 +309,8 @@ fn bench_select_range<T: Default + Item + native_db::ToInput + Clone + Debug>(
                     let rows =
                         stmt.query_map(&[(":from_sk", &from_sk), (":to_sk", &to_sk)], |row| {
                             let binary: Vec<u8> = row.get(1)?;
-                            let item = T::native_db_bincode_decode_from_slice(&binary).unwrap();
+                            let item =
+                                T::native_db_bincode_decode_from_slice(&binary).unwrap();
                             Ok(item)
                         });
                     let _out = rows.unwrap().map(|r| r.unwrap()).collect::<Vec<T>>();
@@ -321,6 +322,85 @@ fn bench_select_range<T: Default + Item + native_db::ToInput + Clone + Debug>(
     );
 }
 
+// Compare `skip_take` (skipped rows are not decoded) to `Iterator::skip` + `take` (skipped rows are decoded).
+fn bench_skip_take<T: Default + Item + native_db::ToInput + Clone + Debug>(
+    c: &mut Criterion,
+    bench_display: BenchDisplay,
+    key_def: impl ToKeyDefinition<KeyOptions>,
+) {
+    let mut group = c.benchmark_group("Skip Take");
+    group.plot_config(
+        criterion::PlotConfiguration::default().summary_scale(criterion::AxisScale::Linear),
+    );
+    group.sampling_mode(criterion::SamplingMode::Flat);
+
+    const NUMBER_OF_ITEMS: usize = 10000;
+    const OFFSET: usize = 9000;
+    const LIMIT: usize = 10;
+
+    let key_def = key_def.key_definition();
+
+    for skip_take in [true, false] {
+        let title = if skip_take {
+            "primary skip_take"
+        } else {
+            "primary skip + take"
+        };
+        group.bench_function(
+            BenchmarkId::new(DB_NAME_NATIVE_DB, bench_display.display_read_custom(title)),
+            |b| {
+                b.iter_custom(|iters| {
+                    let native_db = NativeDBBenchDatabase::setup();
+                    native_db.insert_bulk_inc::<T>(0, NUMBER_OF_ITEMS);
+                    let native_db = native_db.db();
+                    let start = std::time::Instant::now();
+                    let native_db = native_db.r_transaction().unwrap();
+                    for _ in 0..iters {
+                        let scan = native_db.scan().primary().unwrap();
+                        let iter = scan.all().unwrap();
+                        let items: Vec<T> = if skip_take {
+                            iter.skip_take(OFFSET, LIMIT).try_collect().unwrap()
+                        } else {
+                            iter.skip(OFFSET).take(LIMIT).try_collect().unwrap()
+                        };
+                        assert_eq!(items.len(), LIMIT);
+                    }
+                    start.elapsed()
+                })
+            },
+        );
+
+        let title = if skip_take {
+            "secondary skip_take"
+        } else {
+            "secondary skip + take"
+        };
+        group.bench_function(
+            BenchmarkId::new(DB_NAME_NATIVE_DB, bench_display.display_read_custom(title)),
+            |b| {
+                b.iter_custom(|iters| {
+                    let native_db = NativeDBBenchDatabase::setup();
+                    native_db.insert_bulk_sk_random::<T>(NUMBER_OF_ITEMS);
+                    let native_db = native_db.db();
+                    let start = std::time::Instant::now();
+                    let native_db = native_db.r_transaction().unwrap();
+                    for _ in 0..iters {
+                        let scan = native_db.scan().secondary(key_def.clone()).unwrap();
+                        let iter = scan.all().unwrap();
+                        let items: Vec<T> = if skip_take {
+                            iter.skip_take(OFFSET, LIMIT).try_collect().unwrap()
+                        } else {
+                            iter.skip(OFFSET).take(LIMIT).try_collect().unwrap()
+                        };
+                        assert_eq!(items.len(), LIMIT);
+                    }
+                    start.elapsed()
+                })
+            },
+        );
+    }
+}
+
 fn bench_get<T: Default + Item + native_db::ToInput + Clone + Debug>(
     c: &mut Criterion,
     bench_display: BenchDisplay,
@@ -584,6 +664,39 @@ fn bench_delete<T: Default + Item + native_db::ToInput + Clone + Debug>(
     );
 }
 
+fn bench_update_non_indexed<T: Default + Item + native_db::ToInput + Clone + Debug>(
+    c: &mut Criterion,
+    bench_display: BenchDisplay,
+) {
+    let mut group = c.benchmark_group("Update non-indexed field".to_string());
+    group.plot_config(
+        criterion::PlotConfiguration::default().summary_scale(criterion::AxisScale::Linear),
+    );
+    group.sampling_mode(criterion::SamplingMode::Flat);
+
+    // Only the primary table is written, the secondary tables are left untouched.
+    group.bench_function(
+        BenchmarkId::new(DB_NAME_NATIVE_DB, bench_display.display_n_by_tranaction()),
+        |b| {
+            b.iter_custom(|iters| {
+                let native_db = NativeDBBenchDatabase::setup();
+                let items = native_db.insert_bulk_inc::<T>(0, iters as usize);
+
+                let native_db = native_db.db();
+                let start = std::time::Instant::now();
+                let w = native_db.rw_transaction().unwrap();
+                for item in items {
+                    let mut updated_item = item.clone();
+                    updated_item.update_value(1);
+                    w.update(item, updated_item).unwrap();
+                }
+                w.commit().unwrap();
+                start.elapsed()
+            })
+        },
+    );
+}
+
 fn run_all(c: &mut Criterion) {
     // Insert
     bench_insert::<Item1SK_NUni_NOpt>(c, BenchDisplay::SK_1);
@@ -640,6 +753,14 @@ fn run_all(c: &mut Criterion) {
         BenchSelectRangeRandomDataCfg::new(Item100SK_NUni_NOptKey::sk_1).random(),
     );
 
+    // Skip take
+    bench_skip_take::<Item1SK_NUni_NOpt>(c, BenchDisplay::SK_1, Item1SK_NUni_NOptKey::sk_1);
+    bench_skip_take::<Item100SK_NUni_NOpt>(c, BenchDisplay::SK_100, Item100SK_NUni_NOptKey::sk_1);
+
+    // Update
+    bench_update_non_indexed::<Item1SK_NUni_NOpt>(c, BenchDisplay::SK_1);
+    bench_update_non_indexed::<Item100SK_NUni_NOpt>(c, BenchDisplay::SK_100);
+
     // Delete
     bench_delete::<Item1SK_NUni_NOpt>(c, BenchDisplay::SK_1);
     bench_delete::<Item10SK_NUni_NOpt>(c, BenchDisplay::SK_10);
@@ -0,0 +1,92 @@
+//! Counts the allocations of a key-only scan, run with `cargo bench --bench key_alloc`.
+use native_db::*;
+use native_model::{native_model, Model};
+use redb::{ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[derive(Serialize, Deserialize)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Item {
+    #[primary_key]
+    id: u64,
+    #[secondary_key]
+    name: String,
+}
+
+// Primary table of `Item`: "{id}_{version}_{primary key name}"
+const ITEM_TABLE: TableDefinition<Key, &[u8]> = TableDefinition::new("1_1_id");
+
+const N: u64 = 1_000_000;
+
+fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = f();
+    (result, ALLOCATIONS.load(Ordering::Relaxed) - before)
+}
+
+fn main() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..N {
+        rw.insert(Item {
+            id,
+            name: format!("name {id}"),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let (_, to_key) = count_allocations(|| {
+        for id in 0..N {
+            std::hint::black_box(id.to_key());
+        }
+    });
+
+    let redb = db.redb_database().unwrap();
+    let r = redb.begin_read().unwrap();
+    let table = r.open_table(ITEM_TABLE).unwrap();
+    let (count, scan) = count_allocations(|| {
+        let mut count = 0;
+        for entry in table.iter().unwrap() {
+            let (key, _) = entry.unwrap();
+            std::hint::black_box(key.value());
+            count += 1;
+        }
+        count
+    });
+    assert_eq!(count, N);
+
+    println!("{N} u64 keys");
+    println!(
+        "to_key:        {to_key} allocations ({:.2} per key)",
+        to_key as f64 / N as f64
+    );
+    println!(
+        "key-only scan: {scan} allocations ({:.2} per key)",
+        scan as f64 / N as f64
+    );
+}
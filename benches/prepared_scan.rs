@@ -0,0 +1,66 @@
+//! Compares secondary range scans prepared once with `prepare_secondary` and scans opening the
+//! tables each time, run with `cargo bench --bench prepared_scan`.
+use criterion::{criterion_group, criterion_main, Criterion};
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Measure {
+    #[primary_key]
+    id: u64,
+    #[secondary_key]
+    timestamp: u64,
+}
+
+const N: u64 = 100_000;
+const QUERIES: u64 = 1_000;
+
+fn bench_range_secondary(c: &mut Criterion) {
+    let mut models = Models::new();
+    models.define::<Measure>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..N {
+        rw.insert(Measure { id, timestamp: id }).unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    // Small ranges, so that the setup of the scan weighs
+    let starts: Vec<u64> = (0..QUERIES).map(|i| i * (N / QUERIES)).collect();
+
+    let mut group = c.benchmark_group("Range secondary");
+    group.bench_function("fresh", |b| {
+        b.iter(|| {
+            for start in &starts {
+                let scan = r
+                    .scan()
+                    .secondary::<Measure>(MeasureKey::timestamp)
+                    .unwrap();
+                for value in scan.range(*start..*start + 4).unwrap() {
+                    std::hint::black_box(value.unwrap());
+                }
+            }
+        })
+    });
+    group.bench_function("prepared", |b| {
+        let scan = r
+            .prepare_secondary::<Measure>(MeasureKey::timestamp)
+            .unwrap();
+        b.iter(|| {
+            for start in &starts {
+                for value in scan.range(*start..*start + 4).unwrap() {
+                    std::hint::black_box(value.unwrap());
+                }
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_range_secondary);
+criterion_main!(benches);
@@ -8,6 +8,9 @@ use rusqlite::TransactionBehavior;
 use serde::{Deserialize, Serialize};
 use shortcut_assert_fs::TmpFs;
 
+mod mode;
+pub use mode::Mode;
+
 pub trait Item {
     fn generate_sqlite_table() -> String;
     fn generate_sqlite_insert(&self) -> String;
@@ -18,6 +21,7 @@ pub trait Item {
     fn update_pk(&mut self, pk: i64);
     fn update_sk_with_random(&mut self);
     fn update_sk_with_value(&mut self, value: i64);
+    fn update_value(&mut self, value: i64);
 }
 
 pub const REDB_TABLE: redb::TableDefinition<i64, Vec<u8>> = redb::TableDefinition::new("item");
@@ -35,6 +39,8 @@ macro_rules! define_item_struct {
                 #[secondary_key]
                 pub $secondary_key: i64,
             )*
+            // Not indexed
+            pub value: i64,
         }
 
         impl Item for $struct_name {
@@ -52,6 +58,10 @@ macro_rules! define_item_struct {
                 )*
             }
 
+            fn update_value(&mut self, value: i64) {
+                self.value = value;
+            }
+
             fn get_pk(&self) -> i64 {
                 self.pk
             }
@@ -277,12 +287,6 @@ pub trait BenchDatabase {
     );
 }
 
-pub enum Mode {
-    Default,
-    TwoPhaseCommit,
-    QuickRepair
-}
-
 pub struct NativeDBBenchDatabase {
     _tmp: TmpFs,
     db: Database<'static>,
@@ -313,9 +317,7 @@ impl BenchDatabase for NativeDBBenchDatabase {
 
     fn insert_bulk<T: native_db::ToInput + Item + Debug + Clone>(&self, items: Vec<T>) -> Vec<T> {
         let mut rw = self.db.rw_transaction().unwrap();
-        if !matches!(self.mode, Mode::TwoPhaseCommit) {
-            rw.set_two_phase_commit(true);
-        }
+        self.apply_mode(&mut rw);
         for item in &items {
             rw.insert(item.clone()).unwrap();
         }
@@ -367,12 +369,7 @@ impl BenchDatabase for NativeDBBenchDatabase {
 
     fn insert<T: native_db::ToInput>(&self, item: T) {
         let mut rw = self.db.rw_transaction().unwrap();
-        if !matches!(self.mode, Mode::TwoPhaseCommit) {
-            rw.set_two_phase_commit(true);
-        }
-        else if !matches!(self.mode, Mode::QuickRepair) {
-            rw.set_quick_repair(true);
-        }
+        self.apply_mode(&mut rw);
         rw.insert(item).unwrap();
         rw.commit().unwrap();
     }
@@ -383,6 +380,10 @@ impl NativeDBBenchDatabase {
     {
         self.mode = mode;
     }
+
+    pub fn apply_mode(&self, rw: &mut transaction::RwTransaction) {
+        self.mode.apply(rw);
+    }
 }
 
 pub struct SqliteBenchDatabase {
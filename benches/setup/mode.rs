@@ -0,0 +1,23 @@
+use native_db::transaction::RwTransaction;
+
+pub enum Mode {
+    Default,
+    TwoPhaseCommit,
+    QuickRepair,
+}
+
+impl Mode {
+    pub fn two_phase_commit(&self) -> bool {
+        matches!(self, Mode::TwoPhaseCommit)
+    }
+
+    pub fn quick_repair(&self) -> bool {
+        matches!(self, Mode::QuickRepair)
+    }
+
+    /// Sets the redb flags of the mode on a write transaction.
+    pub fn apply(&self, rw: &mut RwTransaction) {
+        rw.set_two_phase_commit(self.two_phase_commit());
+        rw.set_quick_repair(self.quick_repair());
+    }
+}
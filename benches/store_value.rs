@@ -0,0 +1,86 @@
+//! Compares `get().secondary` with and without `store_value`, run with
+//! `cargo bench --bench store_value`.
+use criterion::{criterion_group, criterion_main, Criterion};
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct User {
+    #[primary_key]
+    id: u64,
+    #[secondary_key(unique)]
+    email: String,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[native_model(id = 2, version = 1)]
+#[native_db]
+struct UserStoreValue {
+    #[primary_key]
+    id: u64,
+    #[secondary_key(unique, store_value)]
+    email: String,
+    name: String,
+}
+
+const N: u64 = 100_000;
+
+fn email(id: u64) -> String {
+    format!("user{id}@test")
+}
+
+fn bench_get_secondary(c: &mut Criterion) {
+    let mut models = Models::new();
+    models.define::<User>().unwrap();
+    models.define::<UserStoreValue>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..N {
+        rw.insert(User {
+            id,
+            email: email(id),
+            name: format!("name {id}"),
+        })
+        .unwrap();
+        rw.insert(UserStoreValue {
+            id,
+            email: email(id),
+            name: format!("name {id}"),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let emails: Vec<String> = (0..N).step_by(97).map(email).collect();
+    let r = db.r_transaction().unwrap();
+
+    let mut group = c.benchmark_group("Get secondary");
+    group.bench_function("default", |b| {
+        b.iter(|| {
+            for email in &emails {
+                let user: Option<User> = r.get().secondary(UserKey::email, email.as_str()).unwrap();
+                std::hint::black_box(user.unwrap());
+            }
+        })
+    });
+    group.bench_function("store_value", |b| {
+        b.iter(|| {
+            for email in &emails {
+                let user: Option<UserStoreValue> = r
+                    .get()
+                    .secondary(UserStoreValueKey::email, email.as_str())
+                    .unwrap();
+                std::hint::black_box(user.unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_get_secondary);
+criterion_main!(benches);
@@ -1,17 +1,23 @@
 use crate::struct_name::StructName;
 use crate::ToTokenStream;
 use quote::quote;
-use quote::ToTokens;
+use quote::{format_ident, ToTokens};
 use std::hash::Hash;
-use syn::PathArguments;
+use syn::ext::IdentExt;
 use syn::{parse_str, Ident, Type};
+use syn::{GenericArgument, PathArguments};
 
 #[derive(Clone, Debug)]
 pub(crate) struct KeyDefinition<O: ToTokenStream> {
     pub(super) struct_name: StructName,
     field_name: Option<Ident>,
+    // Path of a nested field of `field_name`, e.g. `id` for `#[secondary_key(customer.id)]`.
+    nested_path: Vec<Ident>,
     function_name: Option<Ident>,
     pub(crate) field_type: Option<String>,
+    // The type of the field is checked to implement `ToKey`, its other uses go through
+    // `ToKeyProbe` to not report it again.
+    pub(crate) to_key_checked: bool,
     pub(crate) options: O,
 }
 
@@ -32,43 +38,45 @@ impl<O: ToTokenStream> Hash for KeyDefinition<O> {
 impl<O: ToTokenStream> ToTokenStream for KeyDefinition<O> {
     fn new_to_token_stream(&self) -> proc_macro2::TokenStream {
         let options = self.options.new_to_token_stream();
-        let struct_name = self.struct_name.ident();
+        // A generic model can't be named without its type parameters: its key definitions
+        // are only built inside its own impls, through `Self`.
+        let struct_name = if self.struct_name.is_generic() {
+            quote! {Self}
+        } else {
+            self.struct_name.ident().to_token_stream()
+        };
         let key_name = self.name();
-        let rust_type_name = self
-            .field_type
-            .clone()
-            .expect("KeyDefinition must have a field type");
-
-        // DEBUG print
-        // let rust_type_name: &str = "Vec<u32>";
-        // let type_str = "u32";
-        let mut parsed_type: Type = parse_str(&rust_type_name).expect("Failed to parse type");
-
-        if let Type::Path(ref mut path, ..) = parsed_type {
-            if let Some(segment) = path.path.segments.last_mut() {
-                if let PathArguments::AngleBracketed(ref mut args) = segment.arguments {
-                    if args.colon2_token.is_none() {
-                        let new_args = args.clone();
-                        segment.arguments = PathArguments::None;
+        let Some(parsed_type_token_stream) = self.parsed_type() else {
+            // The type of a nested field isn't written, it is inferred from the field itself.
+            let field_path = self.field_path();
+            return quote! {
+                native_db::db_type::KeyDefinition::new(
+                    #struct_name::native_model_id(),
+                    #struct_name::native_model_version(),
+                    #key_name,
+                    native_db::db_type::key_names_of(|model: &#struct_name| &model.#field_path),
+                    #options
+                )
+            };
+        };
 
-                        let modified_path: syn::Path = syn::parse_quote! {
-                            #path :: #new_args
-                        };
-
-                        path.path.segments = modified_path.segments;
-                    }
+        let key_names = if self.to_key_checked {
+            quote! {
+                {
+                    use native_db::db_type::{ToKeyProbeImplemented as _, ToKeyProbeMissing as _};
+                    (&native_db::db_type::ToKeyProbe::<#parsed_type_token_stream>::new()).probe_key_names()
                 }
             }
-        }
-
-        let parsed_type_token_stream = parsed_type.to_token_stream();
+        } else {
+            quote! { <#parsed_type_token_stream>::key_names() }
+        };
 
         quote! {
             native_db::db_type::KeyDefinition::new(
                 #struct_name::native_model_id(),
                 #struct_name::native_model_version(),
                 #key_name,
-                <#parsed_type_token_stream>::key_names(),
+                #key_names,
                 #options
             )
         }
@@ -79,21 +87,86 @@ impl<O: ToTokenStream> ToTokenStream for KeyDefinition<O> {
 pub(crate) struct KeyOptions {
     pub(crate) unique: bool,
     pub(crate) optional: bool,
+    pub(crate) multi: bool,
+    pub(crate) store_value: bool,
+    // Function computing the bytes stored as key from the field, e.g. a collation key.
+    pub(crate) collation: Option<syn::Path>,
 }
 
 impl ToTokenStream for KeyOptions {
     fn new_to_token_stream(&self) -> proc_macro2::TokenStream {
         let unique = self.unique;
         let optional = self.optional;
+        let multi = self.multi;
+        let store_value = self.store_value;
         quote! {
             native_db::db_type::KeyOptions {
                 unique: #unique,
                 optional: #optional,
+                multi: #multi,
+                store_value: #store_value,
             }
         }
     }
 }
 
+impl KeyDefinition<KeyOptions> {
+    /// With `multi`, each element of the collection is a key: the keys have the type of the
+    /// elements, e.g. `String` for `Vec<String>`.
+    pub(crate) fn check_multi(&mut self) {
+        if !self.options.multi {
+            return;
+        }
+        if self.options.optional {
+            panic!("secondary_key can not be both 'optional' and 'multi'");
+        }
+        if !self.nested_path.is_empty() {
+            panic!("secondary_key 'multi' is not supported on a nested field");
+        }
+        let field_type = self
+            .field_type
+            .as_ref()
+            .expect("KeyDefinition must have a field type");
+        let parsed_type: Type = parse_str(field_type).expect("Failed to parse type");
+        let element_type = match parsed_type {
+            Type::Path(path) => path.path.segments.last().and_then(|segment| {
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    args.args.iter().find_map(|arg| match arg {
+                        GenericArgument::Type(element_type) => Some(element_type.clone()),
+                        _ => None,
+                    })
+                } else {
+                    None
+                }
+            }),
+            _ => None,
+        };
+        let element_type = element_type
+            .expect("secondary_key 'multi' expects a collection type, e.g. Vec<String>");
+        self.field_type = Some(element_type.to_token_stream().to_string());
+    }
+
+    /// With `store_value`, the value is read from the secondary key, so it must be unique.
+    pub(crate) fn check_store_value(&self) {
+        if self.options.store_value && !self.options.unique {
+            panic!("secondary_key 'store_value' requires 'unique'");
+        }
+    }
+
+    /// With `collation`, the key is the `Vec<u8>` returned by the collation function.
+    pub(crate) fn check_collation(&mut self) {
+        if self.options.collation.is_none() {
+            return;
+        }
+        let field_type = if self.options.optional {
+            "Option<Vec<u8>>"
+        } else {
+            "Vec<u8>"
+        };
+        self.field_type = Some(field_type.to_string());
+    }
+}
+
 impl ToTokenStream for () {
     fn new_to_token_stream(&self) -> proc_macro2::TokenStream {
         quote! {()}
@@ -101,8 +174,66 @@ impl ToTokenStream for () {
 }
 
 impl<O: ToTokenStream> KeyDefinition<O> {
+    /// The type of the key usable in an expression, e.g. `Vec::<u32>`, `None` for a nested
+    /// field whose type isn't written.
+    fn parsed_type(&self) -> Option<proc_macro2::TokenStream> {
+        let rust_type_name = self.field_type.clone()?;
+
+        // DEBUG print
+        // let rust_type_name: &str = "Vec<u32>";
+        // let type_str = "u32";
+        let mut parsed_type: Type = parse_str(&rust_type_name).expect("Failed to parse type");
+
+        if let Type::Path(ref mut path, ..) = parsed_type {
+            if let Some(segment) = path.path.segments.last_mut() {
+                if let PathArguments::AngleBracketed(ref mut args) = segment.arguments {
+                    if args.colon2_token.is_none() {
+                        let new_args = args.clone();
+                        segment.arguments = PathArguments::None;
+
+                        let modified_path: syn::Path = syn::parse_quote! {
+                            #path :: #new_args
+                        };
+
+                        path.path.segments = modified_path.segments;
+                    }
+                }
+            }
+        }
+
+        Some(parsed_type.to_token_stream())
+    }
+
+    /// Expression telling if the type of the key changed encoding since the encoding `version`,
+    /// see `ToKey::key_encoding_changed_since`.
+    pub(crate) fn key_encoding_changed_since(&self) -> proc_macro2::TokenStream {
+        let Some(parsed_type) = self.parsed_type() else {
+            let struct_name = if self.struct_name.is_generic() {
+                quote! {Self}
+            } else {
+                self.struct_name.ident().to_token_stream()
+            };
+            let field_path = self.field_path();
+            return quote! {
+                native_db::db_type::key_encoding_changed_since_of(|model: &#struct_name| &model.#field_path, version)
+            };
+        };
+        if self.to_key_checked {
+            quote! {
+                {
+                    use native_db::db_type::{ToKeyProbeImplemented as _, ToKeyProbeMissing as _};
+                    (&native_db::db_type::ToKeyProbe::<#parsed_type>::new()).probe_key_encoding_changed_since(version)
+                }
+            }
+        } else {
+            quote! { <#parsed_type as native_db::db_type::ToKey>::key_encoding_changed_since(version) }
+        }
+    }
+
     pub(crate) fn name(&self) -> String {
-        if let Some(field_name) = &self.field_name {
+        if !self.nested_path.is_empty() {
+            self.ident().to_string().to_lowercase()
+        } else if let Some(field_name) = &self.field_name {
             field_name.to_string().to_lowercase()
         } else if let Some(function_name) = &self.function_name {
             function_name.to_string().to_lowercase()
@@ -113,10 +244,16 @@ impl<O: ToTokenStream> KeyDefinition<O> {
 
     pub(crate) fn ident(&self) -> Ident {
         if self.is_field() {
-            self.field_name
+            let field_name = self
+                .field_name
                 .as_ref()
-                .expect("Trying to get an undefined field name")
-                .clone()
+                .expect("Trying to get an undefined field name");
+            // `customer.id` is named `customer_id`
+            self.nested_path
+                .iter()
+                .fold(field_name.clone(), |ident, nested| {
+                    format_ident!("{}_{}", ident.unraw(), nested.unraw())
+                })
         } else {
             self.function_name
                 .as_ref()
@@ -125,6 +262,22 @@ impl<O: ToTokenStream> KeyDefinition<O> {
         }
     }
 
+    /// Path of the field from the model, e.g. `customer.id`, to be used as `self.#field_path`.
+    pub(crate) fn field_path(&self) -> proc_macro2::TokenStream {
+        let field_name = self
+            .field_name
+            .as_ref()
+            .expect("Trying to get an undefined field name");
+        let nested_path = &self.nested_path;
+        quote! { #field_name #(.#nested_path)* }
+    }
+
+    /// Name of the method generated on the model to get the key definition,
+    /// e.g. `Item::secondary_key_name()`.
+    pub(crate) fn accessor_ident(&self) -> Ident {
+        format_ident!("secondary_key_{}", self.ident().unraw())
+    }
+
     pub(crate) fn new_field(
         table_name: StructName,
         field_name: Ident,
@@ -134,12 +287,22 @@ impl<O: ToTokenStream> KeyDefinition<O> {
         Self {
             struct_name: table_name,
             field_name: Some(field_name),
+            nested_path: vec![],
             function_name: None,
             field_type: Some(field_type),
+            to_key_checked: false,
             options,
         }
     }
 
+    /// Key on the nested field `path` of the field, its type is inferred.
+    pub(crate) fn set_nested_path(&mut self, path: Vec<Ident>) {
+        if !path.is_empty() {
+            self.nested_path = path;
+            self.field_type = None;
+        }
+    }
+
     pub(crate) fn set_function_name(&mut self, function_name: Ident) {
         self.function_name = Some(function_name);
     }
@@ -151,8 +314,10 @@ impl<O: ToTokenStream> KeyDefinition<O> {
         Self {
             struct_name: table_name,
             field_name: None,
+            nested_path: vec![],
             function_name: None,
             field_type: None,
+            to_key_checked: false,
             options: O::default(),
         }
     }
@@ -3,16 +3,28 @@ use crate::struct_name::StructName;
 use proc_macro2::TokenStream;
 use quote::ToTokens;
 use std::collections::HashSet;
+use syn::ext::IdentExt;
 use syn::meta::ParseNestedMeta;
-use syn::parse::Result;
-use syn::{Field, LitBool};
+use syn::parse::{ParseStream, Parser, Result};
+use syn::{Field, Ident, LitBool, Token};
 
 #[derive(Clone)]
 pub(crate) struct ModelAttributes {
     pub(crate) struct_name: StructName,
     pub(crate) primary_key: Option<KeyDefinition<()>>,
     pub(crate) secondary_keys: HashSet<KeyDefinition<KeyOptions>>,
+    // Fields used as is as secondary keys, their type must implement `ToKey`.
+    pub(crate) secondary_key_field_types: Vec<(Ident, syn::Type)>,
     pub(crate) do_export_keys: Option<LitBool>,
+    pub(crate) hashed_primary_key: bool,
+    pub(crate) whole_primary_key: bool,
+    pub(crate) blob: bool,
+    // Variant of `native_db::Compression`, set by `compress = "..."`.
+    pub(crate) compression: Option<Ident>,
+    // Values stored as rkyv archives, set by `rkyv`.
+    pub(crate) rkyv: bool,
+    pub(crate) version_field: Option<syn::Ident>,
+    pub(crate) validate: Option<syn::Path>,
 }
 
 impl ModelAttributes {
@@ -28,6 +40,19 @@ impl ModelAttributes {
 
             // Parse the identifier
             let ident: syn::Ident = content.parse()?;
+
+            // `primary_key(whole)`: the whole value is the key, its type is the model itself.
+            if ident == "whole" && content.is_empty() {
+                key.set_function_name(ident);
+                key.field_type = Some(if self.struct_name.is_generic() {
+                    "Self".to_string()
+                } else {
+                    self.struct_name.ident().to_string()
+                });
+                self.whole_primary_key = true;
+                self.primary_key = Some(key);
+                return Ok(());
+            }
             key.set_function_name(ident);
 
             // Expect a comma
@@ -64,18 +89,50 @@ impl ModelAttributes {
                 match option.to_string().as_str() {
                     "unique" => key.options.unique = true,
                     "optional" => key.options.optional = true,
+                    "multi" => key.options.multi = true,
+                    "store_value" => key.options.store_value = true,
                     _ => {
                         return Err(syn::Error::new_spanned(
                             option,
-                            "Unknown option for secondary_key, expected 'unique' or 'optional'",
+                            "Unknown option for secondary_key, expected 'unique', 'optional', 'multi' or 'store_value'",
                         ));
                     }
                 }
             }
+            key.check_multi();
+            key.check_store_value();
 
             self.secondary_keys.insert(key);
         } else if meta.path.is_ident("export_keys") {
             self.do_export_keys = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("hashed_primary_key") {
+            self.hashed_primary_key = true;
+        } else if meta.path.is_ident("blob") {
+            self.blob = true;
+        } else if meta.path.is_ident("rkyv") {
+            if self.compression.is_some() {
+                return Err(meta.error("'rkyv' can not be combined with 'compress'"));
+            }
+            self.rkyv = true;
+        } else if meta.path.is_ident("compress") {
+            if self.rkyv {
+                return Err(meta.error("'compress' can not be combined with 'rkyv'"));
+            }
+            let compression: syn::LitStr = meta.value()?.parse()?;
+            let variant = match compression.value().as_str() {
+                "zstd" => "Zstd",
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        compression,
+                        "Unknown compression, expected 'zstd'",
+                    ));
+                }
+            };
+            self.compression = Some(Ident::new(variant, compression.span()));
+        } else if meta.path.is_ident("version_field") {
+            self.version_field = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("validate") {
+            self.validate = Some(meta.value()?.parse()?);
         } else {
             panic!(
                 "Unknown attribute: {}",
@@ -100,33 +157,101 @@ impl ModelAttributes {
                     field_type,
                     (),
                 ));
+                let primary_key_name = field.ident.as_ref();
+                if self
+                    .secondary_keys
+                    .iter()
+                    .any(|key| Some(&key.ident()) == primary_key_name)
+                {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "the primary key has the name of a secondary key, a nested field `a.b` is named `a_b`",
+                    ));
+                }
             } else if attr.path().is_ident("secondary_key") {
                 let mut field_type_token_stream = TokenStream::new();
                 field.ty.to_tokens(&mut field_type_token_stream);
                 let field_type = field_type_token_stream.to_string();
+                let field_ident = field
+                    .ident
+                    .clone()
+                    .expect("Parsed field expected to have an ident for secondary_key");
                 let mut secondary_options = KeyOptions::default();
+                let mut nested_path = vec![];
                 if attr.meta.require_list().is_ok() {
-                    attr.parse_nested_meta(|meta| {
+                    let options_parser = syn::meta::parser(|meta| {
                         if meta.path.is_ident("unique") {
                             secondary_options.unique = true;
                         } else if meta.path.is_ident("optional") {
                             secondary_options.optional = true;
+                        } else if meta.path.is_ident("multi") {
+                            secondary_options.multi = true;
+                        } else if meta.path.is_ident("store_value") {
+                            secondary_options.store_value = true;
+                        } else if meta.path.is_ident("collation") {
+                            secondary_options.collation = Some(meta.value()?.parse()?);
                         } else {
-                            panic!("secondary_key support only 'unique' or 'composable'");
+                            panic!("secondary_key support only 'unique', 'optional', 'multi', 'store_value' or 'collation'");
                         }
                         Ok(())
+                    });
+                    attr.parse_args_with(|input: ParseStream| {
+                        // `#[secondary_key(customer.id, ...)]` on the field `customer`: the key
+                        // is the nested field `customer.id`.
+                        if input.peek(Ident::peek_any) && input.peek2(Token![.]) {
+                            let root = input.call(Ident::parse_any)?;
+                            if root != field_ident {
+                                return Err(syn::Error::new_spanned(
+                                    root,
+                                    format!(
+                                        "the path of secondary_key must start with the field name '{}'",
+                                        field_ident
+                                    ),
+                                ));
+                            }
+                            while input.peek(Token![.]) {
+                                input.parse::<Token![.]>()?;
+                                nested_path.push(input.call(Ident::parse_any)?);
+                            }
+                            if !input.is_empty() {
+                                input.parse::<Token![,]>()?;
+                            }
+                        }
+                        options_parser.parse2(input.parse()?)
                     })?;
                 }
 
-                self.secondary_keys.insert(KeyDefinition::new_field(
+                // The type parameters of a generic model are not in scope of the check.
+                let to_key_checked = nested_path.is_empty()
+                    && secondary_options.collation.is_none()
+                    && !self.struct_name.is_generic();
+                if to_key_checked {
+                    self.secondary_key_field_types
+                        .push((field_ident.clone(), field.ty.clone()));
+                }
+                let mut key = KeyDefinition::new_field(
                     self.struct_name.clone(),
-                    field
-                        .ident
-                        .clone()
-                        .expect("Parsed field expected to have an ident for secondary_key"),
+                    field_ident,
                     field_type,
                     secondary_options,
-                ));
+                );
+                key.to_key_checked = to_key_checked;
+                key.set_nested_path(nested_path);
+                key.check_multi();
+                key.check_store_value();
+                key.check_collation();
+                // `customer.id` is named `customer_id`, like a field or a function key.
+                let primary_key_name = self.primary_key.as_ref().map(|key| key.ident());
+                if self.secondary_keys.contains(&key) || primary_key_name == Some(key.ident()) {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        format!(
+                            "two keys are named '{}', a nested field `a.b` is named `a_b`",
+                            key.name()
+                        ),
+                    ));
+                }
+                self.secondary_keys.insert(key);
             }
         }
         Ok(())
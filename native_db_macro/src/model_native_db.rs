@@ -2,7 +2,8 @@ use crate::model_attributes::ModelAttributes;
 use crate::struct_name::StructName;
 use crate::ToTokenStream;
 use proc_macro::Span;
-use quote::quote;
+use quote::{quote, quote_spanned, ToTokens};
+use syn::spanned::Spanned;
 use syn::Ident;
 
 pub(crate) struct ModelNativeDB {
@@ -22,16 +23,62 @@ impl ModelNativeDB {
             .iter()
             .map(|key| {
                 let key_ident = key.ident();
+                let field_path = if key.is_field() {
+                    key.field_path()
+                } else {
+                    key_ident.to_token_stream()
+                };
                 let new_secondary_key = key.new_to_token_stream();
-                let out = if key.is_field() {
+                let to_key = |value: proc_macro2::TokenStream| {
+                    if key.to_key_checked {
+                        quote! { (&native_db::db_type::ToKeyProbe::of(#value)).probe_to_key(#value) }
+                    } else {
+                        quote! { (#value).to_key() }
+                    }
+                };
+                let out = if key.options.multi {
+                    let values = if key.is_field() {
+                        quote! { self.#field_path.iter() }
+                    } else {
+                        quote! { self.#key_ident().iter() }
+                    };
+                    if let Some(collation) = &key.options.collation {
+                        quote! {
+                            let value = native_db::db_type::KeyEntry::new_multi(#values.map(|v| #collation(v).to_key()));
+                        }
+                    } else {
+                        let to_key = to_key(quote! { v });
+                        quote! {
+                            let value = native_db::db_type::KeyEntry::new_multi(#values.map(|v| #to_key));
+                        }
+                    }
+                } else if let Some(collation) = &key.options.collation {
+                    if key.options.optional {
+                        quote! {
+                            let value: Option<native_db::db_type::Key> = self.#field_path.as_ref().map(|v| #collation(v).to_key());
+                            let value = native_db::db_type::KeyEntry::Optional(value);
+                        }
+                    } else {
+                        quote! {
+                            let value: native_db::db_type::Key = #collation(&self.#field_path).to_key();
+                            let value = native_db::db_type::KeyEntry::Default(value);
+                        }
+                    }
+                } else if key.is_field() {
                     if key.options.optional {
+                        let to_key = if key.to_key_checked {
+                            to_key(quote! { v })
+                        } else {
+                            quote! { (&v).to_key() }
+                        };
                         quote! {
-                            let value: Option<native_db::db_type::Key>  = self.#key_ident.as_ref().map(|v|(&v).to_key());
+                            let value: Option<native_db::db_type::Key>  = self.#field_path.as_ref().map(|v| #to_key);
                             let value = native_db::db_type::KeyEntry::Optional(value);
                         }
                     } else {
+                        let to_key = to_key(quote! { &self.#field_path });
                         quote! {
-                            let value: native_db::db_type::Key  = (&self.#key_ident).to_key();
+                            let value: native_db::db_type::Key  = #to_key;
                             let value = native_db::db_type::KeyEntry::Default(value);
                         }
                     }
@@ -60,6 +107,8 @@ impl ModelNativeDB {
 
         quote! {
             fn native_db_secondary_keys(&self) -> std::collections::HashMap<native_db::db_type::KeyDefinition<native_db::db_type::KeyOptions>, native_db::db_type::KeyEntry> {
+                #[allow(unused_imports)]
+                use native_db::db_type::{ToKeyProbeImplemented as _, ToKeyProbeMissing as _};
                 let mut secondary_tables_name = std::collections::HashMap::new();
                 #(#tokens)*
                 secondary_tables_name
@@ -67,10 +116,59 @@ impl ModelNativeDB {
         }
     }
 
+    /// Check that the type of each secondary key field implements `ToKey`, with an error on the
+    /// field instead of one in the generated code.
+    pub(crate) fn secondary_keys_to_key_check(&self) -> proc_macro2::TokenStream {
+        let checks = self
+            .attrs
+            .secondary_key_field_types
+            .iter()
+            .map(|(field, ty)| {
+                let message = format!(
+                    "secondary key field `{}: {{Self}}` requires `{{Self}}: ToKey`",
+                    field
+                );
+                let check = quote_spanned! {ty.span()=>
+                    check::<#ty>();
+                };
+                quote! {
+                    const _: () = {
+                        #[diagnostic::on_unimplemented(
+                            message = #message,
+                            label = "the trait `ToKey` is not implemented for `{Self}`"
+                        )]
+                        trait SecondaryKey {}
+                        // The error is on `SecondaryKey`, without the list of the types
+                        // implementing `ToKey`.
+                        #[diagnostic::do_not_recommend]
+                        impl<T: native_db::db_type::ToKey + ?Sized> SecondaryKey for T {}
+                        fn check<T: SecondaryKey + ?Sized>() {}
+                        #[allow(dead_code)]
+                        fn check_field() {
+                            #check
+                        }
+                    };
+                }
+            });
+        quote! {
+            #(#checks)*
+        }
+    }
+
     pub(crate) fn native_db_primary_key(&self) -> proc_macro2::TokenStream {
         let primary_key = self.attrs.primary_key();
         let ident = primary_key.ident();
-        if primary_key.is_function() {
+        if self.attrs.whole_primary_key {
+            quote! {
+                fn native_db_primary_key(&self) -> native_db::db_type::Key {
+                    native_db::db_type::ToKey::to_key(self)
+                }
+
+                fn native_db_try_primary_key(&self) -> native_db::db_type::Result<native_db::db_type::Key> {
+                    Ok(native_db::db_type::Key::new(native_db::bincode_encode_to_vec(self)?))
+                }
+            }
+        } else if primary_key.is_function() {
             quote! {
                 fn native_db_primary_key(&self) -> native_db::db_type::Key {
                     (&self.#ident()).to_key()
@@ -85,8 +183,146 @@ impl ModelNativeDB {
         }
     }
 
+    /// With `primary_key(whole)`, the model is its own key: the bytes of the encoded value.
+    ///
+    /// A value that fails to encode gets an empty key, which matches no stored value since
+    /// `native_db_try_primary_key` returns the encoding error before anything is written.
+    pub(crate) fn whole_primary_key_to_key(&self) -> Option<proc_macro2::TokenStream> {
+        if !self.attrs.whole_primary_key {
+            return None;
+        }
+        let name = if self.struct_name.is_generic() {
+            quote! {std::any::type_name::<Self>()}
+        } else {
+            self.struct_name.ident().to_string().to_token_stream()
+        };
+        Some(quote! {
+            fn to_key(&self) -> native_db::db_type::Key {
+                let bytes = native_db::bincode_encode_to_vec(self).unwrap_or_default();
+                native_db::db_type::Key::new(bytes)
+            }
+
+            fn key_names() -> Vec<String> {
+                vec![#name.to_string()]
+            }
+
+            fn key_encoding_changed_since(_version: u32) -> bool {
+                false
+            }
+        })
+    }
+
+    pub(crate) fn native_db_key_encoding_changed_since(&self) -> proc_macro2::TokenStream {
+        let primary_key = self.attrs.primary_key().key_encoding_changed_since();
+        let secondary_keys = self
+            .attrs
+            .secondary_keys
+            .iter()
+            .map(|key| key.key_encoding_changed_since());
+        quote! {
+            fn native_db_key_encoding_changed_since(version: u32) -> bool {
+                #primary_key #(|| #secondary_keys)*
+            }
+        }
+    }
+
+    pub(crate) fn native_db_swap_primary_key(&self) -> proc_macro2::TokenStream {
+        let primary_key = self.attrs.primary_key();
+        if primary_key.is_function() {
+            return quote! {};
+        }
+        let ident = primary_key.ident();
+        quote! {
+            fn native_db_swap_primary_key(&mut self, other: &mut Self) -> bool {
+                std::mem::swap(&mut self.#ident, &mut other.#ident);
+                true
+            }
+        }
+    }
+
+    pub(crate) fn native_db_version_field(&self) -> proc_macro2::TokenStream {
+        if let Some(ident) = &self.attrs.version_field {
+            quote! {
+                fn native_db_version(&self) -> Option<u64> {
+                    Some(self.#ident)
+                }
+
+                fn native_db_set_version(&mut self, version: u64) {
+                    self.#ident = version;
+                }
+            }
+        } else {
+            quote! {}
+        }
+    }
+
+    pub(crate) fn native_db_validate(&self) -> proc_macro2::TokenStream {
+        if let Some(path) = &self.attrs.validate {
+            quote! {
+                fn native_db_validate(&self) -> native_db::db_type::Result<()> {
+                    #path(self).map_err(|message| native_db::db_type::Error::ValidationFailed { message })
+                }
+            }
+        } else {
+            quote! {}
+        }
+    }
+
+    pub(crate) fn native_db_encode_decode(&self) -> proc_macro2::TokenStream {
+        if self.attrs.rkyv {
+            quote! {
+                fn native_db_bincode_encode_to_vec(&self) -> native_db::db_type::Result<Vec<u8>> {
+                    native_db::rkyv_encode_to_vec(self)
+                }
+
+                fn native_db_bincode_decode_from_slice(slice: &[u8]) -> native_db::db_type::Result<Self> {
+                    native_db::rkyv_decode_from_slice(slice)
+                }
+            }
+        } else if let Some(compression) = &self.attrs.compression {
+            quote! {
+                fn native_db_bincode_encode_to_vec(&self) -> native_db::db_type::Result<Vec<u8>> {
+                    native_db::Compression::#compression.compress(&native_db::bincode_encode_to_vec(self)?)
+                }
+
+                fn native_db_bincode_decode_from_slice(slice: &[u8]) -> native_db::db_type::Result<Self> {
+                    Self::native_db_bincode_decode_from_slice_limited(slice, None)
+                }
+
+                fn native_db_bincode_decode_from_slice_limited(slice: &[u8], max_value_size: Option<usize>) -> native_db::db_type::Result<Self> {
+                    let slice = native_db::Compression::#compression.decompress_bounded(slice, max_value_size)?;
+                    Ok(native_db::bincode_decode_from_slice(&slice)?.0)
+                }
+            }
+        } else {
+            quote! {
+                fn native_db_bincode_encode_to_vec(&self) -> native_db::db_type::Result<Vec<u8>> {
+                    native_db::bincode_encode_to_vec(self)
+                }
+
+                fn native_db_bincode_decode_from_slice(slice: &[u8]) -> native_db::db_type::Result<Self> {
+                    Ok(native_db::bincode_decode_from_slice(slice)?.0)
+                }
+            }
+        }
+    }
+
     pub(crate) fn native_db_model(&self) -> proc_macro2::TokenStream {
+        // The monomorphizations of a generic model are told apart by their full type name.
+        let name = if self.struct_name.is_generic() {
+            quote! {std::any::type_name::<Self>()}
+        } else {
+            self.struct_name.ident().to_string().to_token_stream()
+        };
         let primary_key = self.attrs.primary_key().new_to_token_stream();
+        let hashed_primary_key = self.attrs.hashed_primary_key;
+        let whole_primary_key = self.attrs.whole_primary_key;
+        let blob = self.attrs.blob;
+        let rkyv = self.attrs.rkyv;
+        let compression = match &self.attrs.compression {
+            Some(compression) => quote! {Some(native_db::Compression::#compression)},
+            None => quote! {None},
+        };
         let secondary_keys = self
             .attrs
             .secondary_keys
@@ -103,10 +339,19 @@ impl ModelNativeDB {
             fn native_db_model() -> native_db::Model {
                 let mut secondary_tables_name = std::collections::HashSet::new();
                 #(#secondary_keys)*
-                native_db::Model {
-                    primary_key: #primary_key,
-                    secondary_keys: secondary_tables_name,
-                }
+                let mut model = native_db::Model::new(
+                    #name,
+                    <Self as native_db::native_model::Model>::native_model_id(),
+                    <Self as native_db::native_model::Model>::native_model_version(),
+                    #primary_key,
+                    secondary_tables_name,
+                );
+                model.hashed_primary_key = #hashed_primary_key;
+                model.whole_primary_key = #whole_primary_key;
+                model.blob = #blob;
+                model.compression = #compression;
+                model.rkyv = #rkyv;
+                model
             }
         }
     }
@@ -145,6 +390,24 @@ impl ModelNativeDB {
             .collect::<Vec<_>>()
     }
 
+    pub(crate) fn secondary_keys_accessors(&self) -> Vec<proc_macro2::TokenStream> {
+        let visibility = self.keys_enum_visibility();
+        self.attrs
+            .secondary_keys
+            .iter()
+            .map(|key| {
+                let accessor = key.accessor_ident();
+                let new_key = key.new_to_token_stream();
+                quote! {
+                    #[allow(dead_code)]
+                    #visibility fn #accessor() -> native_db::db_type::KeyDefinition<native_db::db_type::KeyOptions> {
+                        #new_key
+                    }
+                }
+            })
+            .collect::<Vec<_>>()
+    }
+
     pub(crate) fn keys_enum_database_key(&self) -> proc_macro2::TokenStream {
         let keys_enum_name_token = self.keys_enum_name();
 
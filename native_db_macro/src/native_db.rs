@@ -3,17 +3,25 @@ use crate::model_native_db::ModelNativeDB;
 use crate::struct_name::StructName;
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Fields};
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields};
 
 pub fn native_db(args: TokenStream, input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
-    let struct_name = StructName::new(ast.ident.clone());
+    let struct_name = StructName::new(ast.ident.clone(), !ast.generics.params.is_empty());
 
     let mut attrs = ModelAttributes {
         struct_name: struct_name.clone(),
         primary_key: None,
         secondary_keys: Default::default(),
+        secondary_key_field_types: vec![],
         do_export_keys: None,
+        hashed_primary_key: false,
+        whole_primary_key: false,
+        blob: false,
+        compression: None,
+        rkyv: false,
+        version_field: None,
+        validate: None,
     };
     let model_attributes_parser = syn::meta::parser(|meta| attrs.parse(meta));
     parse_macro_input!(args with model_attributes_parser);
@@ -32,39 +40,80 @@ pub fn native_db(args: TokenStream, input: TokenStream) -> TokenStream {
 
     let native_db_pk = model_native_db.native_db_primary_key();
     let native_db_gks = model_native_db.native_db_secondary_key();
+    let native_db_swap_pk = model_native_db.native_db_swap_primary_key();
+    let native_db_key_encoding_changed_since =
+        model_native_db.native_db_key_encoding_changed_since();
     let native_db_model = model_native_db.native_db_model();
+    let native_db_version_field = model_native_db.native_db_version_field();
+    let native_db_validate = model_native_db.native_db_validate();
+    let native_db_encode_decode = model_native_db.native_db_encode_decode();
+    let secondary_keys_to_key_check = model_native_db.secondary_keys_to_key_check();
+
+    let whole_primary_key_to_key = model_native_db.whole_primary_key_to_key();
 
     let keys_enum_visibility = model_native_db.keys_enum_visibility();
     let keys_enum_name = model_native_db.keys_enum_name();
     let keys_enum = model_native_db.secondary_keys_enum();
     let keys_enum_database_key = model_native_db.keys_enum_database_key();
+    let keys_accessors = model_native_db.secondary_keys_accessors();
+
+    // A generic model is only a model for the type parameters that implement
+    // `native_model::Model`, e.g. with one manual impl per monomorphization.
+    let mut generics = ast.generics.clone();
+    if struct_name.is_generic() {
+        generics.make_where_clause().predicates.push(parse_quote! {
+            Self: native_db::native_model::Model + serde::Serialize + serde::de::DeserializeOwned
+        });
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // The keys enum can't tell the monomorphizations of a generic model apart, the key
+    // definitions are reached with the accessors instead, e.g. `Cache::<u64>::secondary_key_name()`.
+    let keys_enum = (!struct_name.is_generic()).then(|| {
+        quote! {
+            #[allow(non_camel_case_types)]
+            #keys_enum_visibility enum #keys_enum_name {
+                #(#keys_enum),*
+            }
+
+            impl native_db::db_type::ToKeyDefinition<native_db::db_type::KeyOptions> for #keys_enum_name {
+                #keys_enum_database_key
+            }
+        }
+    });
 
     let struct_name = struct_name.ident();
+    let whole_primary_key_to_key = whole_primary_key_to_key.map(|to_key| {
+        quote! {
+            impl #impl_generics native_db::db_type::ToKey for #struct_name #ty_generics #where_clause {
+                #to_key
+            }
+        }
+    });
     let gen = quote! {
         #[derive(native_db::KeyAttributes)]
         #ast
 
-        impl native_db::db_type::ToInput for #struct_name {
-            fn native_db_bincode_encode_to_vec(&self) -> native_db::db_type::Result<Vec<u8>> {
-                native_db::bincode_encode_to_vec(self)
-            }
+        #secondary_keys_to_key_check
 
-            fn native_db_bincode_decode_from_slice(slice: &[u8]) -> native_db::db_type::Result<Self> {
-                Ok(native_db::bincode_decode_from_slice(slice)?.0)
-            }
+        impl #impl_generics native_db::db_type::ToInput for #struct_name #ty_generics #where_clause {
+            #native_db_encode_decode
 
             #native_db_model
             #native_db_pk
             #native_db_gks
+            #native_db_swap_pk
+            #native_db_key_encoding_changed_since
+            #native_db_version_field
+            #native_db_validate
         }
 
-        #[allow(non_camel_case_types)]
-        #keys_enum_visibility enum #keys_enum_name {
-            #(#keys_enum),*
-        }
+        #keys_enum
+
+        #whole_primary_key_to_key
 
-        impl native_db::db_type::ToKeyDefinition<native_db::db_type::KeyOptions> for #keys_enum_name {
-            #keys_enum_database_key
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #(#keys_accessors)*
         }
     };
 
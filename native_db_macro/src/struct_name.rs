@@ -1,13 +1,19 @@
 use proc_macro2::Ident;
 
 #[derive(Clone, Debug)]
-pub(crate) struct StructName(Ident);
+pub(crate) struct StructName {
+    ident: Ident,
+    is_generic: bool,
+}
 
 impl StructName {
     pub(crate) fn ident(&self) -> &Ident {
-        &self.0
+        &self.ident
+    }
+    pub(crate) fn is_generic(&self) -> bool {
+        self.is_generic
     }
-    pub(crate) fn new(ident: Ident) -> Self {
-        Self(ident)
+    pub(crate) fn new(ident: Ident, is_generic: bool) -> Self {
+        Self { ident, is_generic }
     }
 }
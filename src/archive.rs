@@ -0,0 +1,154 @@
+use crate::db_type::{check_value_size, Error, Result, ToInput};
+use crate::transaction::internal::blob;
+use crate::transaction::internal::private_readable_transaction::PrivateReadableTransaction;
+use crate::Database;
+use redb::ReadableTable;
+use std::io::{Read, Write};
+
+// Length written in place of the blob of a value which has none.
+const NO_BLOB: u64 = u64::MAX;
+
+impl Database<'_> {
+    /// Export the values of the model `T` to `w`, returns the number of values.
+    ///
+    /// The archive is a sequence of records, one per value in the order of the primary keys:
+    /// the length of the value as a little endian `u64`, then the value encoded with
+    /// [`native_model`](https://crates.io/crates/native_model), which starts with the id and the
+    /// version of the model, then the length of its blob (`u64::MAX` if it has none, see
+    /// `#[native_db(blob)]`) followed by the blob. The values are decoded and encoded again, the
+    /// archive doesn't depend on how they are stored (e.g. `#[native_db(compress = ...)]` or
+    /// `#[native_db(rkyv)]`). Use [`import_model_archive`](Self::import_model_archive) to read
+    /// it back, e.g. in another database.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Data { id: 1 })?;
+    ///     rw.commit()?;
+    ///
+    ///     let mut archive = vec![];
+    ///     assert_eq!(db.export_model_archive::<Data>(&mut archive)?, 1);
+    ///
+    ///     let other_db = Builder::new().create_in_memory(&models)?;
+    ///     assert_eq!(other_db.import_model_archive::<Data>(archive.as_slice())?, 1);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn export_model_archive<T: ToInput>(&self, mut w: impl Write) -> Result<usize> {
+        let r = self.r_transaction()?;
+        let model = T::native_db_model();
+        let table = r.internal.get_primary_table(&model)?;
+        let blob_table = r.internal.get_blob_table(&model)?;
+        let mut count = 0;
+        for result in table.iter()? {
+            let (_, value) = result?;
+            check_value_size(value.value(), self.max_value_size)?;
+            let value =
+                T::native_db_bincode_decode_from_slice_limited(value.value(), self.max_value_size)?;
+            let encoded = value.native_model_encode()?;
+            w.write_all(&(encoded.len() as u64).to_le_bytes())?;
+            w.write_all(&encoded)?;
+            let primary_key = value.native_db_primary_key();
+            let blob_len = match &blob_table {
+                Some(blob_table) => blob::len(blob_table, &primary_key)?,
+                None => None,
+            };
+            w.write_all(&blob_len.unwrap_or(NO_BLOB).to_le_bytes())?;
+            if let (Some(blob_table), Some(_)) = (&blob_table, blob_len) {
+                blob::copy_to(blob_table, &primary_key, &mut w)?;
+            }
+            count += 1;
+        }
+        w.flush()?;
+        Ok(count)
+    }
+
+    /// Insert the values of an archive written by [`export_model_archive`](Self::export_model_archive),
+    /// returns the number of values.
+    ///
+    /// The values archived with an older version of the model are upgraded with the `from`
+    /// chain of `T` (see [`native_model`](https://crates.io/crates/native_model)). The values are
+    /// inserted in a single transaction: if one of them can't be decoded or inserted (e.g.
+    /// [`DuplicateKey`](crate::db_type::Error::DuplicateKey)), nothing is imported.
+    ///
+    /// A record longer than the limit set with [`Builder::set_max_value_size`](crate::Builder::set_max_value_size)
+    /// returns [`ValueTooLarge`](crate::db_type::Error::ValueTooLarge) before it is read. A
+    /// record with a blob returns [`BlobNotDefined`](crate::db_type::Error::BlobNotDefined) if
+    /// `T` has no blob.
+    pub fn import_model_archive<T: ToInput>(&self, mut r: impl Read) -> Result<usize> {
+        let rw = self.rw_transaction()?;
+        let model = T::native_db_model();
+        let mut count = 0;
+        while let Some(len) = read_record_len(&mut r)? {
+            let size = usize::try_from(len).unwrap_or(usize::MAX);
+            if let Some(limit) = self.max_value_size.filter(|limit| size > *limit) {
+                return Err(Error::ValueTooLarge { size, limit });
+            }
+            // Not allocated upfront, the length of a truncated archive may be garbage.
+            let mut value = vec![];
+            r.by_ref().take(len).read_to_end(&mut value)?;
+            if value.len() != size {
+                return Err(unexpected_eof());
+            }
+            let (value, _) = native_model::decode::<T>(value)?;
+            let primary_key = value.native_db_primary_key();
+            rw.insert(value)?;
+            match read_record_len(&mut r)?.ok_or_else(unexpected_eof)? {
+                NO_BLOB => {}
+                _ if !model.blob => {
+                    return Err(Error::BlobNotDefined {
+                        table: model.primary_key.unique_table_name.to_string(),
+                    })
+                }
+                blob_len => {
+                    rw.check_not_mirrored("import_model_archive of a blob")?;
+                    let mut blob = r.by_ref().take(blob_len);
+                    rw.internal
+                        .concrete_write_blob(&model, &primary_key, &mut blob)?;
+                    if blob.limit() > 0 {
+                        return Err(unexpected_eof());
+                    }
+                }
+            }
+            count += 1;
+        }
+        rw.commit()?;
+        Ok(count)
+    }
+}
+
+/// Reads the length of the next record, `None` at the end of the archive.
+fn read_record_len(r: &mut impl Read) -> Result<Option<u64>> {
+    let mut len = [0; 8];
+    let mut read = 0;
+    while read < len.len() {
+        match r.read(&mut len[read..]) {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => return Err(unexpected_eof()),
+            Ok(n) => read += n,
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error.into()),
+        }
+    }
+    Ok(Some(u64::from_le_bytes(len)))
+}
+
+fn unexpected_eof() -> Error {
+    std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()
+}
@@ -0,0 +1,167 @@
+use crate::db_type::{Error, Result};
+use crate::transaction::RwTransaction;
+use crate::Database;
+use redb::{ReadableTable, TableDefinition};
+
+// Reserved table, the model tables are named "{id}_{version}_{name}".
+pub(crate) const COUNTERS_TABLE: TableDefinition<&str, i64> =
+    TableDefinition::new("native_db_counters");
+
+/// Named `i64` counters, see [`Database::counters`] and [`RwTransaction::counters`].
+///
+/// A counter that was never set is `0`.
+pub struct Counters<'db, 'a> {
+    database: &'db Database<'a>,
+}
+
+impl Counters<'_, '_> {
+    /// Add `by` to the counter `name` in its own read-write transaction and return the new value.
+    ///
+    /// Returns [`CounterOverflow`](crate::db_type::Error::CounterOverflow) if the new value
+    /// doesn't fit in an `i64`.
+    pub fn incr(&self, name: &str, by: i64) -> Result<i64> {
+        let rw = self.database.rw_transaction()?;
+        let value = rw.counters().incr(name, by)?;
+        rw.commit()?;
+        Ok(value)
+    }
+
+    /// Get the value of the counter `name`.
+    pub fn get(&self, name: &str) -> Result<i64> {
+        let r = self.database.instance.redb_database()?.begin_read()?;
+        let table = match r.open_table(COUNTERS_TABLE) {
+            Ok(table) => table,
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(0),
+            Err(error) => return Err(error.into()),
+        };
+        Ok(table.get(name)?.map_or(0, |value| value.value()))
+    }
+
+    /// Set the counter `name` to `value` in its own read-write transaction.
+    pub fn set(&self, name: &str, value: i64) -> Result<()> {
+        let rw = self.database.rw_transaction()?;
+        rw.counters().set(name, value)?;
+        rw.commit()
+    }
+}
+
+/// Named `i64` counters in a read-write transaction, see [`RwTransaction::counters`].
+///
+/// The changes are applied on commit, with the other changes of the transaction.
+pub struct RwCounters<'db, 'txn> {
+    rw: &'txn RwTransaction<'db>,
+}
+
+impl RwCounters<'_, '_> {
+    /// Add `by` to the counter `name` and return the new value.
+    ///
+    /// Returns [`CounterOverflow`](crate::db_type::Error::CounterOverflow) if the new value
+    /// doesn't fit in an `i64`.
+    pub fn incr(&self, name: &str, by: i64) -> Result<i64> {
+        let mut table = self
+            .rw
+            .internal
+            .redb_transaction
+            .open_table(COUNTERS_TABLE)?;
+        let current = table.get(name)?.map_or(0, |value| value.value());
+        let value = current
+            .checked_add(by)
+            .ok_or_else(|| Error::CounterOverflow {
+                name: name.to_string(),
+            })?;
+        table.insert(name, value)?;
+        self.rw
+            .counter_changes
+            .borrow_mut()
+            .insert(name.to_string(), value);
+        Ok(value)
+    }
+
+    /// Get the value of the counter `name`, including the changes of the transaction.
+    pub fn get(&self, name: &str) -> Result<i64> {
+        let table = self
+            .rw
+            .internal
+            .redb_transaction
+            .open_table(COUNTERS_TABLE)?;
+        let value = table.get(name)?.map_or(0, |value| value.value());
+        Ok(value)
+    }
+
+    /// Set the counter `name` to `value`.
+    pub fn set(&self, name: &str, value: i64) -> Result<()> {
+        let mut table = self
+            .rw
+            .internal
+            .redb_transaction
+            .open_table(COUNTERS_TABLE)?;
+        table.insert(name, value)?;
+        self.rw
+            .counter_changes
+            .borrow_mut()
+            .insert(name.to_string(), value);
+        Ok(())
+    }
+}
+
+impl<'a> Database<'a> {
+    /// Named `i64` counters, e.g. sequence generators or rate limits, stored in a table reserved
+    /// to native_db. Each call runs in its own transaction, use
+    /// [`RwTransaction::counters`] to change a counter along with other values.
+    ///
+    /// The read-write transactions are serialized, so concurrent increments never lose an update.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     assert_eq!(db.counters().incr("visits", 1)?, 1);
+    ///     assert_eq!(db.counters().incr("visits", 2)?, 3);
+    ///     db.counters().set("visits", 10)?;
+    ///     assert_eq!(db.counters().get("visits")?, 10);
+    ///     assert_eq!(db.counters().get("unknown")?, 0);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn counters(&self) -> Counters<'_, 'a> {
+        Counters { database: self }
+    }
+}
+
+impl<'db> RwTransaction<'db> {
+    /// Named `i64` counters, changed with the other values of the transaction.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Invoice {
+    ///     #[primary_key]
+    ///     number: i64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Invoice>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     let number = rw.counters().incr("invoice_number", 1)?;
+    ///     rw.insert(Invoice { number })?;
+    ///     rw.commit()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn counters<'txn>(&'txn self) -> RwCounters<'db, 'txn> {
+        RwCounters { rw: self }
+    }
+}
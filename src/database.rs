@@ -1,19 +1,31 @@
-use crate::database_builder::ModelBuilder;
+use crate::database_builder::{ModelBuilder, OnCommit};
 use crate::database_instance::DatabaseInstance;
-use crate::db_type::Result;
+use crate::db_type::{check_value_size, Error, Key, KeyDefinition, KeyOptions, Result, ToInput};
+use crate::mirror::Mirror;
 use crate::stats::{Stats, StatsTable};
-use crate::table_definition::PrimaryTableDefinition;
+use crate::table_definition::{PrimaryTableDefinition, SecondaryTableDefinition};
+use crate::transaction::internal::private_readable_transaction::PrivateReadableTransaction;
 use crate::transaction::internal::r_transaction::InternalRTransaction;
-use crate::transaction::internal::rw_transaction::InternalRwTransaction;
+use crate::transaction::internal::rw_transaction::{InternalRwTransaction, SizeQuota};
+use crate::transaction::OpenWriteTransaction;
 use crate::transaction::RTransaction;
 use crate::transaction::RwTransaction;
-use crate::watch::query::{InternalWatch, Watch};
-use crate::{watch, Metadata};
-use redb::{MultimapTableHandle, ReadableTableMetadata, TableHandle};
+use crate::transaction::SnapshotReader;
+use crate::unique_secondary_cache::UniqueSecondaryCaches;
+use crate::watch::query::{InternalWatch, Watch, WatchGuard};
+use crate::{metadata, upgrade, watch, HealthReport, Metadata, Model};
+use redb::{
+    MultimapTableHandle, ReadableMultimapTable, ReadableTable, ReadableTableMetadata, TableHandle,
+};
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::sync::atomic::AtomicU64;
+use std::fmt::Debug;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
 /// The database instance. Allows you to create [rw_transaction](database/struct.Database.html#method.rw_transaction) and [r_transaction](database/struct.Database.html#method.r_transaction), [watch](database/struct.Database.html#method.watch) queries, and [unwatch](database/struct.Database.html#method.unwatch) etc.
 ///
@@ -35,8 +47,21 @@ pub struct Database<'a> {
     pub(crate) instance: DatabaseInstance,
     pub(crate) metadata: Metadata,
     pub(crate) primary_table_definitions: HashMap<String, PrimaryTableDefinition<'a>>,
+    // Only the database holds the watchers: dropping it drops all the senders, so the
+    // receivers get disconnected instead of waiting forever.
     pub(crate) watchers: Arc<RwLock<watch::Watchers>>,
     pub(crate) watchers_counter_id: AtomicU64,
+    pub(crate) read_transactions_counter: AtomicUsize,
+    pub(crate) write_transaction_open: AtomicBool,
+    pub(crate) commit_sequence: AtomicU64,
+    pub(crate) on_commit: Option<OnCommit>,
+    pub(crate) unique_secondary_caches: UniqueSecondaryCaches,
+    pub(crate) mirror: Option<Mirror<'a>>,
+    // Used to reopen the database file, see `compact_safe`.
+    pub(crate) cache_size_bytes: Option<usize>,
+    pub(crate) metadata_table_name: String,
+    pub(crate) max_value_size: Option<usize>,
+    pub(crate) max_db_size: Option<u64>,
 }
 
 impl Database<'_> {
@@ -54,15 +79,39 @@ impl Database<'_> {
     ///    - [`get`](crate::transaction::RwTransaction::get) - Get a item.
     ///    - [`scan`](crate::transaction::RwTransaction::scan) - Scan items.
     ///    - [`len`](crate::transaction::RwTransaction::len) - Get the number of items.
+    ///    - [`committed_view`](crate::transaction::RwTransaction::committed_view) - Read the committed state, without the changes of the transaction.
+    ///
+    /// Only one read-write transaction can be open at a time, `rw_transaction` blocks until the
+    /// previous one is committed or aborted. So the commits are applied one after the other, in
+    /// the order the transactions are opened, and each commit increments the
+    /// [`current_commit_sequence`](Self::current_commit_sequence) by one.
     pub fn rw_transaction(&self) -> Result<RwTransaction> {
-        let rw = self.instance.redb_database()?.begin_write()?;
+        let redb_database = self.instance.redb_database()?;
+        let rw = redb_database.begin_write()?;
+        let size_quota = match (self.max_db_size, self.instance.path()) {
+            (Some(limit), Some(path)) => Some(SizeQuota::new(path, limit)?),
+            _ => None,
+        };
         let write_txn = RwTransaction {
             watcher: &self.watchers,
+            on_commit: self.on_commit.as_ref(),
+            commit_sequence: &self.commit_sequence,
+            unique_secondary_caches: &self.unique_secondary_caches,
+            mirror: self.mirror.as_ref(),
+            redb_database,
+            read_transactions_counter: &self.read_transactions_counter,
             batch: RefCell::new(watch::Batch::new()),
+            counter_changes: RefCell::new(HashMap::new()),
+            bulk_events: false,
             internal: InternalRwTransaction {
                 redb_transaction: rw,
                 primary_table_definitions: &self.primary_table_definitions,
+                max_value_size: self.max_value_size,
+                size_quota,
+                two_phase_commit: false,
+                quick_repair: false,
             },
+            _open: OpenWriteTransaction::new(&self.write_transaction_open),
         };
         Ok(write_txn)
     }
@@ -77,13 +126,55 @@ impl Database<'_> {
     pub fn r_transaction(&self) -> Result<RTransaction> {
         let txn = self.instance.redb_database()?.begin_read()?;
         let read_txn = RTransaction {
-            internal: InternalRTransaction {
-                redb_transaction: txn,
-                table_definitions: &self.primary_table_definitions,
-            },
+            internal: InternalRTransaction::new(
+                txn,
+                &self.primary_table_definitions,
+                &self.read_transactions_counter,
+                self.max_value_size,
+            ),
+            unique_secondary_caches: &self.unique_secondary_caches,
         };
         Ok(read_txn)
     }
+
+    /// Creates a read-only transaction that several threads can use at the same time, e.g. for
+    /// parallel analytics passes over one consistent version of the database.
+    ///
+    /// The version is held until the last clone of the `Arc` is dropped: the pages it uses are
+    /// not reused by the following commits meanwhile, and [`compact`](Self::compact) fails.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let snapshot = db.snapshot_reader()?;
+    ///     std::thread::scope(|s| {
+    ///         for _ in 0..2 {
+    ///             let snapshot = snapshot.clone();
+    ///             s.spawn(move || snapshot.len().primary::<Data>());
+    ///         }
+    ///     });
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn snapshot_reader(&self) -> Result<Arc<SnapshotReader>> {
+        Ok(Arc::new(SnapshotReader(self.r_transaction()?)))
+    }
 }
 
 impl Database<'_> {
@@ -96,7 +187,52 @@ impl Database<'_> {
             internal: InternalWatch {
                 watchers: &self.watchers,
                 watchers_counter_id: &self.watchers_counter_id,
+                #[cfg(feature = "tokio")]
+                debounce: None,
+            },
+            _marker: PhantomData,
+        }
+    }
+
+    /// Watch queries returning a [`WatchGuard`](crate::watch::query::WatchGuard) instead of the
+    /// watcher id, the watcher is removed when the guard is dropped so
+    /// [`unwatch`](Self::unwatch) can't be forgotten.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     {
+    ///         let (_recv, _guard) = db.watch_scoped().get().primary::<Data>(1u64)?;
+    ///         // Use the receiver ...
+    ///     } // Unwatched here
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn watch_scoped(&self) -> Watch<'_, WatchGuard<'_>> {
+        Watch {
+            internal: InternalWatch {
+                watchers: &self.watchers,
+                watchers_counter_id: &self.watchers_counter_id,
+                #[cfg(feature = "tokio")]
+                debounce: None,
             },
+            _marker: PhantomData,
         }
     }
 
@@ -112,7 +248,36 @@ impl Database<'_> {
 }
 
 impl<'a> Database<'a> {
-    pub(crate) fn seed_model(&mut self, model_builder: &'a ModelBuilder) -> Result<()> {
+    pub(crate) fn load_unique_secondary_cache(
+        &mut self,
+        model: &Model,
+        key_def: &KeyDefinition<KeyOptions>,
+    ) -> Result<()> {
+        let mut entries = vec![];
+        {
+            let r = self.r_transaction()?;
+            let table = r.internal.get_secondary_table(model, key_def)?;
+            let hashed_primary_key_table = r.internal.get_hashed_primary_key_table(model)?;
+            for result in table.iter()? {
+                let (secondary_key, primary_keys) = result?;
+                for primary_key in primary_keys {
+                    let mut primary_key = primary_key?.value();
+                    // The cache holds the original primary key
+                    if let Some(table) = &hashed_primary_key_table {
+                        let original = table.get(&primary_key)?.ok_or(Error::PrimaryKeyNotFound)?;
+                        primary_key = Key::from_slice(original.value());
+                    }
+                    entries.push((secondary_key.value(), primary_key));
+                }
+            }
+        }
+        self.unique_secondary_caches.load(key_def.clone(), entries);
+        Ok(())
+    }
+
+    /// Returns `true` if the keys of the model use an older encoding and must be re-keyed, see
+    /// [`upgrade_key_encoding`](Self::upgrade_key_encoding).
+    pub(crate) fn seed_model(&mut self, model_builder: &'a ModelBuilder) -> Result<bool> {
         let main_table_definition =
             redb::TableDefinition::new(model_builder.model.primary_key.unique_table_name.as_str());
         let mut primary_table_definition: PrimaryTableDefinition =
@@ -120,13 +285,87 @@ impl<'a> Database<'a> {
 
         let rw = self.instance.redb_database()?.begin_write()?;
         rw.open_table(primary_table_definition.redb)?;
+        metadata::check_or_stamp_compression(
+            &rw,
+            &self.metadata_table_name,
+            &model_builder.model.primary_key.unique_table_name,
+            model_builder.model.compression,
+        )?;
+        metadata::check_or_stamp_encoding(
+            &rw,
+            &self.metadata_table_name,
+            &model_builder.model.primary_key.unique_table_name,
+            model_builder.model.rkyv,
+        )?;
+        metadata::check_or_stamp_codec(
+            &rw,
+            &self.metadata_table_name,
+            &model_builder.model.primary_key.unique_table_name,
+            model_builder.model.rkyv,
+        )?;
+        let key_encoding_outdated = match metadata::key_encoding_outdated(
+            &rw,
+            &self.metadata_table_name,
+            &model_builder.model.primary_key.unique_table_name,
+        )? {
+            Some(version) if (model_builder.key_encoding_changed_since)(version) => true,
+            // The keys of the model are the same in both encodings.
+            Some(_) => {
+                metadata::stamp_key_encoding(
+                    &rw,
+                    &self.metadata_table_name,
+                    &model_builder.model.primary_key.unique_table_name,
+                )?;
+                false
+            }
+            None => false,
+        };
+        if let Some(hashed_primary_key) = primary_table_definition.hashed_primary_key {
+            rw.open_table(hashed_primary_key)?;
+        }
+        if let Some(blob) = primary_table_definition.blob {
+            rw.open_table(blob)?;
+        }
 
         for secondary_key in model_builder.model.secondary_keys.iter() {
-            primary_table_definition.secondary_tables.insert(
-                secondary_key.clone(),
-                redb::MultimapTableDefinition::new(secondary_key.unique_table_name.as_str()).into(),
-            );
-            rw.open_multimap_table(primary_table_definition.secondary_tables[secondary_key].redb)?;
+            let mut secondary_table_definition: SecondaryTableDefinition =
+                redb::MultimapTableDefinition::new(secondary_key.unique_table_name.as_str()).into();
+            secondary_table_definition.value = model_builder
+                .secondary_value_table_names
+                .get(&secondary_key.unique_table_name)
+                .map(|name| redb::TableDefinition::new(name.as_str()));
+            let secondary_table = rw.open_multimap_table(secondary_table_definition.redb)?;
+            if let Some(value) = secondary_table_definition.value {
+                let mut value_table = rw.open_table(value)?;
+                // Store the values of the items inserted before `store_value` was set
+                if value_table.is_empty()? {
+                    let primary_table = rw.open_table(primary_table_definition.redb)?;
+                    for result in secondary_table.iter()? {
+                        let (secondary_key, primary_keys) = result?;
+                        for primary_key in primary_keys {
+                            let item = primary_table
+                                .get(primary_key?.value())?
+                                .ok_or(Error::PrimaryKeyNotFound)?;
+                            value_table.insert(secondary_key.value(), item.value())?;
+                        }
+                    }
+                }
+            } else {
+                // Values stored before `store_value` was unset would be stale if it's set again
+                let value_table_name = format!("{}_value", secondary_key.unique_table_name);
+                if rw
+                    .list_tables()?
+                    .any(|table| table.name() == value_table_name)
+                {
+                    let value: redb::TableDefinition<Key, &[u8]> =
+                        redb::TableDefinition::new(&value_table_name);
+                    rw.delete_table(value)?;
+                }
+            }
+            drop(secondary_table);
+            primary_table_definition
+                .secondary_tables
+                .insert(secondary_key.clone(), secondary_table_definition);
         }
         rw.commit()?;
 
@@ -135,6 +374,158 @@ impl<'a> Database<'a> {
             primary_table_definition,
         );
 
+        Ok(key_encoding_outdated)
+    }
+
+    /// Re-keys the values of the model stored with an older encoding of the keys, e.g. the
+    /// signed integers before they were sorted in numeric order. Returns `None` if the keys
+    /// already use the current encoding, otherwise the keys of the values left as is since they
+    /// can not be decoded.
+    pub(crate) fn upgrade_key_encoding(
+        &self,
+        model_builder: &ModelBuilder,
+    ) -> Result<Option<Vec<Key>>> {
+        let rw = InternalRwTransaction {
+            redb_transaction: self.instance.redb_database()?.begin_write()?,
+            primary_table_definitions: &self.primary_table_definitions,
+            max_value_size: self.max_value_size,
+            size_quota: None,
+            two_phase_commit: false,
+            quick_repair: false,
+        };
+        let table_name = &model_builder.model.primary_key.unique_table_name;
+        let outdated = metadata::key_encoding_outdated(
+            &rw.redb_transaction,
+            &self.metadata_table_name,
+            table_name,
+        )?;
+        let skipped = match outdated {
+            Some(version) if (model_builder.key_encoding_changed_since)(version) => {
+                Some((model_builder.rekey)(&rw)?)
+            }
+            _ => None,
+        };
+        metadata::stamp_key_encoding(&rw.redb_transaction, &self.metadata_table_name, table_name)?;
+        rw.commit()?;
+        Ok(skipped)
+    }
+
+    /// Returns the number of values of all the defined models, in one read transaction.
+    ///
+    /// Sums [`len().primary()`](crate::transaction::query::RLen::primary) over all models,
+    /// including the old versions of a model that are not migrated yet.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     // Define models ...
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///     assert_eq!(db.total_len()?, 0);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn total_len(&self) -> Result<u64> {
+        let r = self.r_transaction()?;
+        let mut total_len = 0;
+        for primary_table in self.primary_table_definitions.values() {
+            total_len += r.internal.primary_len(primary_table.model.clone())?;
+        }
+        Ok(total_len)
+    }
+
+    /// Returns all the values of the model `T` ordered by primary key, in one read transaction.
+    ///
+    /// Shortcut for `r_transaction()?.scan().primary()?.all()?` collected in a `Vec`, so the
+    /// values don't borrow the transaction.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Data { id: 2 })?;
+    ///     rw.insert(Data { id: 1 })?;
+    ///     rw.commit()?;
+    ///
+    ///     let values: Vec<Data> = db.scan_all()?;
+    ///     assert_eq!(values.iter().map(|data| data.id).collect::<Vec<_>>(), vec![1, 2]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn scan_all<T: ToInput>(&self) -> Result<Vec<T>> {
+        let r = self.r_transaction()?;
+        let values = r.scan().primary()?.all()?.collect::<Result<Vec<T>>>()?;
+        Ok(values)
+    }
+
+    /// Writes a debug dump of the values of the model `T`, one line per value in the order of the
+    /// table (by primary key, or by its hash with `#[native_db(hashed_primary_key)]`): the
+    /// primary key in hexadecimal (as encoded by [`ToKey`](crate::ToKey)), a tab and the value
+    /// formatted with `{:?}`.
+    ///
+    /// Only [`Debug`] is required, unlike an export in a serialization format.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize, Debug)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u16,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Data { id: 10 })?;
+    ///     rw.commit()?;
+    ///
+    ///     let mut dump = vec![];
+    ///     db.dump_table::<Data>(&mut dump)?;
+    ///     assert_eq!(String::from_utf8(dump).unwrap(), "000a\tData { id: 10 }\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn dump_table<T: ToInput + Debug>(&self, mut w: impl Write) -> Result<()> {
+        let r = self.r_transaction()?;
+        let table = r.internal.get_primary_table(&T::native_db_model())?;
+        for result in table.iter()? {
+            let (_, value) = result?;
+            check_value_size(value.value(), self.max_value_size)?;
+            let value =
+                T::native_db_bincode_decode_from_slice_limited(value.value(), self.max_value_size)?;
+            // The stored key of a model with `hashed_primary_key` is a hash.
+            for byte in value.native_db_primary_key().as_slice() {
+                write!(w, "{:02x}", byte)?;
+            }
+            writeln!(w, "\t{:?}", value)?;
+        }
         Ok(())
     }
 
@@ -143,6 +534,65 @@ impl<'a> Database<'a> {
         &self.metadata
     }
 
+    /// Returns the path of the database file, `None` if the database is in memory or created
+    /// with [`from_redb`](crate::Builder::from_redb).
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///     assert!(db.path().is_none());
+    ///     assert!(db.is_in_memory());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn path(&self) -> Option<&Path> {
+        self.instance.path()
+    }
+
+    /// Returns `true` if the database has been created with [`create_in_memory`](crate::Builder::create_in_memory).
+    pub fn is_in_memory(&self) -> bool {
+        self.instance.is_in_memory()
+    }
+
+    /// Returns the sequence number of the latest commit: incremented by one on each successful
+    /// [`commit`](crate::transaction::RwTransaction::commit), starting from `0` when the database
+    /// is opened.
+    ///
+    /// Allows a client to checkpoint the commits it has already processed. The sequence number of
+    /// a given commit is returned by
+    /// [`commit_with_info`](crate::transaction::RwTransaction::commit_with_info), reading this
+    /// method after the commit may already see the next ones.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///     let before = db.current_commit_sequence();
+    ///     db.rw_transaction()?.commit()?;
+    ///     assert_eq!(db.current_commit_sequence(), before + 1);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn current_commit_sequence(&self) -> u64 {
+        self.commit_sequence.load(Ordering::Relaxed)
+    }
+
+    /// Returns the underlying redb database, e.g. to use raw redb tables alongside the
+    /// native_db ones (see [`from_redb`](crate::Builder::from_redb)).
+    ///
+    /// Do not write to the native_db tables directly: the secondary keys would no longer
+    /// match, and the watchers would not be notified.
+    pub fn redb_database(&self) -> Result<&redb::Database> {
+        self.instance.redb_database()
+    }
+
     /// Check the integrity of the database.
     ///
     /// Similar to [redb::Database::check_integrity()](https://docs.rs/redb/latest/redb/struct.Database.html#method.check_integrity).
@@ -151,12 +601,470 @@ impl<'a> Database<'a> {
         Ok(true)
     }
 
+    /// Check the health of the database in one call, e.g. for a liveness probe:
+    /// - no file is left behind by an interrupted upgrade,
+    /// - the database was not written by a newer version of native_db,
+    /// - the number of values of each model.
+    ///
+    /// The issues are reported in the [`HealthReport`](crate::HealthReport) instead of
+    /// being returned as errors. The database is only read. The redb integrity check needs
+    /// exclusive access, use [`health_check_full`](Self::health_check_full) for it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///     let report = db.health_check()?;
+    ///     assert!(report.is_healthy, "{:?}", report.issues);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn health_check(&self) -> Result<HealthReport> {
+        let mut issues = vec![];
+
+        let stale_upgrade_file = self
+            .instance
+            .path()
+            .and_then(upgrade::interrupted_upgrade_file);
+        if let Some(file) = &stale_upgrade_file {
+            issues.push(format!(
+                "an interrupted upgrade left the file {}",
+                file.display()
+            ));
+        }
+
+        let current_version = metadata::CURRENT_VERSION.to_string();
+        let stored_version =
+            match metadata::load_metadata(&self.instance, &self.metadata_table_name)? {
+                Some(stored) => stored
+                    .previous_version()
+                    .unwrap_or(stored.current_version())
+                    .to_string(),
+                None => String::new(),
+            };
+        match (
+            semver::Version::parse(&stored_version),
+            semver::Version::parse(&current_version),
+        ) {
+            _ if stored_version.is_empty() => {
+                issues.push("the metadata table is missing".to_string())
+            }
+            (Ok(stored), Ok(current)) if stored <= current => {}
+            (Ok(_), Ok(_)) => issues.push(format!(
+                "the database was written by native_db {} but the running version is {}",
+                stored_version, current_version
+            )),
+            _ => issues.push(format!(
+                "the database has an invalid native_db version {}",
+                stored_version
+            )),
+        }
+
+        let r = self.r_transaction()?;
+        let mut row_counts = vec![];
+        for primary_table in self.primary_table_definitions.values() {
+            row_counts.push((
+                primary_table.model.primary_key.unique_table_name.clone(),
+                r.internal.primary_len(primary_table.model.clone())?,
+            ));
+        }
+        row_counts.sort();
+
+        Ok(HealthReport {
+            is_healthy: issues.is_empty(),
+            issues,
+            integrity: None,
+            stale_upgrade_file,
+            stored_version,
+            current_version,
+            row_counts,
+        })
+    }
+
+    /// Like [`health_check`](Self::health_check), and run the redb integrity check first (see
+    /// [`check_integrity`](Self::check_integrity)), which needs exclusive access to the
+    /// database. A corrupted database, even if repaired by the check, is reported unhealthy.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let mut db = Builder::new().create_in_memory(&models)?;
+    ///     let report = db.health_check_full()?;
+    ///     assert_eq!(report.integrity, Some(true));
+    ///     assert!(report.is_healthy, "{:?}", report.issues);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn health_check_full(&mut self) -> Result<HealthReport> {
+        let integrity_issue = match self.instance.redb_database_mut()?.check_integrity() {
+            Ok(true) => None,
+            Ok(false) => Some("the redb integrity check repaired the database".to_string()),
+            Err(error) => Some(format!("the redb integrity check failed: {}", error)),
+        };
+        let mut report = self.health_check()?;
+        report.integrity = Some(integrity_issue.is_none());
+        if let Some(issue) = integrity_issue {
+            report.issues.insert(0, issue);
+            report.is_healthy = false;
+        }
+        Ok(report)
+    }
+
     /// Compact the database.
     ///
     /// Similar to [redb::Database::compact()](https://docs.rs/redb/latest/redb/struct.Database.html#method.compact).
+    ///
+    /// Redb refuses to compact while a read transaction is still alive, because it pins the pages
+    /// that compaction would relocate. In that case this method returns
+    /// [`CompactionBlockedByReaders`](crate::db_type::Error::CompactionBlockedByReaders) with the number
+    /// of read transactions still open, so you can drop them and retry.
+    ///
+    /// Compaction also needs the write lock. `&mut self` guarantees that no other thread can open
+    /// a read-write transaction meanwhile (share the database behind a `RwLock` to compact it from
+    /// a long-lived application, the writers then wait for the compaction). If a read-write
+    /// transaction is still alive anyway (e.g. leaked with [`std::mem::forget`]), this method
+    /// returns [`CompactionBlockedByWriter`](crate::db_type::Error::CompactionBlockedByWriter)
+    /// instead of waiting for it forever.
     pub fn compact(&mut self) -> Result<bool> {
-        self.instance.redb_database_mut()?.compact()?;
-        Ok(true)
+        if self.write_transaction_open.load(Ordering::SeqCst) {
+            return Err(Error::CompactionBlockedByWriter);
+        }
+        match self.instance.redb_database_mut()?.compact() {
+            Err(redb::CompactionError::TransactionInProgress) => {
+                Err(Error::CompactionBlockedByReaders {
+                    reader_count: self.read_transactions_counter.load(Ordering::SeqCst),
+                })
+            }
+            Err(error) => Err(error.into()),
+            Ok(_) => Ok(true),
+        }
+    }
+
+    /// Like [`compact`](Self::compact), but reports the progress of the compaction to `progress`,
+    /// from `0.0` to `1.0`, e.g. to show that the application is busy. Returns the time spent
+    /// compacting.
+    ///
+    /// Redb compacts the file in a single call without reporting its progress, so `progress` is
+    /// called with `0.0` when the compaction starts and with `1.0` when it succeeds. If the
+    /// compaction fails, the error is returned without calling `progress` with `1.0`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let mut db = Builder::new().create_in_memory(&models)?;
+    ///     let elapsed = db.compact_with_progress(|progress| println!("{:.0}%", progress * 100.0))?;
+    ///     println!("compacted in {:?}", elapsed);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn compact_with_progress(&mut self, mut progress: impl FnMut(f64)) -> Result<Duration> {
+        let start = Instant::now();
+        progress(0.0);
+        self.compact()?;
+        progress(1.0);
+        Ok(start.elapsed())
+    }
+
+    /// Like [`compact`](Self::compact), but keeps a copy of the database file until the
+    /// compaction succeeds.
+    ///
+    /// The file is first copied to `{file name}.pre-compact` next to it, then compacted. The copy
+    /// is removed on success. If the compaction fails, the file is replaced by the copy and
+    /// reopened. If the process crashes during the compaction, the copy is left behind: it holds
+    /// the data as it was before the compaction and can replace the database file.
+    ///
+    /// Returns the error of [`compact`](Self::compact) if the compaction is blocked (by an open
+    /// transaction or a savepoint), the file is left as is. Returns
+    /// [`CompactionFailed`](crate::db_type::Error::CompactionFailed) if the compaction failed and
+    /// the file was restored. If the file can't be restored, the error is returned and the
+    /// database is closed: its operations fail with
+    /// [`DatabaseClosed`](crate::db_type::Error::DatabaseClosed), and the copy is left behind.
+    ///
+    /// The copy needs as much free disk space as the database file, so up to twice its size in
+    /// total. A database without file (in memory or [`from_redb`](crate::Builder::from_redb)) is
+    /// compacted without copy.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let mut db = Builder::new().create_in_memory(&models)?;
+    ///     db.compact_safe()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn compact_safe(&mut self) -> Result<()> {
+        let Some(path) = self.instance.path().map(Path::to_path_buf) else {
+            self.compact()?;
+            return Ok(());
+        };
+        if self.write_transaction_open.load(Ordering::SeqCst) {
+            return Err(Error::CompactionBlockedByWriter);
+        }
+
+        let backup_path = pre_compact_path(&path);
+        std::fs::copy(&path, &backup_path)?;
+        match self.compact() {
+            Ok(_) => {
+                std::fs::remove_file(&backup_path)?;
+                Ok(())
+            }
+            // Blocked: nothing was written.
+            Err(
+                error @ (Error::CompactionBlockedByWriter
+                | Error::CompactionBlockedByReaders { .. }
+                | Error::RedbCompactionError(
+                    redb::CompactionError::PersistentSavepointExists
+                    | redb::CompactionError::EphemeralSavepointExists,
+                )),
+            ) => {
+                std::fs::remove_file(&backup_path)?;
+                Err(error)
+            }
+            Err(error) => {
+                let mut redb_builder = redb::Builder::new();
+                if let Some(cache_size_bytes) = self.cache_size_bytes {
+                    redb_builder.set_cache_size(cache_size_bytes);
+                }
+                self.instance.restore_on_disk(redb_builder, &backup_path)?;
+                Err(Error::CompactionFailed(Box::new(error)))
+            }
+        }
+    }
+
+    /// Compact the database repeatedly until a pass reclaims less than one page (4 KiB), or after
+    /// 16 passes. Useful after removing a large part of the data.
+    ///
+    /// Like [`compact`](Self::compact), it requires exclusive access: all the read transactions
+    /// must be dropped.
+    ///
+    /// The reclaimed bytes are measured on the file size, or on the fragmented bytes reported by
+    /// redb when the database has no file (in memory or [`from_redb`](crate::Builder::from_redb)).
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let mut db = Builder::new().create_in_memory(&models)?;
+    ///     let report = db.compact_fully()?;
+    ///     assert!(report.iterations >= 1);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn compact_fully(&mut self) -> Result<CompactReport> {
+        self.compact_passes(None)
+    }
+
+    /// Like [`compact_fully`](Self::compact_fully), but stops once `max` would be exceeded.
+    ///
+    /// A redb compaction pass can't be interrupted, so the budget is best effort: a pass is only
+    /// started when its estimated duration fits in the remaining budget. The first estimate
+    /// assumes a conservative throughput, the following ones use the throughput measured on the
+    /// previous pass. [`CompactReport::completed`] is `false` when a pass was skipped, and
+    /// `iterations` is `0` when nothing was done at all.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use std::time::Duration;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let mut db = Builder::new().create_in_memory(&models)?;
+    ///     let report = db.compact_timeboxed(Duration::from_secs(1))?;
+    ///     assert!(report.completed);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn compact_timeboxed(&mut self, max: Duration) -> Result<CompactReport> {
+        self.compact_passes(Some(max))
+    }
+
+    fn compact_passes(&mut self, max: Option<Duration>) -> Result<CompactReport> {
+        const MIN_RECLAIMED_BYTES: u64 = 4096;
+        const MAX_ITERATIONS: u32 = 16;
+        // Estimated throughput of the first pass, on a slow device.
+        const INITIAL_BYTES_PER_SECOND: f64 = 16.0 * 1024.0 * 1024.0;
+
+        let start = Instant::now();
+        let mut report = CompactReport::default();
+        let mut bytes_per_second = INITIAL_BYTES_PER_SECOND;
+        let mut size = self.compactable_size()?;
+        while report.iterations < MAX_ITERATIONS {
+            if let Some(max) = max {
+                let estimated = Duration::from_secs_f64(size as f64 / bytes_per_second);
+                if start.elapsed() + estimated > max {
+                    report.elapsed = start.elapsed();
+                    return Ok(report);
+                }
+            }
+            let pass_start = Instant::now();
+            self.compact()?;
+            let pass_elapsed = pass_start.elapsed().as_secs_f64();
+            if pass_elapsed > 0.0 {
+                bytes_per_second = size as f64 / pass_elapsed;
+            }
+            report.iterations += 1;
+            let new_size = self.compactable_size()?;
+            let reclaimed = size.saturating_sub(new_size);
+            report.reclaimed_bytes += reclaimed;
+            size = new_size;
+            if reclaimed < MIN_RECLAIMED_BYTES {
+                break;
+            }
+        }
+        report.completed = true;
+        report.elapsed = start.elapsed();
+        Ok(report)
+    }
+
+    fn compactable_size(&self) -> Result<u64> {
+        if let Some(path) = self.instance.path() {
+            return Ok(std::fs::metadata(path)?.len());
+        }
+        let txn = self.instance.redb_database()?.begin_write()?;
+        let fragmented_bytes = txn.stats()?.fragmented_bytes();
+        txn.abort()?;
+        Ok(fragmented_bytes)
+    }
+
+    /// Share of the file that a compaction is estimated to reclaim, see
+    /// [`Builder::set_auto_compact_on_open`](crate::Builder::set_auto_compact_on_open).
+    pub(crate) fn reclaimable_ratio(&self) -> Result<f64> {
+        let Some(path) = self.instance.path() else {
+            return Ok(0.0);
+        };
+        let file_size = std::fs::metadata(path)?.len();
+        if file_size == 0 {
+            return Ok(0.0);
+        }
+        let txn = self.instance.redb_database()?.begin_write()?;
+        let fragmented_bytes = txn.stats()?.fragmented_bytes();
+        txn.abort()?;
+        Ok(fragmented_bytes as f64 / file_size as f64)
+    }
+
+    /// Rename a table, e.g. after renaming the primary key of a model, which changes the name of its
+    /// primary table: `<id>_<version>_<primary key name>`. The same goes for the secondary tables:
+    /// `<id>_<version>_<secondary key name>`.
+    ///
+    /// All the entries are moved from the table `old` to the table `new` in a single transaction,
+    /// then `old` is deleted. When `old` is a primary table, its hashed primary keys and its blobs
+    /// are moved too, and the metadata recorded for it (compression, encoding and codec of the
+    /// values, encoding of the keys) replaces the one of `new`. When `old` is a secondary table,
+    /// the values it stores are moved too.
+    ///
+    /// Returns error:
+    /// - [`TableDefinitionNotFound`](crate::db_type::Error::TableDefinitionNotFound) if `old` does not exist.
+    /// - [`TableAlreadyExists`](crate::db_type::Error::TableAlreadyExists) if `new` already contains
+    ///   entries. An empty table, e.g. created when the database is opened with the renamed model, is
+    ///   replaced.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// // The primary key of the model `id=1, version=1` was renamed from `id` to `key`
+    /// let db = Builder::new().open(&models, path)?;
+    /// db.rename_table("1_1_id", "1_1_key")?;
+    /// ```
+    pub fn rename_table(&self, old: &str, new: &str) -> Result<()> {
+        if self.mirror.is_some() {
+            return Err(Error::NotMirrored {
+                operation: "rename_table".to_string(),
+            });
+        }
+        let rw = self.instance.redb_database()?.begin_write()?;
+        let is_table = |txn: &redb::WriteTransaction, name: &str| -> Result<bool> {
+            Ok(txn.list_tables()?.any(|table| table.name() == name))
+        };
+        let is_multimap_table = |txn: &redb::WriteTransaction, name: &str| -> Result<bool> {
+            Ok(txn
+                .list_multimap_tables()?
+                .any(|table| table.name() == name))
+        };
+
+        if is_table(&rw, new)? && is_multimap_table(&rw, old)?
+            || is_multimap_table(&rw, new)? && is_table(&rw, old)?
+        {
+            return Err(Error::TableAlreadyExists {
+                table: new.to_string(),
+            });
+        }
+
+        let mut renames = vec![];
+        if is_table(&rw, old)? {
+            metadata::move_table_stamps(&rw, &self.metadata_table_name, old, new)?;
+            renames.push((old.to_string(), new.to_string()));
+            for suffix in ["_hashed", "_blob"] {
+                let old_suffixed = format!("{}{}", old, suffix);
+                if is_table(&rw, &old_suffixed)? {
+                    renames.push((old_suffixed, format!("{}{}", new, suffix)));
+                }
+            }
+        } else if is_multimap_table(&rw, old)? {
+            let old_value = format!("{}_value", old);
+            if is_table(&rw, &old_value)? {
+                renames.push((old_value, format!("{}_value", new)));
+            }
+            let old: redb::MultimapTableDefinition<Key, Key> =
+                redb::MultimapTableDefinition::new(old);
+            let new: redb::MultimapTableDefinition<Key, Key> =
+                redb::MultimapTableDefinition::new(new);
+            {
+                let old_table = rw.open_multimap_table(old)?;
+                let mut new_table = rw.open_multimap_table(new)?;
+                if !new_table.is_empty()? {
+                    return Err(Error::TableAlreadyExists {
+                        table: new.name().to_string(),
+                    });
+                }
+                for result in old_table.iter()? {
+                    let (key, values) = result?;
+                    for value in values {
+                        new_table.insert(key.value(), value?.value())?;
+                    }
+                }
+            }
+            rw.delete_multimap_table(old)?;
+        } else {
+            return Err(Error::TableDefinitionNotFound {
+                table: old.to_string(),
+            });
+        }
+
+        for (old, new) in renames {
+            let old: redb::TableDefinition<Key, &[u8]> = redb::TableDefinition::new(&old);
+            let new: redb::TableDefinition<Key, &[u8]> = redb::TableDefinition::new(&new);
+            {
+                let old_table = rw.open_table(old)?;
+                let mut new_table = rw.open_table(new)?;
+                if !new_table.is_empty()? {
+                    return Err(Error::TableAlreadyExists {
+                        table: new.name().to_string(),
+                    });
+                }
+                for result in old_table.iter()? {
+                    let (key, value) = result?;
+                    new_table.insert(key.value(), value.value())?;
+                }
+            }
+            rw.delete_table(old)?;
+        }
+
+        rw.commit()?;
+        Ok(())
     }
 
     /// Returns true if the database is upgrading from the given version selector.
@@ -261,3 +1169,25 @@ impl<'a> Database<'a> {
         })
     }
 }
+
+/// Returns the path of the copy made by [`compact_safe`](Database::compact_safe).
+fn pre_compact_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".pre-compact");
+    path.with_file_name(file_name)
+}
+
+/// Result of [`compact_fully`](Database::compact_fully) and
+/// [`compact_timeboxed`](Database::compact_timeboxed).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CompactReport {
+    /// Number of compaction passes.
+    pub iterations: u32,
+    /// Total number of bytes reclaimed by all the passes.
+    pub reclaimed_bytes: u64,
+    /// `false` if [`compact_timeboxed`](Database::compact_timeboxed) skipped a pass because it
+    /// would not fit in the budget.
+    pub completed: bool,
+    /// Time spent compacting.
+    pub elapsed: Duration,
+}
@@ -1,16 +1,45 @@
+use crate::counters::COUNTERS_TABLE;
 use crate::database_instance::DatabaseInstance;
-use crate::db_type::{Error, Result};
-use crate::table_definition::NativeModelOptions;
+use crate::db_type::{Error, Key, KeyDefinition, KeyOptions, Result, ToInput, ToKeyDefinition};
+use crate::mirror::Mirror;
+use crate::savepoints::SAVEPOINTS_TABLE;
+use crate::table_definition::{NativeModelOptions, RedbPrimaryTableDefinition};
+use crate::transaction::internal::rw_transaction::InternalRwTransaction;
+use crate::transaction::RwTransaction;
+use crate::unique_secondary_cache::UniqueSecondaryCaches;
 use crate::{metadata, Models};
 use crate::{upgrade, watch, Database, Model};
+use redb::{ReadableTableMetadata, TableHandle};
 use std::collections::HashMap;
-use std::path::Path;
-use std::sync::atomic::AtomicU64;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize};
 use std::sync::{Arc, RwLock};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct Configuration {
     pub(crate) cache_size_bytes: Option<usize>,
+    pub(crate) open_integrity_check: bool,
+    pub(crate) repair_on_open: bool,
+    pub(crate) on_commit: Option<OnCommit>,
+    pub(crate) unique_secondary_caches: Vec<(Model, KeyDefinition<KeyOptions>)>,
+    pub(crate) mirror_path: Option<PathBuf>,
+    pub(crate) metadata_table_name: String,
+    pub(crate) max_value_size: Option<usize>,
+    pub(crate) max_db_size: Option<u64>,
+    pub(crate) auto_compact_on_open: Option<f64>,
+    // Set by `Builder::upgrade_key_encoding` to open the tables whose keys must be re-keyed.
+    pub(crate) allow_outdated_key_encoding: bool,
+}
+
+/// Hook registered with [`Builder::on_commit`].
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub(crate) struct OnCommit(pub(crate) Arc<dyn Fn(&[watch::Event]) + Send + Sync>);
+
+impl std::fmt::Debug for OnCommit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "OnCommit")
+    }
 }
 
 impl Configuration {
@@ -19,6 +48,9 @@ impl Configuration {
         if let Some(cache_size_bytes) = self.cache_size_bytes {
             redb_builder.set_cache_size(cache_size_bytes);
         }
+        if !self.repair_on_open {
+            redb_builder.set_repair_callback(|session| session.abort());
+        }
         redb_builder
     }
 }
@@ -33,6 +65,25 @@ impl Configuration {
         redb_builder
     }
 }
+/// Whether [`Builder::open_reporting`] created the database or opened an existing one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenKind {
+    /// The database was initialized by this call, e.g. the file did not exist.
+    CreatedNew,
+    /// The database was already initialized.
+    OpenedExisting,
+}
+
+/// Result of [`Builder::upgrade_key_encoding`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct KeyEncodingReport {
+    /// Names of the primary tables re-keyed, e.g. `"1_1_id"`.
+    pub upgraded_tables: Vec<String>,
+    /// Values that can not be decoded, by primary table name and primary key in the old
+    /// encoding. They are left under their old key, without their secondary keys.
+    pub skipped: Vec<(String, Key)>,
+}
+
 /// Builder that allows you to create a [`Database`](crate::Database) instance via [`create`](Self::create) or [`open`](Self::open) etc.
 #[derive(Debug)]
 pub struct Builder {
@@ -44,8 +95,17 @@ impl Builder {
         &self,
         database_instance: DatabaseInstance,
         models: &'a Models,
-    ) -> Result<Database<'a>> {
-        let database_metadata = metadata::load_or_create_metadata(&database_instance)?;
+    ) -> Result<(Database<'a>, OpenKind)> {
+        let metadata_table_name = &self.database_configuration.metadata_table_name;
+        check_metadata_table_name(metadata_table_name)?;
+        check_forward_version(&database_instance, models)?;
+        let open_kind = if metadata::metadata_exists(&database_instance, metadata_table_name)? {
+            OpenKind::OpenedExisting
+        } else {
+            OpenKind::CreatedNew
+        };
+        let database_metadata =
+            metadata::load_or_create_metadata(&database_instance, metadata_table_name)?;
 
         let mut database = Database {
             instance: database_instance,
@@ -53,18 +113,125 @@ impl Builder {
             primary_table_definitions: HashMap::new(),
             watchers: Arc::new(RwLock::new(watch::Watchers::new())),
             watchers_counter_id: AtomicU64::new(0),
+            read_transactions_counter: AtomicUsize::new(0),
+            write_transaction_open: AtomicBool::new(false),
+            commit_sequence: AtomicU64::new(0),
+            on_commit: self.database_configuration.on_commit.clone(),
+            unique_secondary_caches: UniqueSecondaryCaches::default(),
+            mirror: None,
+            cache_size_bytes: self.database_configuration.cache_size_bytes,
+            metadata_table_name: metadata_table_name.clone(),
+            max_value_size: self.database_configuration.max_value_size,
+            max_db_size: self.database_configuration.max_db_size,
         };
 
         for (_, model_builder) in models.models_builder.iter() {
-            database.seed_model(model_builder)?;
+            if database.seed_model(model_builder)?
+                && !self.database_configuration.allow_outdated_key_encoding
+            {
+                return Err(Error::KeyEncodingUpgradeRequired {
+                    table: model_builder.model.primary_key.unique_table_name.clone(),
+                });
+            }
+        }
+
+        for (model, key_def) in &self.database_configuration.unique_secondary_caches {
+            model.check_secondary_options(key_def, |options| options.unique)?;
+            database.load_unique_secondary_cache(model, key_def)?;
+        }
+
+        if let Some(mirror_path) = &self.database_configuration.mirror_path {
+            let mut mirror_builder = Builder::new();
+            mirror_builder.database_configuration.cache_size_bytes =
+                self.database_configuration.cache_size_bytes;
+            mirror_builder
+                .database_configuration
+                .metadata_table_name
+                .clone_from(metadata_table_name);
+            let mirror = if mirror_path.exists() {
+                mirror_builder.open(models, mirror_path)?
+            } else {
+                mirror_builder.create(models, mirror_path)?
+            };
+            database.mirror = Some(Mirror::new(mirror));
         }
 
         // TODO: Maybe we can do some migration with models here.
 
-        Ok(database)
+        Ok((database, open_kind))
     }
 }
 
+/// Integrity check of [`Builder::set_open_integrity_check`], a database repaired by the check
+/// is reported as corrupted.
+fn check_open_integrity(database_instance: &mut DatabaseInstance) -> Result<()> {
+    if !database_instance.redb_database_mut()?.check_integrity()? {
+        return Err(Error::DatabaseCorrupted {
+            detail: "the redb integrity check repaired the database".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Refuse a metadata table name used by native_db for other tables.
+fn check_metadata_table_name(name: &str) -> Result<()> {
+    // Model table names are formatted as "{id}_{version}_{name}".
+    let mut parts = name.splitn(3, '_');
+    let is_model_table = matches!(
+        (
+            parts.next().map(str::parse::<u32>),
+            parts.next().map(str::parse::<u32>)
+        ),
+        (Some(Ok(_)), Some(Ok(_)))
+    );
+    if is_model_table || name == COUNTERS_TABLE.name() || name == SAVEPOINTS_TABLE.name() {
+        return Err(Error::MetadataTableNameCollision {
+            name: name.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Refuse to open a database containing data written with a newer version of a model
+/// than the defined ones (e.g. after a rollback): the older model would misdecode it.
+fn check_forward_version(database_instance: &DatabaseInstance, models: &Models) -> Result<()> {
+    let mut max_known_versions: HashMap<u32, u32> = HashMap::new();
+    for model_builder in models.models_builder.values() {
+        let options = &model_builder.native_model_options;
+        let max_known = max_known_versions
+            .entry(options.native_model_id)
+            .or_insert(options.native_model_version);
+        *max_known = (*max_known).max(options.native_model_version);
+    }
+
+    let rx = database_instance.redb_database()?.begin_read()?;
+    for table in rx.list_tables()? {
+        // Primary table names are formatted as "{id}_{version}_{name}".
+        let mut parts = table.name().splitn(3, '_');
+        let (Some(Ok(id)), Some(Ok(stored_version))) = (
+            parts.next().map(str::parse::<u32>),
+            parts.next().map(str::parse::<u32>),
+        ) else {
+            continue;
+        };
+        let Some(&max_known) = max_known_versions.get(&id) else {
+            continue;
+        };
+        if stored_version <= max_known {
+            continue;
+        }
+        let table_definition: RedbPrimaryTableDefinition = redb::TableDefinition::new(table.name());
+        if !rx.open_table(table_definition)?.is_empty()? {
+            return Err(Error::ForwardVersionUnsupported {
+                id,
+                stored_version,
+                max_known,
+            });
+        }
+    }
+    Ok(())
+}
+
 impl Default for Builder {
     fn default() -> Self {
         Self::new()
@@ -77,6 +244,16 @@ impl Builder {
         Self {
             database_configuration: Configuration {
                 cache_size_bytes: None,
+                open_integrity_check: false,
+                repair_on_open: true,
+                on_commit: None,
+                unique_secondary_caches: vec![],
+                mirror_path: None,
+                metadata_table_name: metadata::DEFAULT_METADATA_TABLE_NAME.to_string(),
+                max_value_size: None,
+                max_db_size: None,
+                auto_compact_on_open: None,
+                allow_outdated_key_encoding: false,
             },
         }
     }
@@ -87,40 +264,683 @@ impl Builder {
         self
     }
 
+    /// Set the name of the table where native_db stores its metadata (e.g. its version).
+    /// Default: `metadata`.
+    ///
+    /// Useful with [`from_redb`](Self::from_redb), when the redb database already has a table
+    /// named `metadata`. The same name must be used every time the database is opened, otherwise
+    /// a new metadata table is created.
+    ///
+    /// Creating or opening the database fails with [`MetadataTableNameCollision`](crate::db_type::Error::MetadataTableNameCollision)
+    /// if the name is one of the names used for the tables of the models (`{id}_{version}_{name}`)
+    /// or of the counters (`native_db_counters`).
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let _db = Builder::new()
+    ///         .set_metadata_table_name("my_app_native_db_metadata")
+    ///         .create_in_memory(&models)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_metadata_table_name(&mut self, name: &str) -> &mut Self {
+        self.database_configuration.metadata_table_name = name.to_string();
+        self
+    }
+
+    /// Refuse to read the stored values larger than `bytes`. Default: no limit.
+    ///
+    /// A robustness guard for a database that might be corrupted or tampered with: the reads
+    /// (get, scan, etc.) of a larger value return [`ValueTooLarge`](crate::db_type::Error::ValueTooLarge)
+    /// before the value is copied or decoded, instead of attempting a huge allocation. The
+    /// values of a model with `#[native_db(compress = ...)]` are also limited once decompressed.
+    /// The writes are not limited.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let _db = Builder::new()
+    ///         .set_max_value_size(16 * 1024 * 1024)
+    ///         .create_in_memory(&models)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_max_value_size(&mut self, bytes: usize) -> &mut Self {
+        self.database_configuration.max_value_size = Some(bytes);
+        self
+    }
+
+    /// Refuse to commit a read-write transaction which would grow the database beyond `bytes`.
+    /// Default: no limit.
+    ///
+    /// The [`commit`](crate::transaction::RwTransaction::commit) of such a transaction returns
+    /// [`QuotaExceeded`](crate::db_type::Error::QuotaExceeded) and the transaction is aborted. A
+    /// transaction which only removes data is still committed.
+    ///
+    /// The limit is enforced once, at commit, on an estimate: the length of the file when the
+    /// transaction began plus the bytes written by the transaction (keys, values and blobs). An
+    /// update counts the whole new value, even if it replaces a value of the same size. So a
+    /// transaction is refused as a whole, not write by write, and the file can end up somewhat
+    /// larger than the limit: redb grows the file ahead of the allocations.
+    ///
+    /// The length of the file does not go down when data is removed: redb keeps the freed pages
+    /// in the file. Once the limit is reached, remove data then [`compact`](crate::Database::compact)
+    /// the database to write again.
+    ///
+    /// Only the databases with a file are limited, not the ones in memory or created with
+    /// [`from_redb`](Self::from_redb).
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let db_path = std::env::temp_dir().join("native_db_max_db_size.redb");
+    ///     let _db = Builder::new()
+    ///         .set_max_db_size(64 * 1024 * 1024)
+    ///         .create(&models, &db_path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_max_db_size(&mut self, bytes: u64) -> &mut Self {
+        self.database_configuration.max_db_size = Some(bytes);
+        self
+    }
+
+    /// [`compact`](crate::Database::compact) the database when it is opened with
+    /// [`open`](Self::open), if the estimated reclaimable space exceeds `free_ratio` (between `0.0`
+    /// and `1.0`) of the file size. Default: disabled.
+    ///
+    /// Keeps a long-lived database from growing without a separate maintenance job. The
+    /// reclaimable space is estimated from the fragmented bytes reported by redb. The compaction
+    /// rewrites the file, which delays the return of [`open`](Self::open). It is skipped while a
+    /// [savepoint](crate::Database::create_savepoint) exists.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let db_path = std::env::temp_dir().join("native_db_auto_compact_on_open.redb");
+    ///     Builder::new().create(&models, &db_path)?;
+    ///
+    ///     // Compact if more than 30% of the file could be reclaimed
+    ///     let _db = Builder::new()
+    ///         .set_auto_compact_on_open(0.3)
+    ///         .open(&models, &db_path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_auto_compact_on_open(&mut self, free_ratio: f64) -> &mut Self {
+        self.database_configuration.auto_compact_on_open = Some(free_ratio);
+        self
+    }
+
+    /// Run a full [`check_integrity`](crate::Database::check_integrity) when the database
+    /// is opened with [`open`](Self::open). Default: `false`.
+    ///
+    /// What happens on [`open`](Self::open):
+    /// - redb always takes the file lock, checks the file format version and, if the
+    ///   previous process did not shut down cleanly, repairs the database (verifying the
+    ///   page checksums of the last commits, see [`set_repair_on_open`](Self::set_repair_on_open)).
+    ///   A cleanly closed file is trusted as is.
+    /// - native_db then upgrades the file format and the tables if needed, loads (or
+    ///   creates) its metadata and opens the tables of each defined model.
+    ///
+    /// When enabled, a checksum verification of the whole file runs before the upgrade
+    /// steps. A corrupted file is then reported by [`open`](Self::open) with
+    /// [`DatabaseCorrupted`](crate::db_type::Error::DatabaseCorrupted) instead of by a later
+    /// read or write, also when redb could repair it. Enable it for critical applications, keep it disabled for a
+    /// fast startup: the check reads the entire file.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let db_path = std::env::temp_dir().join("native_db_open_integrity_check.redb");
+    ///     Builder::new().create(&models, &db_path)?;
+    ///
+    ///     let _db = Builder::new()
+    ///         .set_open_integrity_check(true)
+    ///         .open(&models, &db_path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_open_integrity_check(&mut self, enabled: bool) -> &mut Self {
+        self.database_configuration.open_integrity_check = enabled;
+        self
+    }
+
+    /// Repair the database when it is opened with [`open`](Self::open) after the previous
+    /// process did not shut down cleanly, e.g. a crash in the middle of a commit. Default: `true`.
+    ///
+    /// The repair rolls the database back to its last complete commit: the changes of the
+    /// transaction being committed during the crash are lost, the previous commits are kept.
+    /// When the repair fails, or when it is disabled and needed, `open` returns
+    /// [`DatabaseCorrupted`](crate::db_type::Error::DatabaseCorrupted) with the reason. A
+    /// truncated file (e.g. by a partial copy) can not be repaired and is reported the same way.
+    ///
+    /// Disable it to decide what to do with such a file, e.g. keep a copy of it before
+    /// opening it again with the repair enabled.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let db_path = std::env::temp_dir().join("native_db_repair_on_open.redb");
+    ///     Builder::new().create(&models, &db_path)?;
+    ///
+    ///     match Builder::new().set_repair_on_open(false).open(&models, &db_path) {
+    ///         Err(db_type::Error::DatabaseCorrupted { detail }) => {
+    ///             eprintln!("{}, repairing", detail);
+    ///             let _db = Builder::new().set_repair_on_open(true).open(&models, &db_path)?;
+    ///         }
+    ///         result => {
+    ///             let _db = result?;
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_repair_on_open(&mut self, enabled: bool) -> &mut Self {
+        self.database_configuration.repair_on_open = enabled;
+        self
+    }
+
+    /// Register a hook called after each successful [`commit`](crate::transaction::RwTransaction::commit)
+    /// with the events of the transaction, in the order they happened.
+    ///
+    /// The events are the same as the ones sent to the [watchers](crate::Database::watch), but for
+    /// all the tables at once. The hook runs synchronously on the committing thread, before
+    /// `commit` returns, so keep it short. It is not called for a transaction without changes.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use std::sync::Arc;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let models = Models::new();
+    ///     let _db = Builder::new()
+    ///         .on_commit(Arc::new(|events: &[watch::Event]| {
+    ///             println!("committed {} changes", events.len());
+    ///         }))
+    ///         .create_in_memory(&models)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn on_commit(&mut self, hook: Arc<dyn Fn(&[watch::Event]) + Send + Sync>) -> &mut Self {
+        self.database_configuration.on_commit = Some(OnCommit(hook));
+        self
+    }
+
+    /// Keep an in memory map of the unique secondary key `key_def` of the model `T`, from
+    /// each secondary key to its primary key. [`get().secondary`](crate::transaction::query::RGet::secondary)
+    /// of a read transaction then finds the item without the lookup in the secondary table.
+    ///
+    /// The map is loaded from the database when it is opened, and updated with the changes
+    /// of each committed transaction; an aborted transaction leaves it untouched. The item
+    /// found with the map is checked against the snapshot of the read transaction, if it does
+    /// not match (e.g. the transaction is older than the map) the secondary table is used.
+    /// Read-write transactions always use the secondary table.
+    ///
+    /// Memory cost: two entries per item, each holding a copy of its secondary key and of its
+    /// primary key. The map is per process, it is not shared with other processes opening the
+    /// same file. Returns an error when the database is opened if the key is not unique.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct User {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key(unique)]
+    ///     email: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<User>()?;
+    ///     let db = Builder::new()
+    ///         .cache_unique_secondary::<User>(UserKey::email)
+    ///         .create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(User { id: 1, email: "a@example.com".to_string() })?;
+    ///     rw.commit()?;
+    ///
+    ///     let r = db.r_transaction()?;
+    ///     let user: Option<User> = r.get().secondary(UserKey::email, "a@example.com")?;
+    ///     assert_eq!(user.map(|user| user.id), Some(1));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn cache_unique_secondary<T: ToInput>(
+        &mut self,
+        key_def: impl ToKeyDefinition<KeyOptions>,
+    ) -> &mut Self {
+        self.database_configuration
+            .unique_secondary_caches
+            .push((T::native_db_model(), key_def.key_definition()));
+        self
+    }
+
+    /// Mirror the database to a second file at `mirror_path`, e.g. to keep a warm standby.
+    ///
+    /// When the database is opened, the mirror is replaced by a copy of the database file (or
+    /// created empty if the database has no file yet). Then each commit is replayed on the
+    /// mirror, in a transaction of its own.
+    ///
+    /// Consistency: the mirror is written synchronously, after the database commit and before
+    /// [`commit`](crate::transaction::RwTransaction::commit) returns, so it doesn't lag once
+    /// `commit` has returned. If the process stops between the two commits, the mirror lags by
+    /// one commit until the next open. If the mirror can't be written, `commit` returns
+    /// [`MirrorOutOfSync`](crate::db_type::Error::MirrorOutOfSync) although the database is
+    /// committed. Only the changes sent to the [watchers](crate::Database::watch) and the
+    /// [counters](crate::Database::counters) are replayed, the other operations return
+    /// [`NotMirrored`](crate::db_type::Error::NotMirrored) while a mirror is set:
+    /// [`drain`](crate::transaction::RwTransaction::drain), [`convert_all`](crate::transaction::RwTransaction::convert_all),
+    /// [`migrate`](crate::transaction::RwTransaction::migrate), [`migrate_with`](crate::transaction::RwTransaction::migrate_with),
+    /// [`refresh`](crate::transaction::RwTransaction::refresh), the blobs and
+    /// [`rename_table`](crate::Database::rename_table). Run them on a database opened without
+    /// the mirror, it is replaced by a copy at the next open with the mirror.
+    ///
+    /// The mirror must not be opened for writing while the database is open.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let dir = std::env::temp_dir().join("native_db_with_mirror_example");
+    ///     std::fs::create_dir_all(&dir)?;
+    ///     let models = Models::new();
+    ///     let _db = Builder::new()
+    ///         .with_mirror(dir.join("mirror.redb"))
+    ///         .create(&models, dir.join("db.redb"))?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_mirror(&mut self, mirror_path: impl AsRef<Path>) -> &mut Self {
+        self.database_configuration.mirror_path = Some(mirror_path.as_ref().to_path_buf());
+        self
+    }
+
+    fn copy_to_mirror(&self, path: impl AsRef<Path>) -> Result<()> {
+        let Some(mirror_path) = &self.database_configuration.mirror_path else {
+            return Ok(());
+        };
+        let path = path.as_ref();
+        if mirror_path == path {
+            return Err(Error::MirrorPathIsDatabasePath {
+                path: path.to_path_buf(),
+            });
+        }
+        if path.exists() {
+            std::fs::copy(path, mirror_path)?;
+        }
+        Ok(())
+    }
+
     /// Creates a new `Db` instance using the given path.
     ///
     /// Similar to [redb::Builder.create(...)](https://docs.rs/redb/latest/redb/struct.Builder.html#method.create)
     pub fn create<'a>(&self, models: &'a Models, path: impl AsRef<Path>) -> Result<Database<'a>> {
+        self.copy_to_mirror(&path)?;
         let builder = self.database_configuration.new_rdb_builder();
         let database_instance = DatabaseInstance::create_on_disk(builder, path)?;
-        self.init(database_instance, models)
+        let (database, _) = self.init(database_instance, models)?;
+        Ok(database)
+    }
+
+    /// Opens the database at `path` if it is initialized, otherwise creates it and runs `seed`
+    /// in a read-write transaction, e.g. to insert the default values on the first run.
+    ///
+    /// Like [`open_reporting`](Self::open_reporting), the database is seeded when it is reported
+    /// as [`CreatedNew`](OpenKind::CreatedNew): an empty file, e.g. created by another process,
+    /// is seeded too.
+    ///
+    /// The transaction is committed if `seed` succeeds. If `seed` or the commit fails, the new
+    /// database file is removed (or, for a file that already existed, its native_db metadata)
+    /// and the error is returned, so the next call seeds again.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Setting {
+    ///     #[primary_key]
+    ///     name: String,
+    ///     value: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Setting>()?;
+    ///     let db_path = std::env::temp_dir().join("native_db_create_with_seed.redb");
+    ///     let _db = Builder::new().create_with_seed(&models, &db_path, |rw| {
+    ///         rw.insert(Setting {
+    ///             name: "theme".to_string(),
+    ///             value: "dark".to_string(),
+    ///         })
+    ///     })?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn create_with_seed<'a>(
+        &self,
+        models: &'a Models,
+        path: impl AsRef<Path>,
+        seed: impl FnOnce(&mut RwTransaction) -> Result<()>,
+    ) -> Result<Database<'a>> {
+        let path = path.as_ref();
+        let file_existed = path.exists();
+        let (database, open_kind) = self.open_or_create(models, path, true)?;
+        if open_kind == OpenKind::OpenedExisting {
+            return Ok(database);
+        }
+        let seeded = database.rw_transaction().and_then(|mut rw| {
+            seed(&mut rw)?;
+            rw.commit()
+        });
+        if let Err(error) = seeded {
+            if file_existed {
+                metadata::remove_metadata(
+                    &database.instance,
+                    &self.database_configuration.metadata_table_name,
+                )?;
+                drop(database);
+            } else {
+                drop(database);
+                std::fs::remove_file(path)?;
+            }
+            return Err(error);
+        }
+        Ok(database)
     }
 
     /// Similar to [redb::Builder::open(...)](https://docs.rs/redb/latest/redb/struct.Builder.html#method.open)
     /// But it also upgrades the database if needed.
+    ///
+    /// The upgrade of a legacy file copies the data to a `{file name}_redb2` file next to it,
+    /// then replaces the legacy file by the copy. If a previous upgrade was interrupted by a
+    /// crash, the copy is left behind: `open` removes it (and starts the upgrade over) or, if
+    /// the legacy file was already removed, completes the replacement. A copy still locked by
+    /// a running upgrade is not touched, `open` returns an error for which
+    /// [`is_conflict`](crate::db_type::Error::is_conflict) is `true`.
+    ///
+    /// The upgrade of a file created with redb 1.x (native_db 0.5.x) requires the feature
+    /// `upgrade_0_5_x`, without it `open` returns
+    /// [`RedbUpgradeAvailable`](crate::db_type::Error::RedbUpgradeAvailable).
+    ///
+    /// A model whose keys were stored by a previous version with an older encoding, e.g. the
+    /// signed integers and the floats before they were sorted in numeric order, is refused with
+    /// [`KeyEncodingUpgradeRequired`](crate::db_type::Error::KeyEncodingUpgradeRequired), see
+    /// [`upgrade_key_encoding`](Self::upgrade_key_encoding). A file written by a newer encoding
+    /// is refused with [`KeyEncodingMismatch`](crate::db_type::Error::KeyEncodingMismatch).
+    ///
+    /// A file left by a crash is repaired, losing the transaction being committed during the
+    /// crash, and a file that can not be repaired is reported as
+    /// [`DatabaseCorrupted`](crate::db_type::Error::DatabaseCorrupted), see
+    /// [`set_repair_on_open`](Self::set_repair_on_open).
     pub fn open<'a>(&self, models: &'a Models, path: impl AsRef<Path>) -> Result<Database<'a>> {
+        let (database, _) = self.open_or_create(models, path, false)?;
+        Ok(database)
+    }
+
+    /// Like [`open`](Self::open), but creates the database if the file does not exist and
+    /// reports which of the two happened, e.g. to insert the default values on the first run.
+    ///
+    /// The report comes from the metadata of native_db, not from the existence of the file:
+    /// a file created but not initialized by another process is reported as
+    /// [`CreatedNew`](OpenKind::CreatedNew).
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Setting {
+    ///     #[primary_key]
+    ///     name: String,
+    ///     value: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Setting>()?;
+    ///     let db_path = std::env::temp_dir().join("native_db_open_reporting.redb");
+    ///     let (db, open_kind) = Builder::new().open_reporting(&models, &db_path)?;
+    ///     if open_kind == OpenKind::CreatedNew {
+    ///         let rw = db.rw_transaction()?;
+    ///         rw.insert(Setting {
+    ///             name: "theme".to_string(),
+    ///             value: "dark".to_string(),
+    ///         })?;
+    ///         rw.commit()?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn open_reporting<'a>(
+        &self,
+        models: &'a Models,
+        path: impl AsRef<Path>,
+    ) -> Result<(Database<'a>, OpenKind)> {
+        self.open_or_create(models, path, true)
+    }
+
+    /// Moves the values stored by a previous version with an older encoding of the keys to
+    /// their keys in the current encoding, then [`open`](Self::open) accepts the database.
+    ///
+    /// Only the models whose key types changed encoding are re-keyed, in one write transaction
+    /// per model; the other ones are only recorded with the current encoding. The stored bytes
+    /// of the values are moved as is, they are decoded to compute the keys: a value that can not
+    /// be decoded stays under its old key, without its secondary keys, and is reported in
+    /// [`KeyEncodingReport::skipped`].
+    ///
+    /// The mirror set with [`with_mirror`](Self::with_mirror) is not upgraded, call
+    /// `upgrade_key_encoding` with its path too.
+    pub fn upgrade_key_encoding(
+        &self,
+        models: &Models,
+        path: impl AsRef<Path>,
+    ) -> Result<KeyEncodingReport> {
+        let builder = Builder {
+            database_configuration: Configuration {
+                on_commit: None,
+                unique_secondary_caches: vec![],
+                mirror_path: None,
+                auto_compact_on_open: None,
+                allow_outdated_key_encoding: true,
+                ..self.database_configuration.clone()
+            },
+        };
+        let (database, open_kind) = builder.open_or_create(models, path, false)?;
+        let mut report = KeyEncodingReport::default();
+        for model_builder in models.models_builder.values() {
+            let table_name = &model_builder.model.primary_key.unique_table_name;
+            if let Some(skipped) = database.upgrade_key_encoding(model_builder)? {
+                report.upgraded_tables.push(table_name.clone());
+                report.skipped.extend(
+                    skipped
+                        .into_iter()
+                        .map(|primary_key| (table_name.clone(), primary_key)),
+                );
+            }
+        }
+        if open_kind == OpenKind::CreatedNew {
+            // The next open still reports the previous version as unknown, see
+            // `Database::upgrading_from_version`.
+            metadata::remove_versions(
+                &database.instance,
+                &self.database_configuration.metadata_table_name,
+            )?;
+        }
+        Ok(report)
+    }
+
+    fn open_or_create<'a>(
+        &self,
+        models: &'a Models,
+        path: impl AsRef<Path>,
+        create: bool,
+    ) -> Result<(Database<'a>, OpenKind)> {
+        upgrade::recover_interrupted_upgrade(&self.database_configuration, &path)?;
+        self.copy_to_mirror(&path)?;
         let builder = self.database_configuration.new_rdb_builder();
-        let database_instance = match DatabaseInstance::open_on_disk(builder, &path) {
+        let database_instance = if create {
+            DatabaseInstance::create_on_disk(builder, &path)
+        } else {
+            DatabaseInstance::open_on_disk(builder, &path)
+        };
+        let mut database_instance = match database_instance {
             Err(Error::RedbDatabaseError(redb::DatabaseError::UpgradeRequired(_))) => {
                 upgrade::upgrade_redb(&self.database_configuration, &path, &models.models_builder)
             }
             Err(error) => return Err(error),
             Ok(database_instance) => Ok(database_instance),
         }?;
+        if self.database_configuration.open_integrity_check {
+            check_open_integrity(&mut database_instance)?;
+        }
         upgrade::upgrade_underlying_database(&database_instance, &models.models_builder)?;
-        self.init(database_instance, models)
+        let (mut database, open_kind) = self.init(database_instance, models)?;
+        if let Some(free_ratio) = self.database_configuration.auto_compact_on_open {
+            if database.reclaimable_ratio()? > free_ratio {
+                // Nothing else is open yet: the compaction is only blocked by a savepoint, which
+                // redb counts as a live read transaction. The database is opened uncompacted.
+                match database.compact() {
+                    Err(Error::CompactionBlockedByReaders { .. })
+                    | Err(Error::RedbCompactionError(
+                        redb::CompactionError::PersistentSavepointExists
+                        | redb::CompactionError::EphemeralSavepointExists,
+                    ))
+                    | Ok(_) => {}
+                    Err(error) => return Err(error),
+                }
+            }
+        }
+        Ok((database, open_kind))
     }
 
     /// Creates a new [`Database`](crate::Database) instance in memory.
     pub fn create_in_memory<'a>(&self, models: &'a Models) -> Result<Database<'a>> {
         let builder = self.database_configuration.new_rdb_builder();
         let database_instance = DatabaseInstance::create_in_memory(builder)?;
-        self.init(database_instance, models)
+        let (database, _) = self.init(database_instance, models)?;
+        Ok(database)
+    }
+
+    /// Creates a new [`Database`](crate::Database) instance from a redb database opened by
+    /// the caller, e.g. to store native_db tables and raw redb tables in the same file.
+    ///
+    /// Like [`open`](Self::open), the database is upgraded if needed and the tables of the
+    /// models are created. The redb settings of this builder (e.g. the cache size) are not
+    /// applied, they are the ones of the given database. Use [`redb_database`](crate::Database::redb_database)
+    /// to access the raw tables afterwards.
+    ///
+    /// The tables share the same namespace, the raw tables must not use the names used by
+    /// native_db:
+    /// - `metadata` (see [`set_metadata_table_name`](Self::set_metadata_table_name)),
+    /// - `native_db_counters`,
+    /// - names starting with `{id}_{version}_`, where `id` and `version` are numbers
+    ///   (the tables of the models, named after the [`native_model`](https://crates.io/crates/native_model) id and version).
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let redb_database = redb::Database::builder()
+    ///         .create_with_backend(redb::backends::InMemoryBackend::new())?;
+    ///
+    ///     let models = Models::new();
+    ///     // Define models ...
+    ///     let db = Builder::new().from_redb(&models, redb_database)?;
+    ///     // Raw redb tables can be used with `db.redb_database()?`
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_redb<'a>(
+        &self,
+        models: &'a Models,
+        redb_database: redb::Database,
+    ) -> Result<Database<'a>> {
+        let mut database_instance = DatabaseInstance::from_redb(redb_database);
+        if self.database_configuration.open_integrity_check {
+            check_open_integrity(&mut database_instance)?;
+        }
+        upgrade::upgrade_underlying_database(&database_instance, &models.models_builder)?;
+        let (database, _) = self.init(database_instance, models)?;
+        Ok(database)
     }
 }
 
 #[derive(Debug)]
 pub(crate) struct ModelBuilder {
     pub(crate) model: Model,
+    // Full path of the Rust type, tells a model defined twice from two models sharing a table.
+    pub(crate) type_name: &'static str,
     pub(crate) native_model_options: NativeModelOptions,
+    pub(crate) hashed_primary_key_table_name: Option<String>,
+    pub(crate) blob_table_name: Option<String>,
+    // Secondary table name -> table of the values, see `#[secondary_key(unique, store_value)]`.
+    pub(crate) secondary_value_table_names: HashMap<String, String>,
+    // Other names of the primary table, see `Models::define_alias`.
+    pub(crate) aliases: Vec<String>,
+    // Moves the values of the model to their keys in the current encoding, the builder doesn't
+    // know its type, see `InternalRwTransaction::rekey`.
+    pub(crate) rekey: fn(&InternalRwTransaction<'_>) -> Result<Vec<Key>>,
+    // See `ToInput::native_db_key_encoding_changed_since`.
+    pub(crate) key_encoding_changed_since: fn(u32) -> bool,
+}
+
+impl ModelBuilder {
+    // All the tables of the model: primary, secondary and side tables.
+    pub(crate) fn table_names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.model.primary_key.unique_table_name.as_str())
+            .chain(
+                self.model
+                    .secondary_keys
+                    .iter()
+                    .map(|key| key.unique_table_name.as_str()),
+            )
+            .chain(self.hashed_primary_key_table_name.as_deref())
+            .chain(self.blob_table_name.as_deref())
+            .chain(
+                self.secondary_value_table_names
+                    .values()
+                    .map(String::as_str),
+            )
+    }
 }
@@ -1,5 +1,6 @@
-use crate::db_type::Result;
+use crate::db_type::{Error, Result};
 use redb::Builder;
+use std::io::Read;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -9,7 +10,7 @@ pub(crate) struct DatabaseInstance {
 
 impl DatabaseInstance {
     pub(crate) fn create_on_disk(builder: Builder, path: impl AsRef<Path>) -> Result<Self> {
-        let db = builder.create(path.as_ref())?;
+        let db = open_file(path.as_ref(), || builder.create(path.as_ref()))?;
         Ok(Self {
             kind: DatabaseInstanceKind::OnDisk {
                 redb_database: db,
@@ -19,7 +20,7 @@ impl DatabaseInstance {
     }
 
     pub(crate) fn open_on_disk(builder: Builder, path: impl AsRef<Path>) -> Result<Self> {
-        let db = builder.open(path.as_ref())?;
+        let db = open_file(path.as_ref(), || builder.open(path.as_ref()))?;
         Ok(Self {
             kind: DatabaseInstanceKind::OnDisk {
                 redb_database: db,
@@ -36,6 +37,12 @@ impl DatabaseInstance {
         })
     }
 
+    pub(crate) fn from_redb(redb_database: redb::Database) -> Self {
+        Self {
+            kind: DatabaseInstanceKind::External { redb_database },
+        }
+    }
+
     pub(crate) fn redb_database(&self) -> Result<&redb::Database> {
         self.kind.redb_database()
     }
@@ -43,6 +50,98 @@ impl DatabaseInstance {
     pub(crate) fn redb_database_mut(&mut self) -> Result<&mut redb::Database> {
         self.kind.redb_database_mut()
     }
+
+    pub(crate) fn path(&self) -> Option<&Path> {
+        match &self.kind {
+            DatabaseInstanceKind::InMemory { .. }
+            | DatabaseInstanceKind::External { .. }
+            | DatabaseInstanceKind::Closed { .. } => None,
+            DatabaseInstanceKind::OnDisk { path, .. } => Some(path),
+        }
+    }
+
+    /// Replace the database file by `backup` and reopen it. Does nothing if the database has no
+    /// file. If the file can't be replaced or reopened, the instance stays closed: the following
+    /// operations fail with [`DatabaseClosed`](Error::DatabaseClosed).
+    pub(crate) fn restore_on_disk(&mut self, builder: Builder, backup: &Path) -> Result<()> {
+        let DatabaseInstanceKind::OnDisk { path, .. } = &self.kind else {
+            return Ok(());
+        };
+        let path = path.clone();
+        // Close the file before replacing it.
+        self.kind = DatabaseInstanceKind::Closed { path: path.clone() };
+        std::fs::rename(backup, &path)?;
+        *self = Self::open_on_disk(builder, &path)?;
+        Ok(())
+    }
+
+    pub(crate) fn is_in_memory(&self) -> bool {
+        matches!(self.kind, DatabaseInstanceKind::InMemory { .. })
+    }
+}
+
+/// Report a file truncated (e.g. by a crash during a write or a partial copy) as
+/// [`DatabaseCorrupted`](Error::DatabaseCorrupted) rather than as an IO error or a panic.
+fn open_file(
+    path: &Path,
+    open: impl FnOnce() -> std::result::Result<redb::Database, redb::DatabaseError>,
+) -> Result<redb::Database> {
+    check_file_len(path)?;
+    Ok(open()?)
+}
+
+// Layout of the header of a redb file, see `redb::tree_store::page_store::header`. It is private
+// to redb: its version is pinned and the layout is checked by `test_check_file_len_redb_header`.
+const REDB_MAGIC_NUMBER: [u8; 9] = [b'r', b'e', b'd', b'b', 0x1A, 0x0A, 0xA9, 0x0D, 0x0A];
+const REDB_HEADER_LEN: u64 = 320;
+const REDB_PAGE_SIZE_OFFSET: usize = 12;
+
+/// redb asserts that the file is at least as long as the regions recorded in its header, check
+/// it before opening the file. A file which is missing or not a redb file (e.g. empty) is left
+/// to redb.
+fn check_file_len(path: &Path) -> Result<()> {
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error.into()),
+    };
+    let file_len = file.metadata()?.len();
+    let mut header = [0; REDB_PAGE_SIZE_OFFSET + 5 * 4];
+    if file.read_exact(&mut header).is_err()
+        || header[..REDB_MAGIC_NUMBER.len()] != REDB_MAGIC_NUMBER
+    {
+        return Ok(());
+    }
+    if file_len < REDB_HEADER_LEN {
+        return Err(Error::DatabaseCorrupted {
+            detail: "the file is shorter than its header".to_string(),
+        });
+    }
+
+    // The page size, the header pages and the data pages of a region, the number of full
+    // regions and the data pages of the trailing partial region.
+    let mut fields = header[REDB_PAGE_SIZE_OFFSET..]
+        .chunks_exact(4)
+        .map(|bytes| u128::from(u32::from_le_bytes(bytes.try_into().unwrap())));
+    let mut next = || fields.next().unwrap_or_default();
+    let (page_size, region_header_pages, region_data_pages, full_regions, trailing_data_pages) =
+        (next(), next(), next(), next(), next());
+    let full_region_len = (region_header_pages + region_data_pages) * page_size;
+    let trailing_region_len = if trailing_data_pages > 0 {
+        (region_header_pages + trailing_data_pages) * page_size
+    } else {
+        0
+    };
+    let layout_len = page_size + full_regions * full_region_len + trailing_region_len;
+    if u128::from(file_len) < layout_len {
+        return Err(Error::DatabaseCorrupted {
+            detail: format!(
+                "the file is truncated ({} bytes, {} recorded in its header)",
+                file_len, layout_len
+            ),
+        });
+    }
+    Ok(())
 }
 
 enum DatabaseInstanceKind {
@@ -51,7 +150,14 @@ enum DatabaseInstanceKind {
     },
     OnDisk {
         redb_database: redb::Database,
-        #[allow(dead_code)]
+        path: PathBuf,
+    },
+    /// Created outside of native_db, the backend is unknown.
+    External {
+        redb_database: redb::Database,
+    },
+    /// The file could not be reopened after being replaced.
+    Closed {
         path: PathBuf,
     },
 }
@@ -61,6 +167,10 @@ impl DatabaseInstanceKind {
         match self {
             DatabaseInstanceKind::InMemory { redb_database } => Ok(redb_database),
             DatabaseInstanceKind::OnDisk { redb_database, .. } => Ok(redb_database),
+            DatabaseInstanceKind::External { redb_database } => Ok(redb_database),
+            DatabaseInstanceKind::Closed { path } => {
+                Err(Error::DatabaseClosed { path: path.clone() })
+            }
         }
     }
 
@@ -68,6 +178,53 @@ impl DatabaseInstanceKind {
         match self {
             DatabaseInstanceKind::InMemory { redb_database } => Ok(redb_database),
             DatabaseInstanceKind::OnDisk { redb_database, .. } => Ok(redb_database),
+            DatabaseInstanceKind::External { redb_database } => Ok(redb_database),
+            DatabaseInstanceKind::Closed { path } => {
+                Err(Error::DatabaseClosed { path: path.clone() })
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shortcut_assert_fs::TmpFs;
+
+    // `check_file_len` reads the header of redb, which is private: the test fails if a new
+    // version of redb changes its layout.
+    #[test]
+    fn test_check_file_len_redb_header() {
+        let tf = TmpFs::new().unwrap();
+        let path = tf.path("test");
+        {
+            let db = redb::Database::create(&path).unwrap();
+            let rw = db.begin_write().unwrap();
+            {
+                let mut table = rw
+                    .open_table(redb::TableDefinition::<u64, &[u8]>::new("data"))
+                    .unwrap();
+                for i in 0..1000u64 {
+                    table.insert(i, [0u8; 100].as_slice()).unwrap();
+                }
+            }
+            rw.commit().unwrap();
+        }
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(bytes[..REDB_MAGIC_NUMBER.len()], REDB_MAGIC_NUMBER);
+        let page_size = u32::from_le_bytes(
+            bytes[REDB_PAGE_SIZE_OFFSET..REDB_PAGE_SIZE_OFFSET + 4]
+                .try_into()
+                .unwrap(),
+        ) as usize;
+        assert!(page_size.is_power_of_two());
+        check_file_len(path.as_std_path()).unwrap();
+
+        // The last page is recorded in the header
+        std::fs::write(&path, &bytes[..bytes.len() - page_size]).unwrap();
+        assert!(matches!(
+            check_file_len(path.as_std_path()),
+            Err(Error::DatabaseCorrupted { .. })
+        ));
+    }
+}
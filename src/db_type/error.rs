@@ -7,7 +7,10 @@ pub enum Error {
     Redb(#[from] redb::Error),
 
     #[error("Redb database error")]
-    RedbDatabaseError(#[from] redb::DatabaseError),
+    RedbDatabaseError(redb::DatabaseError),
+
+    #[error("The database {} was created with redb 1.x (native_db 0.5.x), enable the feature `upgrade_0_5_x` to upgrade it when it is opened", path.display())]
+    RedbUpgradeAvailable { path: std::path::PathBuf },
 
     #[cfg(feature = "redb1")]
     #[error("Legacy redb1 database error")]
@@ -37,18 +40,83 @@ pub enum Error {
     #[error("Redb commit error")]
     RedbCommitError(#[from] redb::CommitError),
 
+    #[error("Redb savepoint error")]
+    RedbSavepointError(#[from] redb::SavepointError),
+
+    #[error("Savepoint not found {name}")]
+    SavepointNotFound { name: String },
+
+    #[error("Savepoint {name} is not the latest one, the changes since it can not be read")]
+    SavepointNotLatest { name: String },
+
     #[error("Redb compaction error")]
     RedbCompactionError(#[from] redb::CompactionError),
 
+    #[error("Compaction blocked by {reader_count} open read transaction(s)")]
+    CompactionBlockedByReaders { reader_count: usize },
+
+    #[error("Compaction blocked by an open read-write transaction")]
+    CompactionBlockedByWriter,
+
+    #[error("The compaction failed, the database was restored from its copy")]
+    CompactionFailed(#[source] Box<Error>),
+
+    #[error("Counter {name} overflowed")]
+    CounterOverflow { name: String },
+
+    #[error("The commit was applied to the database but not to its mirror")]
+    MirrorOutOfSync(#[source] Box<Error>),
+
+    #[error("The change of {table} can not be replayed on the mirror: {reason}")]
+    MirrorReplay { table: String, reason: String },
+
+    #[error("{operation} is not replayed on the mirror set with Builder::with_mirror")]
+    NotMirrored { operation: String },
+
+    #[error("The mirror path {} is the path of the database", path.display())]
+    MirrorPathIsDatabasePath { path: std::path::PathBuf },
+
     #[error("Database instance need upgrade")]
     DatabaseInstanceNeedUpgrade(u8),
 
+    #[error("The database is corrupted: {detail}")]
+    DatabaseCorrupted { detail: String },
+
+    #[error("The database {} is closed, its file could not be restored after a failed compaction", path.display())]
+    DatabaseClosed { path: std::path::PathBuf },
+
     #[error("IO error")]
     Io(#[from] std::io::Error),
 
     #[error("Table definition not found {table}")]
     TableDefinitionNotFound { table: String },
 
+    #[error("The model {model} (native_model id {id}, version {version}) is not defined, did you forget `models.define::<{model}>()`?")]
+    ModelNotDefined {
+        model: String,
+        id: u32,
+        version: u32,
+    },
+
+    #[error("The model of the table {table} is already defined")]
+    ModelAlreadyDefined { table: String },
+
+    #[error("The models {first} and {second} both use the table {table}, give them distinct native_model ids")]
+    ModelTableCollision {
+        table: String,
+        first: String,
+        second: String,
+    },
+
+    #[error("The metadata table name {name} collides with the tables of native_db")]
+    MetadataTableNameCollision { name: String },
+
+    #[error("The table {table} already exists")]
+    TableAlreadyExists { table: String },
+
+    #[error("The model {table} can not have an alias, its primary key is hashed or it has a blob")]
+    AliasNotSupported { table: String },
+
     #[error("Secondary key definition not found {table} {key}")]
     SecondaryKeyDefinitionNotFound { table: String, key: String },
 
@@ -83,18 +151,167 @@ pub enum Error {
     #[error("Watch event error")]
     WatchEventError(#[from] watch::WatchEventError),
 
+    #[error("A bulk event summarizes several values, it does not hold them")]
+    BulkEventWithoutValue,
+
     #[error("Max watcher reached (should be impossible)")]
     MaxWatcherReached,
 
+    #[error("Migration error")]
+    Migration(#[source] Box<dyn std::error::Error + Send + Sync>),
+
     #[error("You can not migrate the table {0} because it is a legacy model")]
     MigrateLegacyModel(String),
 
+    #[error("The model {id} is stored with the version {stored_version} but the highest known version is {max_known}")]
+    ForwardVersionUnsupported {
+        id: u32,
+        stored_version: u32,
+        max_known: u32,
+    },
+
+    #[error("Version conflict: expected the version {expected} but the stored version is {actual}")]
+    VersionConflict { expected: u64, actual: u64 },
+
+    #[error("The version of the value in {table} is at its maximum, it can not be incremented")]
+    VersionOverflow { table: String },
+
+    #[error("The values of {table} are stored with the compression {stored} but the model uses {current}")]
+    CompressionMismatch {
+        table: String,
+        stored: String,
+        current: String,
+    },
+
+    #[error("The compression {compression} is not enabled, enable the feature `{compression}`")]
+    CompressionNotEnabled { compression: String },
+
+    #[error("The model {table} has no version field, use #[native_db(version_field = ...)]")]
+    VersionFieldNotDefined { table: String },
+
+    #[error("The primary key of the model {table} is computed by a function, it can not be swapped")]
+    PrimaryKeyNotSwappable { table: String },
+
+    #[error("Validation failed: {message}")]
+    ValidationFailed { message: String },
+
+    #[error("The view {view} must have the id and version of the model {table}, use #[native_model(id = {id}, version = {version})]")]
+    IncompatibleView {
+        view: String,
+        table: String,
+        id: u32,
+        version: u32,
+    },
+
+    #[error("The model {table} has no blob, use #[native_db(blob)]")]
+    BlobNotDefined { table: String },
+
+    #[error("The model {table} is not stored as rkyv archives, use #[native_db(rkyv)]")]
+    RkyvNotDefined { table: String },
+
+    #[error("The model {table} is stored as rkyv archives, it can not be read with a view, use get_archived")]
+    ViewNotSupported { table: String },
+
+    #[error("The values of {table} are encoded with {stored} but the model uses {current}")]
+    EncodingMismatch {
+        table: String,
+        stored: String,
+        current: String,
+    },
+
+    #[error("The values of {table} are encoded with the codec {stored} but this version of native_db uses {current}")]
+    CodecMismatch {
+        table: String,
+        stored: String,
+        current: String,
+    },
+
+    #[error("The keys of {table} are encoded with the version {stored} but this version of native_db uses {current}")]
+    KeyEncodingMismatch {
+        table: String,
+        stored: String,
+        current: String,
+    },
+
+    #[error("The keys of {table} use an older encoding, upgrade them with Builder::upgrade_key_encoding")]
+    KeyEncodingUpgradeRequired { table: String },
+
+    #[cfg(feature = "rkyv")]
+    #[error("Rkyv error")]
+    Rkyv(#[from] rkyv::rancor::Error),
+
     #[error("Model error")]
     ModelError(#[from] native_model::Error),
 
     #[error("Fail to remove secondary key: {0}")]
     RemoveSecondaryKeyError(String),
 
+    #[error("The stored value has {size} bytes, more than the limit of {limit} bytes")]
+    ValueTooLarge { size: usize, limit: usize },
+
+    #[error("The commit would grow the database beyond the limit of {limit} bytes")]
+    QuotaExceeded { limit: u64 },
+
+    #[error("The debounced watch must be created inside a tokio runtime")]
+    TokioRuntimeNotFound,
+
     #[error("Inccorect input data it does not match the model")]
     IncorrectInputData { value: Vec<u8> },
 }
+
+impl From<redb::DatabaseError> for Error {
+    fn from(error: redb::DatabaseError) -> Self {
+        match error {
+            redb::DatabaseError::Storage(redb::StorageError::Corrupted(detail)) => {
+                Error::DatabaseCorrupted { detail }
+            }
+            // Only aborted by `Builder::set_repair_on_open(false)`.
+            redb::DatabaseError::RepairAborted => Error::DatabaseCorrupted {
+                detail: "the database was not shut down cleanly and the repair is disabled"
+                    .to_string(),
+            },
+            error => Error::RedbDatabaseError(error),
+        }
+    }
+}
+
+impl Error {
+    /// Returns `true` if the database is already opened (and locked) by another instance, e.g.
+    /// by another process or by a running upgrade.
+    pub fn is_conflict(&self) -> bool {
+        matches!(
+            self,
+            Error::RedbDatabaseError(redb::DatabaseError::DatabaseAlreadyOpen)
+        )
+    }
+
+    /// Returns `true` if the error is an IO error because the storage is full (`ENOSPC`),
+    /// e.g. returned by [`commit`](crate::transaction::RwTransaction::commit).
+    pub fn is_storage_full(&self) -> bool {
+        match self {
+            Error::Io(error) | Error::RedbStorageError(redb::StorageError::Io(error)) => {
+                error.kind() == std::io::ErrorKind::StorageFull
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the error is transient and the operation can be retried later
+    /// (conflict, compaction blocked by readers or a writer, interrupted or timed out IO).
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::RedbDatabaseError(redb::DatabaseError::DatabaseAlreadyOpen)
+            | Error::CompactionBlockedByReaders { .. }
+            | Error::CompactionBlockedByWriter => true,
+            Error::Io(error) | Error::RedbStorageError(redb::StorageError::Io(error)) => {
+                matches!(
+                    error.kind(),
+                    std::io::ErrorKind::WouldBlock
+                        | std::io::ErrorKind::TimedOut
+                        | std::io::ErrorKind::Interrupted
+                )
+            }
+            _ => false,
+        }
+    }
+}
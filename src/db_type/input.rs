@@ -8,6 +8,22 @@ pub struct Input {
 }
 
 impl Input {
+    /// Estimate of the bytes written to store the input: the primary key and the value, and
+    /// the entries of its secondary keys.
+    pub(crate) fn written_size(&self) -> u64 {
+        let primary_key = self.primary_key.as_slice().len();
+        let mut size = primary_key + self.value.len();
+        for (key_definition, entry) in &self.secondary_keys {
+            for key in entry.clone().into_keys() {
+                size += key.as_slice().len() + primary_key;
+                if key_definition.options.store_value {
+                    size += self.value.len();
+                }
+            }
+        }
+        size as u64
+    }
+
     pub(crate) fn secondary_key_value(
         &self,
         secondary_key_def: &KeyDefinition<KeyOptions>,
@@ -31,6 +47,7 @@ impl Input {
                     // KeyEntry::Optional(value)
                     KeyEntry::Optional(value.as_ref().map(|value| value.to_owned()))
                 }
+                KeyEntry::Multi(values) => KeyEntry::Multi(values.to_owned()),
             }
         } else {
             secondary_key.clone()
@@ -0,0 +1,59 @@
+use super::{Key, ToKey};
+use rust_decimal::Decimal;
+
+/// Order preserving encoding of a `Decimal`: the value is scaled to the maximum scale (28)
+/// into a 192 bits integer, so `1.5` and `1.50` have the same key.
+///
+/// The first byte is the sign (`0` negative, `1` zero or positive) followed by the big-endian
+/// magnitude, inverted for negative values so a larger magnitude sorts first.
+///
+/// The default serde form of `Decimal` can't be decoded by bincode, serialize the field with
+/// e.g. `#[serde(with = "rust_decimal::serde::str")]` (feature `serde-with-str` of `rust_decimal`).
+impl ToKey for Decimal {
+    fn to_key(&self) -> Key {
+        let magnitude = scaled_magnitude(self.mantissa().unsigned_abs(), self.scale());
+        let mut bytes = Vec::with_capacity(25);
+        if self.is_sign_negative() && !self.is_zero() {
+            bytes.push(0);
+            bytes.extend(magnitude.iter().map(|byte| !byte));
+        } else {
+            bytes.push(1);
+            bytes.extend(magnitude);
+        }
+        Key::new(bytes)
+    }
+
+    fn key_names() -> Vec<String> {
+        vec!["Decimal".to_string(), "rust_decimal::Decimal".to_string()]
+    }
+
+    fn is_fixed_width() -> bool {
+        true
+    }
+
+    fn key_encoding_changed_since(_version: u32) -> bool {
+        false
+    }
+}
+
+/// `mantissa * 10^(MAX_SCALE - scale)` as a big-endian 192 bits integer.
+///
+/// The mantissa fits in 96 bits and the factor in 94 bits, so the product fits in 190 bits.
+fn scaled_magnitude(mantissa: u128, scale: u32) -> [u8; 24] {
+    let factor = 10u128.pow(Decimal::MAX_SCALE - scale);
+    let (m_lo, m_hi) = (mantissa as u64 as u128, mantissa >> 64);
+    let (f_lo, f_hi) = (factor as u64 as u128, factor >> 64);
+
+    let lo = m_lo * f_lo;
+    let mid = m_lo * f_hi + m_hi * f_lo;
+    let carry = (lo >> 64) + (mid as u64 as u128);
+    let limb0 = lo as u64;
+    let limb1 = carry as u64;
+    let limb2 = ((carry >> 64) + (mid >> 64) + m_hi * f_hi) as u64;
+
+    let mut bytes = [0; 24];
+    bytes[..8].copy_from_slice(&limb2.to_be_bytes());
+    bytes[8..16].copy_from_slice(&limb1.to_be_bytes());
+    bytes[16..].copy_from_slice(&limb0.to_be_bytes());
+    bytes
+}
@@ -1,22 +1,124 @@
 use redb::{Key as RedbKey, TypeName, Value as RedbValue};
 use std::fmt::Debug;
+use std::marker::PhantomData;
 use std::ops::{Bound, Range, RangeBounds, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Key(Vec<u8>);
+/// The bytes of a key, ordered bytewise.
+///
+/// Keys of at most 16 bytes (e.g. integers) are stored inline, without heap allocation.
+#[derive(Clone)]
+pub struct Key(KeyBytes);
+
+const INLINE_CAPACITY: usize = 16;
+
+#[derive(Clone)]
+enum KeyBytes {
+    Inline {
+        len: u8,
+        data: [u8; INLINE_CAPACITY],
+    },
+    Heap(Vec<u8>),
+}
 
 impl Key {
     pub fn new(data: Vec<u8>) -> Self {
-        Self(data)
+        if data.len() <= INLINE_CAPACITY {
+            Self::from_slice(&data)
+        } else {
+            Self(KeyBytes::Heap(data))
+        }
+    }
+
+    pub(crate) fn from_slice(slice: &[u8]) -> Self {
+        if slice.len() <= INLINE_CAPACITY {
+            let mut data = [0; INLINE_CAPACITY];
+            data[..slice.len()].copy_from_slice(slice);
+            Self(KeyBytes::Inline {
+                len: slice.len() as u8,
+                data,
+            })
+        } else {
+            Self(KeyBytes::Heap(slice.to_vec()))
+        }
     }
 
     pub fn extend_with_delimiter(&mut self, delimiter: u8, data: &Key) {
-        self.0.push(delimiter);
-        self.0.extend(data.0.iter());
+        self.extend_from_slice(&[delimiter]);
+        self.extend_from_slice(data.as_slice());
+    }
+
+    pub(crate) fn extend_from_slice(&mut self, slice: &[u8]) {
+        match &mut self.0 {
+            KeyBytes::Inline { len, data } if *len as usize + slice.len() <= INLINE_CAPACITY => {
+                data[*len as usize..*len as usize + slice.len()].copy_from_slice(slice);
+                *len += slice.len() as u8;
+            }
+            KeyBytes::Inline { len, data } => {
+                let mut vec = Vec::with_capacity(*len as usize + slice.len());
+                vec.extend_from_slice(&data[..*len as usize]);
+                vec.extend_from_slice(slice);
+                self.0 = KeyBytes::Heap(vec);
+            }
+            KeyBytes::Heap(vec) => vec.extend_from_slice(slice),
+        }
     }
 
     pub(crate) fn as_slice(&self) -> &[u8] {
-        self.0.as_slice()
+        match &self.0 {
+            KeyBytes::Inline { len, data } => &data[..*len as usize],
+            KeyBytes::Heap(vec) => vec.as_slice(),
+        }
+    }
+}
+
+/// Returns the lowest key greater than all the keys starting with `prefix`, or `None` if there
+/// is none (the prefix is empty or has only `0xff` bytes).
+pub(crate) fn prefix_successor(prefix: &Key) -> Option<Key> {
+    let mut successor = prefix.as_slice().to_vec();
+    while let Some(last) = successor.pop() {
+        if last < u8::MAX {
+            successor.push(last + 1);
+            return Some(Key::new(successor));
+        }
+    }
+    None
+}
+
+impl Default for Key {
+    fn default() -> Self {
+        Self::from_slice(&[])
+    }
+}
+
+/// Shows the bytes in hex and, if they are valid UTF-8, the string too,
+/// e.g. `Key(hex=61_62, utf8="ab")`.
+impl Debug for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Key(hex=")?;
+        for (i, byte) in self.as_slice().iter().enumerate() {
+            if i > 0 {
+                write!(f, "_")?;
+            }
+            write!(f, "{byte:02x}")?;
+        }
+        if let Ok(utf8) = std::str::from_utf8(self.as_slice()) {
+            write!(f, ", utf8={utf8:?}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for Key {}
+
+impl std::hash::Hash for Key {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
     }
 }
 
@@ -133,7 +235,7 @@ impl Key {
 ///
 /// impl ToKey for DateTime {
 ///     fn to_key(&self) -> Key {
-///         Key::new(self.0.timestamp_millis().to_be_bytes().to_vec())
+///         self.0.timestamp_millis().to_key()
 ///     }
 ///
 ///     fn key_names() -> Vec<String> {
@@ -177,16 +279,150 @@ impl Key {
 pub trait ToKey: Debug {
     fn to_key(&self) -> Key;
     fn key_names() -> Vec<String>;
+
+    /// Returns `true` if the keys of all the values have the same length, e.g. the integers.
+    ///
+    /// In a tuple key, the other components are delimited so they can't collide, e.g.
+    /// `("ab", "c")` and `("a", "bc")`. Defaults to `false`, which is always correct.
+    fn is_fixed_width() -> bool {
+        false
+    }
+
+    /// Encoding of the value as a component of a tuple key followed by other components, see
+    /// [`is_fixed_width`](Self::is_fixed_width).
+    #[doc(hidden)]
+    fn to_component_key(&self) -> Key {
+        let key = self.to_key();
+        if Self::is_fixed_width() {
+            return key;
+        }
+        delimit_component(&key)
+    }
+
+    /// Returns `true` if the keys differ from the ones written by the encoding `version` of an
+    /// older native_db, e.g. the signed integers before they were sorted in numeric order. The
+    /// tables keyed by the type are then re-keyed by
+    /// [`Builder::upgrade_key_encoding`](crate::Builder::upgrade_key_encoding).
+    ///
+    /// Defaults to `true`, which is always correct: an implementation can delegate to a type
+    /// whose encoding changed, e.g. `self.0.timestamp_millis().to_key()`.
+    fn key_encoding_changed_since(_version: u32) -> bool {
+        true
+    }
+}
+
+// A variable width component of a tuple key has its `0x00` bytes escaped as `0x00 0xff` and is
+// terminated by `0x00 0x00`: it can't be mistaken for the start of the next component and the
+// tuples keep the order of their components.
+fn delimit_component(key: &Key) -> Key {
+    let mut data = Vec::with_capacity(key.as_slice().len() + 2);
+    for byte in key.as_slice() {
+        data.push(*byte);
+        if *byte == 0 {
+            data.push(0xff);
+        }
+    }
+    data.extend([0, 0]);
+    Key::new(data)
+}
+
+/// Returns the [`key_names`](ToKey::key_names) of the field returned by `field`. Used by the
+/// macro for `#[secondary_key(customer.id)]`, where the type of the nested field isn't written.
+#[doc(hidden)]
+pub fn key_names_of<T, K: ToKey>(_field: fn(&T) -> &K) -> Vec<String> {
+    K::key_names()
+}
+
+/// Returns the [`key_encoding_changed_since`](ToKey::key_encoding_changed_since) of the field
+/// returned by `field`, like [`key_names_of`].
+#[doc(hidden)]
+pub fn key_encoding_changed_since_of<T, K: ToKey>(_field: fn(&T) -> &K, version: u32) -> bool {
+    K::key_encoding_changed_since(version)
+}
+
+/// Used by the macro for the secondary key fields whose type is checked to implement
+/// [`ToKey`]: the methods of [`ToKeyProbeImplemented`] are called through `&ToKeyProbe<T>`,
+/// when `T` doesn't implement [`ToKey`], the call falls back to [`ToKeyProbeMissing`] and the
+/// check of the field is the only error reported.
+#[doc(hidden)]
+pub struct ToKeyProbe<T: ?Sized>(PhantomData<T>);
+
+impl<T: ?Sized> ToKeyProbe<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+
+    pub fn of(_value: &T) -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: ?Sized> Default for ToKeyProbe<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ?Sized> Clone for ToKeyProbe<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for ToKeyProbe<T> {}
+
+#[doc(hidden)]
+pub trait ToKeyProbeImplemented<T: ?Sized> {
+    fn probe_to_key(self, value: &T) -> Key;
+    fn probe_key_names(self) -> Vec<String>;
+    fn probe_key_encoding_changed_since(self, version: u32) -> bool;
+}
+
+impl<T: ToKey + ?Sized> ToKeyProbeImplemented<T> for &ToKeyProbe<T> {
+    fn probe_to_key(self, value: &T) -> Key {
+        value.to_key()
+    }
+
+    fn probe_key_names(self) -> Vec<String> {
+        T::key_names()
+    }
+
+    fn probe_key_encoding_changed_since(self, version: u32) -> bool {
+        T::key_encoding_changed_since(version)
+    }
+}
+
+#[doc(hidden)]
+pub trait ToKeyProbeMissing<T: ?Sized>: Sized {
+    fn probe_to_key(self, _value: &T) -> Key {
+        unreachable!("the type of the secondary key field does not implement ToKey")
+    }
+
+    fn probe_key_names(self) -> Vec<String> {
+        unreachable!("the type of the secondary key field does not implement ToKey")
+    }
+
+    fn probe_key_encoding_changed_since(self, _version: u32) -> bool {
+        unreachable!("the type of the secondary key field does not implement ToKey")
+    }
 }
 
+impl<T: ?Sized> ToKeyProbeMissing<T> for ToKeyProbe<T> {}
+
 // Implement for char
 impl ToKey for char {
     fn to_key(&self) -> Key {
-        Key::new(u32::from(*self).to_be_bytes().to_vec())
+        Key::from_slice(&u32::from(*self).to_be_bytes())
     }
     fn key_names() -> Vec<String> {
         vec!["char".to_string()]
     }
+    fn is_fixed_width() -> bool {
+        true
+    }
+    fn key_encoding_changed_since(_version: u32) -> bool {
+        false
+    }
 }
 
 // Implement for &String
@@ -197,16 +433,22 @@ impl ToKey for String {
     fn key_names() -> Vec<String> {
         vec!["String".to_string()]
     }
+    fn key_encoding_changed_since(_version: u32) -> bool {
+        false
+    }
 }
 
 // Implement for &str
 impl ToKey for &str {
     fn to_key(&self) -> Key {
-        Key::new(self.as_bytes().to_vec())
+        Key::from_slice(self.as_bytes())
     }
     fn key_names() -> Vec<String> {
         vec!["String".to_string(), "&str".to_string()]
     }
+    fn key_encoding_changed_since(_version: u32) -> bool {
+        false
+    }
 }
 
 impl ToKey for Key {
@@ -219,16 +461,26 @@ impl ToKey for Key {
     fn key_names() -> Vec<String> {
         vec!["Key".to_string()]
     }
+
+    fn key_encoding_changed_since(_version: u32) -> bool {
+        false
+    }
 }
 
 // Implement for tuples
 impl ToKey for () {
     fn to_key(&self) -> Key {
-        Key::new(Vec::new())
+        Key::default()
     }
     fn key_names() -> Vec<String> {
         vec!["()".to_string()]
     }
+    fn is_fixed_width() -> bool {
+        true
+    }
+    fn key_encoding_changed_since(_version: u32) -> bool {
+        false
+    }
 }
 
 // Macro for tuples
@@ -236,12 +488,23 @@ macro_rules! impl_inner_key_value_for_tuple {
     ( $($t:ident, $i:tt),+ | $t_last:ident, $i_last:tt ) => {
         impl<$($t: ToKey,)+ $t_last: ToKey> ToKey for ($($t,)+ $t_last) {
             fn to_key(&self) -> Key {
-                let mut data = Vec::new();
+                let mut key = Key::default();
                 $(
-                    data.extend(self.$i.to_key().0);
+                    key.extend_from_slice(self.$i.to_component_key().as_slice());
                 )+
-                data.extend(self.$i_last.to_key().0);
-                Key::new(data)
+                key.extend_from_slice(self.$i_last.to_key().as_slice());
+                key
+            }
+            fn is_fixed_width() -> bool {
+                $(<$t as ToKey>::is_fixed_width() &&)+ <$t_last as ToKey>::is_fixed_width()
+            }
+            fn to_component_key(&self) -> Key {
+                let mut key = Key::default();
+                $(
+                    key.extend_from_slice(self.$i.to_component_key().as_slice());
+                )+
+                key.extend_from_slice(self.$i_last.to_component_key().as_slice());
+                key
             }
             fn key_names() -> Vec<String> {
                 let mut name = String::new();
@@ -252,6 +515,12 @@ macro_rules! impl_inner_key_value_for_tuple {
                 name.push_str(<$t_last as ToKey>::key_names()[0].as_str());
                 vec![format!("({})", name)]
             }
+            // The components but the last are delimited since the encoding 3.
+            fn key_encoding_changed_since(version: u32) -> bool {
+                $(<$t as ToKey>::key_encoding_changed_since(version) ||)+
+                    <$t_last as ToKey>::key_encoding_changed_since(version)
+                    || (version < 3 && !($(<$t as ToKey>::is_fixed_width())&&+))
+            }
         }
     }
 }
@@ -339,11 +608,11 @@ where
     T: ToKey,
 {
     fn to_key(&self) -> Key {
-        let mut data = Vec::new();
+        let mut key = Key::default();
         for item in self {
-            data.extend(item.to_key().0);
+            key.extend_from_slice(item.to_key().as_slice());
         }
-        Key::new(data)
+        key
     }
     fn key_names() -> Vec<String> {
         let mut names = Vec::new();
@@ -353,6 +622,9 @@ where
         }
         names
     }
+    fn key_encoding_changed_since(version: u32) -> bool {
+        T::key_encoding_changed_since(version)
+    }
 }
 
 // Implement for Slice
@@ -361,11 +633,11 @@ where
     T: ToKey,
 {
     fn to_key(&self) -> Key {
-        let mut data = Vec::new();
+        let mut key = Key::default();
         for item in self.iter().as_slice() {
-            data.extend(item.to_key().0);
+            key.extend_from_slice(item.to_key().as_slice());
         }
-        Key::new(data)
+        key
     }
     fn key_names() -> Vec<String> {
         let mut names = Vec::new();
@@ -375,17 +647,25 @@ where
 
         names
     }
+    fn key_encoding_changed_since(version: u32) -> bool {
+        T::key_encoding_changed_since(version)
+    }
 }
 
-// Implement InnerKeyValue for Option<T> where T: InnerKeyValue
+/// `None` is encoded as the byte `0x00` and `Some(value)` as the byte `0x01` followed by the
+/// encoding of `value`, so `None` comes before all the `Some` values.
 impl<T> ToKey for Option<T>
 where
     T: ToKey,
 {
     fn to_key(&self) -> Key {
         match self {
-            Some(value) => value.to_key(),
-            None => Key::new(Vec::new()),
+            Some(value) => {
+                let mut data = vec![1];
+                data.extend_from_slice(value.to_key().as_slice());
+                Key::new(data)
+            }
+            None => Key::new(vec![0]),
         }
     }
     fn key_names() -> Vec<String> {
@@ -395,18 +675,73 @@ where
         }
         names
     }
+    // `None` was an empty key and `Some(value)` the key of `value` before the encoding 2.
+    fn key_encoding_changed_since(version: u32) -> bool {
+        version < 2 || T::key_encoding_changed_since(version)
+    }
 }
 
-// Macro for implementing InnerKeyValue for u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64
+// Macro for implementing InnerKeyValue for u8, u16, u32, u64, u128
 macro_rules! impl_inner_key_value_for_primitive {
     ($type:ty) => {
         impl ToKey for $type {
             fn to_key(&self) -> Key {
-                Key::new(self.to_be_bytes().to_vec())
+                Key::from_slice(&self.to_be_bytes())
+            }
+            fn key_names() -> Vec<String> {
+                vec![stringify!($type).to_string()]
+            }
+            fn is_fixed_width() -> bool {
+                true
+            }
+            fn key_encoding_changed_since(_version: u32) -> bool {
+                false
+            }
+        }
+    };
+}
+
+// Macro for implementing InnerKeyValue for i8, i16, i32, i64, i128: the sign bit is flipped so
+// the negative values sort before the positive ones.
+macro_rules! impl_inner_key_value_for_signed_primitive {
+    ($type:ty) => {
+        impl ToKey for $type {
+            fn to_key(&self) -> Key {
+                Key::from_slice(&(self ^ <$type>::MIN).to_be_bytes())
+            }
+            fn key_names() -> Vec<String> {
+                vec![stringify!($type).to_string()]
+            }
+            fn is_fixed_width() -> bool {
+                true
+            }
+            fn key_encoding_changed_since(version: u32) -> bool {
+                version < 2
+            }
+        }
+    };
+}
+
+// Macro for implementing InnerKeyValue for f32, f64: the sign bit is flipped for the positive
+// values and all the bits for the negative ones, so the values sort in numeric order.
+macro_rules! impl_inner_key_value_for_float {
+    ($type:ty, $bits:ty) => {
+        impl ToKey for $type {
+            fn to_key(&self) -> Key {
+                let sign: $bits = 1 << (<$bits>::BITS - 1);
+                let bits = self.to_bits();
+                let bits = if bits & sign == 0 { bits ^ sign } else { !bits };
+                Key::from_slice(&bits.to_be_bytes())
             }
             fn key_names() -> Vec<String> {
                 vec![stringify!($type).to_string()]
             }
+            fn is_fixed_width() -> bool {
+                true
+            }
+            fn key_encoding_changed_since(version: u32) -> bool {
+                version < 2
+            }
         }
     };
 }
@@ -416,27 +751,186 @@ impl_inner_key_value_for_primitive!(u16);
 impl_inner_key_value_for_primitive!(u32);
 impl_inner_key_value_for_primitive!(u64);
 impl_inner_key_value_for_primitive!(u128);
-impl_inner_key_value_for_primitive!(i8);
-impl_inner_key_value_for_primitive!(i16);
-impl_inner_key_value_for_primitive!(i32);
-impl_inner_key_value_for_primitive!(i64);
-impl_inner_key_value_for_primitive!(i128);
-impl_inner_key_value_for_primitive!(f32);
-impl_inner_key_value_for_primitive!(f64);
+impl_inner_key_value_for_signed_primitive!(i8);
+impl_inner_key_value_for_signed_primitive!(i16);
+impl_inner_key_value_for_signed_primitive!(i32);
+impl_inner_key_value_for_signed_primitive!(i64);
+impl_inner_key_value_for_signed_primitive!(i128);
+impl_inner_key_value_for_float!(f32, u32);
+impl_inner_key_value_for_float!(f64, u64);
 
 impl ToKey for bool {
     fn to_key(&self) -> Key {
-        Key::new(vec![*self as u8])
+        Key::from_slice(&[*self as u8])
     }
 
     fn key_names() -> Vec<String> {
         vec!["bool".to_string()]
     }
+
+    fn is_fixed_width() -> bool {
+        true
+    }
+
+    fn key_encoding_changed_since(_version: u32) -> bool {
+        false
+    }
+}
+
+// Paths and OS strings are keyed by their encoded bytes (`OsStr::as_encoded_bytes`). A valid
+// UTF-8 path is keyed by its UTF-8 bytes, the same on all platforms, so it sorts and
+// prefix-scans like a string (e.g. `start_with(Path::new("/home/user/"))`). The other paths
+// keep their raw bytes (Unix) or their WTF-8 bytes (Windows): two distinct paths never share a
+// key, but such a key is not portable between platforms. Note that serde refuses to serialize
+// a `PathBuf` that is not valid UTF-8, so such a path can only be a key computed from the value.
+
+impl ToKey for std::path::PathBuf {
+    fn to_key(&self) -> Key {
+        self.as_path().to_key()
+    }
+    fn key_names() -> Vec<String> {
+        vec!["PathBuf".to_string(), "std::path::PathBuf".to_string()]
+    }
+    fn key_encoding_changed_since(_version: u32) -> bool {
+        false
+    }
+}
+
+impl ToKey for &std::path::Path {
+    fn to_key(&self) -> Key {
+        self.as_os_str().to_key()
+    }
+    fn key_names() -> Vec<String> {
+        vec![
+            "PathBuf".to_string(),
+            "std::path::PathBuf".to_string(),
+            "&Path".to_string(),
+        ]
+    }
+    fn key_encoding_changed_since(_version: u32) -> bool {
+        false
+    }
+}
+
+impl ToKey for std::ffi::OsString {
+    fn to_key(&self) -> Key {
+        self.as_os_str().to_key()
+    }
+    fn key_names() -> Vec<String> {
+        vec!["OsString".to_string(), "std::ffi::OsString".to_string()]
+    }
+    fn key_encoding_changed_since(_version: u32) -> bool {
+        false
+    }
+}
+
+impl ToKey for &std::ffi::OsStr {
+    fn to_key(&self) -> Key {
+        Key::from_slice(self.as_encoded_bytes())
+    }
+    fn key_names() -> Vec<String> {
+        vec![
+            "OsString".to_string(),
+            "std::ffi::OsString".to_string(),
+            "&OsStr".to_string(),
+        ]
+    }
+    fn key_encoding_changed_since(_version: u32) -> bool {
+        false
+    }
+}
+
+/// Wrap a key to sort it in descending order, e.g. to scan the newest values first.
+///
+/// The bytes of the inner key are inverted, so an ascending scan returns the values in the
+/// descending order of the inner key. The order is exactly reversed for fixed width keys
+/// (integers, `bool`, `char` and tuples of them). For variable length keys (e.g. `String`),
+/// a key that is a prefix of another one still comes first.
+///
+/// Serialized as the inner value.
+///
+/// # Example
+/// ```rust
+/// use native_db::*;
+/// use native_db::db_type::Descending;
+/// use native_db::native_model::{native_model, Model};
+/// use serde::{Deserialize, Serialize};
+/// use itertools::Itertools;
+///
+/// #[derive(Serialize, Deserialize)]
+/// #[native_model(id=1, version=1)]
+/// #[native_db]
+/// struct Post {
+///     #[primary_key]
+///     id: u64,
+///     #[secondary_key]
+///     timestamp: Descending<u64>,
+/// }
+///
+/// fn main() -> Result<(), db_type::Error> {
+///     let mut models = Models::new();
+///     models.define::<Post>()?;
+///     let db = Builder::new().create_in_memory(&models)?;
+///
+///     let rw = db.rw_transaction()?;
+///     rw.insert(Post { id: 1, timestamp: Descending(100) })?;
+///     rw.insert(Post { id: 2, timestamp: Descending(200) })?;
+///     rw.commit()?;
+///
+///     // Newest first
+///     let r = db.r_transaction()?;
+///     let posts: Vec<Post> = r.scan().secondary(PostKey::timestamp)?.all()?.try_collect()?;
+///     assert_eq!(posts[0].id, 2);
+///     assert_eq!(posts[1].id, 1);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Descending<T>(pub T);
+
+impl<T: ToKey> ToKey for Descending<T> {
+    fn to_key(&self) -> Key {
+        let inner = self.0.to_key();
+        let bytes: Vec<u8> = inner.as_slice().iter().map(|byte| !byte).collect();
+        Key::new(bytes)
+    }
+
+    fn key_names() -> Vec<String> {
+        let mut names = Vec::new();
+        for name in T::key_names() {
+            names.push(format!("Descending<{}>", name));
+        }
+        names
+    }
+
+    fn is_fixed_width() -> bool {
+        T::is_fixed_width()
+    }
+
+    // The type came with the encoding 2, no table with an older one holds it.
+    fn key_encoding_changed_since(version: u32) -> bool {
+        version >= 2 && T::key_encoding_changed_since(version)
+    }
+}
+
+impl<T: serde::Serialize> serde::Serialize for Descending<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Descending<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Descending)
+    }
 }
 
 impl RedbValue for Key {
     type SelfType<'a> = Key;
-    type AsBytes<'a> = &'a [u8] where Self: 'a;
+    type AsBytes<'a>
+        = &'a [u8]
+    where
+        Self: 'a;
 
     fn fixed_width() -> Option<usize> {
         None
@@ -446,7 +940,7 @@ impl RedbValue for Key {
     where
         Self: 'a,
     {
-        data.to_key()
+        Key::from_slice(data)
     }
 
     fn as_bytes<'a, 'b: 'a>(value: &'a Self::SelfType<'b>) -> Self::AsBytes<'a>
@@ -454,7 +948,7 @@ impl RedbValue for Key {
         Self: 'a,
         Self: 'b,
     {
-        value.0.as_slice()
+        value.as_slice()
     }
 
     fn type_name() -> TypeName {
@@ -468,6 +962,7 @@ impl RedbKey for Key {
     }
 }
 
+#[derive(Clone, PartialEq, Eq)]
 pub enum KeyRange {
     Range(Range<Key>),
     RangeInclusive(RangeInclusive<Key>),
@@ -510,6 +1005,22 @@ impl KeyRange {
             (Bound::Unbounded, Bound::Unbounded) => KeyRange::RangeFull,
         }
     }
+
+    /// Returns `true` if the range contains `key`, keys are compared like in the tables.
+    pub(crate) fn contains_key(&self, key: &Key) -> bool {
+        let key = key.as_slice();
+        let after_start = match self.start_bound() {
+            Bound::Included(start) => key >= start.as_slice(),
+            Bound::Excluded(start) => key > start.as_slice(),
+            Bound::Unbounded => true,
+        };
+        let before_end = match self.end_bound() {
+            Bound::Included(end) => key <= end.as_slice(),
+            Bound::Excluded(end) => key < end.as_slice(),
+            Bound::Unbounded => true,
+        };
+        after_start && before_end
+    }
 }
 
 impl RangeBounds<Key> for KeyRange {
@@ -566,9 +1077,154 @@ mod tests {
         let table = read_txn.open_table(TABLE).unwrap();
         assert_eq!(table.get(0u32.to_key()).unwrap().unwrap().value(), 123);
 
-        let range = range(0..2);
+        let range = range(0u32..2);
         let iter = table.range::<Key>(range).unwrap();
         let result: Vec<_> = iter.collect();
         assert_eq!(result.len(), 1);
     }
+
+    #[test]
+    fn test_option_order() {
+        let mut keys = vec![
+            Some(1u32).to_key(),
+            None::<u32>.to_key(),
+            Some(0u32).to_key(),
+            Some(u32::MAX).to_key(),
+        ];
+        keys.sort_by(|a, b| a.as_slice().cmp(b.as_slice()));
+        assert_eq!(
+            keys,
+            vec![
+                None::<u32>.to_key(),
+                Some(0u32).to_key(),
+                Some(1u32).to_key(),
+                Some(u32::MAX).to_key(),
+            ]
+        );
+
+        // None before the empty string
+        assert!(None::<&str>.to_key().as_slice() < Some("").to_key().as_slice());
+    }
+
+    #[test]
+    fn test_tuple_components_delimited() {
+        assert_ne!(("ab", "c").to_key(), ("a", "bc").to_key());
+        assert_ne!(("a\0", "b").to_key(), ("a", "\0b").to_key());
+        assert_ne!((vec![1u8], 2u8).to_key(), (vec![1u8, 2], ()).to_key());
+
+        // The fixed width components are not delimited
+        assert_eq!(
+            (1u32, 2u64).to_key().as_slice(),
+            &[0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2]
+        );
+
+        // The tuples keep the order of their components
+        let mut keys = vec![
+            ("b", 0u32).to_key(),
+            ("a\0", 0u32).to_key(),
+            ("ab", 0u32).to_key(),
+            ("a", 1u32).to_key(),
+            ("", 2u32).to_key(),
+        ];
+        keys.sort_by(|a, b| a.as_slice().cmp(b.as_slice()));
+        assert_eq!(
+            keys,
+            vec![
+                ("", 2u32).to_key(),
+                ("a", 1u32).to_key(),
+                ("a\0", 0u32).to_key(),
+                ("ab", 0u32).to_key(),
+                ("b", 0u32).to_key(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_signed_order() {
+        fn assert_sorted<T: ToKey>(values: &[T]) {
+            for pair in values.windows(2) {
+                assert!(
+                    pair[0].to_key().as_slice() < pair[1].to_key().as_slice(),
+                    "{:?} < {:?}",
+                    pair[0],
+                    pair[1]
+                );
+            }
+        }
+
+        assert_sorted(&[i8::MIN, -1, 0, 1, i8::MAX]);
+        assert_sorted(&[i16::MIN, -256, -1, 0, 1, 256, i16::MAX]);
+        assert_sorted(&[i32::MIN, -65536, -1, 0, 1, 65536, i32::MAX]);
+        assert_sorted(&[i64::MIN, -1 << 40, -1, 0, 1, 1 << 40, i64::MAX]);
+        assert_sorted(&[i128::MIN, -1, 0, 1, i128::MAX]);
+
+        // In composite keys too
+        assert_sorted(&[(-1i32, 0u32), (0i32, 0u32), (0i32, 1u32), (1i32, 0u32)]);
+
+        assert_sorted(&[f32::NEG_INFINITY, -1.5, -0.0, 0.0, 1.5, f32::INFINITY]);
+        assert_sorted(&[f64::MIN, -1e300, -1.0, -0.0, 0.0, 1.0, 1e300, f64::MAX]);
+    }
+
+    #[test]
+    fn test_inline_and_heap_keys() {
+        let bytes: Vec<u8> = (0..40).collect();
+        for len in [0, 1, 15, 16, 17, 40] {
+            let key = Key::new(bytes[..len].to_vec());
+            assert_eq!(key.as_slice(), &bytes[..len]);
+            assert_eq!(key, Key::from_slice(&bytes[..len]));
+        }
+
+        // Grows from inline to heap
+        let mut key = 1u64.to_key();
+        key.extend_with_delimiter(0, &2u64.to_key());
+        key.extend_with_delimiter(0, &3u64.to_key());
+        let mut expected = vec![];
+        expected.extend(1u64.to_be_bytes());
+        expected.push(0);
+        expected.extend(2u64.to_be_bytes());
+        expected.push(0);
+        expected.extend(3u64.to_be_bytes());
+        assert_eq!(key.as_slice(), expected.as_slice());
+        assert_eq!(key, Key::new(expected.clone()));
+
+        // Composite keys across the inline capacity
+        assert_eq!(
+            (1u64, 2u64, 3u32).to_key().as_slice(),
+            [
+                &1u64.to_be_bytes()[..],
+                &2u64.to_be_bytes(),
+                &3u32.to_be_bytes()
+            ]
+            .concat()
+        );
+
+        // Same hash as the bytes
+        use std::collections::HashSet;
+        let keys: HashSet<Key> = [Key::new(expected.clone()), Key::from_slice(&expected)].into();
+        assert_eq!(keys.len(), 1);
+    }
+
+    #[test]
+    fn test_prefix_successor() {
+        assert_eq!(prefix_successor(&"ab".to_key()), Some("ac".to_key()));
+        assert_eq!(
+            prefix_successor(&Key::new(vec![1, 0xff, 0xff])),
+            Some(Key::new(vec![2]))
+        );
+        assert_eq!(prefix_successor(&Key::new(vec![0xff])), None);
+        assert_eq!(prefix_successor(&Key::default()), None);
+    }
+
+    #[test]
+    fn test_key_debug() {
+        assert_eq!(
+            format!("{:?}", "ab".to_key()),
+            r#"Key(hex=61_62, utf8="ab")"#
+        );
+        // Not valid UTF-8, only the hex
+        assert_eq!(
+            format!("{:?}", Key::new(vec![0xff, 0x00, 0x1a])),
+            "Key(hex=ff_00_1a)"
+        );
+    }
 }
@@ -70,6 +70,8 @@ impl Hash for KeyDefinition<KeyOptions> {
 pub struct KeyOptions {
     pub unique: bool,
     pub optional: bool,
+    pub multi: bool,
+    pub store_value: bool,
 }
 
 pub fn composite_key(secondary_key: &Key, primary_key: &Key) -> Key {
@@ -124,6 +126,62 @@ pub(crate) fn check_key_type_from_key_definition<K: ToKey>(
     _check_key_type_from_key_definition::<K>(key_definition)
 }
 
+// Splits the name of a tuple type, e.g. `(String, Option<(u32, u64)>)`, into the names of its
+// components. Returns `None` if the type is not a tuple.
+fn tuple_components(name: &str) -> Option<Vec<&str>> {
+    let inner = name.strip_prefix('(')?.strip_suffix(')')?;
+    let mut components = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (index, c) in inner.char_indices() {
+        match c {
+            '(' | '<' | '[' => depth += 1,
+            ')' | '>' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                components.push(inner[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    components.push(inner[start..].trim());
+    Some(components)
+}
+
+/// Check that `K` is the type of the leading components of the tuple type of the key, e.g.
+/// `String` or `(String, u32)` for a key of type `(String, u32, u64)`. The key must have at
+/// least one component after them.
+pub(crate) fn check_key_component_type_from_key_definition<K: ToKey, O>(
+    key_definition: &KeyDefinition<O>,
+    _component: &K,
+) -> Result<()> {
+    let is_leading_components = |key: &String, component: &String| {
+        let Some(key_components) = tuple_components(key) else {
+            return false;
+        };
+        let is_leading = |components: &[&str]| {
+            components.len() < key_components.len()
+                && key_components[..components.len()] == *components
+        };
+        is_leading(&[component.as_str()])
+            || tuple_components(component).is_some_and(|components| is_leading(&components))
+    };
+    if !K::key_names().iter().any(|component| {
+        key_definition
+            .rust_types
+            .iter()
+            .any(|key| is_leading_components(key, component))
+    }) {
+        return Err(Error::MissmatchedKeyType {
+            key_name: key_definition.unique_table_name.to_string(),
+            expected_types: key_definition.rust_types.clone(),
+            got_types: K::key_names(),
+            operation: "start_with_component".to_string(),
+        });
+    }
+    Ok(())
+}
+
 pub(crate) fn check_range_key_range_bounds<K: ToKey>(
     model: &Model,
     _range: &impl RangeBounds<K>,
@@ -1,7 +1,33 @@
-use crate::db_type::Key;
+use crate::db_type::{Key, ToKey};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum KeyEntry {
     Default(Key),
+    /// The key of a `#[secondary_key(optional)]` value, not indexed if `None`. `Some(key)` is
+    /// stored with the encoding of the `Option` keys, so the queries take an `Option`.
     Optional(Option<Key>),
+    /// One key per element of a `#[secondary_key(multi)]` collection, without duplicates.
+    Multi(Vec<Key>),
+}
+
+impl KeyEntry {
+    /// Creates the entry of a `#[secondary_key(multi)]` collection, skipping the duplicates.
+    pub fn new_multi(keys: impl IntoIterator<Item = Key>) -> Self {
+        let mut unique_keys: Vec<Key> = vec![];
+        for key in keys {
+            if !unique_keys.contains(&key) {
+                unique_keys.push(key);
+            }
+        }
+        KeyEntry::Multi(unique_keys)
+    }
+
+    /// Returns the keys to store in the secondary table, none if the optional key is not set.
+    pub(crate) fn into_keys(self) -> Vec<Key> {
+        match self {
+            KeyEntry::Default(key) => vec![key],
+            KeyEntry::Optional(key) => key.map(|key| Some(key).to_key()).into_iter().collect(),
+            KeyEntry::Multi(keys) => keys,
+        }
+    }
 }
@@ -2,11 +2,16 @@
 mod key;
 mod key_definition;
 mod key_value;
+mod optional_range;
 
 #[cfg(feature = "redb1")]
 pub mod inner_key_value_redb1;
 
+#[cfg(feature = "rust_decimal")]
+mod decimal;
+
 pub use key::*;
 
 pub use key_definition::*;
 pub use key_value::*;
+pub use optional_range::*;
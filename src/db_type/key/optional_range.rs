@@ -0,0 +1,148 @@
+use crate::db_type::{Key, ToKey};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{RangeFrom, RangeInclusive};
+
+/// Range of the `None` values of a key of type `Option<T>`.
+///
+/// The `None` values of a secondary key declared with `#[secondary_key(optional)]` are not
+/// indexed, so this range only matches a primary key or a secondary key without `optional`.
+///
+/// # Example
+/// ```rust
+/// use itertools::Itertools;
+/// use native_db::*;
+/// use native_db::db_type::none_range;
+/// use native_model::{native_model, Model};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// #[native_model(id=1, version=1)]
+/// #[native_db]
+/// struct Data {
+///     #[primary_key]
+///     id: u64,
+///     #[secondary_key]
+///     parent: Option<u64>,
+/// }
+///
+/// fn main() -> Result<(), db_type::Error> {
+///     let mut models = Models::new();
+///     models.define::<Data>()?;
+///     let db = Builder::new().create_in_memory(&models)?;
+///
+///     // Get the values without parent
+///     let r = db.r_transaction()?;
+///     let _roots: Vec<Data> = r
+///         .scan()
+///         .secondary(DataKey::parent)?
+///         .range(none_range::<u64>())?
+///         .try_collect()?;
+///     Ok(())
+/// }
+/// ```
+pub fn none_range<T: ToKey>() -> RangeInclusive<Option<T>> {
+    None..=None
+}
+
+/// Range of the `Some` values of a key of type `Option<T>` from `Some(start)` to `Some(end)`,
+/// both included.
+///
+/// # Example
+/// ```rust
+/// use itertools::Itertools;
+/// use native_db::*;
+/// use native_db::db_type::some_range;
+/// use native_model::{native_model, Model};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// #[native_model(id=1, version=1)]
+/// #[native_db]
+/// struct Data {
+///     #[primary_key]
+///     id: u64,
+///     #[secondary_key(optional)]
+///     name: Option<String>,
+/// }
+///
+/// fn main() -> Result<(), db_type::Error> {
+///     let mut models = Models::new();
+///     models.define::<Data>()?;
+///     let db = Builder::new().create_in_memory(&models)?;
+///
+///     // Get the values named from "a" to "c"
+///     let r = db.r_transaction()?;
+///     let _values: Vec<Data> = r
+///         .scan()
+///         .secondary(DataKey::name)?
+///         .range(some_range("a", "c"))?
+///         .try_collect()?;
+///     Ok(())
+/// }
+/// ```
+pub fn some_range<T: ToKey>(start: T, end: T) -> RangeInclusive<Option<T>> {
+    Some(start)..=Some(end)
+}
+
+/// Range of all the `Some` values of a key of type `Option<T>`.
+///
+/// # Example
+/// ```rust
+/// use itertools::Itertools;
+/// use native_db::*;
+/// use native_db::db_type::present_range;
+/// use native_model::{native_model, Model};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// #[native_model(id=1, version=1)]
+/// #[native_db]
+/// struct Data {
+///     #[primary_key]
+///     id: u64,
+///     #[secondary_key]
+///     parent: Option<u64>,
+/// }
+///
+/// fn main() -> Result<(), db_type::Error> {
+///     let mut models = Models::new();
+///     models.define::<Data>()?;
+///     let db = Builder::new().create_in_memory(&models)?;
+///
+///     // Get the values with a parent
+///     let r = db.r_transaction()?;
+///     let _children: Vec<Data> = r
+///         .scan()
+///         .secondary(DataKey::parent)?
+///         .range(present_range::<u64>())?
+///         .try_collect()?;
+///     Ok(())
+/// }
+/// ```
+pub fn present_range<T: ToKey>() -> RangeFrom<AnySome<T>> {
+    AnySome(PhantomData)..
+}
+
+/// Key of type `Option<T>` before all the `Some` values and after `None`, see [`present_range`].
+pub struct AnySome<T>(PhantomData<T>);
+
+impl<T> fmt::Debug for AnySome<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AnySome")
+    }
+}
+
+impl<T: ToKey> ToKey for AnySome<T> {
+    fn to_key(&self) -> Key {
+        Key::new(vec![1])
+    }
+
+    fn key_names() -> Vec<String> {
+        Option::<T>::key_names()
+    }
+
+    fn key_encoding_changed_since(version: u32) -> bool {
+        Option::<T>::key_encoding_changed_since(version)
+    }
+}
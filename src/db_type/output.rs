@@ -1,34 +1,73 @@
-use crate::db_type::{Result, ToInput};
+use crate::db_type::{Error, Result, ToInput};
+use crate::Compression;
 
 use super::Input;
 
+/// An encoded value, with the limit of its decoded size when it was read from the database.
 #[derive(Clone, Debug)]
-pub(crate) struct Output(pub(crate) Vec<u8>);
+pub(crate) struct Output(pub(crate) Vec<u8>, pub(crate) Option<usize>);
 
 impl From<Input> for Output {
     fn from(input: Input) -> Self {
-        Self(input.value)
+        Self(input.value, None)
     }
 }
 
 impl From<&[u8]> for Output {
     fn from(slice: &[u8]) -> Self {
-        Self(slice.to_vec())
+        Self(slice.to_vec(), None)
     }
 }
 
 impl Output {
     pub fn inner<T: ToInput>(&self) -> Result<T> {
-        T::native_db_bincode_decode_from_slice(&self.0)
+        T::native_db_bincode_decode_from_slice_limited(&self.0, self.1)
+    }
+
+    /// Decodes the value into a view of its model, see [`RGet::primary_as`](crate::transaction::query::RGet::primary_as).
+    ///
+    /// The decompressed value is checked against `max_value_size` like the stored one.
+    pub(crate) fn inner_view<V>(
+        &self,
+        compression: Option<Compression>,
+        max_value_size: Option<usize>,
+    ) -> Result<V>
+    where
+        V: serde::de::DeserializeOwned + native_model::Model,
+    {
+        match compression {
+            Some(compression) => Ok(crate::bincode_decode_from_slice(
+                &compression.decompress_bounded(&self.0, max_value_size)?,
+            )?
+            .0),
+            None => Ok(crate::bincode_decode_from_slice(&self.0)?.0),
+        }
+    }
+}
+
+/// Returns [`ValueTooLarge`](crate::db_type::Error::ValueTooLarge) if a stored value is larger
+/// than the limit set with [`Builder::set_max_value_size`](crate::Builder::set_max_value_size),
+/// before it is copied or decoded.
+pub(crate) fn check_value_size(value: &[u8], max_value_size: Option<usize>) -> Result<()> {
+    match max_value_size {
+        Some(limit) if value.len() > limit => Err(Error::ValueTooLarge {
+            size: value.len(),
+            limit,
+        }),
+        _ => Ok(()),
     }
 }
 
 pub(crate) fn unwrap_item<T: ToInput>(
     item: Option<redb::AccessGuard<&'static [u8]>>,
+    max_value_size: Option<usize>,
 ) -> Option<Result<T>> {
     if let Some(item) = item {
         let item = item.value();
-        let item = T::native_db_bincode_decode_from_slice(item);
+        if let Err(error) = check_value_size(item, max_value_size) {
+            return Some(Err(error));
+        }
+        let item = T::native_db_bincode_decode_from_slice_limited(item, max_value_size);
         Some(item)
     } else {
         None
@@ -11,9 +11,123 @@ pub trait ToInput: Sized + native_model::Model {
     fn native_db_bincode_encode_to_vec(&self) -> Result<Vec<u8>>;
     fn native_db_bincode_decode_from_slice(slice: &[u8]) -> Result<Self>;
 
+    /// Decodes a stored value, the value of a model with `#[native_db(compress = ...)]` is
+    /// checked against `max_value_size` once decompressed.
+    fn native_db_bincode_decode_from_slice_limited(
+        slice: &[u8],
+        _max_value_size: Option<usize>,
+    ) -> Result<Self> {
+        Self::native_db_bincode_decode_from_slice(slice)
+    }
+
+    /// Encode the value into the same binary format used to store it in the database.
+    ///
+    /// The bytes can be stored or sent outside of the database and decoded back with
+    /// [`decode`](Self::decode).
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     name: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let data = Data { id: 1, name: "test".to_string() };
+    ///
+    ///     // Encode the value, e.g. to push it in a message queue
+    ///     let bytes: Vec<u8> = data.encode()?;
+    ///
+    ///     // ... send the bytes over the wire ...
+    ///     let received = bytes.clone();
+    ///
+    ///     // Decode the value on the other side
+    ///     let decoded = Data::decode(&received)?;
+    ///     assert_eq!(decoded, data);
+    ///     Ok(())
+    /// }
+    /// ```
+    fn encode(&self) -> Result<Vec<u8>> {
+        self.native_db_bincode_encode_to_vec()
+    }
+
+    /// Decode a value encoded with [`encode`](Self::encode) or read from the database.
+    fn decode(slice: &[u8]) -> Result<Self> {
+        Self::native_db_bincode_decode_from_slice(slice)
+    }
+
+    /// Number of bytes the value occupies when stored, e.g. to check it against a size limit
+    /// before inserting it, or to log the size of each record.
+    ///
+    /// The value is encoded (and compressed with `#[native_db(compress = ...)]`) like by an
+    /// insert, the keys and the storage overhead of redb are not counted.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     payload: Vec<u8>,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let data = Data { id: 1, payload: vec![0; 1024] };
+    ///     assert!(data.encoded_size()? > 1024);
+    ///     Ok(())
+    /// }
+    /// ```
+    fn encoded_size(&self) -> Result<usize> {
+        Ok(self.native_db_bincode_encode_to_vec()?.len())
+    }
+
+    /// Swap the primary key field with the one of `other`, `false` if the primary key is
+    /// computed by a function and can not be swapped.
+    fn native_db_swap_primary_key(&mut self, _other: &mut Self) -> bool {
+        false
+    }
+
+    /// Value of the field set with `#[native_db(version_field = ...)]`, `None` if not set.
+    fn native_db_version(&self) -> Option<u64> {
+        None
+    }
+
+    fn native_db_set_version(&mut self, _version: u64) {}
+
+    /// Calls the function set with `#[native_db(validate = ...)]`, `Ok` if not set.
+    fn native_db_validate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// The primary key used to write the value, fails with `primary_key(whole)` if the value
+    /// can not be encoded.
+    fn native_db_try_primary_key(&self) -> Result<Key> {
+        Ok(self.native_db_primary_key())
+    }
+
+    /// `true` if the type of the primary key or of a secondary key changed encoding since the
+    /// encoding `version`, see [`ToKey::key_encoding_changed_since`](crate::db_type::ToKey::key_encoding_changed_since).
+    fn native_db_key_encoding_changed_since(_version: u32) -> bool {
+        true
+    }
+
     fn native_db_input(&self) -> Result<Input> {
         Ok(Input {
-            primary_key: self.native_db_primary_key(),
+            primary_key: self.native_db_try_primary_key()?,
             secondary_keys: self.native_db_secondary_keys(),
             value: self.native_db_bincode_encode_to_vec()?,
         })
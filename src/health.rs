@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+/// Result of [`health_check`](crate::Database::health_check).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthReport {
+    /// `true` if none of the checks below found an issue.
+    pub is_healthy: bool,
+    /// Description of each issue found, empty if the database is healthy.
+    pub issues: Vec<String>,
+    /// Result of the redb integrity check, `None` if it was not run, see
+    /// [`health_check_full`](crate::Database::health_check_full).
+    pub integrity: Option<bool>,
+    /// The file left behind by an interrupted upgrade, see [`open`](crate::Builder::open).
+    pub stale_upgrade_file: Option<PathBuf>,
+    /// Version of native_db stored in the database, empty if the metadata table is missing.
+    pub stored_version: String,
+    /// Version of native_db running, the stored version must not be newer.
+    pub current_version: String,
+    /// Number of values of each model, by primary table name, sorted by name.
+    pub row_counts: Vec<(String, u64)>,
+}
@@ -10,35 +10,66 @@
 //!    - [Insert a model in the database](#insert-a-model-in-the-database)
 //!    - [Update a model](#update-a-model)
 //!    - [Migration](#migration)
+//! - [Cargo features](#cargo-features)
 //! - Advanced
 //!    - [`Define a type as a key`](crate::db_type::ToKey)
 //!       - [Example with `uuid`](crate::db_type::ToKey#example-with-uuid)
 //!       - [Example with `chrono`](crate::db_type::ToKey#example-with-chrono)
+//!    - [`Ranges of optional keys`](crate::db_type::present_range): [`none_range`](crate::db_type::none_range), [`some_range`](crate::db_type::some_range), [`present_range`](crate::db_type::present_range)
 //!
 //! # Api
 //!
 //! - [`Models`] - Collection of models. *Equivalent to a schema in a traditional database*.
 //!    - [`new`](crate::Models::new) - Create a new collection of models.
 //!    - [`define`](crate::Models::define) - Define a model.
+//!    - [`define_alias`](crate::Models::define_alias) - Register another name of the table of a model, e.g. during a table rename.
+//!    - [`define_and_describe`](crate::Models::define_and_describe) - Define a model and get its [`ModelDescriptor`](crate::ModelDescriptor).
+//!    - [`schema_fingerprint`](crate::Models::schema_fingerprint) - Get a stable hash of the models, e.g. to detect schema changes in CI.
 //! - [`Builder`] - Builder to create a database.
 //!    - [`create_in_memory`](crate::Builder::create_in_memory) - Create a database in memory.
 //!    - [`create`](crate::Builder::create) - Create a database in a file.
 //!    - [`open`](crate::Builder::open) - Open a database.
+//!    - [`from_redb`](crate::Builder::from_redb) - Create a database from a redb database.
+//!    - [`with_mirror`](crate::Builder::with_mirror) - Mirror the commits to a second database file.
 //! - [`Database`] - Database instance.
 //!    - [`compact`](crate::Database::compact) - Compact the database.
+//!    - [`current_commit_sequence`](crate::Database::current_commit_sequence) - Get the sequence number of the latest commit.
+//!    - [`compact_fully`](crate::Database::compact_fully) - Compact the database until the file stops shrinking.
+//!    - [`compact_timeboxed`](crate::Database::compact_timeboxed) - Compact the database within a time budget.
+//!    - [`compact_safe`](crate::Database::compact_safe) - Compact the database, keeping a copy of the file until it succeeds.
+//!    - [`compact_with_progress`](crate::Database::compact_with_progress) - Compact the database, reporting its progress.
+//!    - [`rename_table`](crate::Database::rename_table) - Rename a table, e.g. after renaming a key.
 //!    - [`check_integrity`](crate::Database::check_integrity) - Check the integrity of the database.
+//!    - [`health_check`](crate::Database::health_check) - Check the version and the upgrade state of the database.
+//!    - [`health_check_full`](crate::Database::health_check_full) - Check the health of the database, including its integrity.
+//!    - [`scan_all`](crate::Database::scan_all) - Get all the items of a model.
+//!    - [`dump_table`](crate::Database::dump_table) - Write a debug dump of the items of a model.
+//!    - [`counters`](crate::Database::counters) - Increment, get and set named counters.
 //!    - [`rw_transaction`](crate::Database::rw_transaction) - Create a read-write transaction.
 //!       - [`insert`](crate::transaction::RwTransaction::insert) - Insert a item, fail if the item already exists.
+//!       - [`insert_with_blob`](crate::transaction::RwTransaction::insert_with_blob) - Insert a item and its blob.
 //!       - [`upsert`](crate::transaction::RwTransaction::upsert) - Upsert a item, update if the item already exists.
+//!       - [`write_many`](crate::transaction::RwTransaction::write_many) - Insert, upsert or insert-or-ignore many items.
+//!       - [`upsert_many`](crate::transaction::RwTransaction::upsert_many) - Upsert many items.
 //!       - [`update`](crate::transaction::RwTransaction::update) - Update a item, replace an existing item.
+//!       - [`update_checked`](crate::transaction::RwTransaction::update_checked) - Update a item if its version field matches the stored one.
 //!       - [`remove`](crate::transaction::RwTransaction::remove) - Remove a item, remove an existing item.
+//!       - [`remove_many_by_keys`](crate::transaction::RwTransaction::remove_many_by_keys) - Remove the items having the given primary keys.
 //!       - [`migrate`](crate::transaction::RwTransaction::migrate) - Migrate a model, affect all items.
+//!       - [`migrate_with`](crate::transaction::RwTransaction::migrate_with) - Migrate a model and adjust each item with a closure.
+//!       - [`counters`](crate::transaction::RwTransaction::counters) - Increment, get and set named counters in the transaction.
 //!       - [`commit`](crate::transaction::RwTransaction::commit) - Commit the transaction.
+//!       - [`commit_with_info`](crate::transaction::RwTransaction::commit_with_info) - Commit the transaction and get the number of changes.
 //!       - [`abort`](crate::transaction::RwTransaction::abort) - Abort the transaction.
+//!       - [`abort_returning`](crate::transaction::RwTransaction::abort_returning) - Abort the transaction and get the staged changes.
 //!   - [`r_transaction`](crate::Database::r_transaction) - Create a read-only transaction.
 //!       - [`get`](crate::transaction::RTransaction::get) - Get a item.
 //!          - [`primary`](crate::transaction::query::RGet::primary) - Get a item by primary key.
+//!          - [`primary_as`](crate::transaction::query::RGet::primary_as) - Get a item by primary key, decoded as a view of its leading fields.
+//!          - [`primary_checked`](crate::transaction::query::RGet::primary_checked) - Get a item by primary key, telling a corrupt value from a missing one.
+//!          - [`primary_alias`](crate::transaction::query::RGet::primary_alias) - Get a item by primary key from an alias of its table.
 //!          - [`secondary`](crate::transaction::query::RGet::secondary) - Get a item by secondary key.
+//!          - [`secondary_primary_key`](crate::transaction::query::RGet::secondary_primary_key) - Get the primary key of a item by secondary key.
 //!       - [`scan`](crate::transaction::RTransaction::scan) - Scan items.
 //!          - [`primary`](crate::transaction::query::RScan::primary) - Scan items by primary key.
 //!             - [`all`](crate::transaction::query::PrimaryScan::all) - Scan all items.
@@ -48,9 +79,13 @@
 //!             - [`all`](crate::transaction::query::SecondaryScan::all) - Scan items with a given secondary key.
 //!             - [`start_with`](crate::transaction::query::SecondaryScan::start_with) - Scan items with a secondary key starting with a key.
 //!             - [`range`](crate::transaction::query::SecondaryScan::range) - Scan items with a secondary key in a given range.
+//!       - [`merge_scan`](crate::transaction::RTransaction::merge_scan) - Scan items of several models ordered by a shared secondary key.
 //!       - [`len`](crate::transaction::RTransaction::len) - Get the number of items.
 //!          - [`primary`](crate::transaction::query::RLen::primary) - Get the number of items by primary key.
 //!          - [`secondary`](crate::transaction::query::RLen::secondary) - Get the number of items by secondary key.    
+//!       - [`open_blob`](crate::transaction::RTransaction::open_blob) - Stream the blob of a item.
+//!       - `get_archived` - Read a item stored as a rkyv archive without deserializing it, requires the feature `rkyv`.
+//!   - [`snapshot_reader`](crate::Database::snapshot_reader) - Create a read-only transaction shared by several threads.
 //!   - [`watch`](crate::Database::watch) - Watch items in real-time.  Works via [std channel](https://doc.rust-lang.org/std/sync/mpsc/fn.channel.html) based or [tokio channel](https://docs.rs/tokio/latest/tokio/sync/mpsc/fn.unbounded_channel.html) based depending on the feature `tokio`.
 //!       - [`get`](crate::watch::query::Watch::get) - Watch a item.
 //!          - [`primary`](crate::watch::query::WatchGet::primary) - Watch a item by primary key.
@@ -63,7 +98,9 @@
 //!          - [`secondary`](crate::watch::query::WatchScan::secondary) - Watch items by secondary key.
 //!             - [`all`](crate::watch::query::WatchScanSecondary::all) - Watch items with a given secondary key.
 //!             - [`start_with`](crate::watch::query::WatchScanSecondary::start_with) - Watch items with a secondary key starting with a key.
+//!             - [`start_with_component`](crate::watch::query::WatchScanSecondary::start_with_component) - Watch items with a compound secondary key starting with the given components.
 //!             - [`range`](crate::watch::query::WatchScanSecondary::range) - Watch items with a secondary key in a given range.
+//!   - [`watch_scoped`](crate::Database::watch_scoped) - Watch items like [`watch`](crate::Database::watch), unwatched when the returned guard is dropped.
 //!
 //!
 //! # Quick Start
@@ -363,18 +400,43 @@
 //!
 //! More details [`migrate`](crate::transaction::RwTransaction::migrate) method.
 //!
+//! # Cargo features
+//!
+//! With `default-features = false`, Native DB only depends on `redb`, `serde` and `native_model`,
+//! plus `native_db_macro`, `thiserror`, `semver` and `xxhash-rust` (only `xxh64`, for the hashed
+//! primary keys and the schema fingerprint), which is the minimal build for constrained targets.
+//!
+//! | Feature         | Default | Description                                                                 |
+//! |-----------------|---------|-----------------------------------------------------------------------------|
+//! | `upgrade_0_5_x` | yes     | Upgrade databases created with native_db `0.5.x` (pulls `redb` `1.x`).       |
+//! | `upgrade_0_7_x` | yes     | Upgrade databases created with native_db `0.7.x`.                           |
+//! | `tokio`         | no      | Watch with [`tokio`](https://crates.io/crates/tokio) channels and `Watch::debounce` (pulls `tokio`). |
+//! | `diagnostics`   | no      | Query plans of the scans, e.g. `SecondaryScanIterator::explain`.                |
+//! | `rust_decimal`  | no      | Use `rust_decimal::Decimal` as a key, ordered by value (pulls `rust_decimal`). |
+//! | `zstd`          | no      | Compress the values of a model with `#[native_db(compress = "zstd")]` (pulls `zstd`). |
+//! | `rkyv`          | no      | Store the values of a model as rkyv archives, read without deserializing, with `#[native_db(rkyv)]` (pulls `rkyv`). |
+//!
+//! Types from crates like `chrono` or `uuid` do not need a feature, they can be used as keys
+//! by implementing [`ToKey`](crate::db_type::ToKey).
+//!
+mod archive;
+mod counters;
 mod database;
 mod database_builder;
 mod database_instance;
 
 /// A collection of type used by native_db internally (macro included).
 pub mod db_type;
+mod health;
 mod metadata;
+mod mirror;
 mod model;
+mod savepoints;
 mod serialization;
 mod snapshot;
 mod stats;
 mod table_definition;
+mod unique_secondary_cache;
 pub mod upgrade;
 
 mod models;
@@ -390,13 +452,18 @@ pub use db_type::ToInput;
 /// Allow to use a type as a key in the database.
 pub use db_type::ToKey;
 pub use native_model;
+#[cfg(feature = "rkyv")]
+pub use rkyv;
 
 // Export
+pub use counters::*;
 pub use database::*;
 pub use database_builder::*;
+pub use health::*;
 pub use metadata::*;
 pub use model::*;
 pub use models::*;
+pub use savepoints::*;
 
 #[cfg(doctest)]
 #[macro_use]
@@ -1,2 +1,10 @@
 pub const CURRENT_VERSION: &str = "0.8.1";
 pub const CURRENT_NATIVE_MODEL_VERSION: &str = "0.4.19";
+// Encoding of the keys, see `key_encoding_outdated`. To bump when the bytes produced by a
+// `ToKey` change, with `ToKey::key_encoding_changed_since` of the changed types: the tables
+// recorded with an older encoding are re-keyed by `Builder::upgrade_key_encoding`.
+pub const CURRENT_KEY_ENCODING: u32 = 3;
+// Configuration of the codec of the values encoded with native_model, see `check_or_stamp_codec`.
+// To change when a dependency bump changes the bytes produced by the codec (e.g. the integer
+// encoding or the endianness of bincode), the tables recorded with another codec are refused.
+pub const CURRENT_CODEC: &str = "bincode-2/standard/little-endian/varint";
@@ -1,19 +1,39 @@
-use super::Metadata;
-use crate::db_type::Result;
-use redb::TableDefinition;
+use super::{Metadata, CURRENT_CODEC, CURRENT_KEY_ENCODING};
+use crate::db_type::{Error, Key, Result};
+use crate::Compression;
+use redb::{ReadableTable, ReadableTableMetadata, TableDefinition};
 
 pub const VERSION_NATIVE_DB_NAME: &str = "version_native_db";
 pub const VERSION_NATIVE_MODEL_NAME: &str = "version_native_model";
+// Followed by the name of a primary table, e.g. "compression:1_1_id".
+pub const COMPRESSION_NAME_PREFIX: &str = "compression:";
+// Followed by the name of a primary table, e.g. "encoding:1_1_id".
+pub const ENCODING_NAME_PREFIX: &str = "encoding:";
+// Followed by the name of a primary table, e.g. "key_encoding:1_1_id".
+pub const KEY_ENCODING_NAME_PREFIX: &str = "key_encoding:";
+// Followed by the name of a primary table, e.g. "codec:1_1_id".
+pub const CODEC_NAME_PREFIX: &str = "codec:";
+// Encoding of the keys of the tables filled before the record: the signed integers and the
+// floats not sorted in numeric order, and `None` encoded as an empty key. The encoding 2 has the
+// components of the tuples not delimited.
+const FIRST_KEY_ENCODING: u32 = 1;
+// Encodings of the values recorded with `ENCODING_NAME_PREFIX`, see `#[native_db(rkyv)]`.
+const NATIVE_MODEL_ENCODING: &str = "native_model";
+const RKYV_ENCODING: &str = "rkyv";
 
 use crate::database_instance::DatabaseInstance;
 
-const TABLE: TableDefinition<&str, &str> = TableDefinition::new("metadata");
+pub const DEFAULT_METADATA_TABLE_NAME: &str = "metadata";
 
-pub fn save_metadata(database_instance: &DatabaseInstance, configuration: &Metadata) -> Result<()> {
+pub fn save_metadata(
+    database_instance: &DatabaseInstance,
+    table_name: &str,
+    configuration: &Metadata,
+) -> Result<()> {
     let table = database_instance.redb_database()?;
     let write_thx = table.begin_write()?;
     {
-        let mut table = write_thx.open_table(TABLE)?;
+        let mut table = write_thx.open_table(TableDefinition::<&str, &str>::new(table_name))?;
         table.insert(VERSION_NATIVE_DB_NAME, configuration.current_version())?;
         table.insert(
             VERSION_NATIVE_MODEL_NAME,
@@ -25,25 +45,276 @@ pub fn save_metadata(database_instance: &DatabaseInstance, configuration: &Metad
     Ok(())
 }
 
-pub fn load_or_create_metadata(database_instance: &DatabaseInstance) -> Result<Metadata> {
+/// Removes the metadata table, so the database is reported as not initialized on the next open.
+pub fn remove_metadata(database_instance: &DatabaseInstance, table_name: &str) -> Result<()> {
+    let database = database_instance.redb_database()?;
+    let write_thx = database.begin_write()?;
+    write_thx.delete_table(TableDefinition::<&str, &str>::new(table_name))?;
+    write_thx.commit()?;
+    Ok(())
+}
+
+/// Removes the versions from the metadata, so the database is reported as not initialized on
+/// the next open while the records of the tables are kept.
+pub(crate) fn remove_versions(
+    database_instance: &DatabaseInstance,
+    table_name: &str,
+) -> Result<()> {
+    let database = database_instance.redb_database()?;
+    let write_thx = database.begin_write()?;
+    {
+        let mut table = write_thx.open_table(TableDefinition::<&str, &str>::new(table_name))?;
+        table.remove(VERSION_NATIVE_DB_NAME)?;
+        table.remove(VERSION_NATIVE_MODEL_NAME)?;
+    }
+    write_thx.commit()?;
+    Ok(())
+}
+
+/// Returns `true` if the metadata table holds the version, i.e. the database was already
+/// initialized.
+pub fn metadata_exists(database_instance: &DatabaseInstance, table_name: &str) -> Result<bool> {
+    Ok(load_metadata(database_instance, table_name)?.is_some())
+}
+
+/// Returns the metadata, `None` if the metadata table does not exist or does not hold the
+/// version.
+pub fn load_metadata(
+    database_instance: &DatabaseInstance,
+    table_name: &str,
+) -> Result<Option<Metadata>> {
     let database = database_instance.redb_database()?;
     let read_thx = database.begin_read()?;
 
-    if let Ok(table) = read_thx.open_table(TABLE) {
-        let current_version = table
-            .get(VERSION_NATIVE_DB_NAME)?
-            .expect("Fatal error: current_version not found");
+    if let Ok(table) = read_thx.open_table(TableDefinition::<&str, &str>::new(table_name)) {
+        let Some(current_version) = table.get(VERSION_NATIVE_DB_NAME)? else {
+            return Ok(None);
+        };
         let current_native_model_version = table
             .get(VERSION_NATIVE_MODEL_NAME)?
             .expect("Fatal error: current_native_model_version not found");
-        Ok(Metadata::new(
+        Ok(Some(Metadata::new(
             current_version.value().to_string(),
             current_native_model_version.value().to_string(),
-        ))
+        )))
+    } else {
+        Ok(None)
+    }
+}
+
+pub fn load_or_create_metadata(
+    database_instance: &DatabaseInstance,
+    table_name: &str,
+) -> Result<Metadata> {
+    if let Some(metadata) = load_metadata(database_instance, table_name)? {
+        Ok(metadata)
     } else {
         // Create the metadata table if it does not exist
         let metadata = Metadata::default();
-        save_metadata(database_instance, &metadata)?;
+        save_metadata(database_instance, table_name, &metadata)?;
         Ok(metadata)
     }
 }
+
+/// Compare the compression recorded in the metadata for the primary table `table_name` with
+/// the one of its model, see `#[native_db(compress = ...)]`.
+///
+/// The compression is recorded while the table is empty, so it can still be changed. The
+/// tables filled before the record are not compressed.
+pub(crate) fn check_or_stamp_compression(
+    write_thx: &redb::WriteTransaction,
+    metadata_table_name: &str,
+    table_name: &str,
+    compression: Option<Compression>,
+) -> Result<()> {
+    let current = compression.map_or(Compression::NONE_NAME, |compression| compression.name());
+    let name = format!("{}{}", COMPRESSION_NAME_PREFIX, table_name);
+    let stored = check_or_stamp_table(
+        write_thx,
+        metadata_table_name,
+        table_name,
+        &name,
+        current,
+        Compression::NONE_NAME,
+    )?;
+    match stored {
+        Some(stored) => Err(Error::CompressionMismatch {
+            table: table_name.to_string(),
+            stored,
+            current: current.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Compare the encoding recorded in the metadata for the primary table `table_name` with
+/// the one of its model, `rkyv` with `#[native_db(rkyv)]` and `native_model` otherwise.
+///
+/// Like the compression, the encoding is recorded while the table is empty. The tables filled
+/// before the record are encoded with `native_model`.
+pub(crate) fn check_or_stamp_encoding(
+    write_thx: &redb::WriteTransaction,
+    metadata_table_name: &str,
+    table_name: &str,
+    rkyv: bool,
+) -> Result<()> {
+    let current = if rkyv {
+        RKYV_ENCODING
+    } else {
+        NATIVE_MODEL_ENCODING
+    };
+    let name = format!("{}{}", ENCODING_NAME_PREFIX, table_name);
+    let stored = check_or_stamp_table(
+        write_thx,
+        metadata_table_name,
+        table_name,
+        &name,
+        current,
+        NATIVE_MODEL_ENCODING,
+    )?;
+    match stored {
+        Some(stored) => Err(Error::EncodingMismatch {
+            table: table_name.to_string(),
+            stored,
+            current: current.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Compare the codec recorded in the metadata for the primary table `table_name` with the
+/// current one, see `CURRENT_CODEC`. The tables of the models with `#[native_db(rkyv)]` are
+/// not encoded with native_model and have no codec.
+///
+/// Like the compression, the codec is recorded while the table is empty. The tables filled
+/// before the record are assumed to use the current codec.
+pub(crate) fn check_or_stamp_codec(
+    write_thx: &redb::WriteTransaction,
+    metadata_table_name: &str,
+    table_name: &str,
+    rkyv: bool,
+) -> Result<()> {
+    if rkyv {
+        return Ok(());
+    }
+    let name = format!("{}{}", CODEC_NAME_PREFIX, table_name);
+    let stored = check_or_stamp_table(
+        write_thx,
+        metadata_table_name,
+        table_name,
+        &name,
+        CURRENT_CODEC,
+        CURRENT_CODEC,
+    )?;
+    match stored {
+        Some(stored) => Err(Error::CodecMismatch {
+            table: table_name.to_string(),
+            stored,
+            current: CURRENT_CODEC.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Compare the encoding of the keys recorded in the metadata for the primary table
+/// `table_name` with the current one. Returns the recorded encoding if it is older: the table
+/// is re-keyed by [`Builder::upgrade_key_encoding`](crate::Builder::upgrade_key_encoding), or
+/// only recorded with [`stamp_key_encoding`] if its key types did not change.
+///
+/// Like the compression, the encoding is recorded while the table is empty. The tables filled
+/// before the record use the first encoding.
+pub(crate) fn key_encoding_outdated(
+    write_thx: &redb::WriteTransaction,
+    metadata_table_name: &str,
+    table_name: &str,
+) -> Result<Option<u32>> {
+    let current = CURRENT_KEY_ENCODING.to_string();
+    let name = format!("{}{}", KEY_ENCODING_NAME_PREFIX, table_name);
+    let stored = check_or_stamp_table(
+        write_thx,
+        metadata_table_name,
+        table_name,
+        &name,
+        &current,
+        &FIRST_KEY_ENCODING.to_string(),
+    )?;
+    let Some(stored) = stored else {
+        return Ok(None);
+    };
+    match stored.parse::<u32>() {
+        Ok(version) if version < CURRENT_KEY_ENCODING => Ok(Some(version)),
+        _ => Err(Error::KeyEncodingMismatch {
+            table: table_name.to_string(),
+            stored,
+            current,
+        }),
+    }
+}
+
+/// Record the current encoding of the keys for the primary table `table_name`.
+pub(crate) fn stamp_key_encoding(
+    write_thx: &redb::WriteTransaction,
+    metadata_table_name: &str,
+    table_name: &str,
+) -> Result<()> {
+    let name = format!("{}{}", KEY_ENCODING_NAME_PREFIX, table_name);
+    write_thx
+        .open_table(TableDefinition::<&str, &str>::new(metadata_table_name))?
+        .insert(name.as_str(), CURRENT_KEY_ENCODING.to_string().as_str())?;
+    Ok(())
+}
+
+/// Move the compression, the encodings and the codec recorded for the primary table `old` to `new`, when
+/// the entries of `old` are moved to `new`. The records of `new`, stamped while it was empty,
+/// are replaced: removed if `old` has none, e.g. if it was filled before the record.
+pub(crate) fn move_table_stamps(
+    write_thx: &redb::WriteTransaction,
+    metadata_table_name: &str,
+    old: &str,
+    new: &str,
+) -> Result<()> {
+    let mut metadata_table =
+        write_thx.open_table(TableDefinition::<&str, &str>::new(metadata_table_name))?;
+    for prefix in [
+        COMPRESSION_NAME_PREFIX,
+        ENCODING_NAME_PREFIX,
+        KEY_ENCODING_NAME_PREFIX,
+        CODEC_NAME_PREFIX,
+    ] {
+        let old_name = format!("{}{}", prefix, old);
+        let new_name = format!("{}{}", prefix, new);
+        let stored = metadata_table
+            .remove(old_name.as_str())?
+            .map(|stored| stored.value().to_string());
+        match stored {
+            Some(stored) => metadata_table.insert(new_name.as_str(), stored.as_str())?,
+            None => metadata_table.remove(new_name.as_str())?,
+        };
+    }
+    Ok(())
+}
+
+/// Record `current` under `name` if the table `table_name` is empty, otherwise compare it with
+/// the recorded value (`default` if there is none). Returns the recorded value if they differ.
+fn check_or_stamp_table(
+    write_thx: &redb::WriteTransaction,
+    metadata_table_name: &str,
+    table_name: &str,
+    name: &str,
+    current: &str,
+    default: &str,
+) -> Result<Option<String>> {
+    let table_is_empty = write_thx
+        .open_table(TableDefinition::<Key, &[u8]>::new(table_name))?
+        .is_empty()?;
+    let mut metadata_table =
+        write_thx.open_table(TableDefinition::<&str, &str>::new(metadata_table_name))?;
+    if table_is_empty {
+        metadata_table.insert(name, current)?;
+        return Ok(None);
+    }
+    let stored = metadata_table
+        .get(name)?
+        .map_or(default.to_string(), |stored| stored.value().to_string());
+    Ok((stored != current).then_some(stored))
+}
@@ -0,0 +1,109 @@
+use crate::counters::COUNTERS_TABLE;
+use crate::db_type::{Error, Input, Output, Result};
+use crate::transaction::internal::rw_transaction::InternalRwTransaction;
+use crate::watch::{Batch, Event, Update, WatcherRequest};
+use crate::Database;
+use std::collections::HashMap;
+
+// Second database receiving the changes of each commit, see `Builder::with_mirror`. The
+// changes are replayed from the batch sent to the watchers and the counters changed by the
+// transaction, the operations whose changes are not watched return `Error::NotMirrored`, see
+// `RwTransaction::check_not_mirrored`.
+pub(crate) struct Mirror<'a>(Box<Database<'a>>);
+
+impl<'a> Mirror<'a> {
+    pub(crate) fn new(database: Database<'a>) -> Self {
+        Self(Box::new(database))
+    }
+
+    /// Applies the changes of a transaction committed on the primary database, in a single
+    /// transaction.
+    pub(crate) fn replay(&self, batch: &Batch, counters: &HashMap<String, i64>) -> Result<()> {
+        if batch.requests().next().is_none() && counters.is_empty() {
+            return Ok(());
+        }
+        let database = &self.0;
+        let internal = InternalRwTransaction {
+            redb_transaction: database.instance.redb_database()?.begin_write()?,
+            primary_table_definitions: &database.primary_table_definitions,
+            max_value_size: database.max_value_size,
+            size_quota: None,
+            two_phase_commit: false,
+            quick_repair: false,
+        };
+        let mut requests = batch.requests();
+        while let Some((request, event)) = requests.next() {
+            let model = database
+                .primary_table_definitions
+                .get(&request.table_name)
+                .ok_or_else(|| Error::TableDefinitionNotFound {
+                    table: request.table_name.clone(),
+                })?
+                .model
+                .clone();
+            match event {
+                Event::Insert(insert) => {
+                    internal.concrete_insert(model, input(request, &insert.0))?;
+                }
+                // Both values of a swap are removed before being inserted again, like in
+                // `RwTransaction::swap`, so a unique secondary key doesn't collide in between.
+                Event::Update(update) if request.swapped_with_next => {
+                    let Some((other_request, Event::Update(other_update))) = requests.next() else {
+                        return Err(Error::MirrorReplay {
+                            table: request.table_name.clone(),
+                            reason: "the swap holds a single update".to_string(),
+                        });
+                    };
+                    internal.concrete_remove(model.clone(), old_input(request, update)?)?;
+                    internal
+                        .concrete_remove(model.clone(), old_input(other_request, other_update)?)?;
+                    internal.concrete_insert(model.clone(), input(request, &update.new))?;
+                    internal.concrete_insert(model, input(other_request, &other_update.new))?;
+                }
+                Event::Update(update) => {
+                    internal.concrete_update(
+                        model,
+                        old_input(request, update)?,
+                        input(request, &update.new),
+                    )?;
+                }
+                Event::Delete(delete) => {
+                    internal.concrete_remove(model, input(request, &delete.0))?;
+                }
+                // Only in the summaries, the values are replayed one by one
+                Event::BulkInsert { .. } | Event::BulkDelete { .. } => {}
+            }
+        }
+        if !counters.is_empty() {
+            let mut table = internal.redb_transaction.open_table(COUNTERS_TABLE)?;
+            for (name, value) in counters {
+                table.insert(name.as_str(), value)?;
+            }
+        }
+        internal.commit()
+    }
+}
+
+// The value of an event with the keys of its request.
+fn input(request: &WatcherRequest, value: &Output) -> Input {
+    Input {
+        primary_key: request.primary_key.clone(),
+        secondary_keys: request.secondary_keys_value.clone(),
+        value: value.0.clone(),
+    }
+}
+
+// The previous value of an update with its keys.
+fn old_input(request: &WatcherRequest, update: &Update) -> Result<Input> {
+    let Some((primary_key, secondary_keys)) = request.old_keys.clone() else {
+        return Err(Error::MirrorReplay {
+            table: request.table_name.clone(),
+            reason: "the update holds no old keys".to_string(),
+        });
+    };
+    Ok(Input {
+        primary_key,
+        secondary_keys,
+        value: update.old.0.clone(),
+    })
+}
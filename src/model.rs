@@ -1,14 +1,82 @@
-use crate::db_type::{Error, KeyDefinition, KeyOptions, Result};
+use crate::db_type::{check_value_size, Error, KeyDefinition, KeyOptions, Result};
+use crate::Compression;
 use std::collections::HashSet;
 
 /// See the documentation [crate::Models::define] to see how to define a model.
+///
+/// Built by `#[native_db]`, new options may add fields.
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub struct Model {
+    /// Name of the Rust type, used in the error messages.
+    pub name: &'static str,
+    /// The `native_model` id of the model, used in the error messages.
+    pub native_model_id: u32,
+    /// The `native_model` version of the model, used in the error messages.
+    pub native_model_version: u32,
     pub primary_key: KeyDefinition<()>,
     pub secondary_keys: HashSet<KeyDefinition<KeyOptions>>,
+    /// Set by `#[native_db(hashed_primary_key)]`, see [Models](crate::Models#hashed-primary-key).
+    pub hashed_primary_key: bool,
+    /// Set by `#[native_db(primary_key(whole))]`, see [Models](crate::Models#whole-primary-key).
+    pub whole_primary_key: bool,
+    /// Set by `#[native_db(blob)]`, see [Models](crate::Models#blob).
+    pub blob: bool,
+    /// Set by `#[native_db(compress = ...)]`, see [Models](crate::Models#compression).
+    pub compression: Option<Compression>,
+    /// Set by `#[native_db(rkyv)]`, see [Models](crate::Models#rkyv).
+    pub rkyv: bool,
 }
 
 impl Model {
+    /// Used by the macro `#[native_db]`, which sets the options on the returned model.
+    #[doc(hidden)]
+    pub fn new(
+        name: &'static str,
+        native_model_id: u32,
+        native_model_version: u32,
+        primary_key: KeyDefinition<()>,
+        secondary_keys: HashSet<KeyDefinition<KeyOptions>>,
+    ) -> Self {
+        Self {
+            name,
+            native_model_id,
+            native_model_version,
+            primary_key,
+            secondary_keys,
+            hashed_primary_key: false,
+            whole_primary_key: false,
+            blob: false,
+            compression: None,
+            rkyv: false,
+        }
+    }
+
+    /// Error returned when the model is used without being [defined](crate::Models::define).
+    pub(crate) fn not_defined_error(&self) -> Error {
+        Error::ModelNotDefined {
+            model: self.name.to_string(),
+            id: self.native_model_id,
+            version: self.native_model_version,
+        }
+    }
+
+    /// Returns the encoded value of a stored one, decompressed if the model has a compression.
+    ///
+    /// The stored value and the decompressed one are checked against `max_value_size`, see
+    /// [`Builder::set_max_value_size`](crate::Builder::set_max_value_size).
+    pub(crate) fn decompress(
+        &self,
+        value: Vec<u8>,
+        max_value_size: Option<usize>,
+    ) -> Result<Vec<u8>> {
+        check_value_size(&value, max_value_size)?;
+        match self.compression {
+            Some(compression) => compression.decompress_bounded(&value, max_value_size),
+            None => Ok(value),
+        }
+    }
+
     pub fn check_secondary_options<F>(
         &self,
         secondary_key: &KeyDefinition<KeyOptions>,
@@ -1,6 +1,11 @@
 use std::collections::HashMap;
+use xxhash_rust::xxh64::xxh64;
 
-use crate::{db_type::Result, table_definition::NativeModelOptions, ModelBuilder, ToInput};
+use crate::{
+    db_type::{Error, Result},
+    table_definition::NativeModelOptions,
+    Model, ModelBuilder, ToInput,
+};
 
 /// A collection of [`Model`](crate::Model) used by the [`Models`](crate::Models) to
 /// [define](Self::define) models.
@@ -154,6 +159,270 @@ impl Models {
     /// In this example, we have:
     /// - **One primary key** named `custom_id` of type `u32`, defined using a custom method. The method `custom_id` computes and returns the primary key value.
     ///
+    /// ### Defining a Model with a Composite Primary Key
+    ///
+    /// A tuple can be used as primary key, on a field or returned by a method.
+    ///
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db(
+    ///     primary_key(composite_pk -> (u32, u32))
+    /// )]
+    /// struct User {
+    ///     tenant_id: u32,
+    ///     user_id: u32,
+    /// }
+    ///
+    /// impl User {
+    ///     fn composite_pk(&self) -> (u32, u32) {
+    ///         (self.tenant_id, self.user_id)
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// The components are concatenated in order, so a get by primary key takes the full tuple and
+    /// the values can be scanned by the first components with
+    /// [`start_with_component`](crate::transaction::query::PrimaryScan::start_with_component).
+    /// - **Note:** The fixed-size components (integers, etc.) are concatenated as is, while the
+    ///   variable-size components (e.g. `String`) but the last are delimited, so `("ab", "c")` and
+    ///   `("a", "bc")` give different keys. See [`ToKey::is_fixed_width`](crate::db_type::ToKey::is_fixed_width).
+    ///
+    /// ### Hashed Primary Key
+    ///
+    /// Very long primary keys (e.g. URLs) are stored in the primary table and in each secondary table.
+    /// With `#[native_db(hashed_primary_key)]`, a fixed-size key of 12 bytes is stored instead: the
+    /// [xxHash64](https://docs.rs/xxhash-rust) of the primary key followed by a salt. The original primary
+    /// key is stored once, in a dedicated table, to resolve collisions: on collision the key is stored
+    /// with the next salt.
+    ///
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db(hashed_primary_key)]
+    /// struct Page {
+    ///     #[primary_key]
+    ///     url: String,
+    ///     #[secondary_key]
+    ///     domain: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Page>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Page {
+    ///         url: "https://example.com/a/very/long/path".to_string(),
+    ///         domain: "example.com".to_string(),
+    ///     })?;
+    ///     rw.commit()?;
+    ///
+    ///     // Get by primary key as usual
+    ///     let r = db.r_transaction()?;
+    ///     let _page: Option<Page> = r.get().primary("https://example.com/a/very/long/path")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// - **Note:** The values are ordered by the hash of the primary key, so [`all`](crate::transaction::query::PrimaryScan::all)
+    ///   does not return them in the order of the primary key, and [`range`](crate::transaction::query::PrimaryScan::range)
+    ///   and [`start_with`](crate::transaction::query::PrimaryScan::start_with) are not meaningful on a hashed primary key.
+    ///
+    /// ### Whole Primary Key
+    ///
+    /// With `#[native_db(primary_key(whole))]`, the whole value is the primary key: the table is a
+    /// set of values rather than a map. The key is the encoded value, and the model implements
+    /// [`ToKey`](crate::ToKey) to query it (so it must implement `Debug`).
+    /// [`insert`](crate::transaction::RwTransaction::insert) of a value already stored does nothing.
+    ///
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize, Debug, Clone)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db(primary_key(whole))]
+    /// struct Edge {
+    ///     from: u32,
+    ///     to: u32,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Edge>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Edge { from: 1, to: 2 })?;
+    ///     rw.insert(Edge { from: 1, to: 2 })?;
+    ///     assert_eq!(rw.len().primary::<Edge>()?, 1);
+    ///
+    ///     let edge: Option<Edge> = rw.get().primary(Edge { from: 1, to: 2 })?;
+    ///     assert!(edge.is_some());
+    ///     rw.commit()
+    /// }
+    /// ```
+    ///
+    /// - **Note:** Changing any field changes the key, so a value can't be updated: remove it
+    ///   and insert the new one.
+    ///
+    /// ### Version Field
+    ///
+    /// With `#[native_db(version_field = <field>)]`, a `u64` field holds the version of the value,
+    /// used by [`update_checked`](crate::transaction::RwTransaction::update_checked) to detect
+    /// concurrent modifications: the update is refused if the stored version is not the version
+    /// of the new value, otherwise the value is written with the version incremented.
+    ///
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db(version_field = rev)]
+    /// struct Document {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     content: String,
+    ///     rev: u64,
+    /// }
+    /// ```
+    ///
+    /// ### Validation
+    ///
+    /// With `#[native_db(validate = <function>)]`, the function `fn(&Self) -> Result<(), String>`
+    /// is called by [`insert`](crate::transaction::RwTransaction::insert),
+    /// [`upsert`](crate::transaction::RwTransaction::upsert) and the updates before anything is
+    /// written. An `Err(message)` is returned as [`ValidationFailed`](crate::db_type::Error::ValidationFailed)
+    /// and the transaction is left unchanged.
+    ///
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db(validate = Payment::validate)]
+    /// struct Payment {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     amount: i64,
+    /// }
+    ///
+    /// impl Payment {
+    ///     fn validate(&self) -> Result<(), String> {
+    ///         if self.amount < 0 {
+    ///             return Err(format!("negative amount: {}", self.amount));
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// ### Blob
+    ///
+    /// With `#[native_db(blob)]`, each value can have a blob: a large payload (e.g. a file) stored
+    /// apart from the value, by chunks of 64 KiB in a dedicated table. The blob is written with
+    /// [`insert_with_blob`](crate::transaction::RwTransaction::insert_with_blob) and streamed with
+    /// [`open_blob`](crate::transaction::RTransaction::open_blob), without loading it entirely
+    /// in memory.
+    ///
+    /// - **Note:** The blob is attached to the primary key: it is removed with the value by
+    ///   [`remove`](crate::transaction::RwTransaction::remove), and kept by the updates that
+    ///   do not change the primary key.
+    ///
+    /// ### Compression
+    ///
+    /// With `#[native_db(compress = "zstd")]`, the values of the model are compressed with
+    /// [Zstandard](https://facebook.github.io/zstd/) before they are stored, and decompressed
+    /// when they are read. Only the models with large values pay the cost of the compression,
+    /// the other models of the database are stored as is. The `zstd` feature must be enabled,
+    /// otherwise [`define`](Self::define) returns
+    /// [`CompressionNotEnabled`](crate::db_type::Error::CompressionNotEnabled).
+    ///
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db(compress = "zstd")]
+    /// struct Document {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     content: String,
+    /// }
+    /// ```
+    ///
+    /// - **Note:** The compression of each table is recorded in the metadata. Adding or removing
+    ///   `compress` on a model whose table is not empty makes the opening of the database fail
+    ///   with [`CompressionMismatch`](crate::db_type::Error::CompressionMismatch), define a new
+    ///   version of the model and [`migrate`](crate::transaction::RwTransaction::migrate) to it instead.
+    ///
+    /// ### Rkyv
+    ///
+    /// With `#[native_db(rkyv)]`, the values of the model are stored as [rkyv](https://rkyv.org)
+    /// archives instead of `native_model`, and can be read with `get_archived` without being
+    /// deserialized: the fields of the archive are read in place. The `rkyv` feature must be
+    /// enabled, `native_db::rkyv` is the version of rkyv used by Native DB.
+    ///
+    /// The model must derive the rkyv traits `Archive`, `Serialize` and `Deserialize` in addition
+    /// to the serde ones, which `native_model` still requires.
+    ///
+    /// ```rust,ignore
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+    /// #[rkyv(crate = native_db::rkyv)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db(rkyv)]
+    /// struct Document {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     title: String,
+    ///     content: Vec<u8>,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Document>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let r = db.r_transaction()?;
+    ///     if let Some(document) = r.get_archived::<Document>(1u64)? {
+    ///         // `document` dereferences to `ArchivedDocument`
+    ///         println!("{}", document.title);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// - **Note:** The stored bytes are copied once in an aligned buffer, and the archive is
+    ///   validated, before it is read.
+    /// - **Note:** The encoding of each table is recorded in the metadata. Adding or removing
+    ///   `rkyv` on a model whose table is not empty makes the opening of the database fail
+    ///   with [`EncodingMismatch`](crate::db_type::Error::EncodingMismatch).
+    /// - **Note:** The values are not encoded with `native_model`, so `rkyv` can not be combined
+    ///   with `compress`, and the models stored as archives can not be
+    ///   [migrated](crate::transaction::RwTransaction::migrate) or read through a view
+    ///   (e.g. [`primary_as`](crate::transaction::query::RGet::primary_as)).
+    ///
     /// ## Secondary Key
     ///
     /// The secondary key is *flexible*, and you can:
@@ -175,6 +444,8 @@ impl Models {
     /// - [`unique`](#unique) (default: false)
     /// - [`optional`](#optional) (default: false)
     ///
+    /// See also [`multi`](#multi), [`store_value`](#store-value) and [`collation`](#collation).
+    ///
     /// ### Defining a Model with a Secondary Key on a Field
     ///
     /// ```rust
@@ -219,7 +490,7 @@ impl Models {
     /// - **One primary key** named `id` of type `u64`, defined on the field.
     /// - **One secondary key** named `name` of type `Option<String>`, defined on the field with options `unique` and `optional`.
     ///
-    /// - **Note:** The secondary key can be `unique`, `optional`, or both.
+    /// - **Note:** The secondary key can be `unique`, `optional`, or both. See also [`multi`](#multi).
     ///
     /// ### Unique
     ///
@@ -236,6 +507,189 @@ impl Models {
     /// Under the hood, the secondary key is stored in a separate `redb` table. So if the secondary key is optional,
     /// the value will be stored in the table only if the value is not `None`.
     ///
+    /// The values are encoded like [`Option`](https://doc.rust-lang.org/std/option/enum.Option.html)
+    /// keys: `Some(value)` is `0x01` followed by the encoding of `value`, and `None` (not indexed)
+    /// would be `0x00`, sorting before all the `Some` values. So the queries take an `Option`,
+    /// e.g. `r.get().secondary(DataKey::name, Some("value"))`.
+    ///
+    /// ### Multi
+    ///
+    /// With `multi`, the value is a collection (e.g. `Vec<String>`) and each element is a
+    /// separate entry of the secondary key, pointing to the same item. The queries take the type
+    /// of the elements. On update, only the added and removed elements are rewritten.
+    /// A scan over a range matching several elements of the same item returns the item once
+    /// per element. `multi` can not be combined with `optional`.
+    ///
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Article {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key(multi)]
+    ///     tags: Vec<String>,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Article>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Article { id: 1, tags: vec!["rust".to_string(), "db".to_string()] })?;
+    ///     rw.commit()?;
+    ///
+    ///     let r = db.r_transaction()?;
+    ///     let articles: Vec<Article> = r
+    ///         .scan()
+    ///         .secondary(ArticleKey::tags)?
+    ///         .range("rust"..="rust")?
+    ///         .collect::<Result<_, _>>()?;
+    ///     assert_eq!(articles.len(), 1);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// ### Store Value
+    ///
+    /// By default, [`get().secondary`](crate::transaction::query::RGet::secondary) reads the
+    /// primary key from the secondary table then the value from the primary table. With
+    /// `store_value`, a unique secondary key also stores a copy of the value, so the lookup is a
+    /// single read. The copy is kept up to date on insert, update and remove, at the cost of
+    /// storing each value twice and writing it twice. `store_value` requires `unique`.
+    ///
+    /// When `store_value` is added to an existing key, the values of the existing items are copied
+    /// when the database is opened.
+    ///
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct User {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key(unique, store_value)]
+    ///     email: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<User>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(User { id: 1, email: "a@test".to_string() })?;
+    ///     rw.commit()?;
+    ///
+    ///     let r = db.r_transaction()?;
+    ///     let user: Option<User> = r.get().secondary(UserKey::email, "a@test")?;
+    ///     assert_eq!(user.unwrap().id, 1);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// ### Collation
+    ///
+    /// Redb orders the keys by bytes. With `collation = <function>`, the key is the `Vec<u8>`
+    /// returned by the function called with a reference to the field (e.g. an ICU collation sort
+    /// key), so the scans follow the collation order. The queries take the collation key too.
+    /// It can be combined with `unique`, `optional` and `multi`.
+    ///
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// fn case_folding(name: &str) -> Vec<u8> {
+    ///     name.to_lowercase().into_bytes()
+    /// }
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct City {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key(collation = case_folding)]
+    ///     name: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<City>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(City { id: 1, name: "Paris".to_string() })?;
+    ///     rw.insert(City { id: 2, name: "berlin".to_string() })?;
+    ///     rw.commit()?;
+    ///
+    ///     let r = db.r_transaction()?;
+    ///     let cities: Vec<City> = r
+    ///         .scan()
+    ///         .secondary(CityKey::name)?
+    ///         .range(case_folding("A")..case_folding("M"))?
+    ///         .collect::<Result<_, _>>()?;
+    ///     assert_eq!(cities[0].name, "berlin");
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// ### Nested Field
+    ///
+    /// `#[secondary_key(<field>.<nested field>)]` on a field whose type is a struct indexes a
+    /// field of this struct, without writing a method. The key is named after the path, e.g.
+    /// `customer_id` for `customer.id`, and its type is the type of the nested field, which must
+    /// implement [`ToKey`](crate::db_type::ToKey). The options (`unique`, `optional`, etc.) follow
+    /// the path, except `multi`. A field can have several secondary keys. The macro refuses a
+    /// path whose name is already the name of another key, e.g. a field `customer_id`.
+    ///
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Customer {
+    ///     id: u64,
+    ///     email: String,
+    /// }
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Order {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key(customer.id)]
+    ///     #[secondary_key(customer.email, unique)]
+    ///     customer: Customer,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Order>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let r = db.r_transaction()?;
+    ///     let _orders: Vec<Order> = r
+    ///         .scan()
+    ///         .secondary(OrderKey::customer_id)?
+    ///         .range(1u64..=1u64)?
+    ///         .collect::<Result<_, _>>()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
     /// ### Defining a Model with a Custom Optional Secondary Key
     ///
     /// ```rust
@@ -274,11 +728,21 @@ impl Models {
     ///
     /// The method `custom_name` returns an `Option<String>` based on some logic involving the `flag` field.
     ///
+    /// ### Key Accessors
+    ///
+    /// For each secondary key, the macro generates the enum variant `<Model>Key::<key_name>` and
+    /// the method `<Model>::secondary_key_<key_name>()`. Both can be used in the queries, e.g.
+    /// `scan().secondary(Data::secondary_key_name())` or `scan().secondary(DataKey::name)`.
+    ///
     /// # Defining Multiple Models
     ///
-    /// To define multiple models, you **must** use different `id` values for each model. If you use the same `id` for two models,
+    /// To define multiple models, you **must** use different `id` values for each model. If two models end up with the same
+    /// table, `define` returns [`ModelTableCollision`](crate::db_type::Error::ModelTableCollision). Otherwise, if you use the same `id` for two models,
     /// the program will panic with the message: `The table <table_name> has the same native model version as the table <table_name> and it's not allowed`.
     ///
+    /// A model is defined once: defining it again returns
+    /// [`ModelAlreadyDefined`](crate::db_type::Error::ModelAlreadyDefined) and leaves the models unchanged.
+    ///
     /// Example:
     ///
     /// ```rust
@@ -314,15 +778,109 @@ impl Models {
     /// - Both have:
     ///   - **One primary key** named `name` of type `String`, defined on the field.
     /// - Each model has a unique `id` (`id=1` for `Animal`, `id=2` for `Vegetable`), which is necessary to avoid conflicts.
+    ///
+    /// # Defining Generic Models
+    ///
+    /// A generic struct can be a model: each monomorphization, e.g. `Cache<String>` and `Cache<u64>`, is a distinct
+    /// model stored in its own tables. `#[native_model]` doesn't support generics, [`native_model_impl!`](crate::native_model_impl)
+    /// implements `native_model::Model` for each monomorphization instead, with the same options as the derive.
+    ///
+    /// All the tables of a model, the secondary, blob, hashed and value tables included, are named after its `id` and
+    /// `version`, the concrete type is not part of the names: `std::any::type_name` is not stable across compilers and
+    /// would orphan the tables. Each monomorphization **must** have a distinct `id`, `define` returns
+    /// [`ModelTableCollision`](crate::db_type::Error::ModelTableCollision) otherwise.
+    ///
+    /// Their secondary keys are reached with the accessors, e.g. `Cache::<u64>::secondary_key_name()`, as the keys
+    /// enum is not generated for a generic model.
+    ///
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{self, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_db]
+    /// struct Cache<T> {
+    ///     #[primary_key]
+    ///     key: String,
+    ///     value: T,
+    /// }
+    ///
+    /// native_model_impl!(Cache<String>, id = 1, version = 1, with = native_model::bincode_1_3::Bincode);
+    /// native_model_impl!(Cache<u64>, id = 2, version = 1, with = native_model::bincode_1_3::Bincode);
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Cache<String>>()?;
+    ///     models.define::<Cache<u64>>()
+    /// }
+    /// ```
     pub fn define<T: ToInput>(&mut self) -> Result<()> {
+        let model = T::native_db_model();
+        if let Some(compression) = model.compression {
+            compression.check_enabled()?;
+        }
+        let hashed_primary_key_table_name = model
+            .hashed_primary_key
+            .then(|| format!("{}_hashed", model.primary_key.unique_table_name));
+        let blob_table_name = model
+            .blob
+            .then(|| format!("{}_blob", model.primary_key.unique_table_name));
+        let secondary_value_table_names = model
+            .secondary_keys
+            .iter()
+            .filter(|key| key.options.store_value)
+            .map(|key| {
+                (
+                    key.unique_table_name.clone(),
+                    format!("{}_value", key.unique_table_name),
+                )
+            })
+            .collect();
         let mut new_model_builder = ModelBuilder {
-            model: T::native_db_model(),
+            model,
+            type_name: std::any::type_name::<T>(),
             native_model_options: NativeModelOptions::default(),
+            hashed_primary_key_table_name,
+            blob_table_name,
+            secondary_value_table_names,
+            aliases: vec![],
+            rekey: |rw| rw.rekey::<T>(),
+            key_encoding_changed_since: T::native_db_key_encoding_changed_since,
         };
 
         new_model_builder.native_model_options.native_model_id = T::native_model_id();
         new_model_builder.native_model_options.native_model_version = T::native_model_version();
 
+        for model in self.models_builder.values() {
+            if model.type_name == new_model_builder.type_name {
+                return Err(Error::ModelAlreadyDefined {
+                    table: model.model.primary_key.unique_table_name.clone(),
+                });
+            }
+            // The side tables are named after the primary table, but their names can still
+            // meet the ones of another model, e.g. a primary key named `id_blob`.
+            let table = new_model_builder
+                .table_names()
+                .find(|table| model.table_names().any(|other| other == *table));
+            if let Some(table) = table {
+                return Err(Error::ModelTableCollision {
+                    table: table.to_string(),
+                    first: model.model.name.to_string(),
+                    second: new_model_builder.model.name.to_string(),
+                });
+            }
+            // The aliases are checked against the tables defined before them, see `define_alias`.
+            if let Some(alias) = new_model_builder
+                .table_names()
+                .find(|table| model.aliases.iter().any(|alias| alias == table))
+            {
+                return Err(Error::TableAlreadyExists {
+                    table: alias.to_string(),
+                });
+            }
+        }
+
         // Set native model legacy
         for model in self.models_builder.values_mut() {
             if model.native_model_options.native_model_version
@@ -361,3 +919,278 @@ impl Models {
         Ok(())
     }
 }
+
+impl Models {
+    /// Register `alias` as another name of the primary table of `T`, e.g. its name before a
+    /// key was renamed, to read the values still stored under the old name with
+    /// [`RGet::primary_alias`](crate::transaction::query::RGet::primary_alias).
+    ///
+    /// For migration windows only: the alias is only read, the writes and the other queries
+    /// use the primary table of `T`. Once the table is renamed with
+    /// [`Database::rename_table`](crate::Database::rename_table), remove the alias.
+    ///
+    /// Returns error:
+    /// - [`ModelNotDefined`](crate::db_type::Error::ModelNotDefined) if `T` is not [defined](Self::define) yet.
+    /// - [`TableAlreadyExists`](crate::db_type::Error::TableAlreadyExists) if `alias` is a
+    ///   table or an alias of a defined model. [`define`](Self::define) returns it too for a model
+    ///   defined afterwards whose table is an alias.
+    /// - [`AliasNotSupported`](crate::db_type::Error::AliasNotSupported) if the primary key of
+    ///   `T` is hashed or `T` has a blob.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     // Renamed from `id`, the table `1_1_id` is now `1_1_key`
+    ///     #[primary_key]
+    ///     key: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     models.define_alias::<Data>("1_1_id")?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let r = db.r_transaction()?;
+    ///     let _value: Option<Data> = r.get().primary_alias("1_1_id", 1u64)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn define_alias<T: ToInput>(&mut self, alias: &str) -> Result<()> {
+        let model = T::native_db_model();
+        if self.models_builder.values().any(|builder| {
+            builder.table_names().any(|table| table == alias)
+                || builder.aliases.iter().any(|other| other == alias)
+        }) {
+            return Err(Error::TableAlreadyExists {
+                table: alias.to_string(),
+            });
+        }
+        let model_builder = self
+            .models_builder
+            .get_mut(&model.primary_key.unique_table_name)
+            .ok_or_else(|| model.not_defined_error())?;
+        if model.hashed_primary_key || model.blob {
+            return Err(Error::AliasNotSupported {
+                table: model.primary_key.unique_table_name.clone(),
+            });
+        }
+        model_builder.aliases.push(alias.to_string());
+        Ok(())
+    }
+}
+
+impl Models {
+    /// Same as [`define`](Self::define) but returns a [`ModelDescriptor`] of the model
+    /// that was just registered, e.g. to log or check it at startup.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     let descriptor = models.define_and_describe::<Data>()?;
+    ///     assert_eq!(descriptor.native_model_id(), 1);
+    ///     assert_eq!(descriptor.native_model_version(), 1);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn define_and_describe<T: ToInput>(&mut self) -> Result<ModelDescriptor<'_>> {
+        self.define::<T>()?;
+        let model_builder =
+            &self.models_builder[&T::native_db_model().primary_key.unique_table_name];
+        Ok(ModelDescriptor { model_builder })
+    }
+}
+
+impl Models {
+    /// A stable hash of the models: their ids, versions, tables, storage options (blob,
+    /// compression, rkyv), primary key and secondary keys (types and options).
+    ///
+    /// The fingerprint only depends on the models, not on the order they were defined in. Commit
+    /// it and compare it in a test to catch a model changed without bumping its version.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     assert_eq!(models.schema_fingerprint().len(), 16);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn schema_fingerprint(&self) -> String {
+        let mut model_builders: Vec<&ModelBuilder> = self.models_builder.values().collect();
+        model_builders.sort_by(|a, b| {
+            a.model
+                .primary_key
+                .unique_table_name
+                .cmp(&b.model.primary_key.unique_table_name)
+        });
+
+        let mut descriptors = String::new();
+        for model_builder in model_builders {
+            let model = &model_builder.model;
+            descriptors.push_str(&format!(
+                "model {} {} {} hashed_primary_key={} blob={}\n",
+                model_builder.native_model_options.native_model_id,
+                model_builder.native_model_options.native_model_version,
+                model.primary_key.unique_table_name,
+                model.hashed_primary_key,
+                model.blob,
+            ));
+            // Only the compressed models have it, to keep the fingerprints of the others.
+            if let Some(compression) = model.compression {
+                descriptors.push_str(&format!("compression {}\n", compression.name()));
+            }
+            if model.rkyv {
+                descriptors.push_str("codec rkyv\n");
+            }
+            descriptors.push_str(&format!("primary_key {:?}\n", model.primary_key.rust_types));
+            let mut secondary_keys: Vec<_> = model.secondary_keys.iter().collect();
+            secondary_keys.sort_by(|a, b| a.unique_table_name.cmp(&b.unique_table_name));
+            for secondary_key in secondary_keys {
+                descriptors.push_str(&format!(
+                    "secondary_key {} {:?} unique={} optional={} multi={}",
+                    secondary_key.unique_table_name,
+                    secondary_key.rust_types,
+                    secondary_key.options.unique,
+                    secondary_key.options.optional,
+                    secondary_key.options.multi,
+                ));
+                // Only the keys storing the values have it, to keep the fingerprints of the others.
+                if secondary_key.options.store_value {
+                    descriptors.push_str(" store_value=true");
+                }
+                descriptors.push('\n');
+            }
+        }
+        format!("{:016x}", xxh64(descriptors.as_bytes(), 0))
+    }
+}
+
+/// Description of a model defined with [`Models::define_and_describe`].
+#[derive(Debug)]
+pub struct ModelDescriptor<'a> {
+    model_builder: &'a ModelBuilder,
+}
+
+impl ModelDescriptor<'_> {
+    /// The name of the table used to store the model.
+    pub fn table_name(&self) -> &str {
+        &self.model_builder.model.primary_key.unique_table_name
+    }
+
+    /// The `native_model` id of the model.
+    pub fn native_model_id(&self) -> u32 {
+        self.model_builder.native_model_options.native_model_id
+    }
+
+    /// The `native_model` version of the model.
+    pub fn native_model_version(&self) -> u32 {
+        self.model_builder.native_model_options.native_model_version
+    }
+
+    /// The keys of the model.
+    pub fn model(&self) -> &Model {
+        &self.model_builder.model
+    }
+}
+
+/// Implements `native_model::Model` for a monomorphization of a generic model, like
+/// `#[native_model(id = .., version = .., with = ..)]` does for a struct without generics.
+///
+/// See [Defining Generic Models](crate::Models::define#defining-generic-models).
+#[macro_export]
+macro_rules! native_model_impl {
+    ($type:ty, id = $id:literal, version = $version:literal, with = $codec:ty) => {
+        impl $crate::native_model::Model for $type {
+            fn native_model_id() -> u32 {
+                $id
+            }
+
+            fn native_model_id_str() -> &'static str {
+                stringify!($id)
+            }
+
+            fn native_model_version() -> u32 {
+                $version
+            }
+
+            fn native_model_version_str() -> &'static str {
+                stringify!($version)
+            }
+
+            fn native_model_decode_body(
+                data: Vec<u8>,
+                id: u32,
+            ) -> ::std::result::Result<Self, $crate::native_model::DecodeBodyError> {
+                if id != $id {
+                    return Err($crate::native_model::DecodeBodyError::MismatchedModelId);
+                }
+                <$codec as $crate::native_model::Decode<Self>>::decode(data).map_err(|e| {
+                    $crate::native_model::DecodeBodyError::DecodeError {
+                        msg: e.to_string(),
+                        source: e.into(),
+                    }
+                })
+            }
+
+            fn native_model_decode_upgrade_body(
+                data: Vec<u8>,
+                id: u32,
+                _version: u32,
+            ) -> $crate::native_model::Result<Self> {
+                Ok(Self::native_model_decode_body(data, id)?)
+            }
+
+            fn native_model_encode_body(
+                &self,
+            ) -> ::std::result::Result<Vec<u8>, $crate::native_model::EncodeBodyError> {
+                <$codec as $crate::native_model::Encode<Self>>::encode(self).map_err(|e| {
+                    $crate::native_model::EncodeBodyError {
+                        msg: e.to_string(),
+                        source: e.into(),
+                    }
+                })
+            }
+
+            fn native_model_encode_downgrade_body(
+                self,
+                _version: u32,
+            ) -> $crate::native_model::Result<Vec<u8>> {
+                Ok(self.native_model_encode_body()?)
+            }
+        }
+    };
+}
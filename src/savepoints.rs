@@ -0,0 +1,205 @@
+use crate::db_type::{Error, Key, Output, Result};
+use crate::watch::Event;
+use crate::Database;
+use redb::{ReadableTable, TableDefinition, TableHandle};
+
+// Reserved table, maps the name of a savepoint to the id of its redb persistent savepoint.
+pub(crate) const SAVEPOINTS_TABLE: TableDefinition<&str, u64> =
+    TableDefinition::new("native_db_savepoints");
+
+/// A difference between a savepoint and the current state of the database, returned by
+/// [`changes_since`](crate::Database::changes_since).
+#[derive(Debug, Clone)]
+pub struct Change {
+    /// Name of the primary table of the model, e.g. `1_1_id`.
+    pub table: String,
+    /// Primary key of the value.
+    pub primary_key: Key,
+    /// [`Insert`](crate::watch::Event::Insert) if the value was added since the savepoint,
+    /// [`Update`](crate::watch::Event::Update) if it was modified and
+    /// [`Delete`](crate::watch::Event::Delete) if it was removed.
+    pub event: Event,
+}
+
+impl Database<'_> {
+    /// Create a persistent savepoint named `name`, replacing the one with the same name.
+    ///
+    /// The savepoint survives the restart of the application, use
+    /// [`changes_since`](Self::changes_since) to get the changes made after it, and
+    /// [`delete_savepoint`](Self::delete_savepoint) when it is no longer needed.
+    ///
+    /// A savepoint keeps the pages of its state alive: the file grows with the changes made
+    /// after it, and [`compact`](Self::compact) fails while it exists.
+    pub fn create_savepoint(&self, name: &str) -> Result<()> {
+        let w = self.instance.redb_database()?.begin_write()?;
+        // The savepoint must be taken before any write in the transaction.
+        let id = w.persistent_savepoint()?;
+        {
+            let mut table = w.open_table(SAVEPOINTS_TABLE)?;
+            let old_id = table.insert(name, id)?.map(|old_id| old_id.value());
+            if let Some(old_id) = old_id {
+                w.delete_persistent_savepoint(old_id)?;
+            }
+        }
+        w.commit()?;
+        Ok(())
+    }
+
+    /// Delete the savepoint `name`. Returns `false` if it does not exist.
+    pub fn delete_savepoint(&self, name: &str) -> Result<bool> {
+        let w = self.instance.redb_database()?.begin_write()?;
+        let deleted = {
+            let mut table = w.open_table(SAVEPOINTS_TABLE)?;
+            let id = table.remove(name)?.map(|id| id.value());
+            match id {
+                Some(id) => w.delete_persistent_savepoint(id)?,
+                None => false,
+            }
+        };
+        w.commit()?;
+        Ok(deleted)
+    }
+
+    /// Get the changes made since the savepoint `name` (see
+    /// [`create_savepoint`](Self::create_savepoint)), e.g. for a periodic change-data-capture
+    /// export without maintaining a separate log of events.
+    ///
+    /// For each model, the values added or modified are listed in the order of their primary
+    /// keys, followed by the removed values. The changes are computed by comparing all the
+    /// values of the savepoint with the current ones: the cost is a full scan of the primary
+    /// tables, twice, whatever the number of changes. The writers wait meanwhile.
+    ///
+    /// Only the latest savepoint can be read: reading the state of a savepoint invalidates the
+    /// ones created after it until the database is reopened (a limitation of redb). Use a single
+    /// savepoint, created again after each export.
+    ///
+    /// Returns error:
+    /// - [`SavepointNotFound`](crate::db_type::Error::SavepointNotFound) if the savepoint does
+    ///   not exist.
+    /// - [`SavepointNotLatest`](crate::db_type::Error::SavepointNotLatest) if another savepoint
+    ///   was created after it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use native_db::watch::Event;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     db.create_savepoint("export")?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Data { id: 1 })?;
+    ///     rw.commit()?;
+    ///
+    ///     let changes = db.changes_since("export")?;
+    ///     assert_eq!(changes.len(), 1);
+    ///     assert!(matches!(changes[0].event, Event::Insert(_)));
+    ///
+    ///     // Start the next export from here
+    ///     db.create_savepoint("export")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn changes_since(&self, name: &str) -> Result<Vec<Change>> {
+        let database = self.instance.redb_database()?;
+        // The write transaction is opened first, so nothing is committed between the two.
+        let mut w = database.begin_write()?;
+        let r = database.begin_read()?;
+        let id = w
+            .open_table(SAVEPOINTS_TABLE)?
+            .get(name)?
+            .map(|id| id.value())
+            .ok_or_else(|| Error::SavepointNotFound {
+                name: name.to_string(),
+            })?;
+        if w.list_persistent_savepoints()?
+            .any(|other_id| other_id > id)
+        {
+            return Err(Error::SavepointNotLatest {
+                name: name.to_string(),
+            });
+        }
+        let savepoint = w.get_persistent_savepoint(id)?;
+        // `w` only reads the savepoint state, it is aborted at the end.
+        w.restore_savepoint(&savepoint)?;
+
+        let mut changes = vec![];
+        for primary_table_definition in self.primary_table_definitions.values() {
+            let table_name = primary_table_definition.redb.name();
+            let current = match r.open_table(primary_table_definition.redb) {
+                Ok(table) => Some(table),
+                Err(redb::TableError::TableDoesNotExist(_)) => None,
+                Err(error) => return Err(error.into()),
+            };
+            let saved = w.open_table(primary_table_definition.redb)?;
+            let (current_hashed, saved_hashed) = match primary_table_definition.hashed_primary_key {
+                Some(hashed) if current.is_some() => {
+                    (Some(r.open_table(hashed)?), Some(w.open_table(hashed)?))
+                }
+                Some(hashed) => (None, Some(w.open_table(hashed)?)),
+                None => (None, None),
+            };
+
+            if let Some(current) = &current {
+                for result in current.iter()? {
+                    let (key, value) = result?;
+                    let new_value = Output::from(value.value());
+                    let event = match saved.get(key.value())? {
+                        None => Event::new_insert(new_value),
+                        Some(old_value) if old_value.value() != new_value.0.as_slice() => {
+                            Event::new_update(Output::from(old_value.value()), new_value)
+                        }
+                        Some(_) => continue,
+                    };
+                    changes.push(Change {
+                        table: table_name.to_string(),
+                        primary_key: original_key(current_hashed.as_ref(), key.value())?,
+                        event,
+                    });
+                }
+            }
+            for result in saved.iter()? {
+                let (key, value) = result?;
+                let removed = match &current {
+                    Some(current) => current.get(key.value())?.is_none(),
+                    None => true,
+                };
+                if removed {
+                    changes.push(Change {
+                        table: table_name.to_string(),
+                        primary_key: original_key(saved_hashed.as_ref(), key.value())?,
+                        event: Event::new_delete(Output::from(value.value())),
+                    });
+                }
+            }
+        }
+        w.abort()?;
+        Ok(changes)
+    }
+}
+
+/// Returns the original key of a stored key, see `#[native_db(hashed_primary_key)]`.
+fn original_key(
+    hashed_table: Option<&impl ReadableTable<Key, &'static [u8]>>,
+    stored_key: Key,
+) -> Result<Key> {
+    let Some(hashed_table) = hashed_table else {
+        return Ok(stored_key);
+    };
+    let original = hashed_table.get(&stored_key)?.map(|v| v.value().to_vec());
+    Ok(original.map_or(stored_key, Key::new))
+}
@@ -12,3 +12,119 @@ where
     let (data, _) = native_model::decode(slice.to_vec())?;
     Ok((data, 0))
 }
+
+/// Encode a value of a model defined with `#[native_db(rkyv)]`, see [Models](crate::Models#rkyv).
+#[cfg(feature = "rkyv")]
+pub fn rkyv_encode_to_vec<T>(value: &T) -> crate::db_type::Result<Vec<u8>>
+where
+    T: for<'a> rkyv::Serialize<
+        rkyv::api::high::HighSerializer<
+            rkyv::util::AlignedVec,
+            rkyv::ser::allocator::ArenaHandle<'a>,
+            rkyv::rancor::Error,
+        >,
+    >,
+{
+    Ok(rkyv::to_bytes::<rkyv::rancor::Error>(value)?.into_vec())
+}
+
+/// Decode a value of a model defined with `#[native_db(rkyv)]`, see [Models](crate::Models#rkyv).
+#[cfg(feature = "rkyv")]
+pub fn rkyv_decode_from_slice<T>(slice: &[u8]) -> crate::db_type::Result<T>
+where
+    T: rkyv::Archive,
+    T::Archived: for<'a> rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>
+        + rkyv::Deserialize<T, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>,
+{
+    // The stored bytes are not aligned, the archive is read from an aligned copy.
+    let mut bytes = rkyv::util::AlignedVec::<16>::with_capacity(slice.len());
+    bytes.extend_from_slice(slice);
+    Ok(rkyv::from_bytes::<T, rkyv::rancor::Error>(&bytes)?)
+}
+
+/// Compression of the stored values of a model, set with `#[native_db(compress = "zstd")]`,
+/// see [Models](crate::Models#compression).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// [Zstandard](https://facebook.github.io/zstd/), requires the `zstd` feature.
+    Zstd,
+}
+
+impl Compression {
+    /// Name recorded in the metadata for the tables without compression.
+    pub(crate) const NONE_NAME: &'static str = "none";
+
+    /// Name recorded in the metadata, the value of `#[native_db(compress = ...)]`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// Compress an encoded value before it is stored.
+    #[cfg_attr(not(feature = "zstd"), allow(unused_variables))]
+    pub fn compress(&self, data: &[u8]) -> crate::db_type::Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Ok(zstd::encode_all(data, 0)?),
+            #[cfg(not(feature = "zstd"))]
+            Compression::Zstd => Err(self.not_enabled_error()),
+        }
+    }
+
+    /// Decompress a stored value before it is decoded.
+    pub fn decompress(&self, data: &[u8]) -> crate::db_type::Result<Vec<u8>> {
+        self.decompress_bounded(data, None)
+    }
+
+    /// Decompress a stored value, returns [`ValueTooLarge`](crate::db_type::Error::ValueTooLarge)
+    /// as soon as the decompressed value exceeds `limit` bytes, see
+    /// [`Builder::set_max_value_size`](crate::Builder::set_max_value_size).
+    ///
+    /// The `size` of the error is the number of bytes decompressed before stopping, not the
+    /// size of the whole value.
+    #[cfg_attr(not(feature = "zstd"), allow(unused_variables))]
+    pub fn decompress_bounded(
+        &self,
+        data: &[u8],
+        limit: Option<usize>,
+    ) -> crate::db_type::Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                use std::io::Read;
+                let Some(limit) = limit else {
+                    return Ok(zstd::decode_all(data)?);
+                };
+                let mut decompressed = vec![];
+                zstd::stream::read::Decoder::new(data)?
+                    .take(limit as u64 + 1)
+                    .read_to_end(&mut decompressed)?;
+                if decompressed.len() > limit {
+                    return Err(crate::db_type::Error::ValueTooLarge {
+                        size: decompressed.len(),
+                        limit,
+                    });
+                }
+                Ok(decompressed)
+            }
+            #[cfg(not(feature = "zstd"))]
+            Compression::Zstd => Err(self.not_enabled_error()),
+        }
+    }
+
+    /// Returns [`CompressionNotEnabled`](crate::db_type::Error::CompressionNotEnabled) if the
+    /// feature of the compression is disabled.
+    pub(crate) fn check_enabled(&self) -> crate::db_type::Result<()> {
+        match self {
+            Compression::Zstd if !cfg!(feature = "zstd") => Err(self.not_enabled_error()),
+            _ => Ok(()),
+        }
+    }
+
+    fn not_enabled_error(&self) -> crate::db_type::Error {
+        crate::db_type::Error::CompressionNotEnabled {
+            compression: self.name().to_string(),
+        }
+    }
+}
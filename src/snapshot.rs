@@ -1,3 +1,4 @@
+use crate::counters::COUNTERS_TABLE;
 use crate::db_type::Result;
 use crate::{Builder, Database, Models};
 use redb::ReadableMultimapTable;
@@ -19,6 +20,26 @@ impl Database<'_> {
                     new_table.insert(key.value(), value.value())?;
                 }
 
+                // Copy the original primary keys of a hashed primary key
+                if let Some(hashed_primary_key) = primary_table_definition.hashed_primary_key {
+                    let table = r.open_table(hashed_primary_key)?;
+                    let mut new_table = w.open_table(hashed_primary_key)?;
+                    for result in table.iter()? {
+                        let (key, value) = result?;
+                        new_table.insert(key.value(), value.value())?;
+                    }
+                }
+
+                // Copy the blobs
+                if let Some(blob) = primary_table_definition.blob {
+                    let table = r.open_table(blob)?;
+                    let mut new_table = w.open_table(blob)?;
+                    for result in table.iter()? {
+                        let (key, value) = result?;
+                        new_table.insert(key.value(), value.value())?;
+                    }
+                }
+
                 // Copy secondary tables
                 for secondary_table_definition in primary_table_definition.secondary_tables.values()
                 {
@@ -31,7 +52,30 @@ impl Database<'_> {
                             new_table.insert(secondary_key.value(), primary_key.value())?;
                         }
                     }
+
+                    // Copy the values stored by the secondary key
+                    if let Some(value) = secondary_table_definition.value {
+                        let table = r.open_table(value)?;
+                        let mut new_table = w.open_table(value)?;
+                        for result in table.iter()? {
+                            let (key, value) = result?;
+                            new_table.insert(key.value(), value.value())?;
+                        }
+                    }
+                }
+            }
+
+            // Copy the counters
+            match r.open_table(COUNTERS_TABLE) {
+                Ok(table) => {
+                    let mut new_table = w.open_table(COUNTERS_TABLE)?;
+                    for result in table.iter()? {
+                        let (name, value) = result?;
+                        new_table.insert(name.value(), value.value())?;
+                    }
                 }
+                Err(redb::TableError::TableDoesNotExist(_)) => {}
+                Err(error) => return Err(error.into()),
             }
         }
         w.commit()?;
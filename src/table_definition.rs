@@ -11,6 +11,12 @@ pub struct PrimaryTableDefinition<'a> {
     pub(crate) redb: RedbPrimaryTableDefinition<'a>,
     pub(crate) secondary_tables: HashMap<KeyDefinition<KeyOptions>, SecondaryTableDefinition<'a>>,
     pub(crate) native_model_options: NativeModelOptions,
+    // Stored key -> original primary key, see `#[native_db(hashed_primary_key)]`.
+    pub(crate) hashed_primary_key: Option<RedbPrimaryTableDefinition<'a>>,
+    // (Primary key, chunk index) -> chunk, see `#[native_db(blob)]`.
+    pub(crate) blob: Option<RedbPrimaryTableDefinition<'a>>,
+    // Other names of the primary table, see `Models::define_alias`.
+    pub(crate) aliases: Vec<RedbPrimaryTableDefinition<'a>>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -23,14 +29,27 @@ pub struct NativeModelOptions {
     pub(crate) native_model_legacy: bool,
 }
 
-impl<'a> From<(&ModelBuilder, RedbPrimaryTableDefinition<'a>)> for PrimaryTableDefinition<'a> {
-    fn from(input: (&ModelBuilder, RedbPrimaryTableDefinition<'a>)) -> Self {
+impl<'a> From<(&'a ModelBuilder, RedbPrimaryTableDefinition<'a>)> for PrimaryTableDefinition<'a> {
+    fn from(input: (&'a ModelBuilder, RedbPrimaryTableDefinition<'a>)) -> Self {
         let (builder, redb) = input;
         Self {
             model: builder.model.clone(),
             redb,
             secondary_tables: HashMap::new(),
             native_model_options: builder.native_model_options.clone(),
+            hashed_primary_key: builder
+                .hashed_primary_key_table_name
+                .as_deref()
+                .map(redb::TableDefinition::new),
+            blob: builder
+                .blob_table_name
+                .as_deref()
+                .map(redb::TableDefinition::new),
+            aliases: builder
+                .aliases
+                .iter()
+                .map(|alias| redb::TableDefinition::new(alias))
+                .collect(),
         }
     }
 }
@@ -53,10 +72,15 @@ impl Debug for PrimaryTableDefinition<'_> {
 #[derive(Clone)]
 pub(crate) struct SecondaryTableDefinition<'a> {
     pub(crate) redb: RedbSecondaryTableDefinition<'a>,
+    // Secondary key -> value, see `#[secondary_key(unique, store_value)]`.
+    pub(crate) value: Option<RedbPrimaryTableDefinition<'a>>,
 }
 
 impl<'a> From<RedbSecondaryTableDefinition<'a>> for SecondaryTableDefinition<'a> {
     fn from(rdb: RedbSecondaryTableDefinition<'a>) -> SecondaryTableDefinition<'a> {
-        Self { redb: rdb }
+        Self {
+            redb: rdb,
+            value: None,
+        }
     }
 }
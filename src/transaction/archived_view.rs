@@ -0,0 +1,58 @@
+use crate::db_type::Result;
+use rkyv::api::high::{HighDeserializer, HighValidator};
+use rkyv::bytecheck::CheckBytes;
+use rkyv::rancor;
+use rkyv::util::AlignedVec;
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+/// Archived value of a model defined with `#[native_db(rkyv)]`, returned by
+/// [`get_archived`](crate::transaction::RTransaction::get_archived).
+///
+/// Dereferences to the archived type of `T`, e.g. `ArchivedDocument` for `Document`, whose
+/// fields are read in place without deserializing the value. The value is copied once from the
+/// database and validated when the view is created.
+pub struct ArchivedView<T> {
+    bytes: AlignedVec,
+    _model: PhantomData<T>,
+}
+
+impl<T> ArchivedView<T>
+where
+    T: rkyv::Archive,
+    T::Archived: for<'a> CheckBytes<HighValidator<'a, rancor::Error>>,
+{
+    /// The stored bytes are not aligned, they are copied once in an aligned buffer and the
+    /// archive is validated.
+    pub(crate) fn new(value: &[u8]) -> Result<Self> {
+        let mut bytes = AlignedVec::with_capacity(value.len());
+        bytes.extend_from_slice(value);
+        rkyv::access::<T::Archived, rancor::Error>(&bytes)?;
+        Ok(Self {
+            bytes,
+            _model: PhantomData,
+        })
+    }
+
+    /// Deserialize the whole value.
+    pub fn deserialize(&self) -> Result<T>
+    where
+        T::Archived: rkyv::Deserialize<T, HighDeserializer<rancor::Error>>,
+    {
+        Ok(rkyv::deserialize::<T, rancor::Error>(&**self)?)
+    }
+}
+
+impl<T> Deref for ArchivedView<T>
+where
+    T: rkyv::Archive,
+    T::Archived: for<'a> CheckBytes<HighValidator<'a, rancor::Error>>,
+{
+    type Target = T::Archived;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the bytes are validated as an archive of `T` by `ArchivedView::new` and are
+        // not modified afterwards.
+        unsafe { rkyv::access_unchecked::<T::Archived>(&self.bytes) }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::db_type::{Key, Result};
+use crate::transaction::internal::blob;
+use std::io::Read;
+
+/// Reader of a blob, returned by [`open_blob`](crate::transaction::RTransaction::open_blob).
+///
+/// The chunks are read one by one from the database, the blob is never loaded entirely in memory.
+pub struct BlobReader {
+    table: redb::ReadOnlyTable<Key, &'static [u8]>,
+    primary_key: Key,
+    next_index: u32,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl BlobReader {
+    /// Returns `None` if the blob does not exist.
+    pub(crate) fn new(
+        table: redb::ReadOnlyTable<Key, &'static [u8]>,
+        primary_key: Key,
+    ) -> Result<Option<Self>> {
+        if table.get(&blob::chunk_key(&primary_key, 0))?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            table,
+            primary_key,
+            next_index: 0,
+            chunk: Vec::new(),
+            position: 0,
+        }))
+    }
+}
+
+impl Read for BlobReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.position == self.chunk.len() {
+            let chunk = self
+                .table
+                .get(&blob::chunk_key(&self.primary_key, self.next_index))
+                .map_err(std::io::Error::other)?;
+            match chunk {
+                Some(chunk) => {
+                    self.chunk = chunk.value().to_vec();
+                    self.position = 0;
+                    self.next_index += 1;
+                }
+                None => return Ok(0),
+            }
+        }
+        let len = buf.len().min(self.chunk.len() - self.position);
+        buf[..len].copy_from_slice(&self.chunk[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
@@ -0,0 +1,20 @@
+/// Result of [`commit_with_info`](crate::transaction::RwTransaction::commit_with_info).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CommitInfo {
+    /// Number of inserted values.
+    pub inserts: u64,
+    /// Number of updated values.
+    pub updates: u64,
+    /// Number of removed values.
+    pub removes: u64,
+    /// Sequence number assigned to the commit, see
+    /// [`current_commit_sequence`](crate::Database::current_commit_sequence).
+    pub sequence: u64,
+}
+
+impl CommitInfo {
+    /// Total number of changes.
+    pub fn changes(&self) -> u64 {
+        self.inserts + self.updates + self.removes
+    }
+}
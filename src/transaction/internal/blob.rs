@@ -0,0 +1,117 @@
+use crate::db_type::{Key, Result};
+use std::io::Read;
+
+// With `#[native_db(blob)]` a value can have a blob, stored in a dedicated table as chunks of
+// `CHUNK_SIZE` bytes keyed by the primary key followed by the chunk index. A blob has always
+// at least one chunk, empty for an empty blob.
+
+pub(crate) const CHUNK_SIZE: usize = 64 * 1024;
+
+pub(crate) fn chunk_key(primary_key: &Key, index: u32) -> Key {
+    let mut data = primary_key.as_slice().to_vec();
+    data.extend(index.to_be_bytes());
+    Key::new(data)
+}
+
+/// Writes the blob read from `reader`, replacing the previous one. Returns its length.
+pub(crate) fn write(
+    table: &mut redb::Table<Key, &'static [u8]>,
+    primary_key: &Key,
+    reader: &mut impl Read,
+) -> Result<u64> {
+    remove(table, primary_key)?;
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut index = 0;
+    let mut written = 0;
+    loop {
+        let len = read_chunk(reader, &mut buffer)?;
+        if len == 0 && index > 0 {
+            break;
+        }
+        table.insert(&chunk_key(primary_key, index), &buffer[..len])?;
+        written += len as u64;
+        index += 1;
+        if len < CHUNK_SIZE {
+            break;
+        }
+    }
+    Ok(written)
+}
+
+/// Removes the blob, returns `false` if it does not exist.
+pub(crate) fn remove(
+    table: &mut redb::Table<Key, &'static [u8]>,
+    primary_key: &Key,
+) -> Result<bool> {
+    let mut index = 0;
+    while table.remove(&chunk_key(primary_key, index))?.is_some() {
+        index += 1;
+    }
+    Ok(index > 0)
+}
+
+/// Removes the blob and returns its chunks, empty if it does not exist.
+pub(crate) fn take(
+    table: &mut redb::Table<Key, &'static [u8]>,
+    primary_key: &Key,
+) -> Result<Vec<Vec<u8>>> {
+    let mut chunks = vec![];
+    while let Some(chunk) = table.remove(&chunk_key(primary_key, chunks.len() as u32))? {
+        chunks.push(chunk.value().to_vec());
+    }
+    Ok(chunks)
+}
+
+/// Writes the chunks returned by [`take`] under `primary_key`.
+pub(crate) fn put(
+    table: &mut redb::Table<Key, &'static [u8]>,
+    primary_key: &Key,
+    chunks: &[Vec<u8>],
+) -> Result<()> {
+    for (index, chunk) in chunks.iter().enumerate() {
+        table.insert(&chunk_key(primary_key, index as u32), chunk.as_slice())?;
+    }
+    Ok(())
+}
+
+/// Returns the length of the blob, `None` if it does not exist.
+pub(crate) fn len(
+    table: &impl redb::ReadableTable<Key, &'static [u8]>,
+    primary_key: &Key,
+) -> Result<Option<u64>> {
+    let mut len = None;
+    let mut index = 0;
+    while let Some(chunk) = table.get(&chunk_key(primary_key, index))? {
+        len = Some(len.unwrap_or(0) + chunk.value().len() as u64);
+        index += 1;
+    }
+    Ok(len)
+}
+
+/// Writes the chunks of the blob to `writer`, nothing if it does not exist.
+pub(crate) fn copy_to(
+    table: &impl redb::ReadableTable<Key, &'static [u8]>,
+    primary_key: &Key,
+    writer: &mut impl std::io::Write,
+) -> Result<()> {
+    let mut index = 0;
+    while let Some(chunk) = table.get(&chunk_key(primary_key, index))? {
+        writer.write_all(chunk.value())?;
+        index += 1;
+    }
+    Ok(())
+}
+
+// Fills the buffer unless the end of the reader is reached.
+fn read_chunk(reader: &mut impl Read, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut len = 0;
+    while len < buffer.len() {
+        match reader.read(&mut buffer[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(len)
+}
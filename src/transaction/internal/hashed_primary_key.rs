@@ -0,0 +1,129 @@
+use crate::db_type::{Key, Result};
+use redb::ReadableTable;
+use xxhash_rust::xxh64::xxh64;
+
+// With `#[native_db(hashed_primary_key)]` the primary key is stored as the xxHash64 of the
+// original key followed by a salt. The table of the hashed primary keys maps each stored key
+// to its original key, to resolve collisions.
+
+pub(crate) fn hash(original: &Key) -> u64 {
+    xxh64(original.as_slice(), 0)
+}
+
+fn stored_key(hash: u64, salt: u32) -> Key {
+    let mut data = hash.to_be_bytes().to_vec();
+    data.extend(salt.to_be_bytes());
+    Key::new(data)
+}
+
+/// Returns the stored key of the `original` key, `None` if it does not exist.
+pub(crate) fn find(
+    table: &impl ReadableTable<Key, &'static [u8]>,
+    hash: u64,
+    original: &Key,
+) -> Result<Option<Key>> {
+    for result in table.range::<Key>(stored_key(hash, 0)..=stored_key(hash, u32::MAX))? {
+        let (stored_key, value) = result?;
+        if value.value() == original.as_slice() {
+            return Ok(Some(stored_key.value()));
+        }
+    }
+    Ok(None)
+}
+
+/// Inserts the `original` key and returns its stored key, `None` if it already exists.
+pub(crate) fn insert(
+    table: &mut redb::Table<Key, &'static [u8]>,
+    hash: u64,
+    original: &Key,
+) -> Result<Option<Key>> {
+    if find(table, hash, original)?.is_some() {
+        return Ok(None);
+    }
+    let mut salt = 0;
+    loop {
+        let stored_key = stored_key(hash, salt);
+        let collision = table
+            .insert(&stored_key, original.as_slice())?
+            .map(|value| value.value().to_vec());
+        match collision {
+            None => return Ok(Some(stored_key)),
+            Some(value) => {
+                // Collision: restore the other key and re-hash with the next salt.
+                table.insert(&stored_key, value.as_slice())?;
+                salt += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db_type::ToKey;
+
+    const TABLE: redb::TableDefinition<Key, &[u8]> = redb::TableDefinition::new("hashed");
+
+    fn in_memory_database() -> redb::Database {
+        redb::Database::builder()
+            .create_with_backend(redb::backends::InMemoryBackend::new())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_insert_and_find() {
+        let db = in_memory_database();
+        let rw = db.begin_write().unwrap();
+        let mut table = rw.open_table(TABLE).unwrap();
+
+        let original = "https://example.com".to_key();
+        let stored_key = insert(&mut table, hash(&original), &original)
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored_key.as_slice().len(), 12);
+        assert_eq!(
+            find(&table, hash(&original), &original).unwrap(),
+            Some(stored_key)
+        );
+
+        // Already exists
+        assert_eq!(
+            insert(&mut table, hash(&original), &original).unwrap(),
+            None
+        );
+
+        // Not found
+        let other = "https://example.org".to_key();
+        assert_eq!(find(&table, hash(&other), &other).unwrap(), None);
+    }
+
+    #[test]
+    fn test_collision() {
+        let db = in_memory_database();
+        let rw = db.begin_write().unwrap();
+        let mut table = rw.open_table(TABLE).unwrap();
+
+        // Force a collision by using the same hash for different keys
+        let hash = 42;
+        let a = "a".to_key();
+        let b = "b".to_key();
+        let c = "c".to_key();
+        let stored_a = insert(&mut table, hash, &a).unwrap().unwrap();
+        let stored_b = insert(&mut table, hash, &b).unwrap().unwrap();
+        assert_ne!(stored_a, stored_b);
+        assert_eq!(stored_a.as_slice()[..8], stored_b.as_slice()[..8]);
+
+        assert_eq!(find(&table, hash, &a).unwrap(), Some(stored_a.clone()));
+        assert_eq!(find(&table, hash, &b).unwrap(), Some(stored_b.clone()));
+        assert_eq!(find(&table, hash, &c).unwrap(), None);
+        assert_eq!(insert(&mut table, hash, &b).unwrap(), None);
+
+        // Removing the first key keeps the second one reachable, and its slot is reused
+        table.remove(&stored_a).unwrap();
+        assert_eq!(find(&table, hash, &b).unwrap(), Some(stored_b.clone()));
+        assert_eq!(insert(&mut table, hash, &b).unwrap(), None);
+        let stored_c = insert(&mut table, hash, &c).unwrap().unwrap();
+        assert_eq!(stored_c, stored_a);
+        assert_eq!(find(&table, hash, &c).unwrap(), Some(stored_c));
+    }
+}
@@ -1,3 +1,5 @@
+pub(crate) mod blob;
+pub(crate) mod hashed_primary_key;
 pub mod private_readable_transaction;
 pub mod r_transaction;
 pub mod rw_transaction;
@@ -1,11 +1,12 @@
 use crate::db_type::{
-    Error, Key, KeyDefinition, KeyOptions, Output, Result, ToKey, ToKeyDefinition,
+    check_value_size, Error, Key, KeyDefinition, KeyOptions, Output, Result, ToKey, ToKeyDefinition,
 };
 use crate::table_definition::PrimaryTableDefinition;
+use crate::transaction::internal::hashed_primary_key;
 use crate::Model;
 use redb::ReadableTableMetadata;
 use redb::{ReadableMultimapTable, ReadableTable};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub trait PrivateReadableTransaction<'db, 'txn> {
     type RedbPrimaryTable: ReadableTable<Key, &'static [u8]>;
@@ -17,6 +18,16 @@ pub trait PrivateReadableTransaction<'db, 'txn> {
 
     fn table_definitions(&self) -> &HashMap<String, PrimaryTableDefinition>;
 
+    /// See [`Builder::set_max_value_size`](crate::Builder::set_max_value_size).
+    fn max_value_size(&self) -> Option<usize>;
+
+    /// Copies a stored value, checking its size first. The size of the decompressed value is
+    /// checked when it is decoded.
+    fn read_value(&self, value: &[u8]) -> Result<Output> {
+        check_value_size(value, self.max_value_size())?;
+        Ok(Output(value.to_vec(), self.max_value_size()))
+    }
+
     fn get_primary_table(&'txn self, model: &Model) -> Result<Self::RedbPrimaryTable>;
 
     fn get_secondary_table(
@@ -25,11 +36,41 @@ pub trait PrivateReadableTransaction<'db, 'txn> {
         secondary_key: &KeyDefinition<KeyOptions>,
     ) -> Result<Self::RedbSecondaryTable>;
 
+    /// Returns the table of the hashed primary keys, `None` if the primary key is not hashed.
+    fn get_hashed_primary_key_table(
+        &'txn self,
+        model: &Model,
+    ) -> Result<Option<Self::RedbPrimaryTable>>;
+
+    /// Returns the table of the blobs, `None` if the model has no blob.
+    fn get_blob_table(&'txn self, model: &Model) -> Result<Option<Self::RedbPrimaryTable>>;
+
+    /// Returns the table of the values stored by the secondary key, `None` if the secondary key
+    /// does not store the values.
+    fn get_secondary_value_table(
+        &'txn self,
+        model: &Model,
+        secondary_key: &KeyDefinition<KeyOptions>,
+    ) -> Result<Option<Self::RedbPrimaryTable>>;
+
+    /// Returns the key under which the primary key is stored, `None` if it does not exist.
+    fn stored_primary_key(&'txn self, model: &Model, key: Key) -> Result<Option<Key>> {
+        if let Some(table) = self.get_hashed_primary_key_table(model)? {
+            hashed_primary_key::find(&table, hashed_primary_key::hash(&key), &key)
+        } else {
+            Ok(Some(key))
+        }
+    }
+
     fn get_by_primary_key(&'txn self, model: Model, key: impl ToKey) -> Result<Option<Output>> {
+        let key = if let Some(key) = self.stored_primary_key(&model, key.to_key())? {
+            key
+        } else {
+            return Ok(None);
+        };
         let table = self.get_primary_table(&model)?;
-        let key = key.to_key();
         let item = table.get(key)?;
-        Ok(item.map(|item| item.value().into()))
+        item.map(|item| self.read_value(item.value())).transpose()
     }
 
     fn get_by_secondary_key(
@@ -42,6 +83,12 @@ pub trait PrivateReadableTransaction<'db, 'txn> {
         // Provide a better error for the test of unicity of the secondary key
         model.check_secondary_options(&secondary_key, |options| options.unique)?;
 
+        // A single read when the secondary key stores the values
+        if let Some(table) = self.get_secondary_value_table(&model, &secondary_key)? {
+            let item = table.get(key.to_key())?;
+            return item.map(|item| self.read_value(item.value())).transpose();
+        }
+
         let table = self.get_secondary_table(&model, &secondary_key)?;
 
         let mut primary_keys = table.get(key.to_key())?;
@@ -52,10 +99,82 @@ pub trait PrivateReadableTransaction<'db, 'txn> {
             return Ok(None);
         };
 
-        Ok(Some(
-            self.get_by_primary_key(model, primary_key)?
-                .ok_or(Error::PrimaryKeyNotFound)?,
-        ))
+        // The secondary table already holds the stored primary key
+        let table = self.get_primary_table(&model)?;
+        let item = table.get(primary_key)?.ok_or(Error::PrimaryKeyNotFound)?;
+        Ok(Some(self.read_value(item.value())?))
+    }
+
+    /// Returns the original primary key of the value having the unique secondary key `key`,
+    /// without reading the value.
+    fn get_primary_key_by_secondary_key(
+        &'txn self,
+        model: Model,
+        key_def: impl ToKeyDefinition<KeyOptions>,
+        key: impl ToKey,
+    ) -> Result<Option<Key>> {
+        let secondary_key = key_def.key_definition();
+        model.check_secondary_options(&secondary_key, |options| options.unique)?;
+
+        let table = self.get_secondary_table(&model, &secondary_key)?;
+        let mut primary_keys = table.get(key.to_key())?;
+        let primary_key = if let Some(primary_key) = primary_keys.next() {
+            primary_key?.value()
+        } else {
+            return Ok(None);
+        };
+
+        // The secondary table holds the stored primary key
+        if let Some(table) = self.get_hashed_primary_key_table(&model)? {
+            let original = table.get(&primary_key)?.ok_or(Error::PrimaryKeyNotFound)?;
+            Ok(Some(Key::from_slice(original.value())))
+        } else {
+            Ok(Some(primary_key))
+        }
+    }
+
+    /// Returns the stored primary keys of the values having the secondary key `key`.
+    fn secondary_key_primary_keys(
+        &'txn self,
+        model: &Model,
+        key_def: &KeyDefinition<KeyOptions>,
+        key: Key,
+    ) -> Result<Vec<Key>> {
+        let table = self.get_secondary_table(model, key_def)?;
+        let mut primary_keys = vec![];
+        for primary_key in table.get(key)? {
+            primary_keys.push(primary_key?.value());
+        }
+        Ok(primary_keys)
+    }
+
+    /// Returns the values having both secondary keys, ordered by primary key.
+    fn get_by_secondary_keys_intersection(
+        &'txn self,
+        model: Model,
+        (key_def_a, key_a): (KeyDefinition<KeyOptions>, Key),
+        (key_def_b, key_b): (KeyDefinition<KeyOptions>, Key),
+    ) -> Result<Vec<Output>> {
+        let primary_keys_a = self.secondary_key_primary_keys(&model, &key_def_a, key_a)?;
+        let primary_keys_b = self.secondary_key_primary_keys(&model, &key_def_b, key_b)?;
+        // Both lists are ordered by primary key, index the smallest one and keep the order of
+        // the largest one
+        let (small, large) = if primary_keys_a.len() <= primary_keys_b.len() {
+            (primary_keys_a, primary_keys_b)
+        } else {
+            (primary_keys_b, primary_keys_a)
+        };
+        let small: HashSet<Key> = small.into_iter().collect();
+
+        let table = self.get_primary_table(&model)?;
+        let mut items = vec![];
+        for primary_key in large {
+            if small.contains(&primary_key) {
+                let item = table.get(primary_key)?.ok_or(Error::PrimaryKeyNotFound)?;
+                items.push(self.read_value(item.value())?);
+            }
+        }
+        Ok(items)
     }
 
     fn primary_len(&'txn self, model: Model) -> Result<u64> {
@@ -2,11 +2,64 @@ use crate::db_type::{Error, Key, KeyDefinition, KeyOptions, Result};
 use crate::table_definition::PrimaryTableDefinition;
 use crate::transaction::internal::private_readable_transaction::PrivateReadableTransaction;
 use crate::Model;
+use redb::TableHandle;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub struct InternalRTransaction<'db> {
     pub(crate) redb_transaction: redb::ReadTransaction,
     pub(crate) table_definitions: &'db HashMap<String, PrimaryTableDefinition<'db>>,
+    pub(crate) read_transactions_counter: &'db AtomicUsize,
+    pub(crate) max_value_size: Option<usize>,
+}
+
+impl<'db> InternalRTransaction<'db> {
+    pub(crate) fn new(
+        redb_transaction: redb::ReadTransaction,
+        table_definitions: &'db HashMap<String, PrimaryTableDefinition<'db>>,
+        read_transactions_counter: &'db AtomicUsize,
+        max_value_size: Option<usize>,
+    ) -> Self {
+        read_transactions_counter.fetch_add(1, Ordering::SeqCst);
+        Self {
+            redb_transaction,
+            table_definitions,
+            read_transactions_counter,
+            max_value_size,
+        }
+    }
+
+    /// Returns the table registered as `alias` of the primary table of `model`, `None` if it
+    /// does not exist in the database, see [`Models::define_alias`](crate::Models::define_alias).
+    pub(crate) fn get_alias_table(
+        &self,
+        model: &Model,
+        alias: &str,
+    ) -> Result<Option<redb::ReadOnlyTable<Key, &'static [u8]>>> {
+        let table_definition = self
+            .table_definitions
+            .get(model.primary_key.unique_table_name.as_str())
+            .ok_or_else(|| model.not_defined_error())?;
+        let alias = table_definition
+            .aliases
+            .iter()
+            .find(|definition| definition.name() == alias)
+            .ok_or_else(|| Error::TableDefinitionNotFound {
+                table: alias.to_string(),
+            })?;
+        match self.redb_transaction.open_table(*alias) {
+            Ok(table) => Ok(Some(table)),
+            Err(redb::TableError::TableDoesNotExist(_)) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+impl Drop for InternalRTransaction<'_> {
+    fn drop(&mut self) {
+        self.read_transactions_counter
+            .fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 impl<'db, 'txn> PrivateReadableTransaction<'db, 'txn> for InternalRTransaction<'db>
@@ -23,17 +76,46 @@ where
         self.table_definitions
     }
 
+    fn max_value_size(&self) -> Option<usize> {
+        self.max_value_size
+    }
+
     fn get_primary_table(&'txn self, model: &Model) -> Result<Self::RedbPrimaryTable> {
         let table_definition = self
             .table_definitions()
             .get(model.primary_key.unique_table_name.as_str())
-            .ok_or_else(|| Error::TableDefinitionNotFound {
-                table: model.primary_key.unique_table_name.to_string(),
-            })?;
+            .ok_or_else(|| model.not_defined_error())?;
         let table = self.redb_transaction.open_table(table_definition.redb)?;
         Ok(table)
     }
 
+    fn get_hashed_primary_key_table(
+        &'txn self,
+        model: &Model,
+    ) -> Result<Option<Self::RedbPrimaryTable>> {
+        let table_definition = self
+            .table_definitions()
+            .get(model.primary_key.unique_table_name.as_str())
+            .ok_or_else(|| model.not_defined_error())?;
+        if let Some(hashed_primary_key) = table_definition.hashed_primary_key {
+            Ok(Some(self.redb_transaction.open_table(hashed_primary_key)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn get_blob_table(&'txn self, model: &Model) -> Result<Option<Self::RedbPrimaryTable>> {
+        let table_definition = self
+            .table_definitions()
+            .get(model.primary_key.unique_table_name.as_str())
+            .ok_or_else(|| model.not_defined_error())?;
+        if let Some(blob) = table_definition.blob {
+            Ok(Some(self.redb_transaction.open_table(blob)?))
+        } else {
+            Ok(None)
+        }
+    }
+
     fn get_secondary_table(
         &'txn self,
         model: &Model,
@@ -42,9 +124,7 @@ where
         let main_table_definition = self
             .table_definitions()
             .get(model.primary_key.unique_table_name.as_str())
-            .ok_or_else(|| Error::TableDefinitionNotFound {
-                table: model.primary_key.unique_table_name.to_string(),
-            })?;
+            .ok_or_else(|| model.not_defined_error())?;
         let secondary_table_definition = main_table_definition
             .secondary_tables
             .get(secondary_key)
@@ -56,4 +136,26 @@ where
             .open_multimap_table(secondary_table_definition.redb)?;
         Ok(table)
     }
+
+    fn get_secondary_value_table(
+        &'txn self,
+        model: &Model,
+        secondary_key: &KeyDefinition<KeyOptions>,
+    ) -> Result<Option<Self::RedbPrimaryTable>> {
+        let main_table_definition = self
+            .table_definitions()
+            .get(model.primary_key.unique_table_name.as_str())
+            .ok_or_else(|| model.not_defined_error())?;
+        let secondary_table_definition = main_table_definition
+            .secondary_tables
+            .get(secondary_key)
+            .ok_or_else(|| Error::TableDefinitionNotFound {
+                table: secondary_key.unique_table_name.to_string(),
+            })?;
+        if let Some(value) = secondary_table_definition.value {
+            Ok(Some(self.redb_transaction.open_table(value)?))
+        } else {
+            Ok(None)
+        }
+    }
 }
@@ -1,18 +1,46 @@
-use crate::db_type::{Error, Input, Key, KeyDefinition, KeyEntry, KeyOptions, Output, Result};
+use crate::db_type::{Error, Input, Key, KeyDefinition, KeyOptions, Output, Result};
 use crate::table_definition::PrimaryTableDefinition;
+use crate::transaction::internal::blob;
+use crate::transaction::internal::hashed_primary_key;
 use crate::transaction::internal::private_readable_transaction::PrivateReadableTransaction;
+use crate::transaction::{MigrateFailure, MigrateReport, WritePolicy};
 use crate::watch::WatcherRequest;
 use crate::{db_type::ToInput, Model};
 use redb::ReadableMultimapTable;
 use redb::ReadableTable;
 use redb::ReadableTableMetadata;
 use redb::TableHandle;
+use std::cell::Cell;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
+use std::path::Path;
 
 pub struct InternalRwTransaction<'db> {
     pub(crate) redb_transaction: redb::WriteTransaction,
     pub(crate) primary_table_definitions: &'db HashMap<String, PrimaryTableDefinition<'db>>,
+    pub(crate) max_value_size: Option<usize>,
+    pub(crate) size_quota: Option<SizeQuota>,
+    // redb doesn't expose the flags, they are kept to be read back.
+    pub(crate) two_phase_commit: bool,
+    pub(crate) quick_repair: bool,
+}
+
+/// Limit set with [`Builder::set_max_db_size`](crate::Builder::set_max_db_size), the length of
+/// the file when the transaction began and an estimate of the bytes written by the transaction.
+pub(crate) struct SizeQuota {
+    limit: u64,
+    file_len: u64,
+    written_bytes: Cell<u64>,
+}
+
+impl SizeQuota {
+    pub(crate) fn new(path: &Path, limit: u64) -> Result<Self> {
+        Ok(Self {
+            limit,
+            file_len: std::fs::metadata(path)?.len(),
+            written_bytes: Cell::new(0),
+        })
+    }
 }
 
 impl<'db, 'txn> PrivateReadableTransaction<'db, 'txn> for InternalRwTransaction<'db>
@@ -23,23 +51,55 @@ where
     type RedbPrimaryTable = redb::Table<'txn, Key, &'static [u8]>;
     type RedbSecondaryTable = redb::MultimapTable<'txn, Key, Key>;
 
-    type RedbTransaction<'db_bis> = redb::WriteTransaction where Self: 'db_bis;
+    type RedbTransaction<'db_bis>
+        = redb::WriteTransaction
+    where
+        Self: 'db_bis;
 
     fn table_definitions(&self) -> &HashMap<String, PrimaryTableDefinition> {
         self.primary_table_definitions
     }
 
+    fn max_value_size(&self) -> Option<usize> {
+        self.max_value_size
+    }
+
     fn get_primary_table(&'txn self, model: &Model) -> Result<Self::RedbPrimaryTable> {
         let table_definition = self
             .table_definitions()
             .get(model.primary_key.unique_table_name.as_str())
-            .ok_or_else(|| Error::TableDefinitionNotFound {
-                table: model.primary_key.unique_table_name.to_string(),
-            })?;
+            .ok_or_else(|| model.not_defined_error())?;
         let table = self.redb_transaction.open_table(table_definition.redb)?;
         Ok(table)
     }
 
+    fn get_hashed_primary_key_table(
+        &'txn self,
+        model: &Model,
+    ) -> Result<Option<Self::RedbPrimaryTable>> {
+        let table_definition = self
+            .table_definitions()
+            .get(model.primary_key.unique_table_name.as_str())
+            .ok_or_else(|| model.not_defined_error())?;
+        if let Some(hashed_primary_key) = table_definition.hashed_primary_key {
+            Ok(Some(self.redb_transaction.open_table(hashed_primary_key)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn get_blob_table(&'txn self, model: &Model) -> Result<Option<Self::RedbPrimaryTable>> {
+        let table_definition = self
+            .table_definitions()
+            .get(model.primary_key.unique_table_name.as_str())
+            .ok_or_else(|| model.not_defined_error())?;
+        if let Some(blob) = table_definition.blob {
+            Ok(Some(self.redb_transaction.open_table(blob)?))
+        } else {
+            Ok(None)
+        }
+    }
+
     fn get_secondary_table(
         &'txn self,
         model: &Model,
@@ -48,9 +108,7 @@ where
         let main_table_definition = self
             .table_definitions()
             .get(model.primary_key.unique_table_name.as_str())
-            .ok_or_else(|| Error::TableDefinitionNotFound {
-                table: model.primary_key.unique_table_name.to_string(),
-            })?;
+            .ok_or_else(|| model.not_defined_error())?;
         let secondary_table_definition = main_table_definition
             .secondary_tables
             .get(secondary_key)
@@ -62,28 +120,81 @@ where
             .open_multimap_table(secondary_table_definition.redb)?;
         Ok(table)
     }
+
+    fn get_secondary_value_table(
+        &'txn self,
+        model: &Model,
+        secondary_key: &KeyDefinition<KeyOptions>,
+    ) -> Result<Option<Self::RedbPrimaryTable>> {
+        let main_table_definition = self
+            .table_definitions()
+            .get(model.primary_key.unique_table_name.as_str())
+            .ok_or_else(|| model.not_defined_error())?;
+        let secondary_table_definition = main_table_definition
+            .secondary_tables
+            .get(secondary_key)
+            .ok_or_else(|| Error::TableDefinitionNotFound {
+                table: secondary_key.unique_table_name.to_string(),
+            })?;
+        if let Some(value) = secondary_table_definition.value {
+            Ok(Some(self.redb_transaction.open_table(value)?))
+        } else {
+            Ok(None)
+        }
+    }
 }
 
 impl InternalRwTransaction<'_> {
     pub(crate) fn commit(self) -> Result<()> {
-        self.redb_transaction.commit()?;
+        if let Some(quota) = &self.size_quota {
+            let written_bytes = quota.written_bytes.get();
+            // A transaction which only removes data is committed even above the limit, the
+            // space is only given back by a compaction.
+            if written_bytes > 0 && quota.file_len + written_bytes > quota.limit {
+                let limit = quota.limit;
+                self.redb_transaction.abort()?;
+                return Err(Error::QuotaExceeded { limit });
+            }
+        }
+        // The IO errors are returned as is, to keep their kind (e.g. `StorageFull`).
+        self.redb_transaction
+            .commit()
+            .map_err(|error| match error {
+                redb::CommitError::Storage(redb::StorageError::Io(error)) => Error::Io(error),
+                error => error.into(),
+            })?;
         Ok(())
     }
 
+    /// Adds `bytes` to the bytes written by the transaction, see [`SizeQuota`].
+    fn count_written(&self, bytes: u64) {
+        if let Some(quota) = &self.size_quota {
+            quota.written_bytes.set(quota.written_bytes.get() + bytes);
+        }
+    }
+
     pub(crate) fn concrete_insert(
         &self,
         model: Model,
         item: Input,
     ) -> Result<(WatcherRequest, Output)> {
+        self.count_written(item.written_size());
         let mut table = self.get_primary_table(&model)?;
-        if table.get(&item.primary_key)?.is_some() {
-            return Err(Error::DuplicateKey {
-                key_name: model.primary_key.unique_table_name.to_string(),
-            });
-        }
-        table.insert(&item.primary_key, item.value.as_slice())?;
+        let primary_key =
+            if let Some(mut hashed_table) = self.get_hashed_primary_key_table(&model)? {
+                let hash = hashed_primary_key::hash(&item.primary_key);
+                hashed_primary_key::insert(&mut hashed_table, hash, &item.primary_key)?
+            } else if table.get(&item.primary_key)?.is_none() {
+                Some(item.primary_key.clone())
+            } else {
+                None
+            };
+        let primary_key = primary_key.ok_or_else(|| Error::DuplicateKey {
+            key_name: model.primary_key.unique_table_name.to_string(),
+        })?;
+        table.insert(&primary_key, item.value.as_slice())?;
 
-        self.util_insert_secondary_keys(&item, &model)?;
+        self.util_insert_secondary_keys(&item, &primary_key, &model)?;
 
         Ok((
             WatcherRequest::new(
@@ -91,7 +202,7 @@ impl InternalRwTransaction<'_> {
                 item.primary_key,
                 item.secondary_keys,
             ),
-            Output(item.value),
+            Output(item.value, None),
         ))
     }
 
@@ -107,48 +218,48 @@ impl InternalRwTransaction<'_> {
             self.concrete_insert(model.clone(), item.clone())?;
         }
 
+        let mut watcher_request = WatcherRequest::new(
+            model.primary_key.unique_table_name.clone(),
+            item.primary_key,
+            item.secondary_keys,
+        );
+        if let Some(old_item) = &old_item {
+            watcher_request = watcher_request.with_old_keys(old_item);
+        }
         let old_item: Option<Output> = old_item.map(|old_item| old_item.into());
 
-        Ok((
-            WatcherRequest::new(
-                model.primary_key.unique_table_name.clone(),
-                item.primary_key,
-                item.secondary_keys,
-            ),
-            Output(item.value),
-            old_item,
-        ))
+        Ok((watcher_request, Output(item.value, None), old_item))
     }
 
     /// This method insert secondary keys and check conflicts.
     /// It is used by [`concrete_insert`](Self::concrete_insert) and [`concrete_upsert`](Self::concrete_upsert).
-    pub(crate) fn util_insert_secondary_keys(&self, item: &Input, model: &Model) -> Result<()> {
+    pub(crate) fn util_insert_secondary_keys(
+        &self,
+        item: &Input,
+        primary_key: &Key,
+        model: &Model,
+    ) -> Result<()> {
         for secondary_key_def in item.secondary_keys.keys() {
             let mut secondary_table = self.get_secondary_table(model, secondary_key_def)?;
-            let secondary_key = match item.secondary_key_value(secondary_key_def)? {
-                KeyEntry::Default(secondary_key) => secondary_key,
-                KeyEntry::Optional(secondary_key) => {
-                    if let Some(secondary_key) = secondary_key {
-                        secondary_key
-                    } else {
-                        continue;
+            let mut value_table = self.get_secondary_value_table(model, secondary_key_def)?;
+            for secondary_key in item.secondary_key_value(secondary_key_def)?.into_keys() {
+                if secondary_key_def.options.unique {
+                    let check = {
+                        let primary_keys = secondary_table.get(&secondary_key)?;
+                        !primary_keys.is_empty()
+                    };
+                    if check {
+                        return Err(Error::DuplicateKey {
+                            key_name: secondary_key_def.unique_table_name.to_string(),
+                        });
                     }
                 }
-            };
 
-            if secondary_key_def.options.unique {
-                let check = {
-                    let primary_keys = secondary_table.get(&secondary_key)?;
-                    !primary_keys.is_empty()
-                };
-                if check {
-                    return Err(Error::DuplicateKey {
-                        key_name: secondary_key_def.unique_table_name.to_string(),
-                    });
+                secondary_table.insert(&secondary_key, primary_key)?;
+                if let Some(value_table) = &mut value_table {
+                    value_table.insert(&secondary_key, item.value.as_slice())?;
                 }
             }
-
-            secondary_table.insert(secondary_key, &item.primary_key)?;
         }
 
         Ok(())
@@ -160,9 +271,14 @@ impl InternalRwTransaction<'_> {
         item: Input,
     ) -> Result<(WatcherRequest, Output)> {
         let keys = &item.secondary_keys;
+        let primary_key = self
+            .stored_primary_key(&model, item.primary_key.clone())?
+            .ok_or_else(|| Error::KeyNotFound {
+                key: item.primary_key.as_slice().to_vec(),
+            })?;
         {
             let mut table: redb::Table<Key, &[u8]> = self.get_primary_table(&model)?;
-            let result = if let Some(current_item) = table.remove(&item.primary_key)? {
+            let result = if let Some(current_item) = table.remove(&primary_key)? {
                 let current_item = current_item.value();
                 if current_item == item.value {
                     Ok(())
@@ -177,29 +293,25 @@ impl InternalRwTransaction<'_> {
                 })
             };
             if let Err(Error::IncorrectInputData { ref value }) = result {
-                table.insert(&item.primary_key, value.as_slice())?;
+                table.insert(&primary_key, value.as_slice())?;
             }
             result?;
         }
+        if let Some(mut hashed_table) = self.get_hashed_primary_key_table(&model)? {
+            hashed_table.remove(&primary_key)?;
+        }
 
         for secondary_key_def in keys.keys() {
             let mut secondary_table = self.get_secondary_table(&model, secondary_key_def)?;
-            match &item.secondary_key_value(secondary_key_def)? {
-                KeyEntry::Default(secondary_key) => {
-                    if !secondary_table.remove(secondary_key, &item.primary_key)? {
-                        return Err(Error::RemoveSecondaryKeyError(
-                            secondary_key_def.unique_table_name.to_string(),
-                        ));
-                    }
+            let mut value_table = self.get_secondary_value_table(&model, secondary_key_def)?;
+            for secondary_key in item.secondary_key_value(secondary_key_def)?.into_keys() {
+                if !secondary_table.remove(&secondary_key, &primary_key)? {
+                    return Err(Error::RemoveSecondaryKeyError(
+                        secondary_key_def.unique_table_name.to_string(),
+                    ));
                 }
-                KeyEntry::Optional(secondary_key) => {
-                    if let Some(value) = secondary_key {
-                        if !secondary_table.remove(value, &item.primary_key)? {
-                            return Err(Error::RemoveSecondaryKeyError(
-                                secondary_key_def.unique_table_name.to_string(),
-                            ));
-                        }
-                    }
+                if let Some(value_table) = &mut value_table {
+                    value_table.remove(&secondary_key)?;
                 }
             }
         }
@@ -210,19 +322,339 @@ impl InternalRwTransaction<'_> {
                 item.primary_key,
                 item.secondary_keys,
             ),
-            Output(item.value),
+            Output(item.value, None),
         ))
     }
 
+    /// Removes the items having the given primary keys, skipping the absent ones. The tables
+    /// are opened once for all the keys.
+    pub(crate) fn concrete_remove_many_by_keys<T: ToInput>(
+        &self,
+        keys: impl IntoIterator<Item = Key>,
+    ) -> Result<Vec<(WatcherRequest, Output, T)>> {
+        let model = T::native_db_model();
+        let mut table = self.get_primary_table(&model)?;
+        let mut hashed_table = self.get_hashed_primary_key_table(&model)?;
+        let mut blob_table = self.get_blob_table(&model)?;
+        let mut secondary_tables = HashMap::new();
+        for secondary_key_def in &model.secondary_keys {
+            let secondary_table = self.get_secondary_table(&model, secondary_key_def)?;
+            let value_table = self.get_secondary_value_table(&model, secondary_key_def)?;
+            secondary_tables.insert(secondary_key_def.clone(), (secondary_table, value_table));
+        }
+
+        let mut removed = vec![];
+        for key in keys {
+            let primary_key = if let Some(hashed_table) = &mut hashed_table {
+                let hash = hashed_primary_key::hash(&key);
+                match hashed_primary_key::find(hashed_table, hash, &key)? {
+                    Some(stored_key) => {
+                        hashed_table.remove(&stored_key)?;
+                        stored_key
+                    }
+                    None => continue,
+                }
+            } else {
+                key
+            };
+            let Some(value) = table
+                .remove(&primary_key)?
+                .map(|value| value.value().to_vec())
+            else {
+                continue;
+            };
+            let output = Output(value, self.max_value_size);
+            let item: T = output.inner()?;
+            let input = item.native_db_input()?;
+
+            for (secondary_key_def, (secondary_table, value_table)) in &mut secondary_tables {
+                for secondary_key in input.secondary_key_value(secondary_key_def)?.into_keys() {
+                    if !secondary_table.remove(&secondary_key, &primary_key)? {
+                        return Err(Error::RemoveSecondaryKeyError(
+                            secondary_key_def.unique_table_name.to_string(),
+                        ));
+                    }
+                    if let Some(value_table) = value_table {
+                        value_table.remove(&secondary_key)?;
+                    }
+                }
+            }
+            if let Some(blob_table) = &mut blob_table {
+                blob::remove(blob_table, &input.primary_key)?;
+            }
+
+            let watcher_request = WatcherRequest::new(
+                model.primary_key.unique_table_name.clone(),
+                input.primary_key,
+                input.secondary_keys,
+            );
+            removed.push((watcher_request, output, item));
+        }
+        Ok(removed)
+    }
+
+    /// Writes the items with the given policy, returns the written items with their old value
+    /// and the number of skipped items. The tables are opened once for all the items.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn concrete_write_many<T: ToInput>(
+        &self,
+        items: impl IntoIterator<Item = T>,
+        policy: WritePolicy,
+    ) -> Result<(Vec<(WatcherRequest, Output, Option<Output>)>, u64)> {
+        let model = T::native_db_model();
+        let mut table = self.get_primary_table(&model)?;
+        let mut hashed_table = self.get_hashed_primary_key_table(&model)?;
+        let mut secondary_tables = HashMap::new();
+        for secondary_key_def in &model.secondary_keys {
+            let secondary_table = self.get_secondary_table(&model, secondary_key_def)?;
+            let value_table = self.get_secondary_value_table(&model, secondary_key_def)?;
+            secondary_tables.insert(secondary_key_def.clone(), (secondary_table, value_table));
+        }
+
+        let mut written = vec![];
+        let mut skipped = 0;
+        for item in items {
+            item.native_db_validate()?;
+            let input = item.native_db_input()?;
+            let stored_key = match &hashed_table {
+                Some(hashed_table) => {
+                    let hash = hashed_primary_key::hash(&input.primary_key);
+                    hashed_primary_key::find(hashed_table, hash, &input.primary_key)?
+                }
+                None => Some(input.primary_key.clone()),
+            };
+            let old_value = match &stored_key {
+                Some(stored_key) => table.get(stored_key)?.map(|value| value.value().to_vec()),
+                None => None,
+            };
+
+            let Some(old_value) = old_value else {
+                let primary_key = match &mut hashed_table {
+                    Some(hashed_table) => {
+                        let hash = hashed_primary_key::hash(&input.primary_key);
+                        hashed_primary_key::insert(hashed_table, hash, &input.primary_key)?
+                            .ok_or_else(|| Error::DuplicateKey {
+                                key_name: model.primary_key.unique_table_name.to_string(),
+                            })?
+                    }
+                    None => input.primary_key.clone(),
+                };
+                self.count_written(input.written_size());
+                table.insert(&primary_key, input.value.as_slice())?;
+                for (secondary_key_def, (secondary_table, value_table)) in &mut secondary_tables {
+                    for secondary_key in input.secondary_key_value(secondary_key_def)?.into_keys() {
+                        if secondary_key_def.options.unique
+                            && !secondary_table.get(&secondary_key)?.is_empty()
+                        {
+                            return Err(Error::DuplicateKey {
+                                key_name: secondary_key_def.unique_table_name.to_string(),
+                            });
+                        }
+                        secondary_table.insert(&secondary_key, &primary_key)?;
+                        if let Some(value_table) = value_table {
+                            value_table.insert(&secondary_key, input.value.as_slice())?;
+                        }
+                    }
+                }
+                let watcher_request = WatcherRequest::new(
+                    model.primary_key.unique_table_name.clone(),
+                    input.primary_key,
+                    input.secondary_keys,
+                );
+                written.push((watcher_request, Output(input.value, None), None));
+                continue;
+            };
+
+            // With a whole primary key, the same value is already stored.
+            if model.whole_primary_key {
+                skipped += 1;
+                continue;
+            }
+            match policy {
+                WritePolicy::InsertFailOnDup => {
+                    return Err(Error::DuplicateKey {
+                        key_name: model.primary_key.unique_table_name.to_string(),
+                    });
+                }
+                WritePolicy::InsertOrIgnore => {
+                    skipped += 1;
+                    continue;
+                }
+                WritePolicy::Upsert => {}
+            }
+
+            // Same primary key: replace the value in place and only rewrite the secondary keys
+            // that changed.
+            let primary_key = stored_key.expect("Unreachable: the old value has a stored key");
+            let old_value = Output(old_value, self.max_value_size);
+            let old_item: T = old_value.inner()?;
+            let old_input = old_item.native_db_input()?;
+            self.count_written(input.written_size());
+            table.insert(&primary_key, input.value.as_slice())?;
+            for (secondary_key_def, (secondary_table, value_table)) in &mut secondary_tables {
+                let old_secondary_keys = old_input
+                    .secondary_key_value(secondary_key_def)?
+                    .into_keys();
+                let secondary_keys = input.secondary_key_value(secondary_key_def)?.into_keys();
+                for old_secondary_key in &old_secondary_keys {
+                    if secondary_keys.contains(old_secondary_key) {
+                        continue;
+                    }
+                    if !secondary_table.remove(old_secondary_key, &primary_key)? {
+                        return Err(Error::RemoveSecondaryKeyError(
+                            secondary_key_def.unique_table_name.to_string(),
+                        ));
+                    }
+                    if let Some(value_table) = value_table {
+                        value_table.remove(old_secondary_key)?;
+                    }
+                }
+                for secondary_key in &secondary_keys {
+                    if !old_secondary_keys.contains(secondary_key) {
+                        if secondary_key_def.options.unique
+                            && !secondary_table.get(secondary_key)?.is_empty()
+                        {
+                            return Err(Error::DuplicateKey {
+                                key_name: secondary_key_def.unique_table_name.to_string(),
+                            });
+                        }
+                        secondary_table.insert(secondary_key, &primary_key)?;
+                    }
+                    // The stored values are rewritten even if the keys didn't change.
+                    if let Some(value_table) = value_table {
+                        value_table.insert(secondary_key, input.value.as_slice())?;
+                    }
+                }
+            }
+            let watcher_request = WatcherRequest::new(
+                model.primary_key.unique_table_name.clone(),
+                input.primary_key,
+                input.secondary_keys,
+            )
+            .with_old_keys(&old_input);
+            written.push((watcher_request, Output(input.value, None), Some(old_value)));
+        }
+        Ok((written, skipped))
+    }
+
     pub(crate) fn concrete_update(
         &self,
         model: Model,
         old_item: Input,
         updated_item: Input,
     ) -> Result<(WatcherRequest, Output, Output)> {
-        let (_, old_binary_value) = self.concrete_remove(model.clone(), old_item)?;
-        let (watcher_request, new_binary_value) = self.concrete_insert(model, updated_item)?;
-        Ok((watcher_request, old_binary_value, new_binary_value))
+        if old_item.primary_key != updated_item.primary_key {
+            let (old_watcher_request, old_binary_value) =
+                self.concrete_remove(model.clone(), old_item)?;
+            let (watcher_request, new_binary_value) =
+                self.concrete_insert(model.clone(), updated_item)?;
+            // The blob follows the value to its new primary key.
+            if let Some(mut blob_table) = self.get_blob_table(&model)? {
+                let chunks = blob::take(&mut blob_table, &old_watcher_request.primary_key)?;
+                blob::put(&mut blob_table, &watcher_request.primary_key, &chunks)?;
+            }
+            let watcher_request = WatcherRequest {
+                old_keys: Some((
+                    old_watcher_request.primary_key,
+                    old_watcher_request.secondary_keys_value,
+                )),
+                ..watcher_request
+            };
+            return Ok((watcher_request, old_binary_value, new_binary_value));
+        }
+
+        // Same primary key: replace the value in place and only rewrite the secondary keys
+        // that changed.
+        let primary_key = self
+            .stored_primary_key(&model, old_item.primary_key.clone())?
+            .ok_or_else(|| Error::KeyNotFound {
+                key: old_item.primary_key.as_slice().to_vec(),
+            })?;
+        {
+            let mut table = self.get_primary_table(&model)?;
+            let current_item = table
+                .get(&primary_key)?
+                .map(|current_item| current_item.value().to_vec())
+                .ok_or_else(|| Error::KeyNotFound {
+                    key: old_item.primary_key.as_slice().to_vec(),
+                })?;
+            if current_item != old_item.value {
+                return Err(Error::IncorrectInputData {
+                    value: current_item,
+                });
+            }
+            self.count_written(updated_item.written_size());
+            table.insert(&primary_key, updated_item.value.as_slice())?;
+        }
+
+        for secondary_key_def in updated_item.secondary_keys.keys() {
+            let old_secondary_key = old_item.secondary_key_value(secondary_key_def)?;
+            let updated_secondary_key = updated_item.secondary_key_value(secondary_key_def)?;
+            if old_secondary_key == updated_secondary_key {
+                continue;
+            }
+
+            // Only the keys that differ are rewritten, e.g. the added and removed elements of a
+            // multi key.
+            let old_secondary_keys = old_secondary_key.into_keys();
+            let updated_secondary_keys = updated_secondary_key.into_keys();
+            let mut secondary_table = self.get_secondary_table(&model, secondary_key_def)?;
+            for old_secondary_key in &old_secondary_keys {
+                if updated_secondary_keys.contains(old_secondary_key) {
+                    continue;
+                }
+                if !secondary_table.remove(old_secondary_key, &primary_key)? {
+                    return Err(Error::RemoveSecondaryKeyError(
+                        secondary_key_def.unique_table_name.to_string(),
+                    ));
+                }
+            }
+            for updated_secondary_key in updated_secondary_keys {
+                if old_secondary_keys.contains(&updated_secondary_key) {
+                    continue;
+                }
+                if secondary_key_def.options.unique
+                    && !secondary_table.get(&updated_secondary_key)?.is_empty()
+                {
+                    return Err(Error::DuplicateKey {
+                        key_name: secondary_key_def.unique_table_name.to_string(),
+                    });
+                }
+                secondary_table.insert(updated_secondary_key, &primary_key)?;
+            }
+        }
+
+        // The stored values are rewritten even if the keys didn't change.
+        for secondary_key_def in updated_item.secondary_keys.keys() {
+            let Some(mut value_table) =
+                self.get_secondary_value_table(&model, secondary_key_def)?
+            else {
+                continue;
+            };
+            let old_secondary_keys = old_item.secondary_key_value(secondary_key_def)?.into_keys();
+            let updated_secondary_keys = updated_item
+                .secondary_key_value(secondary_key_def)?
+                .into_keys();
+            for old_secondary_key in &old_secondary_keys {
+                if !updated_secondary_keys.contains(old_secondary_key) {
+                    value_table.remove(old_secondary_key)?;
+                }
+            }
+            for updated_secondary_key in &updated_secondary_keys {
+                value_table.insert(updated_secondary_key, updated_item.value.as_slice())?;
+            }
+        }
+
+        Ok((
+            WatcherRequest::new(
+                model.primary_key.unique_table_name.clone(),
+                updated_item.primary_key,
+                updated_item.secondary_keys,
+            )
+            .with_old_keys(&old_item),
+            Output(old_item.value, None),
+            Output(updated_item.value, None),
+        ))
     }
 
     pub(crate) fn concrete_primary_drain(&self, model: Model) -> Result<Vec<Output>> {
@@ -235,17 +667,18 @@ impl InternalRwTransaction<'_> {
         for result in drain {
             let (primary_key, value) = result?;
             // TODO: we should delay to an drain scan
-            let binary_value = Output(value.value().to_vec());
+            let binary_value = Output(value.value().to_vec(), self.max_value_size);
             key_items.insert(primary_key.value().to_owned());
             items.push(binary_value);
         }
+        if let Some(mut hashed_table) = self.get_hashed_primary_key_table(&model)? {
+            hashed_table.retain(|_, _| false)?;
+        }
 
         let secondary_table_names: Vec<&KeyDefinition<KeyOptions>> = self
             .primary_table_definitions
             .get(model.primary_key.unique_table_name.as_str())
-            .ok_or(Error::TableDefinitionNotFound {
-                table: model.primary_key.unique_table_name.to_string(),
-            })?
+            .ok_or_else(|| model.not_defined_error())?
             .secondary_tables
             .keys()
             .collect();
@@ -256,7 +689,12 @@ impl InternalRwTransaction<'_> {
 
             // Detect secondary keys to delete
             let mut secondary_keys_to_delete = vec![];
-            let mut number_detected_key_to_delete = key_items.len();
+            // A multi key has several entries per item
+            let mut number_detected_key_to_delete = if secondary_table_name.options.multi {
+                usize::MAX
+            } else {
+                key_items.len()
+            };
             for secondary_items in secondary_table.iter()? {
                 let (secondary_key, primary_keys) = secondary_items?;
                 for primary_key in primary_keys {
@@ -277,15 +715,66 @@ impl InternalRwTransaction<'_> {
             }
 
             // Delete secondary keys
+            let mut value_table = self.get_secondary_value_table(&model, secondary_table_name)?;
             for (secondary_key, primary_key) in secondary_keys_to_delete {
-                secondary_table.remove(secondary_key, primary_key)?;
+                secondary_table.remove(&secondary_key, primary_key)?;
+                if let Some(value_table) = &mut value_table {
+                    value_table.remove(&secondary_key)?;
+                }
             }
         }
 
         Ok(items)
     }
 
-    pub fn migrate<T: ToInput + Debug>(&self) -> Result<()> {
+    fn blob_table(&self, model: &Model) -> Result<redb::Table<'_, Key, &'static [u8]>> {
+        self.get_blob_table(model)?
+            .ok_or_else(|| Error::BlobNotDefined {
+                table: model.primary_key.unique_table_name.to_string(),
+            })
+    }
+
+    pub(crate) fn concrete_write_blob(
+        &self,
+        model: &Model,
+        primary_key: &Key,
+        reader: &mut impl std::io::Read,
+    ) -> Result<()> {
+        let written = blob::write(&mut self.blob_table(model)?, primary_key, reader)?;
+        self.count_written(written);
+        Ok(())
+    }
+
+    pub(crate) fn concrete_remove_blob(&self, model: &Model, primary_key: &Key) -> Result<bool> {
+        blob::remove(&mut self.blob_table(model)?, primary_key)
+    }
+
+    /// Exchange the blobs of the primary keys `key_a` and `key_b`, see `RwTransaction::swap`.
+    pub(crate) fn concrete_swap_blobs(
+        &self,
+        model: &Model,
+        key_a: &Key,
+        key_b: &Key,
+    ) -> Result<()> {
+        if let Some(mut table) = self.get_blob_table(model)? {
+            let chunks_a = blob::take(&mut table, key_a)?;
+            let chunks_b = blob::take(&mut table, key_b)?;
+            blob::put(&mut table, key_b, &chunks_a)?;
+            blob::put(&mut table, key_a, &chunks_b)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn concrete_clear_blobs(&self, model: &Model) -> Result<()> {
+        if let Some(mut table) = self.get_blob_table(model)? {
+            table.retain(|_, _| false)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the table definition of the old version of `T` holding the data to migrate,
+    /// `None` if there is nothing to migrate.
+    fn migrate_old_table_definition<T: ToInput>(&self) -> Result<Option<&PrimaryTableDefinition>> {
         let new_table_definition = self
             .primary_table_definitions
             .get(T::native_db_model().primary_key.unique_table_name.as_str())
@@ -339,7 +828,7 @@ impl InternalRwTransaction<'_> {
             old_table_definition
         } else {
             // Nothing to migrate
-            return Ok(());
+            return Ok(None);
         };
 
         // If the old table is the same as the new table, nothing to migrate
@@ -347,33 +836,180 @@ impl InternalRwTransaction<'_> {
             == T::native_db_model().primary_key.unique_table_name.as_str()
         {
             // Nothing to migrate
-            return Ok(());
+            return Ok(None);
         }
 
+        Ok(Some(old_table_definition))
+    }
+
+    pub fn migrate<T: ToInput + Debug>(&self) -> Result<()> {
+        self.migrate_with::<T, _>(|_| Ok(()))
+    }
+
+    pub fn migrate_with<T, F>(&self, mut f: F) -> Result<()>
+    where
+        T: ToInput + Debug,
+        F: FnMut(&mut T) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let old_table_definition =
+            if let Some(old_table_definition) = self.migrate_old_table_definition::<T>()? {
+                old_table_definition
+            } else {
+                return Ok(());
+            };
+
         // List all data from the old table
         for old_data in self.concrete_primary_drain(old_table_definition.model.clone())? {
-            let (decoded_item, _) = native_model::decode::<T>(old_data.0)?;
+            let old_data = old_table_definition
+                .model
+                .decompress(old_data.0, self.max_value_size)?;
+            let (mut decoded_item, _) = native_model::decode::<T>(old_data)?;
+            f(&mut decoded_item).map_err(Error::Migration)?;
             let decoded_item = decoded_item.native_db_input()?;
             self.concrete_insert(T::native_db_model(), decoded_item)?;
         }
 
+        // Move the blobs, they are keyed by the original primary key
+        if let Some(mut old_blob_table) = self.get_blob_table(&old_table_definition.model)? {
+            if let Some(mut new_blob_table) = self.get_blob_table(&T::native_db_model())? {
+                for result in old_blob_table.iter()? {
+                    let (key, value) = result?;
+                    self.count_written(value.value().len() as u64);
+                    new_blob_table.insert(key.value(), value.value())?;
+                }
+            }
+            old_blob_table.retain(|_, _| false)?;
+        }
+
         Ok(())
     }
 
+    pub fn migrate_dry_run<T: ToInput + Debug>(&self) -> Result<MigrateReport> {
+        let mut report = MigrateReport::default();
+        let old_table_definition =
+            if let Some(old_table_definition) = self.migrate_old_table_definition::<T>()? {
+                old_table_definition
+            } else {
+                return Ok(report);
+            };
+
+        let table = self.get_primary_table(&old_table_definition.model)?;
+        let hashed_table = self.get_hashed_primary_key_table(&old_table_definition.model)?;
+        for result in table.iter()? {
+            let (primary_key, value) = result?;
+            let value = old_table_definition
+                .model
+                .decompress(value.value().to_vec(), self.max_value_size)?;
+            match native_model::decode::<T>(value) {
+                Ok(_) => report.convertible += 1,
+                Err(error) => {
+                    let mut primary_key = primary_key.value();
+                    // Report the original primary key rather than its hash
+                    if let Some(hashed_table) = &hashed_table {
+                        if let Some(original) = hashed_table.get(&primary_key)? {
+                            primary_key = Key::from_slice(original.value());
+                        }
+                    }
+                    report.failures.push(MigrateFailure {
+                        primary_key,
+                        error: error.into(),
+                    });
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
     pub fn refresh<T: ToInput + Debug>(&self) -> Result<()> {
         for data in self.concrete_primary_drain(T::native_db_model())? {
-            let (decoded_item, _) = native_model::decode::<T>(data.0)?;
+            let decoded_item: T = data.inner()?;
             let decoded_item = decoded_item.native_db_input()?;
             self.concrete_insert(T::native_db_model(), decoded_item)?;
         }
         Ok(())
     }
 
+    /// Moves the values of `T` to their keys in the current encoding, see
+    /// `key_encoding_outdated`. The stored bytes of the values are kept as is, they are only
+    /// decoded to compute the keys: a value that can not be decoded stays under its old key,
+    /// without its secondary keys, and its old primary key is returned. The blobs follow their
+    /// values.
+    pub(crate) fn rekey<T: ToInput>(&self) -> Result<Vec<Key>> {
+        let model = T::native_db_model();
+        let mut rows = vec![];
+        for result in self.get_primary_table(&model)?.iter()? {
+            let (stored_key, value) = result?;
+            rows.push((stored_key.value(), value.value().to_vec()));
+        }
+        // With `hashed_primary_key`, the primary key in the old encoding is in the hashed table.
+        let mut old_primary_keys = HashMap::new();
+        if let Some(hashed_table) = self.get_hashed_primary_key_table(&model)? {
+            for result in hashed_table.iter()? {
+                let (hash, primary_key) = result?;
+                old_primary_keys.insert(hash.value(), Key::from_slice(primary_key.value()));
+            }
+        }
+        self.concrete_primary_drain(model.clone())?;
+
+        // The blobs are all taken before being put back, an old key can be the new key of
+        // another value.
+        let mut blob_table = self.get_blob_table(&model)?;
+        let mut decoded = vec![];
+        let mut undecodable = vec![];
+        for (stored_key, value) in rows {
+            let old_primary_key = old_primary_keys
+                .remove(&stored_key)
+                .unwrap_or_else(|| stored_key.clone());
+            let chunks = match &mut blob_table {
+                Some(blob_table) => blob::take(blob_table, &old_primary_key)?,
+                None => vec![],
+            };
+            let input = Output(value.clone(), self.max_value_size)
+                .inner::<T>()
+                .and_then(|item| item.native_db_input());
+            match input {
+                Ok(mut input) => {
+                    input.value = value;
+                    decoded.push((input, chunks));
+                }
+                Err(_) => undecodable.push((stored_key, old_primary_key, value, chunks)),
+            }
+        }
+
+        for (input, chunks) in decoded {
+            if let Some(blob_table) = &mut blob_table {
+                blob::put(blob_table, &input.primary_key, &chunks)?;
+            }
+            self.concrete_insert(model.clone(), input)?;
+        }
+        let mut skipped = vec![];
+        for (stored_key, old_primary_key, value, chunks) in undecodable {
+            let mut table = self.get_primary_table(&model)?;
+            if table.get(&stored_key)?.is_some() {
+                return Err(Error::DuplicateKey {
+                    key_name: model.primary_key.unique_table_name.to_string(),
+                });
+            }
+            table.insert(&stored_key, value.as_slice())?;
+            if let Some(mut hashed_table) = self.get_hashed_primary_key_table(&model)? {
+                hashed_table.insert(&stored_key, old_primary_key.as_slice())?;
+            }
+            if let Some(blob_table) = &mut blob_table {
+                blob::put(blob_table, &old_primary_key, &chunks)?;
+            }
+            skipped.push(old_primary_key);
+        }
+        Ok(skipped)
+    }
+
     pub fn set_two_phase_commit(&mut self, enabled: bool) {
+        self.two_phase_commit = enabled;
         self.redb_transaction.set_two_phase_commit(enabled)
     }
 
     pub fn set_quick_repair(&mut self, enabled: bool) {
+        self.quick_repair = enabled;
         self.redb_transaction.set_quick_repair(enabled)
     }
 }
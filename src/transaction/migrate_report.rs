@@ -0,0 +1,31 @@
+use crate::db_type::{Error, Key};
+
+/// Result of [`migrate_dry_run`](crate::transaction::RwTransaction::migrate_dry_run).
+#[derive(Debug, Default)]
+pub struct MigrateReport {
+    /// Number of values that convert without error.
+    pub convertible: u64,
+    /// Values that fail to convert.
+    pub failures: Vec<MigrateFailure>,
+}
+
+impl MigrateReport {
+    /// Number of values that fail to convert.
+    pub fn failing(&self) -> u64 {
+        self.failures.len() as u64
+    }
+
+    /// Returns `true` if all the values convert without error.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// A value that fails to convert during a [`migrate_dry_run`](crate::transaction::RwTransaction::migrate_dry_run).
+#[derive(Debug)]
+pub struct MigrateFailure {
+    /// Primary key of the value.
+    pub primary_key: Key,
+    /// Conversion error.
+    pub error: Error,
+}
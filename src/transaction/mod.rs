@@ -3,11 +3,34 @@ pub(crate) mod internal;
 /// All database interactions.
 pub mod query;
 
+#[cfg(feature = "rkyv")]
+mod archived_view;
+mod blob_reader;
+mod commit_info;
+mod migrate_report;
 mod r_transaction;
 
 mod rw_transaction;
+mod snapshot_reader;
+mod staged_change;
+mod write_many;
 
+/// Reads of the values stored as rkyv archives, without deserializing them.
+#[cfg(feature = "rkyv")]
+pub use archived_view::*;
+/// Streaming of the blobs.
+pub use blob_reader::*;
+/// Changes applied by a commit.
+pub use commit_info::*;
+/// Report of a migration dry run.
+pub use migrate_report::*;
 /// Read-only transaction.
 pub use r_transaction::*;
 /// Read-write transaction.
 pub use rw_transaction::*;
+/// Read-only transaction shared by several threads.
+pub use snapshot_reader::*;
+/// Changes staged in an aborted transaction.
+pub use staged_change::*;
+/// Bulk writes with a policy for the existing values.
+pub use write_many::*;
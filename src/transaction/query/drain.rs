@@ -1,8 +1,10 @@
-use crate::db_type::{KeyOptions, Result, ToInput, ToKeyDefinition};
+use crate::db_type::{Error, KeyOptions, Result, ToInput, ToKeyDefinition};
 use crate::transaction::internal::rw_transaction::InternalRwTransaction;
 
 pub struct RwDrain<'db, 'txn> {
     pub(crate) internal: &'txn InternalRwTransaction<'db>,
+    // The drain is not replayed on the mirror, see `RwTransaction::check_not_mirrored`.
+    pub(crate) mirrored: bool,
 }
 
 impl RwDrain<'_, '_> {
@@ -10,8 +12,14 @@ impl RwDrain<'_, '_> {
     ///
     /// **TODO: needs to be improved, so don't use it yet.**
     pub fn primary<T: ToInput>(&self) -> Result<Vec<T>> {
+        if self.mirrored {
+            return Err(Error::NotMirrored {
+                operation: "drain".to_string(),
+            });
+        }
         let model = T::native_db_model();
-        let out = self.internal.concrete_primary_drain(model)?;
+        let out = self.internal.concrete_primary_drain(model.clone())?;
+        self.internal.concrete_clear_blobs(&model)?;
         let out = out
             .into_iter()
             .map(|b| b.inner())
@@ -0,0 +1,107 @@
+use crate::db_type::{Key, KeyRange, Result};
+use redb::ReadableTableMetadata;
+use std::ops::{Bound, RangeBounds};
+
+/// Number of distinct keys read at most by each walk of the index.
+const SAMPLE_KEYS: usize = 1024;
+
+/// Estimate the number of values of a secondary index within `range`.
+///
+/// The keys are walked from the start of the range, and from the bounds of the range to the
+/// ends of the index, each walk stops after [`SAMPLE_KEYS`] keys. If one of the walks reaches its
+/// end, the count is exact. Otherwise the keys are assumed to be uniformly distributed between the
+/// first and the last key of the index.
+pub(crate) fn estimate_range_len<Table>(table: &Table, range: KeyRange) -> Result<u64>
+where
+    Table: redb::ReadableMultimapTable<Key, Key> + ReadableTableMetadata,
+{
+    let (in_range, complete) = sample_len(table, range.clone())?;
+    if complete {
+        return Ok(in_range);
+    }
+    let (before, before_complete) = match range.start_bound() {
+        Bound::Included(start) => {
+            sample_len(table, (Bound::Unbounded, Bound::Excluded(start.clone())))?
+        }
+        Bound::Excluded(start) => {
+            sample_len(table, (Bound::Unbounded, Bound::Included(start.clone())))?
+        }
+        Bound::Unbounded => (0, true),
+    };
+    let (after, after_complete) = match range.end_bound() {
+        Bound::Included(end) => {
+            sample_len(table, (Bound::Excluded(end.clone()), Bound::Unbounded))?
+        }
+        Bound::Excluded(end) => {
+            sample_len(table, (Bound::Included(end.clone()), Bound::Unbounded))?
+        }
+        Bound::Unbounded => (0, true),
+    };
+    let total = table.len()?;
+    let max = total.saturating_sub(before + after);
+    if before_complete && after_complete {
+        return Ok(max);
+    }
+
+    let mut keys = table.range::<Key>(..)?;
+    let (Some(first), Some(last)) = (keys.next(), keys.next_back()) else {
+        return Ok(in_range);
+    };
+    let (first, last) = (first?.0.value(), last?.0.value());
+    let prefix = first
+        .as_slice()
+        .iter()
+        .zip(last.as_slice())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let first_position = key_position(&first, prefix);
+    let last_position = key_position(&last, prefix);
+    let position = |bound: Bound<&Key>, unbounded: f64| match bound {
+        Bound::Included(key) | Bound::Excluded(key) => {
+            if key.as_slice() <= first.as_slice() {
+                first_position
+            } else if key.as_slice() >= last.as_slice() {
+                last_position
+            } else {
+                key_position(key, prefix)
+            }
+        }
+        Bound::Unbounded => unbounded,
+    };
+    let start = position(range.start_bound(), first_position);
+    let end = position(range.end_bound(), last_position);
+    let ratio = if last_position > first_position {
+        ((end - start) / (last_position - first_position)).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    let estimate = (ratio * total as f64).round() as u64;
+    Ok(estimate.clamp(in_range, max.max(in_range)))
+}
+
+/// Returns the number of values of the first [`SAMPLE_KEYS`] keys in `range`, and whether all
+/// the keys were read.
+fn sample_len<Table>(table: &Table, range: impl RangeBounds<Key>) -> Result<(u64, bool)>
+where
+    Table: redb::ReadableMultimapTable<Key, Key>,
+{
+    let mut len = 0;
+    for (index, entry) in table.range::<Key>(range)?.enumerate() {
+        if index == SAMPLE_KEYS {
+            return Ok((len, false));
+        }
+        let (_, values) = entry?;
+        len += values.len();
+    }
+    Ok((len, true))
+}
+
+/// Position of `key` in `[0, 1)`, from the 8 bytes following the `prefix` shared by all the keys.
+fn key_position(key: &Key, prefix: usize) -> f64 {
+    key.as_slice()
+        .iter()
+        .skip(prefix)
+        .take(8)
+        .rev()
+        .fold(0.0, |position, byte| (position + *byte as f64) / 256.0)
+}
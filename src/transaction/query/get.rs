@@ -1,14 +1,38 @@
 use crate::db_type::{
-    check_key_type, check_key_type_from_key_definition, KeyOptions, Result, ToInput, ToKey,
-    ToKeyDefinition,
+    check_key_type, check_key_type_from_key_definition, Error, Key, KeyEntry, KeyOptions, Result,
+    ToInput, ToKey, ToKeyDefinition,
 };
 use crate::transaction::internal::private_readable_transaction::PrivateReadableTransaction;
 use crate::transaction::internal::r_transaction::InternalRTransaction;
 use crate::transaction::internal::rw_transaction::InternalRwTransaction;
+use crate::unique_secondary_cache::UniqueSecondaryCaches;
+use serde::de::DeserializeOwned;
+
+/// Result of [`RGet::primary_checked`].
+#[derive(Debug)]
+pub enum GetResult<T> {
+    /// No value has this primary key.
+    Absent,
+    /// The decoded value.
+    Present(T),
+    /// A value is stored under the primary key but can't be decoded.
+    Corrupt(Key, Box<Error>),
+}
+
+impl<T> GetResult<T> {
+    fn new(primary_key: Key, value: Option<Result<T>>) -> Self {
+        match value {
+            None => GetResult::Absent,
+            Some(Ok(value)) => GetResult::Present(value),
+            Some(Err(error)) => GetResult::Corrupt(primary_key, Box::new(error)),
+        }
+    }
+}
 
 /// Get a value from the database.
 pub struct RGet<'db, 'txn> {
     pub(crate) internal: &'txn InternalRTransaction<'db>,
+    pub(crate) unique_secondary_caches: &'db UniqueSecondaryCaches,
 }
 
 impl RGet<'_, '_> {
@@ -52,6 +76,170 @@ impl RGet<'_, '_> {
         }
     }
 
+    /// Get a value by primary key from the table registered as `alias` of the table of `T` with
+    /// [`Models::define_alias`](crate::Models::define_alias), e.g. the values not moved yet
+    /// during a table rename.
+    ///
+    /// Returns `None` if the alias table does not exist (anymore) in the database, and
+    /// [`TableDefinitionNotFound`](crate::db_type::Error::TableDefinitionNotFound) if `alias` is
+    /// not registered.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     key: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     models.define_alias::<Data>("1_1_id")?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     // Open a read transaction
+    ///     let r = db.r_transaction()?;
+    ///
+    ///     // Look for the value in the table `1_1_key`, then in its former name
+    ///     let value: Option<Data> = match r.get().primary(1u64)? {
+    ///         Some(value) => Some(value),
+    ///         None => r.get().primary_alias("1_1_id", 1u64)?,
+    ///     };
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn primary_alias<T: ToInput>(&self, alias: &str, key: impl ToKey) -> Result<Option<T>> {
+        let model = T::native_db_model();
+        check_key_type(&model, &key)?;
+        let Some(table) = self.internal.get_alias_table(&model, alias)? else {
+            return Ok(None);
+        };
+        let item = table.get(key.to_key())?;
+        item.map(|item| self.internal.read_value(item.value())?.inner())
+            .transpose()
+    }
+
+    /// Get a value from the database by primary key, like [`primary`](Self::primary) but a value
+    /// that fails to decode is returned as [`GetResult::Corrupt`] instead of an error, e.g. to
+    /// skip and log the corrupt values during a recovery.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use native_db::transaction::query::GetResult;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let r = db.r_transaction()?;
+    ///     match r.get().primary_checked::<Data>(1u64)? {
+    ///         GetResult::Present(_data) => {}
+    ///         GetResult::Absent => {}
+    ///         GetResult::Corrupt(key, error) => eprintln!("corrupt value {:?}: {}", key, error),
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn primary_checked<T: ToInput>(&self, key: impl ToKey) -> Result<GetResult<T>> {
+        let model = T::native_db_model();
+        check_key_type(&model, &key)?;
+        let primary_key = key.to_key();
+        let result = self.internal.get_by_primary_key(model, key)?;
+        Ok(GetResult::new(
+            primary_key,
+            result.map(|value| value.inner()),
+        ))
+    }
+
+    /// Get a value of the model `T` by primary key, decoded as the view `V`.
+    ///
+    /// The values are not field-addressable, but they can be decoded into a lighter struct
+    /// made of the leading fields of the model, in the same order: the following fields are
+    /// skipped instead of being decoded. Useful to read a few fields of a wide model in a hot
+    /// loop. The view must have the same [`native_model`](https://crates.io/crates/native_model)
+    /// id and version as the model, else [`IncompatibleView`](crate::db_type::Error::IncompatibleView)
+    /// is returned. It does not need `#[native_db]` and must not be defined in the [`Models`](crate::Models).
+    ///
+    /// The models stored as rkyv archives have no views,
+    /// [`ViewNotSupported`](crate::db_type::Error::ViewNotSupported) is returned: read them with
+    /// `RTransaction::get_archived` instead (feature `rkyv`).
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     name: String,
+    ///     payload: Vec<u8>,
+    /// }
+    ///
+    /// // The leading fields of `Data`
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// struct DataName {
+    ///     id: u64,
+    ///     name: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Data { id: 1, name: "test".to_string(), payload: vec![0; 1024] })?;
+    ///     rw.commit()?;
+    ///
+    ///     let r = db.r_transaction()?;
+    ///     let view: Option<DataName> = r.get().primary_as::<Data, _>(1u64)?;
+    ///     assert_eq!(view.unwrap().name, "test");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn primary_as<T: ToInput, V>(&self, key: impl ToKey) -> Result<Option<V>>
+    where
+        V: DeserializeOwned + native_model::Model,
+    {
+        let model = T::native_db_model();
+        check_key_type(&model, &key)?;
+        check_view::<T, V>()?;
+        let compression = model.compression;
+        let result = self.internal.get_by_primary_key(model, key)?;
+        if let Some(value) = result {
+            Ok(Some(
+                value.inner_view(compression, self.internal.max_value_size())?,
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Get a value from the database by secondary key.
     ///
     /// /!\ The secondary key **must** be [`unique`](crate::models::Models#unique) else this method will return an error [`SecondaryKeyConstraintMismatch`](crate::db_type::Error::SecondaryKeyConstraintMismatch).
@@ -94,7 +282,26 @@ impl RGet<'_, '_> {
         key: impl ToKey,
     ) -> Result<Option<T>> {
         let model = T::native_db_model();
-        check_key_type_from_key_definition(&key_def.key_definition(), &key)?;
+        let key_def = key_def.key_definition();
+        check_key_type_from_key_definition(&key_def, &key)?;
+        let key = key.to_key();
+        if let Some(primary_key) = self.unique_secondary_caches.get(&key_def, &key) {
+            if let Some(value) = self
+                .internal
+                .get_by_primary_key(model.clone(), primary_key)?
+            {
+                let value: T = value.inner()?;
+                // The cache may be ahead of the snapshot of this transaction
+                let found = value
+                    .native_db_secondary_keys()
+                    .remove(&key_def)
+                    .map(KeyEntry::into_keys)
+                    .unwrap_or_default();
+                if found.contains(&key) {
+                    return Ok(Some(value));
+                }
+            }
+        }
         let result = self.internal.get_by_secondary_key(model, key_def, key)?;
         if let Some(value) = result {
             Ok(Some(value.inner()?))
@@ -102,6 +309,53 @@ impl RGet<'_, '_> {
             Ok(None)
         }
     }
+
+    /// Get the primary key of a value by secondary key, without reading the value. Cheaper
+    /// than [`secondary`](Self::secondary) when only the primary key is needed, e.g. to look
+    /// up another model with it.
+    ///
+    /// Like [`secondary`](Self::secondary), the secondary key **must** be [`unique`](crate::models::Models#unique).
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key(unique)]
+    ///     name: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Data { id: 1, name: "test".to_string() })?;
+    ///     rw.commit()?;
+    ///
+    ///     let r = db.r_transaction()?;
+    ///     let primary_key = r.get().secondary_primary_key::<Data>(DataKey::name, "test")?;
+    ///     assert_eq!(primary_key, Some(1u64.to_key()));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn secondary_primary_key<T: ToInput>(
+        &self,
+        key_def: impl ToKeyDefinition<KeyOptions>,
+        key: impl ToKey,
+    ) -> Result<Option<Key>> {
+        check_key_type_from_key_definition(&key_def.key_definition(), &key)?;
+        self.internal
+            .get_primary_key_by_secondary_key(T::native_db_model(), key_def, key)
+    }
 }
 
 pub struct RwGet<'db, 'txn> {
@@ -123,6 +377,42 @@ impl RwGet<'_, '_> {
         }
     }
 
+    /// Get a value from the database by primary key, a value that fails to decode is returned
+    /// as [`GetResult::Corrupt`].
+    ///
+    /// See [`primary_checked`](crate::transaction::query::RGet::primary_checked).
+    pub fn primary_checked<T: ToInput>(&self, key: impl ToKey) -> Result<GetResult<T>> {
+        let model = T::native_db_model();
+        check_key_type(&model, &key)?;
+        let primary_key = key.to_key();
+        let result = self.internal.get_by_primary_key(model, key)?;
+        Ok(GetResult::new(
+            primary_key,
+            result.map(|value| value.inner()),
+        ))
+    }
+
+    /// Get a value of the model `T` by primary key, decoded as the view `V`.
+    ///
+    /// See [`primary_as`](crate::transaction::query::RGet::primary_as).
+    pub fn primary_as<T: ToInput, V>(&self, key: impl ToKey) -> Result<Option<V>>
+    where
+        V: DeserializeOwned + native_model::Model,
+    {
+        let model = T::native_db_model();
+        check_key_type(&model, &key)?;
+        check_view::<T, V>()?;
+        let compression = model.compression;
+        let result = self.internal.get_by_primary_key(model, key)?;
+        if let Some(value) = result {
+            Ok(Some(
+                value.inner_view(compression, self.internal.max_value_size())?,
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Get a value from the database by secondary key.
     ///
     /// See [`secondary`](crate::transaction::query::RGet::secondary).
@@ -140,4 +430,39 @@ impl RwGet<'_, '_> {
             Ok(None)
         }
     }
+
+    /// Get the primary key of a value by secondary key, without reading the value.
+    ///
+    /// See [`secondary_primary_key`](crate::transaction::query::RGet::secondary_primary_key).
+    pub fn secondary_primary_key<T: ToInput>(
+        &self,
+        key_def: impl ToKeyDefinition<KeyOptions>,
+        key: impl ToKey,
+    ) -> Result<Option<Key>> {
+        check_key_type_from_key_definition(&key_def.key_definition(), &key)?;
+        self.internal
+            .get_primary_key_by_secondary_key(T::native_db_model(), key_def, key)
+    }
+}
+
+/// A view must be decoded like its model: same native_model id and version.
+fn check_view<T: ToInput, V: native_model::Model>() -> Result<()> {
+    let model = T::native_db_model();
+    if model.rkyv {
+        return Err(Error::ViewNotSupported {
+            table: model.primary_key.unique_table_name,
+        });
+    }
+    if V::native_model_id() == T::native_model_id()
+        && V::native_model_version() == T::native_model_version()
+    {
+        Ok(())
+    } else {
+        Err(Error::IncompatibleView {
+            view: std::any::type_name::<V>().to_string(),
+            table: T::native_db_model().primary_key.unique_table_name,
+            id: T::native_model_id(),
+            version: T::native_model_version(),
+        })
+    }
 }
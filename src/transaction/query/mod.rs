@@ -1,9 +1,11 @@
 mod drain;
+mod estimate;
 mod get;
 mod len;
 mod scan;
 
 pub use drain::*;
+pub(crate) use estimate::*;
 pub use get::*;
 pub use len::*;
 pub use scan::*;
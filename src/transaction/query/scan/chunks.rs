@@ -0,0 +1,36 @@
+use crate::db_type::Result;
+
+/// Iterator returned by `chunks`, e.g. [`PrimaryScanIterator::chunks`](crate::transaction::query::PrimaryScanIterator::chunks).
+///
+/// Yields vectors of `size` decoded values, the last one may be shorter. A decode error ends
+/// the current chunk: the error is yielded instead of the chunk and the next chunk starts
+/// after the value that failed.
+pub struct Chunks<I> {
+    pub(crate) iter: I,
+    pub(crate) size: usize,
+}
+
+impl<T, I> Iterator for Chunks<I>
+where
+    I: Iterator<Item = Result<T>>,
+{
+    type Item = Result<Vec<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk = Vec::with_capacity(self.size);
+        for item in self.iter.by_ref() {
+            match item {
+                Ok(item) => chunk.push(item),
+                Err(error) => return Some(Err(error)),
+            }
+            if chunk.len() == self.size {
+                break;
+            }
+        }
+        if chunk.is_empty() {
+            None
+        } else {
+            Some(Ok(chunk))
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use crate::db_type::Result;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+/// Collects the decoded values into a `HashMap` keyed by `key`, see
+/// [`PrimaryScanIterator::collect_map`](crate::transaction::query::PrimaryScanIterator::collect_map).
+pub(crate) fn collect_map<T, K, I, F>(iter: I, mut key: F) -> Result<HashMap<K, T>>
+where
+    I: Iterator<Item = Result<T>>,
+    K: Eq + Hash,
+    F: FnMut(&T) -> K,
+{
+    iter.map(|item| item.map(|item| (key(&item), item)))
+        .collect()
+}
+
+/// Collects the decoded values into a `BTreeMap` keyed by `key`, see
+/// [`PrimaryScanIterator::collect_btreemap`](crate::transaction::query::PrimaryScanIterator::collect_btreemap).
+pub(crate) fn collect_btreemap<T, K, I, F>(iter: I, mut key: F) -> Result<BTreeMap<K, T>>
+where
+    I: Iterator<Item = Result<T>>,
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    iter.map(|item| item.map(|item| (key(&item), item)))
+        .collect()
+}
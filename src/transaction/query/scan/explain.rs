@@ -0,0 +1,28 @@
+use crate::db_type::Key;
+use std::ops::Bound;
+
+/// How a scan reads its table, returned by `explain`, e.g.
+/// [`SecondaryScanIterator::explain`](crate::transaction::query::SecondaryScanIterator::explain).
+///
+/// Purely diagnostic, e.g. to check that a slow query uses the expected secondary key and
+/// doesn't read the whole table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanPlan {
+    /// Name of the table read, e.g. `1_1_name` for the secondary key `name` of the model `id=1, version=1`.
+    pub table: String,
+    /// Lower bound of the keys read, as bytes.
+    pub start: Bound<Key>,
+    /// Upper bound of the keys read, as bytes.
+    pub end: Bound<Key>,
+    /// Prefix of the keys read, set by `start_with`. The scan stops at the first key without it.
+    pub prefix: Option<Key>,
+    /// Number of primary keys selected by the scan.
+    pub primary_keys: usize,
+}
+
+impl ScanPlan {
+    /// Returns `true` if the scan reads the whole table.
+    pub fn is_full_scan(&self) -> bool {
+        self.start == Bound::Unbounded && self.end == Bound::Unbounded && self.prefix.is_none()
+    }
+}
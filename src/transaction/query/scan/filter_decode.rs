@@ -0,0 +1,44 @@
+use crate::db_type::Result;
+
+/// Iterator returned by `filter_decode`, e.g. [`PrimaryScanIterator::filter_decode`](crate::transaction::query::PrimaryScanIterator::filter_decode).
+///
+/// Yields the decoded values matching the predicate. Decode errors are always yielded, the
+/// predicate is only applied to the values decoded successfully.
+pub struct FilterDecode<I, F> {
+    pub(crate) iter: I,
+    pub(crate) predicate: F,
+}
+
+impl<T, I, F> Iterator for FilterDecode<I, F>
+where
+    I: Iterator<Item = Result<T>>,
+    F: FnMut(&T) -> bool,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for item in self.iter.by_ref() {
+            match item {
+                Ok(item) if !(self.predicate)(&item) => continue,
+                item => return Some(item),
+            }
+        }
+        None
+    }
+}
+
+impl<T, I, F> DoubleEndedIterator for FilterDecode<I, F>
+where
+    I: DoubleEndedIterator<Item = Result<T>>,
+    F: FnMut(&T) -> bool,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some(item) = self.iter.next_back() {
+            match item {
+                Ok(item) if !(self.predicate)(&item) => continue,
+                item => return Some(item),
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,244 @@
+use crate::db_type::{
+    check_range_key_range_bounds_from_key_definition, check_value_size, Error, Key, KeyDefinition,
+    KeyOptions, KeyRange, Result, ToInput, ToKey, ToKeyDefinition,
+};
+use crate::transaction::internal::private_readable_transaction::PrivateReadableTransaction;
+use crate::transaction::internal::r_transaction::InternalRTransaction;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::ops::RangeBounds;
+
+/// Merge the secondary scans of several models, see [`RTransaction::merge_scan`](crate::transaction::RTransaction::merge_scan).
+pub struct MergeScan<'db, 'txn, E> {
+    pub(crate) internal: &'txn InternalRTransaction<'db>,
+    pub(crate) sources: Vec<MergeScanSource<'txn, E>>,
+}
+
+type Decode<'txn, E> = Box<dyn Fn(&[u8]) -> Result<E> + 'txn>;
+
+pub(crate) struct MergeScanSource<'txn, E> {
+    primary_table: redb::ReadOnlyTable<Key, &'static [u8]>,
+    secondary_table: redb::ReadOnlyMultimapTable<Key, Key>,
+    key_def: KeyDefinition<KeyOptions>,
+    decode: Decode<'txn, E>,
+}
+
+impl<'db, 'txn, E> MergeScan<'db, 'txn, E> {
+    /// Add the secondary key `key_def` of the model `T` to the merge, each value is
+    /// converted with `map` (e.g. a variant of the enum `E`).
+    ///
+    /// All the secondary keys of the merge must have the same type.
+    pub fn secondary<T: ToInput>(
+        mut self,
+        key_def: impl ToKeyDefinition<KeyOptions>,
+        map: impl Fn(T) -> E + 'txn,
+    ) -> Result<Self> {
+        let model = T::native_db_model();
+        let key_def = key_def.key_definition();
+        if let Some(first) = self.sources.first() {
+            if first.key_def.rust_types != key_def.rust_types {
+                return Err(Error::MissmatchedKeyType {
+                    key_name: key_def.unique_table_name,
+                    expected_types: first.key_def.rust_types.clone(),
+                    got_types: key_def.rust_types,
+                    operation: "merge_scan".to_string(),
+                });
+            }
+        }
+        let primary_table = self.internal.get_primary_table(&model)?;
+        let secondary_table = self.internal.get_secondary_table(&model, &key_def)?;
+        let max_value_size = self.internal.max_value_size();
+        self.sources.push(MergeScanSource {
+            primary_table,
+            secondary_table,
+            key_def,
+            decode: Box::new(move |slice| {
+                check_value_size(slice, max_value_size)?;
+                T::native_db_bincode_decode_from_slice_limited(slice, max_value_size).map(&map)
+            }),
+        });
+        Ok(self)
+    }
+
+    /// Iterate over the values of all the models, ordered by secondary key.
+    ///
+    /// Values with the same secondary key are returned in the order the models were added.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    /// use itertools::Itertools;
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Login {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key]
+    ///     timestamp: u64,
+    /// }
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=2, version=1)]
+    /// #[native_db]
+    /// struct Logout {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key]
+    ///     timestamp: u64,
+    /// }
+    ///
+    /// enum Timeline {
+    ///     Login(Login),
+    ///     Logout(Logout),
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Login>()?;
+    ///     models.define::<Logout>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     // Open a read transaction
+    ///     let r = db.r_transaction()?;
+    ///
+    ///     // Get the logins and logouts ordered by timestamp
+    ///     let _values: Vec<Timeline> = r
+    ///         .merge_scan()
+    ///         .secondary(LoginKey::timestamp, Timeline::Login)?
+    ///         .secondary(LogoutKey::timestamp, Timeline::Logout)?
+    ///         .all()?
+    ///         .try_collect()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn all(&self) -> Result<MergeScanIterator<'_, 'txn, E>> {
+        let mut ranges = Vec::with_capacity(self.sources.len());
+        for source in &self.sources {
+            ranges.push(source.secondary_table.range::<Key>(..)?);
+        }
+        Ok(MergeScanIterator::new(&self.sources, ranges))
+    }
+
+    /// Iterate over the values of all the models with a secondary key in a range, ordered by
+    /// secondary key.
+    ///
+    /// Values with the same secondary key are returned in the order the models were added.
+    pub fn range<R: RangeBounds<impl ToKey>>(
+        &self,
+        range: R,
+    ) -> Result<MergeScanIterator<'_, 'txn, E>> {
+        for source in &self.sources {
+            check_range_key_range_bounds_from_key_definition(&source.key_def, &range)?;
+        }
+        let key_range = KeyRange::new(range);
+        let mut ranges = Vec::with_capacity(self.sources.len());
+        for source in &self.sources {
+            ranges.push(source.secondary_table.range::<Key>(key_range.clone())?);
+        }
+        Ok(MergeScanIterator::new(&self.sources, ranges))
+    }
+}
+
+/// Iterator over the values of a [`MergeScan`], ordered by secondary key.
+///
+/// The secondary keys of each model are read one at a time: only the next entry of each model
+/// is held in memory.
+pub struct MergeScanIterator<'a, 'txn, E> {
+    sources: &'a [MergeScanSource<'txn, E>],
+    cursors: Vec<MergeScanCursor>,
+    // The next secondary key of each source, ordered by bytes like redb does.
+    heap: BinaryHeap<Reverse<(Vec<u8>, usize)>>,
+    primary_keys: Vec<Option<Key>>,
+    // Returned before the next value, e.g. a source failed to read its next entry.
+    error: Option<Error>,
+}
+
+// The entries of a source in the order of the secondary keys, then of the primary keys.
+struct MergeScanCursor {
+    range: redb::MultimapRange<'static, Key, Key>,
+    current: Option<(Key, redb::MultimapValue<'static, Key>)>,
+}
+
+impl Iterator for MergeScanCursor {
+    type Item = Result<(Key, Key)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((secondary_key, primary_keys)) = &mut self.current {
+                match primary_keys.next() {
+                    Some(Ok(primary_key)) => {
+                        return Some(Ok((secondary_key.clone(), primary_key.value())))
+                    }
+                    Some(Err(error)) => return Some(Err(error.into())),
+                    None => self.current = None,
+                }
+            }
+            match self.range.next()? {
+                Ok((secondary_key, primary_keys)) => {
+                    self.current = Some((secondary_key.value(), primary_keys))
+                }
+                Err(error) => return Some(Err(error.into())),
+            }
+        }
+    }
+}
+
+impl<'a, 'txn, E> MergeScanIterator<'a, 'txn, E> {
+    fn new(
+        sources: &'a [MergeScanSource<'txn, E>],
+        ranges: Vec<redb::MultimapRange<'static, Key, Key>>,
+    ) -> Self {
+        let mut iter = Self {
+            sources,
+            cursors: ranges
+                .into_iter()
+                .map(|range| MergeScanCursor {
+                    range,
+                    current: None,
+                })
+                .collect(),
+            heap: BinaryHeap::with_capacity(sources.len()),
+            primary_keys: vec![None; sources.len()],
+            error: None,
+        };
+        for index in 0..sources.len() {
+            iter.advance(index);
+        }
+        iter
+    }
+
+    fn advance(&mut self, index: usize) {
+        match self.cursors[index].next() {
+            Some(Ok((secondary_key, primary_key))) => {
+                self.heap
+                    .push(Reverse((secondary_key.as_slice().to_vec(), index)));
+                self.primary_keys[index] = Some(primary_key);
+            }
+            Some(Err(error)) => self.error = Some(error),
+            None => {}
+        }
+    }
+}
+
+impl<E> Iterator for MergeScanIterator<'_, '_, E> {
+    type Item = Result<E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
+        let Reverse((_, index)) = self.heap.pop()?;
+        let primary_key = self.primary_keys[index].take()?;
+        self.advance(index);
+        let source = &self.sources[index];
+        match source.primary_table.get(primary_key) {
+            Ok(Some(value)) => Some((source.decode)(value.value())),
+            Ok(None) => Some(Err(Error::PrimaryKeyNotFound)),
+            Err(error) => Some(Err(error.into())),
+        }
+    }
+}
@@ -1,7 +1,18 @@
+mod chunks;
+mod collect_map;
+#[cfg(feature = "diagnostics")]
+mod explain;
+mod filter_decode;
+mod merge_scan;
 mod primary_scan;
 mod secondary_scan;
 
 use crate::db_type::{Key, KeyOptions, Result, ToInput, ToKeyDefinition};
+pub use chunks::*;
+#[cfg(feature = "diagnostics")]
+pub use explain::*;
+pub use filter_decode::*;
+pub use merge_scan::*;
 pub use primary_scan::*;
 pub use secondary_scan::*;
 
@@ -25,7 +36,7 @@ impl RScan<'_, '_> {
     ) -> Result<PrimaryScan<redb::ReadOnlyTable<Key, &'static [u8]>, T>> {
         let model = T::native_db_model();
         let table = self.internal.get_primary_table(&model)?;
-        let out = PrimaryScan::new(table);
+        let out = PrimaryScan::new(table, self.internal.max_value_size());
         Ok(out)
     }
 
@@ -49,11 +60,24 @@ impl RScan<'_, '_> {
         let primary_table = self.internal.get_primary_table(&model)?;
         let secondary_key = key_def.key_definition();
         let secondary_table = self.internal.get_secondary_table(&model, &secondary_key)?;
-        let out = SecondaryScan::new(primary_table, secondary_table, key_def);
+        let out = SecondaryScan::new(
+            primary_table,
+            secondary_table,
+            key_def,
+            self.internal.max_value_size(),
+        );
         Ok(out)
     }
 }
 
+/// A secondary scan whose tables are open, see
+/// [`RTransaction::prepare_secondary`](crate::transaction::RTransaction::prepare_secondary).
+pub type PreparedScan<T> = SecondaryScan<
+    redb::ReadOnlyTable<Key, &'static [u8]>,
+    redb::ReadOnlyMultimapTable<Key, Key>,
+    T,
+>;
+
 pub struct RwScan<'db, 'txn> {
     pub(crate) internal: &'txn InternalRwTransaction<'db>,
 }
@@ -72,7 +96,7 @@ where
     ) -> Result<PrimaryScan<redb::Table<'db, Key, &'static [u8]>, T>> {
         let model = T::native_db_model();
         let table = self.internal.get_primary_table(&model)?;
-        let out = PrimaryScan::new(table);
+        let out = PrimaryScan::new(table, self.internal.max_value_size());
         Ok(out)
     }
 
@@ -92,7 +116,12 @@ where
         let primary_table = self.internal.get_primary_table(&model)?;
         let secondary_key = key_def.key_definition();
         let secondary_table = self.internal.get_secondary_table(&model, &secondary_key)?;
-        let out = SecondaryScan::new(primary_table, secondary_table, key_def);
+        let out = SecondaryScan::new(
+            primary_table,
+            secondary_table,
+            key_def,
+            self.internal.max_value_size(),
+        );
         Ok(out)
     }
 }
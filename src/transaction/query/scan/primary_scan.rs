@@ -1,5 +1,13 @@
-use crate::db_type::{check_key_type, check_range_key_range_bounds, ToKey};
-use crate::db_type::{unwrap_item, Key, KeyRange, Result, ToInput};
+use super::collect_map::{collect_btreemap, collect_map};
+use crate::db_type::{
+    check_key_component_type_from_key_definition, check_key_type, check_range_key_range_bounds,
+    ToKey,
+};
+use crate::db_type::{prefix_successor, unwrap_item, Key, KeyRange, Result, ToInput};
+use crate::transaction::query::{Chunks, FilterDecode};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::iter::Take;
 use std::marker::PhantomData;
 use std::ops::RangeBounds;
 
@@ -9,6 +17,7 @@ where
     PrimaryTable: redb::ReadableTable<Key, &'static [u8]>,
 {
     pub(crate) primary_table: PrimaryTable,
+    pub(crate) max_value_size: Option<usize>,
     pub(crate) _marker: PhantomData<T>,
 }
 
@@ -16,9 +25,10 @@ impl<PrimaryTable, T: ToInput> PrimaryScan<PrimaryTable, T>
 where
     PrimaryTable: redb::ReadableTable<Key, &'static [u8]>,
 {
-    pub(crate) fn new(table: PrimaryTable) -> Self {
+    pub(crate) fn new(table: PrimaryTable, max_value_size: Option<usize>) -> Self {
         Self {
             primary_table: table,
+            max_value_size,
             _marker: PhantomData,
         }
     }
@@ -57,6 +67,7 @@ where
         let range = self.primary_table.range::<Key>(..)?;
         Ok(PrimaryScanIterator {
             range,
+            max_value_size: self.max_value_size,
             _marker: PhantomData,
         })
     }
@@ -100,6 +111,7 @@ where
             .range::<Key>(database_inner_key_value_range)?;
         Ok(PrimaryScanIterator {
             range,
+            max_value_size: self.max_value_size,
             _marker: PhantomData,
         })
     }
@@ -138,27 +150,319 @@ where
         let model = T::native_db_model();
         check_key_type(&model, &start_with)?;
         let start_with = start_with.to_key();
-        let range = self.primary_table.range::<Key>(start_with.clone()..)?;
+        let range = self
+            .primary_table
+            .range::<Key>(start_with_key_range(&start_with))?;
 
         Ok(PrimaryScanIteratorStartWith {
             range,
             start_with,
+            max_value_size: self.max_value_size,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Iterate over all values with a compound primary key starting with the given components,
+    /// e.g. all the users of a tenant with a primary key `(tenant_id, user_id)`.
+    ///
+    /// The component is the first component of the tuple, or a tuple of the first components,
+    /// e.g. `tenant_id` or `(tenant_id, group_id)` for a primary key
+    /// `(tenant_id, group_id, user_id)`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    /// use itertools::Itertools;
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db(primary_key(composite_pk -> (u32, u32)))]
+    /// struct User {
+    ///     tenant_id: u32,
+    ///     user_id: u32,
+    /// }
+    ///
+    /// impl User {
+    ///     fn composite_pk(&self) -> (u32, u32) {
+    ///         (self.tenant_id, self.user_id)
+    ///     }
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<User>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///     
+    ///     // Open a read transaction
+    ///     let r = db.r_transaction()?;
+    ///     
+    ///     // Get all the users of the tenant 1
+    ///     let _values: Vec<User> = r.scan().primary()?.start_with_component(1u32)?.try_collect()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn start_with_component(
+        &self,
+        component: impl ToKey,
+    ) -> Result<PrimaryScanIteratorStartWith<T>> {
+        let model = T::native_db_model();
+        check_key_component_type_from_key_definition(&model.primary_key, &component)?;
+        let start_with = component.to_component_key();
+        let range = self
+            .primary_table
+            .range::<Key>(start_with_key_range(&start_with))?;
+
+        Ok(PrimaryScanIteratorStartWith {
+            range,
+            start_with,
+            max_value_size: self.max_value_size,
             _marker: PhantomData,
         })
     }
 }
 
+/// Range of the keys starting with `start_with`: from `start_with` to the next prefix excluded,
+/// so the range can be iterated from both ends.
+fn start_with_key_range(start_with: &Key) -> KeyRange {
+    match prefix_successor(start_with) {
+        Some(end) => KeyRange::Range(start_with.clone()..end),
+        // Only 0xFF bytes: all the following keys start with the prefix
+        None => KeyRange::RangeFrom(start_with.clone()..),
+    }
+}
+
 pub struct PrimaryScanIterator<'a, T: ToInput> {
     pub(crate) range: redb::Range<'a, Key, &'static [u8]>,
+    pub(crate) max_value_size: Option<usize>,
     pub(crate) _marker: PhantomData<T>,
 }
 
+impl<T: ToInput> PrimaryScanIterator<'_, T> {
+    /// Skip `offset` values without decoding them, then iterate over the next `limit` values.
+    ///
+    /// Unlike [`Iterator::skip`], the skipped values are never decoded: only the underlying
+    /// iterator is advanced. Useful for offset/limit pagination.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    /// use itertools::Itertools;
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///     
+    ///     // Open a read transaction
+    ///     let r = db.r_transaction()?;
+    ///     
+    ///     // Get the third page of 10 values
+    ///     let _values: Vec<Data> = r.scan().primary()?.all()?.skip_take(20, 10).try_collect()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn skip_take(mut self, offset: usize, limit: usize) -> Take<Self> {
+        for _ in 0..offset {
+            if self.range.next().is_none() {
+                break;
+            }
+        }
+        self.take(limit)
+    }
+
+    /// Iterate over the first `n` values at most: the following values are neither read nor decoded.
+    ///
+    /// Same as [`Iterator::take`], named to make the intent explicit.
+    pub fn limit(self, n: usize) -> Take<Self> {
+        self.take(n)
+    }
+
+    /// Returns the first `n` values at most, only these values are decoded.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     // Open a read transaction
+    ///     let r = db.r_transaction()?;
+    ///
+    ///     // Get the 10 first values
+    ///     let _values: Vec<Data> = r.scan().primary()?.all()?.first_n(10)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn first_n(self, n: usize) -> Result<Vec<T>> {
+        self.take(n).collect()
+    }
+
+    /// Iterate over the values matching `predicate`.
+    ///
+    /// Each value is decoded then passed to `predicate`. Decode errors are not filtered out:
+    /// they are yielded as `Err` so they can be propagated.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    /// use itertools::Itertools;
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     score: u32,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     // Open a read transaction
+    ///     let r = db.r_transaction()?;
+    ///
+    ///     // Get the values with a score above 10
+    ///     let _values: Vec<Data> = r
+    ///         .scan()
+    ///         .primary()?
+    ///         .all()?
+    ///         .filter_decode(|data: &Data| data.score > 10)
+    ///         .try_collect()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn filter_decode<F: FnMut(&T) -> bool>(self, predicate: F) -> FilterDecode<Self, F> {
+        FilterDecode {
+            iter: self,
+            predicate,
+        }
+    }
+
+    /// Iterate over the values in vectors of `size` values, the last one may be shorter.
+    ///
+    /// A decode error ends the current chunk: it is yielded as `Err` instead of the chunk.
+    ///
+    /// # Panics
+    /// Panics if `size` is 0.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     // Open a read transaction
+    ///     let r = db.r_transaction()?;
+    ///
+    ///     // Process the values 100 at a time
+    ///     for chunk in r.scan().primary::<Data>()?.all()?.chunks(100) {
+    ///         let _values: Vec<Data> = chunk?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn chunks(self, size: usize) -> Chunks<Self> {
+        assert!(size != 0, "chunk size must be non-zero");
+        Chunks { iter: self, size }
+    }
+
+    /// Returns the values in a `HashMap` keyed by `key`, e.g. to load a lookup table.
+    ///
+    /// The map is built in one pass, the first decode error is returned. When `key` returns
+    /// the same key for several values, the last value is kept.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    /// use std::collections::HashMap;
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     name: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     // Open a read transaction
+    ///     let r = db.r_transaction()?;
+    ///
+    ///     // Get the values by name
+    ///     let _values: HashMap<String, Data> = r
+    ///         .scan()
+    ///         .primary()?
+    ///         .all()?
+    ///         .collect_map(|data: &Data| data.name.clone())?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn collect_map<K: Eq + Hash, F: FnMut(&T) -> K>(self, key: F) -> Result<HashMap<K, T>> {
+        collect_map(self, key)
+    }
+
+    /// Returns the values in a `BTreeMap` keyed by `key`, like [`PrimaryScanIterator::collect_map`].
+    pub fn collect_btreemap<K: Ord, F: FnMut(&T) -> K>(self, key: F) -> Result<BTreeMap<K, T>> {
+        collect_btreemap(self, key)
+    }
+}
+
 impl<T: ToInput> Iterator for PrimaryScanIterator<'_, T> {
     type Item = Result<T>;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self.range.next() {
-            Some(Ok((_, v))) => unwrap_item(Some(v)),
+            Some(Ok((_, v))) => unwrap_item(Some(v), self.max_value_size),
             _ => None,
         }
     }
@@ -166,7 +470,7 @@ impl<T: ToInput> Iterator for PrimaryScanIterator<'_, T> {
 impl<T: ToInput> DoubleEndedIterator for PrimaryScanIterator<'_, T> {
     fn next_back(&mut self) -> Option<Self::Item> {
         match self.range.next_back() {
-            Some(Ok((_, v))) => unwrap_item(Some(v)),
+            Some(Ok((_, v))) => unwrap_item(Some(v), self.max_value_size),
             _ => None,
         }
     }
@@ -175,9 +479,46 @@ impl<T: ToInput> DoubleEndedIterator for PrimaryScanIterator<'_, T> {
 pub struct PrimaryScanIteratorStartWith<'a, T: ToInput> {
     pub(crate) range: redb::Range<'a, Key, &'static [u8]>,
     pub(crate) start_with: Key,
+    pub(crate) max_value_size: Option<usize>,
     pub(crate) _marker: PhantomData<T>,
 }
 
+impl<T: ToInput> PrimaryScanIteratorStartWith<'_, T> {
+    /// Iterate over the first `n` values at most, like [`PrimaryScanIterator::limit`].
+    pub fn limit(self, n: usize) -> Take<Self> {
+        self.take(n)
+    }
+
+    /// Returns the first `n` values at most, like [`PrimaryScanIterator::first_n`].
+    pub fn first_n(self, n: usize) -> Result<Vec<T>> {
+        self.take(n).collect()
+    }
+
+    /// Iterate over the values matching `predicate`, like [`PrimaryScanIterator::filter_decode`].
+    pub fn filter_decode<F: FnMut(&T) -> bool>(self, predicate: F) -> FilterDecode<Self, F> {
+        FilterDecode {
+            iter: self,
+            predicate,
+        }
+    }
+
+    /// Iterate over the values in vectors of `size` values, like [`PrimaryScanIterator::chunks`].
+    pub fn chunks(self, size: usize) -> Chunks<Self> {
+        assert!(size != 0, "chunk size must be non-zero");
+        Chunks { iter: self, size }
+    }
+
+    /// Returns the values in a `HashMap` keyed by `key`, like [`PrimaryScanIterator::collect_map`].
+    pub fn collect_map<K: Eq + Hash, F: FnMut(&T) -> K>(self, key: F) -> Result<HashMap<K, T>> {
+        collect_map(self, key)
+    }
+
+    /// Returns the values in a `BTreeMap` keyed by `key`, like [`PrimaryScanIterator::collect_btreemap`].
+    pub fn collect_btreemap<K: Ord, F: FnMut(&T) -> K>(self, key: F) -> Result<BTreeMap<K, T>> {
+        collect_btreemap(self, key)
+    }
+}
+
 impl<T: ToInput> Iterator for PrimaryScanIteratorStartWith<'_, T> {
     type Item = Result<T>;
 
@@ -186,7 +527,23 @@ impl<T: ToInput> Iterator for PrimaryScanIteratorStartWith<'_, T> {
             Some(Ok((k, v))) => {
                 let k = k.value();
                 if k.as_slice().starts_with(self.start_with.as_slice()) {
-                    unwrap_item(Some(v))
+                    unwrap_item(Some(v), self.max_value_size)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<T: ToInput> DoubleEndedIterator for PrimaryScanIteratorStartWith<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self.range.next_back() {
+            Some(Ok((k, v))) => {
+                let k = k.value();
+                if k.as_slice().starts_with(self.start_with.as_slice()) {
+                    unwrap_item(Some(v), self.max_value_size)
                 } else {
                     None
                 }
@@ -1,8 +1,12 @@
+use super::collect_map::{collect_btreemap, collect_map};
 use crate::db_type::{
     check_key_type_from_key_definition, check_range_key_range_bounds_from_key_definition,
     KeyDefinition, KeyOptions, ToKey, ToKeyDefinition,
 };
-use crate::db_type::{unwrap_item, Key, KeyRange, Result, ToInput};
+use crate::db_type::{prefix_successor, unwrap_item, Key, KeyRange, Result, ToInput};
+#[cfg(feature = "diagnostics")]
+use crate::transaction::query::ScanPlan;
+use crate::transaction::query::{Chunks, FilterDecode};
 use redb::{self};
 use std::marker::PhantomData;
 use std::ops::RangeBounds;
@@ -16,6 +20,7 @@ where
     pub(crate) primary_table: PrimaryTable,
     pub(crate) secondary_table: SecondaryTable,
     pub(crate) key_def: KeyDefinition<KeyOptions>,
+    pub(crate) max_value_size: Option<usize>,
     pub(crate) _marker: PhantomData<T>,
 }
 
@@ -28,11 +33,13 @@ where
         primary_table: PrimaryTable,
         secondary_table: SecondaryTable,
         key_def: impl ToKeyDefinition<KeyOptions>,
+        max_value_size: Option<usize>,
     ) -> Self {
         Self {
             primary_table,
             secondary_table,
             key_def: key_def.key_definition(),
+            max_value_size,
             _marker: PhantomData,
         }
     }
@@ -75,6 +82,8 @@ where
     /// }
     /// ```
     pub fn all(&self) -> Result<SecondaryScanIterator<PrimaryTable, T>> {
+        #[cfg(feature = "diagnostics")]
+        let plan = self.plan(&KeyRange::RangeFull, None);
         let mut primary_keys = vec![];
         for keys in self.secondary_table.iter()? {
             let (_, l_primary_keys) = keys?;
@@ -85,8 +94,14 @@ where
         }
 
         Ok(SecondaryScanIterator {
+            #[cfg(feature = "diagnostics")]
+            plan: ScanPlan {
+                primary_keys: primary_keys.len(),
+                ..plan
+            },
             primary_table: &self.primary_table,
             primary_keys: primary_keys.into_iter(),
+            max_value_size: self.max_value_size,
             _marker: PhantomData,
         })
     }
@@ -132,6 +147,8 @@ where
         check_range_key_range_bounds_from_key_definition(&self.key_def, &range)?;
         let mut primary_keys = vec![];
         let database_inner_key_value_range = KeyRange::new(range);
+        #[cfg(feature = "diagnostics")]
+        let plan = self.plan(&database_inner_key_value_range, None);
         for keys in self
             .secondary_table
             .range::<Key>(database_inner_key_value_range)?
@@ -144,8 +161,14 @@ where
         }
 
         Ok(SecondaryScanIterator {
+            #[cfg(feature = "diagnostics")]
+            plan: ScanPlan {
+                primary_keys: primary_keys.len(),
+                ..plan
+            },
             primary_table: &self.primary_table,
             primary_keys: primary_keys.into_iter(),
+            max_value_size: self.max_value_size,
             _marker: PhantomData,
         })
     }
@@ -190,6 +213,11 @@ where
     ) -> Result<SecondaryScanIterator<PrimaryTable, T>> {
         check_key_type_from_key_definition(&self.key_def, &start_with)?;
         let start_with = start_with.to_key();
+        #[cfg(feature = "diagnostics")]
+        let plan = self.plan(
+            &KeyRange::RangeFrom(start_with.clone()..),
+            Some(start_with.clone()),
+        );
         let mut primary_keys = vec![];
         for keys in self.secondary_table.range::<Key>(start_with.clone()..)? {
             let (l_secondary_key, l_primary_keys) = keys?;
@@ -207,13 +235,180 @@ where
         }
 
         Ok(SecondaryScanIterator {
+            #[cfg(feature = "diagnostics")]
+            plan: ScanPlan {
+                primary_keys: primary_keys.len(),
+                ..plan
+            },
             primary_table: &self.primary_table,
             primary_keys: primary_keys.into_iter(),
+            max_value_size: self.max_value_size,
             _marker: PhantomData,
         })
     }
+
+    /// Iterate over all values by secondary key from the first key starting with `low_prefix`
+    /// through the last key starting with `high_prefix`, both included. E.g. `prefix_range("a", "m")`
+    /// gets the names from "a" to "mzzz", useful for an alphabetical bucketing.
+    ///
+    /// Anatomy of a secondary key it is a `enum` with the following structure: `<table_name>Key::<name>`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    /// use itertools::Itertools;
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key]
+    ///     name: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///     
+    ///     // Open a read transaction
+    ///     let r = db.r_transaction()?;
+    ///     
+    ///     // Get only values that have the secondary key name starting with a letter from "a" to "m"
+    ///     let _values: Vec<Data> = r.scan().secondary(DataKey::name)?.prefix_range("a", "m")?.try_collect()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn prefix_range(
+        &self,
+        low_prefix: impl ToKey,
+        high_prefix: impl ToKey,
+    ) -> Result<SecondaryScanIterator<PrimaryTable, T>> {
+        check_key_type_from_key_definition(&self.key_def, &low_prefix)?;
+        check_key_type_from_key_definition(&self.key_def, &high_prefix)?;
+        let low_prefix = low_prefix.to_key();
+        let range = match prefix_successor(&high_prefix.to_key()) {
+            Some(end) => KeyRange::Range(low_prefix..end),
+            // Only 0xff bytes, all the keys after `low_prefix` start with it or are lower.
+            None => KeyRange::RangeFrom(low_prefix..),
+        };
+        #[cfg(feature = "diagnostics")]
+        let plan = self.plan(&range, None);
+        let mut primary_keys = vec![];
+        for keys in self.secondary_table.range::<Key>(range)? {
+            let (_, l_primary_keys) = keys?;
+            for primary_key in l_primary_keys {
+                let primary_key = primary_key?;
+                primary_keys.push(primary_key);
+            }
+        }
+
+        Ok(SecondaryScanIterator {
+            #[cfg(feature = "diagnostics")]
+            plan: ScanPlan {
+                primary_keys: primary_keys.len(),
+                ..plan
+            },
+            primary_table: &self.primary_table,
+            primary_keys: primary_keys.into_iter(),
+            max_value_size: self.max_value_size,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns each distinct secondary key value once, in key order.
+    ///
+    /// Only the secondary table is read, no primary value is decoded. Useful to list the
+    /// distinct values of a field (e.g. for a "group by").
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key]
+    ///     category: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///     
+    ///     // Open a read transaction
+    ///     let r = db.r_transaction()?;
+    ///     
+    ///     // Get the distinct categories
+    ///     let _categories: Vec<Key> = r.scan().secondary::<Data>(DataKey::category)?.distinct_keys()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn distinct_keys(&self) -> Result<Vec<Key>> {
+        let mut keys = vec![];
+        for result in self.secondary_table.iter()? {
+            let (secondary_key, _) = result?;
+            keys.push(secondary_key.value());
+        }
+        Ok(keys)
+    }
+
+    /// Same as [`distinct_keys`](Self::distinct_keys) but only for the secondary keys in a range.
+    pub fn distinct_keys_range<R: RangeBounds<impl ToKey>>(&self, range: R) -> Result<Vec<Key>> {
+        check_range_key_range_bounds_from_key_definition(&self.key_def, &range)?;
+        let mut keys = vec![];
+        let database_inner_key_value_range = KeyRange::new(range);
+        for result in self
+            .secondary_table
+            .range::<Key>(database_inner_key_value_range)?
+        {
+            let (secondary_key, _) = result?;
+            keys.push(secondary_key.value());
+        }
+        Ok(keys)
+    }
+
+    /// Same as [`distinct_keys`](Self::distinct_keys) but only for the secondary keys starting with a prefix.
+    pub fn distinct_keys_start_with(&self, start_with: impl ToKey) -> Result<Vec<Key>> {
+        check_key_type_from_key_definition(&self.key_def, &start_with)?;
+        let start_with = start_with.to_key();
+        let mut keys = vec![];
+        for result in self.secondary_table.range::<Key>(start_with.clone()..)? {
+            let (secondary_key, _) = result?;
+            let secondary_key = secondary_key.value();
+            if !secondary_key.as_slice().starts_with(start_with.as_slice()) {
+                break;
+            }
+            keys.push(secondary_key);
+        }
+        Ok(keys)
+    }
+
+    #[cfg(feature = "diagnostics")]
+    fn plan(&self, range: &KeyRange, prefix: Option<Key>) -> ScanPlan {
+        ScanPlan {
+            table: self.key_def.unique_table_name.clone(),
+            start: range.start_bound().cloned(),
+            end: range.end_bound().cloned(),
+            prefix,
+            primary_keys: 0,
+        }
+    }
 }
 
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::iter::Take;
 use std::vec::IntoIter;
 
 pub struct SecondaryScanIterator<'a, PrimaryTable, T: ToInput>
@@ -222,7 +417,134 @@ where
 {
     pub(crate) primary_table: &'a PrimaryTable,
     pub(crate) primary_keys: IntoIter<redb::AccessGuard<'a, Key>>,
+    pub(crate) max_value_size: Option<usize>,
     pub(crate) _marker: PhantomData<T>,
+    #[cfg(feature = "diagnostics")]
+    pub(crate) plan: ScanPlan,
+}
+
+impl<PrimaryTable, T: ToInput> SecondaryScanIterator<'_, PrimaryTable, T>
+where
+    PrimaryTable: redb::ReadableTable<Key, &'static [u8]>,
+{
+    /// Skip `offset` values without reading nor decoding them, then iterate over the next `limit` values.
+    ///
+    /// Unlike [`Iterator::skip`], only the primary keys of the skipped values are consumed:
+    /// they are neither fetched from the primary table nor decoded.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    /// use itertools::Itertools;
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key]
+    ///     name: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///     
+    ///     // Open a read transaction
+    ///     let r = db.r_transaction()?;
+    ///     
+    ///     // Get the third page of 10 values ordered by name
+    ///     let _values: Vec<Data> = r.scan().secondary(DataKey::name)?.all()?.skip_take(20, 10).try_collect()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn skip_take(mut self, offset: usize, limit: usize) -> Take<Self> {
+        for _ in 0..offset {
+            if self.primary_keys.next().is_none() {
+                break;
+            }
+        }
+        self.take(limit)
+    }
+
+    /// Iterate over the first `n` values at most, like [`PrimaryScanIterator::limit`](crate::transaction::query::PrimaryScanIterator::limit).
+    pub fn limit(self, n: usize) -> Take<Self> {
+        self.take(n)
+    }
+
+    /// Returns the first `n` values at most, like [`PrimaryScanIterator::first_n`](crate::transaction::query::PrimaryScanIterator::first_n).
+    pub fn first_n(self, n: usize) -> Result<Vec<T>> {
+        self.take(n).collect()
+    }
+
+    /// Iterate over the values matching `predicate`, like [`PrimaryScanIterator::filter_decode`](crate::transaction::query::PrimaryScanIterator::filter_decode).
+    ///
+    /// Useful to filter on a field after selecting a range of the secondary key.
+    pub fn filter_decode<F: FnMut(&T) -> bool>(self, predicate: F) -> FilterDecode<Self, F> {
+        FilterDecode {
+            iter: self,
+            predicate,
+        }
+    }
+
+    /// Iterate over the values in vectors of `size` values, like [`PrimaryScanIterator::chunks`](crate::transaction::query::PrimaryScanIterator::chunks).
+    pub fn chunks(self, size: usize) -> Chunks<Self> {
+        assert!(size != 0, "chunk size must be non-zero");
+        Chunks { iter: self, size }
+    }
+
+    /// Returns the values in a `HashMap` keyed by `key`, like [`PrimaryScanIterator::collect_map`](crate::transaction::query::PrimaryScanIterator::collect_map).
+    pub fn collect_map<K: Eq + Hash, F: FnMut(&T) -> K>(self, key: F) -> Result<HashMap<K, T>> {
+        collect_map(self, key)
+    }
+
+    /// Returns the values in a `BTreeMap` keyed by `key`, like [`PrimaryScanIterator::collect_btreemap`](crate::transaction::query::PrimaryScanIterator::collect_btreemap).
+    pub fn collect_btreemap<K: Ord, F: FnMut(&T) -> K>(self, key: F) -> Result<BTreeMap<K, T>> {
+        collect_btreemap(self, key)
+    }
+
+    /// Returns how the secondary table is read: its name, the bounds of the keys read and the
+    /// number of primary keys selected. Requires the `diagnostics` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key]
+    ///     name: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     // Open a read transaction
+    ///     let r = db.r_transaction()?;
+    ///
+    ///     let scan = r.scan().secondary::<Data>(DataKey::name)?;
+    ///     let plan = scan.range("A".."C")?.explain();
+    ///     assert_eq!(plan.table, "1_1_name");
+    ///     assert!(!plan.is_full_scan());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "diagnostics")]
+    pub fn explain(&self) -> ScanPlan {
+        self.plan.clone()
+    }
 }
 
 impl<PrimaryTable, T: ToInput> Iterator for SecondaryScanIterator<'_, PrimaryTable, T>
@@ -235,7 +557,7 @@ where
         match self.primary_keys.next() {
             Some(primary_key) => {
                 if let Ok(value) = self.primary_table.get(primary_key.value()) {
-                    unwrap_item(value)
+                    unwrap_item(value, self.max_value_size)
                 } else {
                     None
                 }
@@ -254,7 +576,7 @@ where
         match self.primary_keys.next_back() {
             Some(primary_key) => {
                 if let Ok(value) = self.primary_table.get(primary_key.value()) {
-                    unwrap_item(value)
+                    unwrap_item(value, self.max_value_size)
                 } else {
                     None
                 }
@@ -1,10 +1,24 @@
+use crate::db_type::{
+    check_key_type_from_key_definition, check_range_key_range_bounds_from_key_definition, Error,
+    KeyOptions, KeyRange, Result, ToInput, ToKey, ToKeyDefinition,
+};
+use crate::transaction::internal::private_readable_transaction::PrivateReadableTransaction;
 use crate::transaction::internal::r_transaction::InternalRTransaction;
+use crate::transaction::query::estimate_range_len;
+use crate::transaction::query::MergeScan;
+use crate::transaction::query::PreparedScan;
 use crate::transaction::query::RGet;
 use crate::transaction::query::RLen;
 use crate::transaction::query::RScan;
+#[cfg(feature = "rkyv")]
+use crate::transaction::ArchivedView;
+use crate::transaction::BlobReader;
+use crate::unique_secondary_cache::UniqueSecondaryCaches;
+use std::ops::RangeBounds;
 
 pub struct RTransaction<'db> {
     pub(crate) internal: InternalRTransaction<'db>,
+    pub(crate) unique_secondary_caches: &'db UniqueSecondaryCaches,
 }
 
 impl<'db> RTransaction<'db> {
@@ -15,6 +29,7 @@ impl<'db> RTransaction<'db> {
     pub fn get<'txn>(&'txn self) -> RGet<'db, 'txn> {
         RGet {
             internal: &self.internal,
+            unique_secondary_caches: self.unique_secondary_caches,
         }
     }
 
@@ -28,6 +43,65 @@ impl<'db> RTransaction<'db> {
         }
     }
 
+    /// Merge the secondary scans of several models into a single iterator ordered by secondary
+    /// key, e.g. a timeline of several event models sharing a `timestamp` secondary key.
+    ///
+    /// Each model is added with [`secondary`](crate::transaction::query::MergeScan::secondary)
+    /// and converted into a common type `E`, see [`all`](crate::transaction::query::MergeScan::all)
+    /// and [`range`](crate::transaction::query::MergeScan::range).
+    pub fn merge_scan<'txn, E>(&'txn self) -> MergeScan<'db, 'txn, E> {
+        MergeScan {
+            internal: &self.internal,
+            sources: vec![],
+        }
+    }
+
+    /// Prepare the scans of the secondary key `key_def` of the model `T`, for a query repeated
+    /// many times with different bounds in the same transaction.
+    ///
+    /// The key definition is resolved and the tables are opened once, then each
+    /// [`range`](crate::transaction::query::SecondaryScan::range) (or
+    /// [`start_with`](crate::transaction::query::SecondaryScan::start_with), etc.) reuses them.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    /// use itertools::Itertools;
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Measure {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key]
+    ///     timestamp: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Measure>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let r = db.r_transaction()?;
+    ///     let by_timestamp = r.prepare_secondary::<Measure>(MeasureKey::timestamp)?;
+    ///     for hour in 0..24u64 {
+    ///         let _values: Vec<Measure> = by_timestamp
+    ///             .range(hour * 3600..(hour + 1) * 3600)?
+    ///             .try_collect()?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn prepare_secondary<T: ToInput>(
+        &self,
+        key_def: impl ToKeyDefinition<KeyOptions>,
+    ) -> Result<PreparedScan<T>> {
+        self.scan().secondary(key_def)
+    }
+
     /// Get the number of values in the database.
     ///
     /// - [`primary`](crate::transaction::query::RLen::primary) - Get the number of items by primary key.
@@ -37,4 +111,160 @@ impl<'db> RTransaction<'db> {
             internal: &self.internal,
         }
     }
+
+    /// Estimate the number of values whose secondary key `key_def` is within `range`, without
+    /// reading the values, e.g. to choose between a secondary scan and a full scan. `K` is the
+    /// type of the secondary key.
+    ///
+    /// At most 1024 distinct keys are read from the index by each of the three walks:
+    /// from the start of the range, before the range and after the range. The result is exact
+    /// when one of the walks reaches its end, i.e. when the range or its complement has few keys.
+    /// Otherwise the keys are assumed to be uniformly distributed between the first and the
+    /// last key of the index, so the estimate is close for numeric keys (timestamps, counters)
+    /// and rough for skewed ones (e.g. strings sharing long prefixes).
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Measure {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key]
+    ///     timestamp: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Measure>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let r = db.r_transaction()?;
+    ///     let estimate = r.estimate_secondary_count::<Measure, u64>(MeasureKey::timestamp, 0..3600)?;
+    ///     assert_eq!(estimate, 0);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn estimate_secondary_count<T: ToInput, K: ToKey>(
+        &self,
+        key_def: impl ToKeyDefinition<KeyOptions>,
+        range: impl RangeBounds<K>,
+    ) -> Result<usize> {
+        let key_def = key_def.key_definition();
+        check_range_key_range_bounds_from_key_definition(&key_def, &range)?;
+        let table = self
+            .internal
+            .get_secondary_table(&T::native_db_model(), &key_def)?;
+        let estimate = estimate_range_len(&table, KeyRange::new(range))?;
+        Ok(estimate as usize)
+    }
+
+    /// Get the values matching two secondary keys at once, e.g. `status = Open AND region = EU`,
+    /// ordered by primary key.
+    ///
+    /// The primary keys of each secondary key are read from the indexes and intersected,
+    /// only the values in the intersection are fetched and decoded. So it is efficient when at
+    /// least one of the secondary keys is selective.
+    ///
+    /// Anatomy of a secondary key it is a `enum` with the following structure: `<table_name>Key::<name>`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Ticket {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key]
+    ///     status: String,
+    ///     #[secondary_key]
+    ///     region: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Ticket>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     // Open a read transaction
+    ///     let r = db.r_transaction()?;
+    ///
+    ///     // Get the open tickets of the EU region
+    ///     let _values: Vec<Ticket> = r.scan_intersect(
+    ///         (TicketKey::status, "Open"),
+    ///         (TicketKey::region, "EU"),
+    ///     )?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn scan_intersect<T: ToInput>(
+        &self,
+        (key_def_a, key_a): (impl ToKeyDefinition<KeyOptions>, impl ToKey),
+        (key_def_b, key_b): (impl ToKeyDefinition<KeyOptions>, impl ToKey),
+    ) -> Result<Vec<T>> {
+        let key_def_a = key_def_a.key_definition();
+        let key_def_b = key_def_b.key_definition();
+        check_key_type_from_key_definition(&key_def_a, &key_a)?;
+        check_key_type_from_key_definition(&key_def_b, &key_b)?;
+        self.internal
+            .get_by_secondary_keys_intersection(
+                T::native_db_model(),
+                (key_def_a, key_a.to_key()),
+                (key_def_b, key_b.to_key()),
+            )?
+            .iter()
+            .map(|output| output.inner())
+            .collect()
+    }
+
+    /// Open the blob of a value for streaming, `None` if the value has no blob.
+    ///
+    /// The model must be defined with `#[native_db(blob)]`, see
+    /// [`insert_with_blob`](crate::transaction::RwTransaction::insert_with_blob).
+    pub fn open_blob<T: ToInput>(&self, primary_key: impl ToKey) -> Result<Option<BlobReader>> {
+        let model = T::native_db_model();
+        let table = self
+            .internal
+            .get_blob_table(&model)?
+            .ok_or_else(|| Error::BlobNotDefined {
+                table: model.primary_key.unique_table_name.to_string(),
+            })?;
+        BlobReader::new(table, primary_key.to_key())
+    }
+
+    /// Get the archive of a value by primary key, without deserializing it. `None` if the value
+    /// does not exist.
+    ///
+    /// The model must be defined with `#[native_db(rkyv)]`, see [Models](crate::Models#rkyv),
+    /// otherwise [`RkyvNotDefined`](crate::db_type::Error::RkyvNotDefined) is returned.
+    /// Requires the feature `rkyv`.
+    #[cfg(feature = "rkyv")]
+    pub fn get_archived<T>(&self, primary_key: impl ToKey) -> Result<Option<ArchivedView<T>>>
+    where
+        T: ToInput + rkyv::Archive,
+        T::Archived: for<'a> rkyv::bytecheck::CheckBytes<
+            rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>,
+        >,
+    {
+        let model = T::native_db_model();
+        if !model.rkyv {
+            return Err(Error::RkyvNotDefined {
+                table: model.primary_key.unique_table_name.to_string(),
+            });
+        }
+        self.internal
+            .get_by_primary_key(model, primary_key)?
+            .map(|output| ArchivedView::new(&output.0))
+            .transpose()
+    }
 }
@@ -1,26 +1,66 @@
-use crate::db_type::{Input, Result, ToInput};
+use crate::database_builder::OnCommit;
+use crate::db_type::{check_key_type, Error, Input, Key, Result, ToInput, ToKey};
+use crate::mirror::Mirror;
+use crate::transaction::internal::r_transaction::InternalRTransaction;
 use crate::transaction::internal::rw_transaction::InternalRwTransaction;
 use crate::transaction::query::RwDrain;
 use crate::transaction::query::RwGet;
 use crate::transaction::query::RwLen;
 use crate::transaction::query::RwScan;
+use crate::transaction::{
+    CommitInfo, MigrateReport, RTransaction, StagedChange, WriteManyReport, WritePolicy,
+};
+use crate::unique_secondary_cache::UniqueSecondaryCaches;
 use crate::watch;
 use crate::watch::Event;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 
 use super::internal::private_readable_transaction::PrivateReadableTransaction;
 
 pub struct RwTransaction<'db> {
     pub(crate) watcher: &'db Arc<RwLock<watch::Watchers>>,
+    pub(crate) on_commit: Option<&'db OnCommit>,
+    pub(crate) commit_sequence: &'db AtomicU64,
+    pub(crate) unique_secondary_caches: &'db UniqueSecondaryCaches,
+    pub(crate) mirror: Option<&'db Mirror<'db>>,
+    // Used to open the read transaction of `committed_view`.
+    pub(crate) redb_database: &'db redb::Database,
+    pub(crate) read_transactions_counter: &'db AtomicUsize,
     pub(crate) batch: RefCell<watch::Batch>,
+    // Last value of each counter changed by the transaction, replayed on the mirror.
+    pub(crate) counter_changes: RefCell<HashMap<String, i64>>,
+    pub(crate) bulk_events: bool,
     pub(crate) internal: InternalRwTransaction<'db>,
+    pub(crate) _open: OpenWriteTransaction<'db>,
+}
+
+// Set while the write transaction is alive, so `Database::compact` can fail instead of
+// waiting forever for a transaction that was leaked.
+pub(crate) struct OpenWriteTransaction<'db>(&'db AtomicBool);
+
+impl<'db> OpenWriteTransaction<'db> {
+    pub(crate) fn new(flag: &'db AtomicBool) -> Self {
+        flag.store(true, Ordering::SeqCst);
+        Self(flag)
+    }
+}
+
+impl Drop for OpenWriteTransaction<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
 }
 
 impl<'db> RwTransaction<'db> {
     /// Get a value from the database.
     ///
+    /// The inserts, updates and removes made earlier in this transaction are visible.
+    ///
     /// - [`primary`](crate::transaction::query::RGet::primary) - Get a item by primary key.
     /// - [`secondary`](crate::transaction::query::RGet::secondary) - Get a item by secondary key.
     pub fn get<'txn>(&'txn self) -> RwGet<'db, 'txn> {
@@ -31,6 +71,8 @@ impl<'db> RwTransaction<'db> {
 
     /// Get values from the database.
     ///
+    /// Like [`get`](Self::get), the changes made earlier in this transaction are visible.
+    ///
     /// - [`primary`](crate::transaction::query::RScan::primary) - Scan items by primary key.
     /// - [`secondary`](crate::transaction::query::RScan::secondary) - Scan items by secondary key.
     pub fn scan<'txn>(&'txn self) -> RwScan<'db, 'txn> {
@@ -55,8 +97,72 @@ impl<'db> RwTransaction<'db> {
     pub fn drain<'txn>(&'txn self) -> RwDrain<'db, 'txn> {
         RwDrain {
             internal: &self.internal,
+            mirrored: self.mirror.is_some(),
         }
     }
+
+    /// Returns [`NotMirrored`](crate::db_type::Error::NotMirrored) if the database has a
+    /// mirror, for the operations whose changes are not sent to the watchers, so can not be
+    /// replayed on it.
+    pub(crate) fn check_not_mirrored(&self, operation: &str) -> Result<()> {
+        if self.mirror.is_some() {
+            return Err(Error::NotMirrored {
+                operation: operation.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Open a read-only view of the committed state of the database, ignoring the changes
+    /// made in this transaction, e.g. to compute deltas against the baseline while staging
+    /// changes.
+    ///
+    /// The view is a snapshot of the state at the start of this transaction: no other write
+    /// transaction can commit while this one is open, and the view does not change when this
+    /// transaction commits.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Data { id: 1 })?;
+    ///
+    ///     let committed = rw.committed_view()?;
+    ///     assert_eq!(committed.len().primary::<Data>()?, 0);
+    ///     assert_eq!(rw.len().primary::<Data>()?, 1);
+    ///
+    ///     rw.commit()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn committed_view(&self) -> Result<RTransaction<'db>> {
+        let txn = self.redb_database.begin_read()?;
+        Ok(RTransaction {
+            internal: InternalRTransaction::new(
+                txn,
+                self.internal.primary_table_definitions,
+                self.read_transactions_counter,
+                self.internal.max_value_size,
+            ),
+            unique_secondary_caches: self.unique_secondary_caches,
+        })
+    }
 }
 
 impl RwTransaction<'_> {
@@ -64,6 +170,11 @@ impl RwTransaction<'_> {
     /// All changes will be applied to the database. If the commit fails, the transaction will be aborted. The
     /// database will be unchanged.
     ///
+    /// The IO errors (e.g. the storage is full, see [`is_storage_full`](crate::db_type::Error::is_storage_full))
+    /// are returned as [`Io`](crate::db_type::Error::Io) and no event is sent to the watchers. After
+    /// an IO error, redb refuses the next write transactions until the database is reopened, which
+    /// repairs it to its last successful commit.
+    ///
     /// # Example
     /// ```rust
     /// use native_db::*;
@@ -81,23 +192,120 @@ impl RwTransaction<'_> {
     /// }
     /// ```
     pub fn commit(self) -> Result<()> {
+        self.commit_with_info()?;
+        Ok(())
+    }
+
+    /// Commit the transaction like [`commit`](crate::transaction::RwTransaction::commit) and
+    /// return the number of inserts, updates and removes it applied, with the sequence number
+    /// assigned to the commit.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Data { id: 1 })?;
+    ///     let info = rw.commit_with_info()?;
+    ///     assert_eq!(info.inserts, 1);
+    ///     println!("committed {} changes (seq {})", info.changes(), info.sequence);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn commit_with_info(self) -> Result<CommitInfo> {
         self.internal.commit()?;
+        let sequence = self.commit_sequence.fetch_add(1, Ordering::SeqCst) + 1;
         // Send batch to watchers after commit succeeds
         let batch = self.batch.into_inner();
+        self.unique_secondary_caches.apply(&batch);
+        let commit_info = CommitInfo {
+            sequence,
+            ..batch.commit_info()
+        };
+        if let Some(on_commit) = self.on_commit {
+            let events = batch.events();
+            if !events.is_empty() {
+                (on_commit.0)(&events);
+            }
+        }
+        let mirror_result = match self.mirror {
+            Some(mirror) => mirror.replay(&batch, &self.counter_changes.into_inner()),
+            None => Ok(()),
+        };
         watch::push_batch(Arc::clone(self.watcher), batch)?;
-        Ok(())
+        mirror_result.map_err(|error| Error::MirrorOutOfSync(Box::new(error)))?;
+        Ok(commit_info)
     }
 
     /// Abort the transaction.
     pub fn abort(self) -> Result<()> {
         Ok(self.internal.redb_transaction.abort()?)
     }
+
+    /// Abort the transaction and return the changes that were staged, in the order they were
+    /// staged, e.g. to retry them later.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use native_db::watch::Event;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Data { id: 1 })?;
+    ///     let staged = rw.abort_returning()?;
+    ///     assert_eq!(staged.len(), 1);
+    ///     if let Event::Insert(insert) = &staged[0].event {
+    ///         let data: Data = insert.inner()?;
+    ///         assert_eq!(data.id, 1);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn abort_returning(self) -> Result<Vec<StagedChange>> {
+        let staged_changes = StagedChange::from_batch(&self.batch.borrow());
+        self.internal.redb_transaction.abort()?;
+        Ok(staged_changes)
+    }
 }
 
 impl RwTransaction<'_> {
     /// Insert a value into the database.
     ///
-    /// If the primary key already exists, an error is returned.
+    /// If the primary key already exists, an error is returned. With a
+    /// [whole primary key](crate::Models#whole-primary-key), inserting a value already stored
+    /// does nothing instead.
     ///
     /// # Example
     /// ```rust
@@ -131,6 +339,17 @@ impl RwTransaction<'_> {
     /// }
     /// ```
     pub fn insert<T: ToInput>(&self, item: T) -> Result<()> {
+        item.native_db_validate()?;
+        let model = T::native_db_model();
+        // With a whole primary key, an existing key means the same value is already stored.
+        if model.whole_primary_key
+            && self
+                .internal
+                .get_by_primary_key(model, item.native_db_primary_key())?
+                .is_some()
+        {
+            return Ok(());
+        }
         let (watcher_request, binary_value) = self
             .internal
             .concrete_insert(T::native_db_model(), item.native_db_input()?)?;
@@ -139,6 +358,76 @@ impl RwTransaction<'_> {
         Ok(())
     }
 
+    /// Insert a value and its blob into the database, the model must be defined with
+    /// `#[native_db(blob)]` (see [Models](crate::Models#blob)).
+    ///
+    /// The blob is read from `blob` and stored by chunks, so it is never loaded entirely in
+    /// memory. Use [`open_blob`](crate::transaction::RTransaction::open_blob) to read it.
+    ///
+    /// If the primary key already exists, an error is returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    /// use std::io::Read;
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db(blob)]
+    /// struct Attachment {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     file_name: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Attachment>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     let content: &[u8] = b"hello";
+    ///     rw.insert_with_blob(Attachment { id: 1, file_name: "hello.txt".to_string() }, content)?;
+    ///     rw.commit()?;
+    ///
+    ///     let r = db.r_transaction()?;
+    ///     let mut blob = r.open_blob::<Attachment>(1u64)?.unwrap();
+    ///     let mut content = String::new();
+    ///     blob.read_to_string(&mut content)?;
+    ///     assert_eq!(content, "hello");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn insert_with_blob<T: ToInput>(
+        &self,
+        item: T,
+        mut blob: impl std::io::Read,
+    ) -> Result<()> {
+        self.check_not_mirrored("insert_with_blob")?;
+        item.native_db_validate()?;
+        let model = T::native_db_model();
+        if !model.blob {
+            return Err(Error::BlobNotDefined {
+                table: model.primary_key.unique_table_name.to_string(),
+            });
+        }
+        let primary_key = item.native_db_primary_key();
+        self.insert(item)?;
+        self.internal
+            .concrete_write_blob(&model, &primary_key, &mut blob)
+    }
+
+    /// Remove the blob of a value, the value itself is kept.
+    ///
+    /// Returns `false` if the value has no blob.
+    pub fn remove_blob<T: ToInput>(&self, primary_key: impl ToKey) -> Result<bool> {
+        self.check_not_mirrored("remove_blob")?;
+        self.internal
+            .concrete_remove_blob(&T::native_db_model(), &primary_key.to_key())
+    }
+
     /// Upsert a value into the database.
     ///
     /// If the primary key already exists, the value is updated.
@@ -182,6 +471,7 @@ impl RwTransaction<'_> {
     /// }
     /// ```
     pub fn upsert<T: ToInput>(&self, item: T) -> Result<Option<T>> {
+        item.native_db_validate()?;
         let model = T::native_db_model();
         let old_item: Option<Input> = self
             .internal
@@ -207,6 +497,98 @@ impl RwTransaction<'_> {
         }
     }
 
+    /// Write many values of the model `T`, applying `policy` to the values whose primary key
+    /// already exists (see [`WritePolicy`]). The tables are opened once for all the values.
+    ///
+    /// Returns the number of inserted, updated and skipped values. An [`Insert`](crate::watch::Event::Insert)
+    /// or [`Update`](crate::watch::Event::Update) event is sent for each written value.
+    ///
+    /// The conflicts on the unique secondary keys always return
+    /// [`DuplicateKey`](crate::db_type::Error::DuplicateKey). On error, the values written before
+    /// stay in the transaction: [`abort`](Self::abort) it to discard them.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use native_db::transaction::WritePolicy;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     name: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Data { id: 1, name: "a".to_string() })?;
+    ///
+    ///     let items = vec![
+    ///         Data { id: 1, name: "b".to_string() },
+    ///         Data { id: 2, name: "c".to_string() },
+    ///     ];
+    ///     let report = rw.write_many(items, WritePolicy::InsertOrIgnore)?;
+    ///     assert_eq!((report.inserted, report.skipped), (1, 1));
+    ///
+    ///     rw.commit()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn write_many<T: ToInput>(
+        &self,
+        items: impl IntoIterator<Item = T>,
+        policy: WritePolicy,
+    ) -> Result<WriteManyReport> {
+        let (written, skipped) = self.internal.concrete_write_many(items, policy)?;
+        let mut report = WriteManyReport {
+            skipped,
+            ..Default::default()
+        };
+        let mut batch = self.batch.borrow_mut();
+        let start = batch.len();
+        for (watcher_request, new_binary_value, old_binary_value) in written {
+            let event = match old_binary_value {
+                Some(old_binary_value) => {
+                    report.updated += 1;
+                    Event::new_update(old_binary_value, new_binary_value)
+                }
+                None => {
+                    report.inserted += 1;
+                    Event::new_insert(new_binary_value)
+                }
+            };
+            batch.add(watcher_request, event);
+        }
+        let count = (report.inserted + report.updated) as usize;
+        if self.bulk_events && count > 0 {
+            batch.summarize(
+                start,
+                Event::BulkInsert {
+                    table: T::native_db_model().primary_key.unique_table_name.clone(),
+                    count,
+                },
+            );
+        }
+        Ok(report)
+    }
+
+    /// Upsert many values of the model `T`, see [`write_many`](Self::write_many) with
+    /// [`WritePolicy::Upsert`].
+    pub fn upsert_many<T: ToInput>(
+        &self,
+        items: impl IntoIterator<Item = T>,
+    ) -> Result<WriteManyReport> {
+        self.write_many(items, WritePolicy::Upsert)
+    }
+
     /// Remove a value from the database.
     ///
     /// Returns error:
@@ -247,14 +629,92 @@ impl RwTransaction<'_> {
     /// }
     /// ```
     pub fn remove<T: ToInput>(&self, item: T) -> Result<T> {
+        let model = T::native_db_model();
         let (watcher_request, binary_value) = self
             .internal
-            .concrete_remove(T::native_db_model(), item.native_db_input()?)?;
+            .concrete_remove(model.clone(), item.native_db_input()?)?;
+        if model.blob {
+            self.internal
+                .concrete_remove_blob(&model, &watcher_request.primary_key)?;
+        }
         let event = Event::new_delete(binary_value.clone());
         self.batch.borrow_mut().add(watcher_request, event);
         binary_value.inner()
     }
 
+    /// Remove the values having the given primary keys, e.g. for a bulk cleanup.
+    ///
+    /// The absent keys are skipped. Returns the removed values, in the order of the keys.
+    /// A [`Delete`](crate::watch::Event::Delete) event is sent for each removed value, or a single
+    /// [`BulkDelete`](crate::watch::Event::BulkDelete) event if
+    /// [`set_bulk_events`](Self::set_bulk_events) is enabled.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Data { id: 1 })?;
+    ///     rw.insert(Data { id: 2 })?;
+    ///
+    ///     // The key 3 does not exist, it is skipped
+    ///     let removed: Vec<Data> = rw.remove_many_by_keys([1u64, 3u64])?;
+    ///     assert_eq!(removed.len(), 1);
+    ///
+    ///     rw.commit()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn remove_many_by_keys<T: ToInput, K: ToKey>(
+        &self,
+        keys: impl IntoIterator<Item = K>,
+    ) -> Result<Vec<T>> {
+        let model = T::native_db_model();
+        let keys = keys
+            .into_iter()
+            .map(|key| {
+                check_key_type(&model, &key)?;
+                Ok(key.to_key())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let removed = self.internal.concrete_remove_many_by_keys::<T>(keys)?;
+        let mut batch = self.batch.borrow_mut();
+        let start = batch.len();
+        let mut removed_keys = Vec::with_capacity(removed.len());
+        let mut items = Vec::with_capacity(removed.len());
+        for (watcher_request, binary_value, item) in removed {
+            removed_keys.push(watcher_request.primary_key.clone());
+            batch.add(watcher_request, Event::new_delete(binary_value));
+            items.push(item);
+        }
+        if self.bulk_events && !removed_keys.is_empty() {
+            batch.summarize(
+                start,
+                Event::BulkDelete {
+                    table: model.primary_key.unique_table_name.clone(),
+                    count: removed_keys.len(),
+                    range: key_range(removed_keys),
+                },
+            );
+        }
+        Ok(items)
+    }
+
     /// **Deprecated**: should be replaced by [`auto_update`] which will be renamed to [`update`]
     ///
     /// Update a value in the database.
@@ -300,6 +760,7 @@ impl RwTransaction<'_> {
     /// ```
     #[deprecated = "should be replaced by auto_update"]
     pub fn update<T: ToInput>(&self, old_item: T, updated_item: T) -> Result<()> {
+        updated_item.native_db_validate()?;
         let (watcher_request, old_binary_value, new_binary_value) = self.internal.concrete_update(
             T::native_db_model(),
             old_item.native_db_input()?,
@@ -366,6 +827,7 @@ impl RwTransaction<'_> {
     /// }
     /// ```
     pub fn auto_update<T: ToInput>(&self, item: T) -> Result<Option<T>> {
+        item.native_db_validate()?;
         let model = T::native_db_model();
         let old_item: Option<Input> = self
             .internal
@@ -395,6 +857,190 @@ impl RwTransaction<'_> {
         }
     }
 
+    /// Update a value only if it has not been modified since it was read (optimistic concurrency).
+    ///
+    /// The model must define a version field with `#[native_db(version_field = ...)]` (a `u64`
+    /// field). The stored value is read by primary key, and its version must be equal to the
+    /// version of `item`, otherwise [`VersionConflict`](crate::db_type::Error::VersionConflict)
+    /// is returned. Then `item` is written with its version incremented by one.
+    ///
+    /// Returns [`KeyNotFound`](crate::db_type::Error::KeyNotFound) if the value does not exist,
+    /// and [`VersionOverflow`](crate::db_type::Error::VersionOverflow) if its version is
+    /// `u64::MAX`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize, Clone)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db(version_field = rev)]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     name: String,
+    ///     rev: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Data { id: 1, name: "initial".to_string(), rev: 0 })?;
+    ///
+    ///     let mut data: Data = rw.get().primary(1u64)?.unwrap();
+    ///     data.name = "updated".to_string();
+    ///     rw.update_checked(data.clone())?;
+    ///
+    ///     // `data` is stale now: its version is 0 but the stored one is 1
+    ///     assert!(matches!(
+    ///         rw.update_checked(data),
+    ///         Err(db_type::Error::VersionConflict { expected: 0, actual: 1 })
+    ///     ));
+    ///     rw.commit()
+    /// }
+    /// ```
+    pub fn update_checked<T: ToInput>(&self, mut item: T) -> Result<()> {
+        item.native_db_validate()?;
+        let model = T::native_db_model();
+        let expected = item
+            .native_db_version()
+            .ok_or_else(|| Error::VersionFieldNotDefined {
+                table: model.primary_key.unique_table_name.to_string(),
+            })?;
+        let primary_key = item.native_db_primary_key();
+        let current_item: T = self
+            .internal
+            .get_by_primary_key(model, primary_key.clone())?
+            .ok_or_else(|| Error::KeyNotFound {
+                key: primary_key.as_slice().to_vec(),
+            })?
+            .inner()?;
+        let actual = current_item.native_db_version().unwrap_or_default();
+        if actual != expected {
+            return Err(Error::VersionConflict { expected, actual });
+        }
+        let next = expected
+            .checked_add(1)
+            .ok_or_else(|| Error::VersionOverflow {
+                table: T::native_db_model().primary_key.unique_table_name,
+            })?;
+        item.native_db_set_version(next);
+        let (watcher_request, old_binary_value, new_binary_value) = self.internal.concrete_update(
+            T::native_db_model(),
+            current_item.native_db_input()?,
+            item.native_db_input()?,
+        )?;
+        let event = Event::new_update(old_binary_value, new_binary_value);
+        self.batch.borrow_mut().add(watcher_request, event);
+        Ok(())
+    }
+
+    /// Swap the primary keys of two values, e.g. to exchange the display order of two items.
+    ///
+    /// The value stored under `key_a` is written under `key_b` and the other way around, the
+    /// secondary keys follow their value. Both values are removed before being inserted again,
+    /// so a unique secondary key never collides with itself in between.
+    /// An [`Update`](crate::watch::Event::Update) event is sent for each primary key.
+    /// The blobs follow their value, like the secondary keys.
+    ///
+    /// Returns error:
+    /// - [crate::db_type::Error::KeyNotFound] if one of the primary keys is not found in the database.
+    /// - [crate::db_type::Error::PrimaryKeyNotSwappable] if the primary key is computed by a function.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Item {
+    ///     #[primary_key]
+    ///     position: u64,
+    ///     name: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Item>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.insert(Item { position: 1, name: "first".to_string() })?;
+    ///     rw.insert(Item { position: 2, name: "second".to_string() })?;
+    ///
+    ///     rw.swap::<Item>(1u64, 2u64)?;
+    ///     let item: Item = rw.get().primary(1u64)?.unwrap();
+    ///     assert_eq!(item.name, "second");
+    ///
+    ///     rw.commit()
+    /// }
+    /// ```
+    pub fn swap<T: ToInput>(&self, key_a: impl ToKey, key_b: impl ToKey) -> Result<()> {
+        let model = T::native_db_model();
+        check_key_type(&model, &key_a)?;
+        check_key_type(&model, &key_b)?;
+        let (key_a, key_b) = (key_a.to_key(), key_b.to_key());
+        if key_a == key_b {
+            return Ok(());
+        }
+        let get = |key: Key| -> Result<T> {
+            self.internal
+                .get_by_primary_key(model.clone(), key.clone())?
+                .ok_or_else(|| Error::KeyNotFound {
+                    key: key.as_slice().to_vec(),
+                })?
+                .inner()
+        };
+        let mut item_a = get(key_a.clone())?;
+        let mut item_b = get(key_b.clone())?;
+        let old_input_a = item_a.native_db_input()?;
+        let old_input_b = item_b.native_db_input()?;
+        if !item_a.native_db_swap_primary_key(&mut item_b) {
+            return Err(Error::PrimaryKeyNotSwappable {
+                table: model.primary_key.unique_table_name.to_string(),
+            });
+        }
+        if model.blob {
+            self.check_not_mirrored("swap of values with blobs")?;
+        }
+
+        let (_, old_value_a) = self
+            .internal
+            .concrete_remove(model.clone(), old_input_a.clone())?;
+        let (_, old_value_b) = self
+            .internal
+            .concrete_remove(model.clone(), old_input_b.clone())?;
+        self.internal.concrete_swap_blobs(&model, &key_a, &key_b)?;
+        // `item_b` now holds the primary key `key_a` and `item_a` the primary key `key_b`.
+        let (watcher_request_a, new_value_a) = self
+            .internal
+            .concrete_insert(model.clone(), item_b.native_db_input()?)?;
+        let (watcher_request_b, new_value_b) = self
+            .internal
+            .concrete_insert(model, item_a.native_db_input()?)?;
+
+        let mut batch = self.batch.borrow_mut();
+        batch.add(
+            watcher_request_a
+                .with_old_keys(&old_input_a)
+                .swapped_with_next(),
+            Event::new_update(old_value_a, new_value_a),
+        );
+        batch.add(
+            watcher_request_b.with_old_keys(&old_input_b),
+            Event::new_update(old_value_b, new_value_b),
+        );
+        Ok(())
+    }
+
     /// Convert all values from the database.
     ///
     /// This is useful when you want to change the type/model of a value.
@@ -455,14 +1101,48 @@ impl RwTransaction<'_> {
         OldType: ToInput + Clone,
         NewType: ToInput + From<OldType>,
     {
+        self.check_not_mirrored("convert_all")?;
         let find_all_old: Result<Vec<OldType>> = self.scan().primary()?.all()?.collect();
         let find_all_old = find_all_old?;
+        let mut removed_keys = Vec::with_capacity(find_all_old.len());
+        let mut insert_requests = Vec::with_capacity(find_all_old.len());
+        let mut remove_requests = Vec::with_capacity(find_all_old.len());
         for old in find_all_old {
             let new: NewType = old.clone().into();
-            self.internal
+            let (insert_request, _) = self
+                .internal
                 .concrete_insert(NewType::native_db_model(), new.native_db_input()?)?;
-            self.internal
-                .concrete_remove(OldType::native_db_model(), old.native_db_input()?)?;
+            insert_requests.push(insert_request);
+            let old = old.native_db_input()?;
+            removed_keys.push(old.primary_key.clone());
+            let (remove_request, _) = self
+                .internal
+                .concrete_remove(OldType::native_db_model(), old)?;
+            remove_requests.push(remove_request);
+        }
+        if self.bulk_events && !removed_keys.is_empty() {
+            let mut batch = self.batch.borrow_mut();
+            batch.summarize_requests(
+                insert_requests,
+                Event::BulkInsert {
+                    table: NewType::native_db_model()
+                        .primary_key
+                        .unique_table_name
+                        .clone(),
+                    count: removed_keys.len(),
+                },
+            );
+            batch.summarize_requests(
+                remove_requests,
+                Event::BulkDelete {
+                    table: OldType::native_db_model()
+                        .primary_key
+                        .unique_table_name
+                        .clone(),
+                    count: removed_keys.len(),
+                    range: key_range(removed_keys),
+                },
+            );
         }
         Ok(())
     }
@@ -538,13 +1218,146 @@ impl RwTransaction<'_> {
     /// }
     /// ```
     pub fn migrate<T: ToInput + Debug>(&self) -> Result<()> {
+        self.check_not_mirrored("migrate")?;
         self.internal.migrate::<T>()
     }
 
+    /// Like [`migrate`](crate::transaction::RwTransaction::migrate), but call `f` on each item
+    /// after its conversion and before its insertion, e.g. to fill a new field or to validate it.
+    ///
+    /// An error returned by `f` stops the migration and is returned as
+    /// [`Migration`](crate::db_type::Error::Migration), with the original error as its source.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize, Debug)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct LegacyData {
+    ///     #[primary_key]
+    ///     id: u32,
+    /// }
+    ///
+    /// impl From<Data> for LegacyData {
+    ///     fn from(data: Data) -> Self {
+    ///         LegacyData {
+    ///             id: data.id as u32,
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// #[derive(Serialize, Deserialize, Debug)]
+    /// #[native_model(id=1, version=2, from=LegacyData)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     name: String,
+    /// }
+    ///
+    /// impl From<LegacyData> for Data {
+    ///     fn from(legacy_data: LegacyData) -> Self {
+    ///         Data {
+    ///             id: legacy_data.id as u64,
+    ///             name: String::new(),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<LegacyData>()?;
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     rw.migrate_with::<Data, _>(|data| {
+    ///         data.name = format!("item_{}", data.id);
+    ///         Ok(())
+    ///     })?;
+    ///     rw.commit()
+    /// }
+    /// ```
+    pub fn migrate_with<T, F>(&self, f: F) -> Result<()>
+    where
+        T: ToInput + Debug,
+        F: FnMut(&mut T) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>,
+    {
+        self.check_not_mirrored("migrate_with")?;
+        self.internal.migrate_with::<T, F>(f)
+    }
+
+    /// Check the [`migrate`](crate::transaction::RwTransaction::migrate) of the model `T` without writing anything.
+    ///
+    /// All the values of the old version are read and converted with the `From` or `TryFrom`
+    /// implementation, the values that fail to convert are reported with their primary key.
+    /// Nothing is inserted or removed.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize, Debug)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct LegacyData {
+    ///     #[primary_key]
+    ///     id: u32,
+    /// }
+    ///
+    /// impl From<Data> for LegacyData {
+    ///     fn from(data: Data) -> Self {
+    ///         LegacyData {
+    ///             id: data.id as u32,
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// #[derive(Serialize, Deserialize, Debug)]
+    /// #[native_model(id=1, version=2, from=LegacyData)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// impl From<LegacyData> for Data {
+    ///     fn from(legacy_data: LegacyData) -> Self {
+    ///         Data {
+    ///             id: legacy_data.id as u64,
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<LegacyData>()?;
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     let report = rw.migrate_dry_run::<Data>()?;
+    ///     if report.is_ok() {
+    ///         rw.migrate::<Data>()?;
+    ///     }
+    ///     rw.commit()
+    /// }
+    /// ```
+    pub fn migrate_dry_run<T: ToInput + Debug>(&self) -> Result<MigrateReport> {
+        self.internal.migrate_dry_run::<T>()
+    }
+
     /// Refresh the data for the given model. Is used generally when during an database upgrade,
     /// using the method [crate::Database::upgrading_from_version] (more details/example). Check release notes to know
     /// when to use this method.
     pub fn refresh<T: ToInput + Debug>(&self) -> Result<()> {
+        self.check_not_mirrored("refresh")?;
         self.internal.refresh::<T>()
     }
 
@@ -559,4 +1372,81 @@ impl RwTransaction<'_> {
     pub fn set_quick_repair(&mut self, enabled: bool) {
         self.internal.set_quick_repair(enabled)
     }
+
+    /// Send a single [`BulkInsert`](crate::watch::Event::BulkInsert) or
+    /// [`BulkDelete`](crate::watch::Event::BulkDelete) event to the watchers for each bulk
+    /// operation, instead of an event per value (defaults to disabled).
+    ///
+    /// The bulk operations are [`write_many`](Self::write_many) (and
+    /// [`upsert_many`](Self::upsert_many)), whose inserted and updated values are summarized by a
+    /// single `BulkInsert`, [`remove_many_by_keys`](Self::remove_many_by_keys) and
+    /// [`convert_all`](Self::convert_all), which otherwise sends no event. A watcher can no longer
+    /// know which values changed: a bulk event is sent once to each watcher whose key filter
+    /// matches at least one of the values, and it has no value ([`Event::inner`](crate::watch::Event::inner)
+    /// returns an error). In exchange, a large cleanup does not flood the channels of the watchers. The
+    /// mirror, [`on_commit`](crate::Builder::on_commit) and
+    /// [`abort_returning`](Self::abort_returning) still get the values one by one.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use native_db::watch::Event;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let rw = db.rw_transaction()?;
+    ///     for id in 0..100 {
+    ///         rw.insert(Data { id })?;
+    ///     }
+    ///     rw.commit()?;
+    ///
+    ///     let (recv, _) = db.watch().scan().primary().all::<Data>()?;
+    ///
+    ///     let mut rw = db.rw_transaction()?;
+    ///     rw.set_bulk_events(true);
+    ///     let _: Vec<Data> = rw.remove_many_by_keys(0..100u64)?;
+    ///     rw.commit()?;
+    ///
+    ///     assert!(matches!(recv.try_recv(), Ok(Event::BulkDelete { count: 100, .. })));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_bulk_events(&mut self, enabled: bool) {
+        self.bulk_events = enabled;
+    }
+
+    /// Whether the bulk events are enabled, see [`set_bulk_events`](Self::set_bulk_events).
+    pub fn bulk_events(&self) -> bool {
+        self.bulk_events
+    }
+
+    /// Whether 2-phase commit is enabled, see [`set_two_phase_commit`](Self::set_two_phase_commit).
+    pub fn two_phase_commit(&self) -> bool {
+        self.internal.two_phase_commit
+    }
+
+    /// Whether quick-repair is enabled, see [`set_quick_repair`](Self::set_quick_repair).
+    pub fn quick_repair(&self) -> bool {
+        self.internal.quick_repair
+    }
+}
+
+/// Returns the lowest and highest keys, in the order of the tables.
+fn key_range(keys: Vec<Key>) -> Option<RangeInclusive<Key>> {
+    let start = keys.iter().min_by(|a, b| a.as_slice().cmp(b.as_slice()))?;
+    let end = keys.iter().max_by(|a, b| a.as_slice().cmp(b.as_slice()))?;
+    Some(start.clone()..=end.clone())
 }
@@ -0,0 +1,24 @@
+use crate::transaction::RTransaction;
+use std::ops::Deref;
+
+/// A read-only transaction shared by several threads, created by
+/// [`snapshot_reader`](crate::Database::snapshot_reader).
+///
+/// All the threads read the same version of the database: the methods of
+/// [`RTransaction`](crate::transaction::RTransaction) (e.g. [`get`](crate::transaction::RTransaction::get),
+/// [`scan`](crate::transaction::RTransaction::scan)) are available through `Deref`.
+pub struct SnapshotReader<'db>(pub(crate) RTransaction<'db>);
+
+impl<'db> Deref for SnapshotReader<'db> {
+    type Target = RTransaction<'db>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+// The snapshot is only useful if it can be shared between threads.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SnapshotReader>();
+};
@@ -0,0 +1,28 @@
+use crate::db_type::Key;
+use crate::watch::{Batch, Event};
+
+/// A change staged in a transaction, returned by
+/// [`abort_returning`](crate::transaction::RwTransaction::abort_returning).
+#[derive(Debug, Clone)]
+pub struct StagedChange {
+    /// Name of the primary table of the model, e.g. `1_1_id`.
+    pub table: String,
+    /// Primary key of the value.
+    pub primary_key: Key,
+    /// The change and its values, the same event as the one sent to the watchers on commit.
+    pub event: Event,
+}
+
+impl StagedChange {
+    /// Returns the changes of the batch in the order they were staged.
+    pub(crate) fn from_batch(batch: &Batch) -> Vec<Self> {
+        batch
+            .requests()
+            .map(|(request, event)| Self {
+                table: request.table_name.clone(),
+                primary_key: request.primary_key.clone(),
+                event: event.clone(),
+            })
+            .collect()
+    }
+}
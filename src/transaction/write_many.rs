@@ -0,0 +1,23 @@
+/// What [`write_many`](crate::transaction::RwTransaction::write_many) does with a value whose
+/// primary key already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WritePolicy {
+    /// Fail with [`DuplicateKey`](crate::db_type::Error::DuplicateKey), like
+    /// [`insert`](crate::transaction::RwTransaction::insert).
+    InsertFailOnDup,
+    /// Replace the stored value, like [`upsert`](crate::transaction::RwTransaction::upsert).
+    Upsert,
+    /// Keep the stored value and skip the new one.
+    InsertOrIgnore,
+}
+
+/// Result of [`write_many`](crate::transaction::RwTransaction::write_many).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WriteManyReport {
+    /// Number of inserted values.
+    pub inserted: u64,
+    /// Number of updated values, only with [`Upsert`](WritePolicy::Upsert).
+    pub updated: u64,
+    /// Number of values skipped because their primary key exists.
+    pub skipped: u64,
+}
@@ -0,0 +1,92 @@
+use crate::db_type::{Key, KeyDefinition, KeyOptions};
+use crate::watch::{Batch, Event};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+// In memory maps of the unique secondary keys registered with
+// `Builder::cache_unique_secondary`. They are only updated with the changes of the committed
+// transactions, so an aborted transaction never reaches them. A read checks the item found
+// with the cache against its snapshot and falls back to the secondary table otherwise.
+
+#[derive(Default)]
+struct Cache {
+    // Secondary key -> original primary key
+    primary_keys: HashMap<Key, Key>,
+    // Original primary key -> secondary keys, to remove the previous secondary keys on update
+    secondary_keys: HashMap<Key, Vec<Key>>,
+}
+
+impl Cache {
+    fn insert(&mut self, secondary_key: Key, primary_key: Key) {
+        self.primary_keys
+            .insert(secondary_key.clone(), primary_key.clone());
+        self.secondary_keys
+            .entry(primary_key)
+            .or_default()
+            .push(secondary_key);
+    }
+
+    fn remove(&mut self, primary_key: &Key) {
+        for secondary_key in self.secondary_keys.remove(primary_key).unwrap_or_default() {
+            self.primary_keys.remove(&secondary_key);
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct UniqueSecondaryCaches(HashMap<KeyDefinition<KeyOptions>, RwLock<Cache>>);
+
+impl UniqueSecondaryCaches {
+    /// Registers the cache of `key_def` with the `(secondary key, original primary key)`
+    /// entries of its table.
+    pub(crate) fn load(
+        &mut self,
+        key_def: KeyDefinition<KeyOptions>,
+        entries: impl IntoIterator<Item = (Key, Key)>,
+    ) {
+        let mut cache = Cache::default();
+        for (secondary_key, primary_key) in entries {
+            cache.insert(secondary_key, primary_key);
+        }
+        self.0.insert(key_def, RwLock::new(cache));
+    }
+
+    /// Returns the original primary key cached for `secondary_key`, `None` if the key is
+    /// not cached.
+    pub(crate) fn get(
+        &self,
+        key_def: &KeyDefinition<KeyOptions>,
+        secondary_key: &Key,
+    ) -> Option<Key> {
+        let cache = self.0.get(key_def)?;
+        let cache = cache.read().unwrap();
+        cache.primary_keys.get(secondary_key).cloned()
+    }
+
+    /// Applies the changes of a committed transaction.
+    pub(crate) fn apply(&self, batch: &Batch) {
+        if self.0.is_empty() {
+            return;
+        }
+        for (request, event) in batch.requests() {
+            for (key_def, key_entry) in &request.secondary_keys_value {
+                let Some(cache) = self.0.get(key_def) else {
+                    continue;
+                };
+                let mut cache = cache.write().unwrap();
+                // The request holds the new keys of an update: remove the previous ones
+                // through the primary key.
+                cache.remove(&request.primary_key);
+                if let Some((old_primary_key, _)) = &request.old_keys {
+                    cache.remove(old_primary_key);
+                }
+                if let Event::Delete(_) = event {
+                    continue;
+                }
+                for secondary_key in key_entry.clone().into_keys() {
+                    cache.insert(secondary_key, request.primary_key.clone());
+                }
+            }
+        }
+    }
+}
@@ -3,7 +3,10 @@ mod redb1_to_redb2;
 #[cfg(feature = "upgrade_0_7_x")]
 mod secondary_index_table_multimap;
 
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use crate::{database_instance::DatabaseInstance, db_type::Result, Configuration, ModelBuilder};
 
@@ -12,6 +15,12 @@ pub(crate) fn upgrade_redb(
     path: impl AsRef<Path>,
     _model_builder: &HashMap<String, ModelBuilder>,
 ) -> Result<DatabaseInstance> {
+    // Without the feature, the generic format error would not tell how to open the file.
+    if cfg!(not(feature = "redb1")) {
+        return Err(crate::db_type::Error::RedbUpgradeAvailable {
+            path: path.as_ref().to_path_buf(),
+        });
+    }
     #[cfg(feature = "redb1")]
     redb1_to_redb2::upgrade_redb1_to_redb2(database_configuration, &path, _model_builder)?;
 
@@ -21,6 +30,27 @@ pub(crate) fn upgrade_redb(
     Ok(database_instance)
 }
 
+/// Completes an upgrade interrupted by a crash after the removal of the legacy file. A partial
+/// copy is only removed once the legacy file is known to require the upgrade.
+pub(crate) fn recover_interrupted_upgrade(
+    _database_configuration: &Configuration,
+    _path: impl AsRef<Path>,
+) -> Result<()> {
+    #[cfg(feature = "redb1")]
+    redb1_to_redb2::recover_interrupted_upgrade(_database_configuration, _path)?;
+
+    Ok(())
+}
+
+/// Returns the file left behind by an interrupted upgrade, if any.
+pub(crate) fn interrupted_upgrade_file(_path: &Path) -> Option<PathBuf> {
+    #[cfg(feature = "redb1")]
+    return redb1_to_redb2::interrupted_upgrade_file(_path);
+
+    #[cfg(not(feature = "redb1"))]
+    None
+}
+
 pub(crate) fn upgrade_underlying_database(
     _database_instance: &DatabaseInstance,
     _model_builder: &HashMap<String, ModelBuilder>,
@@ -9,7 +9,7 @@ use crate::{Configuration, ModelBuilder};
 use redb as redb2;
 use redb1;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub(crate) type Redb1PrimaryTableDefinition<'a> =
     redb1::TableDefinition<'a, Redb1DatabaseInnerKeyValue, &'static [u8]>;
@@ -83,6 +83,66 @@ fn upgrade_secondary_table(
     Ok(())
 }
 
+/// The upgrade copies the data to this file, then replaces the redb1 file by it. `None` if the
+/// path has no file name.
+fn redb2_path(redb1_path: &Path) -> Option<PathBuf> {
+    let mut file_name = redb1_path.file_name()?.to_os_string();
+    file_name.push("_redb2");
+    Some(redb1_path.with_file_name(file_name))
+}
+
+pub(crate) fn interrupted_upgrade_file(redb1_path: &Path) -> Option<PathBuf> {
+    let redb2_path = redb2_path(redb1_path)?;
+    redb2_path.exists().then_some(redb2_path)
+}
+
+/// Recovers from an upgrade interrupted by a crash after the removal of the redb1 file: the
+/// redb2 file holds the complete copy and replaces it. Does nothing if the redb1 file exists or
+/// if the redb2 file is not a redb2 database.
+pub(crate) fn recover_interrupted_upgrade(
+    database_configuration: &Configuration,
+    path: impl AsRef<Path>,
+) -> Result<()> {
+    let redb1_path = path.as_ref();
+    let Some(redb2_path) = redb2_path(redb1_path) else {
+        return Ok(());
+    };
+    if redb1_path.exists() || !redb2_path.exists() {
+        return Ok(());
+    }
+
+    // The process running the upgrade holds the lock of the redb2 file.
+    match database_configuration.new_rdb_builder().open(&redb2_path) {
+        Ok(_) => {}
+        Err(error @ redb2::DatabaseError::DatabaseAlreadyOpen) => return Err(error.into()),
+        Err(_) => return Ok(()),
+    }
+
+    std::fs::rename(&redb2_path, redb1_path)?;
+    Ok(())
+}
+
+/// Removes the partial redb2 file left by an upgrade interrupted during the copy, the upgrade
+/// starts over from the redb1 file.
+fn discard_interrupted_upgrade(
+    database_configuration: &Configuration,
+    redb2_path: &Path,
+) -> Result<()> {
+    if !redb2_path.exists() {
+        return Ok(());
+    }
+
+    // The process running the upgrade holds the lock of the redb2 file.
+    if let Err(error @ redb2::DatabaseError::DatabaseAlreadyOpen) =
+        database_configuration.new_rdb_builder().open(redb2_path)
+    {
+        return Err(error.into());
+    }
+
+    std::fs::remove_file(redb2_path)?;
+    Ok(())
+}
+
 pub(crate) fn upgrade_redb1_to_redb2(
     database_configuration: &Configuration,
     path: impl AsRef<Path>,
@@ -92,10 +152,13 @@ pub(crate) fn upgrade_redb1_to_redb2(
     let redb2_builder = database_configuration.new_rdb_builder();
 
     let redb1_path = path.as_ref().to_path_buf();
-    let redb2_path = redb1_path.with_file_name(format!(
-        "{}_redb2",
-        redb1_path.file_name().unwrap().to_str().unwrap()
-    ));
+    let redb2_path = redb2_path(&redb1_path).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the path of the database has no file name",
+        )
+    })?;
+    discard_interrupted_upgrade(database_configuration, &redb2_path)?;
 
     let db1 = redb1_builder.open(&redb1_path)?;
     let mut db2 = redb2_builder.create(&redb2_path)?;
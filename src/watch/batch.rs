@@ -1,31 +1,123 @@
+use crate::transaction::CommitInfo;
 use crate::watch::{Event, WatcherRequest};
 use std::fmt::Debug;
+use std::ops::Range;
 
 #[derive(Clone)]
-pub struct Batch(Vec<(WatcherRequest, Event)>);
+pub struct Batch {
+    entries: Vec<(WatcherRequest, Event)>,
+    // Bulk events sent to the watchers instead of the entries in their range. The entries are
+    // kept for the mirror, the caches and the commit hooks.
+    summaries: Vec<Summary>,
+}
+
+#[derive(Clone)]
+struct Summary {
+    entries: Range<usize>,
+    // Requests of the summarized values, matched against the key filters of the watchers.
+    requests: Vec<WatcherRequest>,
+    event: Event,
+}
 
 impl Batch {
     pub(crate) fn new() -> Self {
-        Self(Vec::new())
+        Self {
+            entries: Vec::new(),
+            summaries: Vec::new(),
+        }
     }
 
     pub(crate) fn add(&mut self, watcher_request: WatcherRequest, event: Event) {
-        self.0.push((watcher_request, event));
+        self.entries.push((watcher_request, event));
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Send `event` to the watchers instead of the entries added since `start`.
+    pub(crate) fn summarize(&mut self, start: usize, event: Event) {
+        let entries = start..self.entries.len();
+        let requests = self.entries[entries.clone()]
+            .iter()
+            .map(|(request, _)| request.clone())
+            .collect();
+        self.summaries.push(Summary {
+            entries,
+            requests,
+            event,
+        });
+    }
+
+    /// Send `event` to the watchers of the values of `requests`, which have no entry.
+    pub(crate) fn summarize_requests(&mut self, requests: Vec<WatcherRequest>, event: Event) {
+        let end = self.entries.len();
+        self.summaries.push(Summary {
+            entries: end..end,
+            requests,
+            event,
+        });
+    }
+
+    /// Returns the bulk events with the requests of the values they summarize, to be sent after
+    /// the entries which are not summarized.
+    pub(crate) fn summaries(&self) -> impl Iterator<Item = (&[WatcherRequest], &Event)> {
+        self.summaries
+            .iter()
+            .map(|summary| (summary.requests.as_slice(), &summary.event))
+    }
+
+    /// Returns the requests and their events in the order they were added.
+    pub(crate) fn requests(&self) -> impl Iterator<Item = &(WatcherRequest, Event)> {
+        self.entries.iter()
+    }
+
+    /// Returns the events in the order they were added.
+    pub(crate) fn events(&self) -> Vec<Event> {
+        self.entries
+            .iter()
+            .map(|(_, event)| event.clone())
+            .collect()
+    }
+
+    pub(crate) fn commit_info(&self) -> CommitInfo {
+        let mut commit_info = CommitInfo::default();
+        for (_, event) in &self.entries {
+            match event {
+                Event::Insert(_) => commit_info.inserts += 1,
+                Event::Update(_) => commit_info.updates += 1,
+                Event::Delete(_) => commit_info.removes += 1,
+                // Only in the summaries
+                Event::BulkInsert { .. } | Event::BulkDelete { .. } => {}
+            }
+        }
+        commit_info
     }
 }
 
+/// Pops the entries which are not summarized.
 impl Iterator for Batch {
     type Item = (WatcherRequest, Event);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.pop()
+        while let Some(entry) = self.entries.pop() {
+            let index = self.entries.len();
+            if !self
+                .summaries
+                .iter()
+                .any(|summary| summary.entries.contains(&index))
+            {
+                return Some(entry);
+            }
+        }
+        None
     }
 }
 
 impl Debug for Batch {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
-        for (watcher_request, event) in &self.0 {
+        for (watcher_request, event) in &self.entries {
             write!(f, "({:?}, {:?}), ", watcher_request.primary_key, event)?;
         }
         write!(f, "]")
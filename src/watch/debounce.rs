@@ -0,0 +1,95 @@
+use crate::db_type::{Key, ToInput};
+use crate::watch::{Event, MpscReceiver};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Forwards the events of `receiver` to the returned receiver, the events received within
+/// `window` after a first event are coalesced by primary key and sent together at the end of
+/// the window, see [`Watch::debounce`](crate::watch::query::Watch::debounce).
+///
+/// The forwarding task is spawned on `runtime`, it ends when `receiver` is closed (after sending the pending events) or
+/// when the returned receiver is dropped.
+pub(crate) fn debounce<T: ToInput>(
+    runtime: &tokio::runtime::Handle,
+    mut receiver: MpscReceiver<Event>,
+    window: Duration,
+) -> MpscReceiver<Event> {
+    let (sender, debounced_receiver) = tokio::sync::mpsc::unbounded_channel();
+    let primary_key: fn(&Event) -> Option<Key> = primary_key::<T>;
+    runtime.spawn(async move {
+        while let Some(event) = receiver.recv().await {
+            let deadline = tokio::time::Instant::now() + window;
+            let mut pending = PendingEvents::default();
+            pending.push(primary_key(&event), event);
+            let closed = loop {
+                match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                    Ok(Some(event)) => pending.push(primary_key(&event), event),
+                    Ok(None) => break true,
+                    Err(_) => break false,
+                }
+            };
+            for event in pending.events.into_iter().flatten() {
+                if sender.send(event).is_err() {
+                    return;
+                }
+            }
+            if closed {
+                return;
+            }
+        }
+    });
+    debounced_receiver
+}
+
+/// The bulk events and the values which can't be decoded have no key, they are not coalesced.
+fn primary_key<T: ToInput>(event: &Event) -> Option<Key> {
+    event
+        .inner::<T>()
+        .ok()
+        .map(|value| value.native_db_primary_key())
+}
+
+/// Events in the order of the first event of each key.
+#[derive(Default)]
+struct PendingEvents {
+    events: Vec<Option<Event>>,
+    indexes: HashMap<Key, usize>,
+}
+
+impl PendingEvents {
+    fn push(&mut self, key: Option<Key>, event: Event) {
+        let Some(key) = key else {
+            self.events.push(Some(event));
+            return;
+        };
+        let Some(&index) = self.indexes.get(&key) else {
+            self.indexes.insert(key, self.events.len());
+            self.events.push(Some(event));
+            return;
+        };
+        let previous = self.events[index].take().expect("pending event");
+        match coalesce(previous, event) {
+            Some(event) => self.events[index] = Some(event),
+            None => {
+                self.indexes.remove(&key);
+            }
+        }
+    }
+}
+
+/// Merge two events of the same key into the event going from the state before `previous` to
+/// the state after `next`, `None` if the value neither existed before nor after.
+fn coalesce(previous: Event, next: Event) -> Option<Event> {
+    match (previous, next) {
+        (Event::Insert(_), Event::Update(update)) => Some(Event::new_insert(update.new)),
+        (Event::Insert(_), Event::Delete(_)) => None,
+        (Event::Update(previous), Event::Update(update)) => {
+            Some(Event::new_update(previous.old, update.new))
+        }
+        (Event::Update(previous), Event::Delete(_)) => Some(Event::new_delete(previous.old)),
+        (Event::Delete(delete), Event::Insert(insert)) => {
+            Some(Event::new_update(delete.0, insert.0))
+        }
+        (_, next) => Some(next),
+    }
+}
@@ -1,11 +1,31 @@
-use crate::db_type::{Output, Result, ToInput};
+use crate::db_type::{Error, Key, Output, Result, ToInput};
 use std::fmt::Debug;
+use std::ops::RangeInclusive;
 
 #[derive(Clone)]
+#[non_exhaustive]
 pub enum Event {
     Insert(Insert),
     Update(Update),
     Delete(Delete),
+    /// Summary of the values inserted (or updated by [`write_many`](crate::transaction::RwTransaction::write_many))
+    /// by a bulk operation, sent instead of an [`Insert`](Event::Insert) per value when
+    /// [`set_bulk_events`](crate::transaction::RwTransaction::set_bulk_events) is enabled.
+    BulkInsert {
+        /// Name of the primary table of the model, e.g. `1_1_id`.
+        table: String,
+        count: usize,
+    },
+    /// Summary of the values removed by a bulk operation, sent instead of a
+    /// [`Delete`](Event::Delete) per value when
+    /// [`set_bulk_events`](crate::transaction::RwTransaction::set_bulk_events) is enabled.
+    BulkDelete {
+        /// Name of the primary table of the model, e.g. `1_1_id`.
+        table: String,
+        count: usize,
+        /// Lowest and highest primary keys removed.
+        range: Option<RangeInclusive<Key>>,
+    },
 }
 
 impl Event {
@@ -28,12 +48,17 @@ impl Event {
 /// Get the inner value of the event
 ///
 /// NOTE: for update, it returns the new value
+///
+/// Returns [`BulkEventWithoutValue`](crate::db_type::Error::BulkEventWithoutValue) for the bulk events.
 impl Event {
     pub fn inner<T: ToInput>(&self) -> Result<T> {
         match self {
             Event::Insert(insert) => insert.inner(),
             Event::Update(update) => update.inner_new(),
             Event::Delete(delete) => delete.inner(),
+            Event::BulkInsert { .. } | Event::BulkDelete { .. } => {
+                Err(Error::BulkEventWithoutValue)
+            }
         }
     }
 }
@@ -44,6 +69,8 @@ impl Debug for Event {
             Event::Insert(_) => write!(f, "Insert"),
             Event::Update(_) => write!(f, "Update"),
             Event::Delete(_) => write!(f, "Delete"),
+            Event::BulkInsert { table, count } => write!(f, "BulkInsert({table}, {count})"),
+            Event::BulkDelete { table, count, .. } => write!(f, "BulkDelete({table}, {count})"),
         }
     }
 }
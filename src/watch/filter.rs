@@ -1,4 +1,4 @@
-use crate::db_type::{Key, KeyDefinition, KeyOptions, ToKeyDefinition};
+use crate::db_type::{Key, KeyDefinition, KeyOptions, KeyRange, ToKeyDefinition};
 
 #[derive(Eq, PartialEq, Clone)]
 pub(crate) struct TableFilter {
@@ -10,6 +10,7 @@ pub(crate) struct TableFilter {
 pub(crate) enum KeyFilter {
     Primary(Option<Key>),
     PrimaryStartWith(Key),
+    PrimaryRange(KeyRange),
     Secondary(KeyDefinition<KeyOptions>, Option<Key>),
     SecondaryStartWith(KeyDefinition<KeyOptions>, Key),
 }
@@ -29,6 +30,13 @@ impl TableFilter {
         }
     }
 
+    pub(crate) fn new_primary_range(table_name: String, range: KeyRange) -> Self {
+        Self {
+            table_name,
+            key_filter: KeyFilter::PrimaryRange(range),
+        }
+    }
+
     pub(crate) fn new_secondary<K: ToKeyDefinition<KeyOptions>>(
         table_name: String,
         key_def: &K,
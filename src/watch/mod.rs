@@ -1,4 +1,6 @@
 mod batch;
+#[cfg(feature = "tokio")]
+mod debounce;
 mod event;
 mod filter;
 pub mod query;
@@ -6,12 +8,15 @@ mod request;
 mod sender;
 
 pub(crate) use batch::*;
+#[cfg(feature = "tokio")]
+pub(crate) use debounce::*;
 pub use event::*;
 pub(crate) use filter::*;
 pub(crate) use request::*;
 pub(crate) use sender::*;
 
 use std::{
+    collections::HashMap,
     sync::{Arc, RwLock},
     vec,
 };
@@ -45,6 +50,52 @@ pub type MpscSender<T> = tokio::sync::mpsc::UnboundedSender<T>;
 #[cfg(feature = "tokio")]
 pub type MpscReceiver<T> = tokio::sync::mpsc::UnboundedReceiver<T>;
 
+/// Wait for at least one event and return it with the other events already received, up to
+/// `max` events, e.g. to process the events of a commit together instead of one by one.
+///
+/// Returns an empty vector if the channel is closed (the database is dropped or the watcher
+/// removed with [`unwatch`](crate::Database::unwatch)) or if `max` is `0`. It is
+/// [`UnboundedReceiver::recv_many`](tokio::sync::mpsc::UnboundedReceiver::recv_many) with a new
+/// buffer.
+///
+/// # Example
+/// ```rust
+/// use native_db::*;
+/// use native_db::native_model::{native_model, Model};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// #[native_model(id=1, version=1)]
+/// #[native_db]
+/// struct Data {
+///     #[primary_key]
+///     id: u64,
+/// }
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() -> Result<(), db_type::Error> {
+///     let mut models = Models::new();
+///     models.define::<Data>()?;
+///     let db = Builder::new().create_in_memory(&models)?;
+///
+///     let (mut recv, _) = db.watch().scan().primary().all::<Data>()?;
+///     let rw = db.rw_transaction()?;
+///     rw.insert(Data { id: 1 })?;
+///     rw.insert(Data { id: 2 })?;
+///     rw.commit()?;
+///
+///     let events = watch::recv_batch(&mut recv, 100).await;
+///     assert_eq!(events.len(), 2);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn recv_batch(receiver: &mut MpscReceiver<Event>, max: usize) -> Vec<Event> {
+    let mut events = Vec::with_capacity(max.min(64));
+    receiver.recv_many(&mut events, max).await;
+    events
+}
+
 pub(crate) fn push_batch(
     senders: Arc<RwLock<Watchers>>,
     batch: Batch,
@@ -54,6 +105,15 @@ pub(crate) fn push_batch(
         .map_err(|_| WatchEventError::LockErrorPoisoned)?;
 
     let mut unused_watchers = vec![];
+    // A bulk event is sent once to each watcher matching one of the values it summarizes.
+    let mut summaries = vec![];
+    for (requests, event) in batch.summaries() {
+        let mut senders = HashMap::new();
+        for request in requests {
+            senders.extend(watchers.find_senders(request));
+        }
+        summaries.push((event.clone(), senders));
+    }
     for (watcher_request, event) in batch {
         for (id, sender) in watchers.find_senders(&watcher_request) {
             let l_sender = sender.lock().unwrap();
@@ -62,6 +122,14 @@ pub(crate) fn push_batch(
             }
         }
     }
+    for (event, senders) in summaries {
+        for (id, sender) in senders {
+            let l_sender = sender.lock().unwrap();
+            if let Err(SendError(_)) = l_sender.send(event.clone()) {
+                unused_watchers.push(id);
+            }
+        }
+    }
     // Drop the lock before removing the watchers to avoid deadlock
     drop(watchers);
 
@@ -3,15 +3,17 @@ use crate::db_type::{
     ToKeyDefinition,
 };
 use crate::watch;
-use crate::watch::query::internal;
+use crate::watch::query::{internal, with_watcher_id, WatcherId};
 use crate::watch::MpscReceiver;
+use std::marker::PhantomData;
 
 /// Watch only one value.
-pub struct WatchGet<'db, 'w> {
+pub struct WatchGet<'db, 'w, I = u64> {
     pub(crate) internal: &'w internal::InternalWatch<'db>,
+    pub(crate) _marker: PhantomData<I>,
 }
 
-impl WatchGet<'_, '_> {
+impl<'db, I: WatcherId<'db>> WatchGet<'db, '_, I> {
     /// Watch the primary key.
     ///
     /// Returns a channel receiver and the watcher id.
@@ -41,13 +43,10 @@ impl WatchGet<'_, '_> {
     ///     Ok(())
     /// }
     /// ```
-    pub fn primary<T: ToInput>(
-        &self,
-        key: impl ToKey,
-    ) -> Result<(MpscReceiver<watch::Event>, u64)> {
+    pub fn primary<T: ToInput>(&self, key: impl ToKey) -> Result<(MpscReceiver<watch::Event>, I)> {
         let model = T::native_db_model();
         check_key_type(&model, &key)?;
-        self.internal.watch_primary::<T>(key)
+        with_watcher_id(self.internal, self.internal.watch_primary::<T>(key))
     }
 
     /// Watch the secondary key.
@@ -85,8 +84,11 @@ impl WatchGet<'_, '_> {
         &self,
         key_def: impl ToKeyDefinition<KeyOptions>,
         key: impl ToKey,
-    ) -> Result<(MpscReceiver<watch::Event>, u64)> {
+    ) -> Result<(MpscReceiver<watch::Event>, I)> {
         check_key_type_from_key_definition(&key_def.key_definition(), &key)?;
-        self.internal.watch_secondary::<T>(&key_def, key)
+        with_watcher_id(
+            self.internal,
+            self.internal.watch_secondary::<T>(&key_def, key),
+        )
     }
 }
@@ -0,0 +1,72 @@
+use crate::db_type::Result;
+use crate::watch::query::internal::InternalWatch;
+use crate::watch::{Event, MpscReceiver, Watchers};
+use std::sync::{Arc, RwLock};
+
+/// The watcher id returned by the watch queries: the raw id with [`Database::watch`](crate::Database::watch)
+/// or a [`WatchGuard`] with [`Database::watch_scoped`](crate::Database::watch_scoped).
+pub trait WatcherId<'db>: private::Sealed<'db> {}
+
+mod private {
+    use super::WatchGuard;
+
+    pub trait Sealed<'db> {
+        fn from_guard(guard: WatchGuard<'db>) -> Self;
+    }
+}
+
+impl<'db> WatcherId<'db> for u64 {}
+
+impl<'db> private::Sealed<'db> for u64 {
+    fn from_guard(guard: WatchGuard<'db>) -> Self {
+        let id = guard.id;
+        // Keep the watcher, it is removed with `Database::unwatch`
+        std::mem::forget(guard);
+        id
+    }
+}
+
+/// Unwatch the watcher when dropped, returned by the queries of [`Database::watch_scoped`](crate::Database::watch_scoped).
+///
+/// Keep the guard as long as the receiver is used, the events are not sent anymore once
+/// it is dropped.
+#[must_use = "the watcher is removed when the guard is dropped"]
+pub struct WatchGuard<'db> {
+    watchers: &'db Arc<RwLock<Watchers>>,
+    id: u64,
+}
+
+impl WatchGuard<'_> {
+    /// The watcher id, see [`Database::unwatch`](crate::Database::unwatch).
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl<'db> WatcherId<'db> for WatchGuard<'db> {}
+
+impl<'db> private::Sealed<'db> for WatchGuard<'db> {
+    fn from_guard(guard: WatchGuard<'db>) -> Self {
+        guard
+    }
+}
+
+impl Drop for WatchGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut watchers) = self.watchers.write() {
+            watchers.remove_sender(self.id);
+        }
+    }
+}
+
+pub(crate) fn with_watcher_id<'db, I: WatcherId<'db>>(
+    internal: &InternalWatch<'db>,
+    watch: Result<(MpscReceiver<Event>, u64)>,
+) -> Result<(MpscReceiver<Event>, I)> {
+    let (receiver, id) = watch?;
+    let guard = WatchGuard {
+        watchers: internal.watchers,
+        id,
+    };
+    Ok((receiver, I::from_guard(guard)))
+}
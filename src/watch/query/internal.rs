@@ -1,16 +1,21 @@
-use crate::db_type::{Error, KeyOptions, Result, ToInput, ToKey, ToKeyDefinition};
+use crate::db_type::{Error, Key, KeyOptions, KeyRange, Result, ToInput, ToKey, ToKeyDefinition};
 use crate::watch;
 use crate::watch::{MpscReceiver, TableFilter};
+use std::ops::RangeBounds;
 use std::sync::atomic::AtomicU64;
 use std::sync::{Arc, Mutex, RwLock};
 
 pub(crate) struct InternalWatch<'db> {
     pub(crate) watchers: &'db Arc<RwLock<watch::Watchers>>,
     pub(crate) watchers_counter_id: &'db AtomicU64,
+    #[cfg(feature = "tokio")]
+    pub(crate) debounce: Option<std::time::Duration>,
 }
 
 impl InternalWatch<'_> {
-    fn watch_generic(
+    // `T` decodes the keys of the debounced events.
+    #[cfg_attr(not(feature = "tokio"), allow(clippy::extra_unused_type_parameters))]
+    fn watch_generic<T: ToInput>(
         &self,
         table_filter: watch::TableFilter,
     ) -> Result<(MpscReceiver<watch::Event>, u64)> {
@@ -19,9 +24,21 @@ impl InternalWatch<'_> {
         #[cfg(feature = "tokio")]
         let (event_sender, event_receiver) = tokio::sync::mpsc::unbounded_channel();
         let event_sender = Arc::new(Mutex::new(event_sender));
+        #[cfg(feature = "tokio")]
+        let runtime = match self.debounce {
+            Some(_) => Some(
+                tokio::runtime::Handle::try_current().map_err(|_| Error::TokioRuntimeNotFound)?,
+            ),
+            None => None,
+        };
         let id = self.generate_watcher_id()?;
         let mut watchers = self.watchers.write().unwrap();
         watchers.add_sender(id, &table_filter, Arc::clone(&event_sender));
+        #[cfg(feature = "tokio")]
+        let event_receiver = match (self.debounce, runtime) {
+            (Some(window), Some(runtime)) => watch::debounce::<T>(&runtime, event_receiver, window),
+            _ => event_receiver,
+        };
         Ok((event_receiver, id))
     }
 
@@ -44,7 +61,7 @@ impl InternalWatch<'_> {
         let key = key.to_key();
         let table_filter =
             TableFilter::new_primary(table_name.unique_table_name.clone(), Some(key));
-        self.watch_generic(table_filter)
+        self.watch_generic::<T>(table_filter)
     }
 
     pub(crate) fn watch_primary_all<T: ToInput>(
@@ -52,7 +69,7 @@ impl InternalWatch<'_> {
     ) -> Result<(MpscReceiver<watch::Event>, u64)> {
         let table_name = T::native_db_model().primary_key;
         let table_filter = TableFilter::new_primary(table_name.unique_table_name.clone(), None);
-        self.watch_generic(table_filter)
+        self.watch_generic::<T>(table_filter)
     }
 
     pub(crate) fn watch_primary_start_with<T: ToInput>(
@@ -63,7 +80,19 @@ impl InternalWatch<'_> {
         let start_with = start_with.to_key();
         let table_filter =
             TableFilter::new_primary_start_with(table_name.unique_table_name.clone(), start_with);
-        self.watch_generic(table_filter)
+        self.watch_generic::<T>(table_filter)
+    }
+
+    pub(crate) fn watch_primary_range<T: ToInput, K: ToKey>(
+        &self,
+        range: impl RangeBounds<K>,
+    ) -> Result<(MpscReceiver<watch::Event>, u64)> {
+        let table_name = T::native_db_model().primary_key;
+        let table_filter = TableFilter::new_primary_range(
+            table_name.unique_table_name.clone(),
+            KeyRange::new(range),
+        );
+        self.watch_generic::<T>(table_filter)
     }
 
     pub(crate) fn watch_secondary<T: ToInput>(
@@ -75,7 +104,7 @@ impl InternalWatch<'_> {
         let key = key.to_key();
         let table_filter =
             TableFilter::new_secondary(table_name.unique_table_name.clone(), key_def, Some(key));
-        self.watch_generic(table_filter)
+        self.watch_generic::<T>(table_filter)
     }
 
     pub(crate) fn watch_secondary_all<T: ToInput>(
@@ -85,21 +114,20 @@ impl InternalWatch<'_> {
         let table_name = T::native_db_model().primary_key;
         let table_filter =
             TableFilter::new_secondary(table_name.unique_table_name.clone(), key_def, None);
-        self.watch_generic(table_filter)
+        self.watch_generic::<T>(table_filter)
     }
 
     pub(crate) fn watch_secondary_start_with<T: ToInput>(
         &self,
         key_def: &impl ToKeyDefinition<KeyOptions>,
-        start_with: impl ToKey,
+        start_with: Key,
     ) -> Result<(MpscReceiver<watch::Event>, u64)> {
         let table_name = T::native_db_model().primary_key;
-        let start_with = start_with.to_key();
         let table_filter = TableFilter::new_secondary_start_with(
             table_name.unique_table_name.clone(),
             key_def,
             start_with,
         );
-        self.watch_generic(table_filter)
+        self.watch_generic::<T>(table_filter)
     }
 }
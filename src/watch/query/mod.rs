@@ -1,33 +1,101 @@
 mod get;
+mod guard;
 mod internal;
 mod scan;
 
 pub use get::*;
+pub use guard::*;
 pub(crate) use internal::*;
 pub use scan::*;
 
+use std::marker::PhantomData;
+
 /// Watch queries.
-pub struct Watch<'db> {
+///
+/// The queries return a channel receiver and a watcher id `I`: the raw id with
+/// [`Database::watch`](crate::Database::watch) or a [`WatchGuard`] with
+/// [`Database::watch_scoped`](crate::Database::watch_scoped).
+pub struct Watch<'db, I = u64> {
     pub(crate) internal: InternalWatch<'db>,
+    pub(crate) _marker: PhantomData<I>,
 }
 
-impl<'db> Watch<'db> {
+impl<'db, I: WatcherId<'db>> Watch<'db, I> {
     /// Watch only one value.
     ///
     /// - [`primary`](crate::watch::query::WatchGet::primary) - Watch a item by primary key.
     /// - [`secondary`](crate::watch::query::WatchGet::secondary) - Watch a item by secondary key.
-    pub fn get<'w>(&'w self) -> WatchGet<'db, 'w> {
+    pub fn get<'w>(&'w self) -> WatchGet<'db, 'w, I> {
         WatchGet {
             internal: &self.internal,
+            _marker: PhantomData,
         }
     }
     /// Watch multiple values.
     ///
     /// - [`primary`](crate::watch::query::WatchScan::primary) - Watch items by primary key.
     /// - [`secondary`](crate::watch::query::WatchScan::secondary) - Watch items by secondary key.
-    pub fn scan<'w>(&'w self) -> WatchScan<'db, 'w> {
+    pub fn scan<'w>(&'w self) -> WatchScan<'db, 'w, I> {
         WatchScan {
             internal: &self.internal,
+            _marker: PhantomData,
         }
     }
+
+    /// Coalesce the events received within `window` after a first event, requires the
+    /// feature `tokio`.
+    ///
+    /// The events of the same primary key are merged into one event going from the value
+    /// before the first event to the value after the last one, e.g. an insert followed by
+    /// updates is received as an insert of the latest value. The events are sent at the end
+    /// of the window: they are delayed by up to `window`.
+    ///
+    /// The events are forwarded by a task spawned on the tokio runtime, the watch queries
+    /// return [`TokioRuntimeNotFound`](crate::db_type::Error::TokioRuntimeNotFound) if they
+    /// are called outside of a runtime.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    /// use std::time::Duration;
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     text: String,
+    /// }
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     let (mut recv, _) = db
+    ///         .watch()
+    ///         .debounce(Duration::from_millis(10))
+    ///         .get()
+    ///         .primary::<Data>(1u64)?;
+    ///     for text in ["a", "ab", "abc"] {
+    ///         let rw = db.rw_transaction()?;
+    ///         rw.upsert(Data { id: 1, text: text.to_string() })?;
+    ///         rw.commit()?;
+    ///     }
+    ///
+    ///     // One insert of the latest value
+    ///     let data: Data = recv.recv().await.unwrap().inner()?;
+    ///     assert_eq!(data.text, "abc");
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub fn debounce(mut self, window: std::time::Duration) -> Self {
+        self.internal.debounce = Some(window);
+        self
+    }
 }
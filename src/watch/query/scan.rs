@@ -1,27 +1,31 @@
 use crate::db_type::{
-    check_key_type, check_key_type_from_key_definition, KeyDefinition, KeyOptions, Result, ToInput,
-    ToKey, ToKeyDefinition,
+    check_key_component_type_from_key_definition, check_key_type,
+    check_key_type_from_key_definition, check_range_key_range_bounds, KeyDefinition, KeyOptions,
+    Result, ToInput, ToKey, ToKeyDefinition,
 };
 use crate::watch;
-use crate::watch::query::internal;
+use crate::watch::query::{internal, with_watcher_id, WatcherId};
 use crate::watch::MpscReceiver;
+use std::marker::PhantomData;
 use std::ops::RangeBounds;
 
 /// Watch multiple values.
-pub struct WatchScan<'db, 'w> {
+pub struct WatchScan<'db, 'w, I = u64> {
     pub(crate) internal: &'w internal::InternalWatch<'db>,
+    pub(crate) _marker: PhantomData<I>,
 }
 
 /// Watch multiple values.
-impl WatchScan<'_, '_> {
+impl<'db, 'w, I: WatcherId<'db>> WatchScan<'db, 'w, I> {
     /// Watch all values.
     ///
     /// - [`all`](crate::watch::query::WatchScanPrimary::all) - Watch all items.
     /// - [`start_with`](crate::watch::query::WatchScanPrimary::start_with) - Watch items with a primary key starting with a key.
     /// - [`range`](crate::watch::query::WatchScanPrimary::range) - Watch items with a primary key in a given range.
-    pub fn primary(&self) -> WatchScanPrimary {
+    pub fn primary(&self) -> WatchScanPrimary<'db, 'w, I> {
         WatchScanPrimary {
             internal: self.internal,
+            _marker: PhantomData,
         }
     }
 
@@ -29,21 +33,27 @@ impl WatchScan<'_, '_> {
     ///
     /// - [`all`](crate::watch::query::WatchScanSecondary::all) - Watch items with a given secondary key.
     /// - [`start_with`](crate::watch::query::WatchScanSecondary::start_with) - Watch items with a secondary key starting with a key.
+    /// - [`start_with_component`](crate::watch::query::WatchScanSecondary::start_with_component) - Watch items with a compound secondary key starting with the given components.
     /// - [`range`](crate::watch::query::WatchScanSecondary::range) - Watch items with a secondary key in a given range.
-    pub fn secondary(&self, key_def: impl ToKeyDefinition<KeyOptions>) -> WatchScanSecondary {
+    pub fn secondary(
+        &self,
+        key_def: impl ToKeyDefinition<KeyOptions>,
+    ) -> WatchScanSecondary<'db, 'w, I> {
         WatchScanSecondary {
             key_def: key_def.key_definition(),
             internal: self.internal,
+            _marker: PhantomData,
         }
     }
 }
 
 /// Watch all values.
-pub struct WatchScanPrimary<'db, 'w> {
+pub struct WatchScanPrimary<'db, 'w, I = u64> {
     pub(crate) internal: &'w internal::InternalWatch<'db>,
+    pub(crate) _marker: PhantomData<I>,
 }
 
-impl WatchScanPrimary<'_, '_> {
+impl<'db, I: WatcherId<'db>> WatchScanPrimary<'db, '_, I> {
     /// Watch all values.
     ///
     /// # Example
@@ -73,16 +83,48 @@ impl WatchScanPrimary<'_, '_> {
     ///     Ok(())
     /// }
     /// ```
-    pub fn all<T: ToInput>(&self) -> Result<(MpscReceiver<watch::Event>, u64)> {
-        self.internal.watch_primary_all::<T>()
+    pub fn all<T: ToInput>(&self) -> Result<(MpscReceiver<watch::Event>, I)> {
+        with_watcher_id(self.internal, self.internal.watch_primary_all::<T>())
     }
 
-    /// **TODO: needs to be implemented**
-    pub fn range<'a>(
+    /// Watch all values with a primary key in the given range.
+    ///
+    /// The primary keys are compared like in the tables, see [`scan`](crate::transaction::query::PrimaryScan::range).
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Data {
+    ///     #[primary_key]
+    ///     id: u64,
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Data>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     // Watch the values with an id from 1000 to 1999
+    ///     let (_recv, _id) = db.watch().scan().primary().range::<Data, _>(1000u64..2000u64)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn range<T: ToInput, R: RangeBounds<impl ToKey>>(
         &self,
-        _range: impl RangeBounds<&'a [u8]> + 'a,
-    ) -> Result<(MpscReceiver<watch::Event>, u64)> {
-        todo!()
+        range: R,
+    ) -> Result<(MpscReceiver<watch::Event>, I)> {
+        let model = T::native_db_model();
+        check_range_key_range_bounds(&model, &range)?;
+        with_watcher_id(
+            self.internal,
+            self.internal.watch_primary_range::<T, _>(range),
+        )
     }
 
     /// Watch all values starting with the given key.
@@ -117,20 +159,24 @@ impl WatchScanPrimary<'_, '_> {
     pub fn start_with<T: ToInput>(
         &self,
         start_with: impl ToKey,
-    ) -> Result<(MpscReceiver<watch::Event>, u64)> {
+    ) -> Result<(MpscReceiver<watch::Event>, I)> {
         let model = T::native_db_model();
         check_key_type(&model, &start_with)?;
-        self.internal.watch_primary_start_with::<T>(start_with)
+        with_watcher_id(
+            self.internal,
+            self.internal.watch_primary_start_with::<T>(start_with),
+        )
     }
 }
 
 /// Watch all values by secondary key.
-pub struct WatchScanSecondary<'db, 'w> {
+pub struct WatchScanSecondary<'db, 'w, I = u64> {
     pub(crate) key_def: KeyDefinition<KeyOptions>,
     pub(crate) internal: &'w internal::InternalWatch<'db>,
+    pub(crate) _marker: PhantomData<I>,
 }
 
-impl WatchScanSecondary<'_, '_> {
+impl<'db, I: WatcherId<'db>> WatchScanSecondary<'db, '_, I> {
     /// Watch all values by secondary key.
     ///
     /// # Example
@@ -162,14 +208,17 @@ impl WatchScanSecondary<'_, '_> {
     ///     Ok(())
     /// }
     /// ```
-    pub fn all<T: ToInput>(&self) -> Result<(MpscReceiver<watch::Event>, u64)> {
-        self.internal.watch_secondary_all::<T>(&self.key_def)
+    pub fn all<T: ToInput>(&self) -> Result<(MpscReceiver<watch::Event>, I)> {
+        with_watcher_id(
+            self.internal,
+            self.internal.watch_secondary_all::<T>(&self.key_def),
+        )
     }
 
     pub fn range<'a, 'ws>(
         &'ws self,
         _range: impl RangeBounds<&'a [u8]> + 'a,
-    ) -> Result<(MpscReceiver<watch::Event>, u64)> {
+    ) -> Result<(MpscReceiver<watch::Event>, I)> {
         todo!()
     }
 
@@ -207,9 +256,61 @@ impl WatchScanSecondary<'_, '_> {
     pub fn start_with<T: ToInput>(
         &self,
         start_with: impl ToKey,
-    ) -> Result<(MpscReceiver<watch::Event>, u64)> {
+    ) -> Result<(MpscReceiver<watch::Event>, I)> {
         check_key_type_from_key_definition(&self.key_def, &start_with)?;
-        self.internal
-            .watch_secondary_start_with::<T>(&self.key_def, start_with)
+        with_watcher_id(
+            self.internal,
+            self.internal
+                .watch_secondary_start_with::<T>(&self.key_def, start_with.to_key()),
+        )
+    }
+
+    /// Watch all values whose compound secondary key (a tuple) starts with the given
+    /// components, e.g. all the changes of the category `"books"` for a key
+    /// `(category, title)`. `component` is the first component of the key, or a tuple of its
+    /// first components.
+    ///
+    /// The components match whole: `"books"` doesn't match the category `"bookstore"`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use native_db::*;
+    /// use native_db::native_model::{native_model, Model};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// #[native_model(id=1, version=1)]
+    /// #[native_db]
+    /// struct Product {
+    ///     #[primary_key]
+    ///     id: u64,
+    ///     #[secondary_key]
+    ///     category_price: (u32, u64),
+    /// }
+    ///
+    /// fn main() -> Result<(), db_type::Error> {
+    ///     let mut models = Models::new();
+    ///     models.define::<Product>()?;
+    ///     let db = Builder::new().create_in_memory(&models)?;
+    ///
+    ///     // Watch the products of the category 7, whatever their price
+    ///     let (_recv, _id) = db
+    ///         .watch()
+    ///         .scan()
+    ///         .secondary(ProductKey::category_price)
+    ///         .start_with_component::<Product>(7u32)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn start_with_component<T: ToInput>(
+        &self,
+        component: impl ToKey,
+    ) -> Result<(MpscReceiver<watch::Event>, I)> {
+        check_key_component_type_from_key_definition(&self.key_def, &component)?;
+        with_watcher_id(
+            self.internal,
+            self.internal
+                .watch_secondary_start_with::<T>(&self.key_def, component.to_component_key()),
+        )
     }
 }
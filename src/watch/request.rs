@@ -1,4 +1,4 @@
-use crate::db_type::{Key, KeyDefinition, KeyEntry, KeyOptions};
+use crate::db_type::{Input, Key, KeyDefinition, KeyEntry, KeyOptions};
 use std::collections::HashMap;
 
 #[derive(Clone)]
@@ -7,6 +7,11 @@ pub struct WatcherRequest {
     pub(crate) table_name: String,
     pub(crate) primary_key: Key,
     pub(crate) secondary_keys_value: HashMap<KeyDefinition<KeyOptions>, KeyEntry>,
+    // For an update, the primary key and the secondary keys of the previous value.
+    #[allow(clippy::type_complexity)]
+    pub(crate) old_keys: Option<(Key, HashMap<KeyDefinition<KeyOptions>, KeyEntry>)>,
+    // The update is the first of the two updates of a swap, see `RwTransaction::swap`.
+    pub(crate) swapped_with_next: bool,
 }
 
 impl WatcherRequest {
@@ -19,6 +24,21 @@ impl WatcherRequest {
             table_name,
             primary_key,
             secondary_keys_value: secondary_keys,
+            old_keys: None,
+            swapped_with_next: false,
         }
     }
+
+    pub(crate) fn with_old_keys(mut self, old_item: &Input) -> Self {
+        self.old_keys = Some((
+            old_item.primary_key.clone(),
+            old_item.secondary_keys.clone(),
+        ));
+        self
+    }
+
+    pub(crate) fn swapped_with_next(mut self) -> Self {
+        self.swapped_with_next = true;
+        self
+    }
 }
@@ -1,4 +1,4 @@
-use crate::db_type::KeyEntry;
+use crate::db_type::{KeyEntry, ToKey};
 use crate::watch::filter::{KeyFilter, TableFilter};
 use crate::watch::request::WatcherRequest;
 use crate::watch::{Event, MpscSender};
@@ -52,6 +52,11 @@ impl Watchers {
                             event_senders.push((*id, Arc::clone(event_sender)));
                         }
                     }
+                    KeyFilter::PrimaryRange(range) => {
+                        if range.contains_key(&request.primary_key) {
+                            event_senders.push((*id, Arc::clone(event_sender)));
+                        }
+                    }
                     KeyFilter::Secondary(key_def, key) => {
                         for (request_secondary_key_def, request_secondary_key) in
                             &request.secondary_keys_value
@@ -66,12 +71,17 @@ impl Watchers {
                                         }
                                         KeyEntry::Optional(value) => {
                                             if let Some(value) = value {
-                                                if value == filter_value {
+                                                if &Some(value.clone()).to_key() == filter_value {
                                                     event_senders
                                                         .push((*id, Arc::clone(event_sender)));
                                                 }
                                             }
                                         }
+                                        KeyEntry::Multi(values) => {
+                                            if values.contains(filter_value) {
+                                                event_senders.push((*id, Arc::clone(event_sender)));
+                                            }
+                                        }
                                     }
                                 } else {
                                     event_senders.push((*id, Arc::clone(event_sender)));
@@ -94,12 +104,24 @@ impl Watchers {
                                 KeyEntry::Optional(value) => {
                                     if let Some(value) = value {
                                         if key_def == request_secondary_key_def
-                                            && value.as_slice().starts_with(key_prefix.as_slice())
+                                            && Some(value.clone())
+                                                .to_key()
+                                                .as_slice()
+                                                .starts_with(key_prefix.as_slice())
                                         {
                                             event_senders.push((*id, Arc::clone(event_sender)));
                                         }
                                     }
                                 }
+                                KeyEntry::Multi(values) => {
+                                    if key_def == request_secondary_key_def
+                                        && values.iter().any(|value| {
+                                            value.as_slice().starts_with(key_prefix.as_slice())
+                                        })
+                                    {
+                                        event_senders.push((*id, Arc::clone(event_sender)));
+                                    }
+                                }
                             }
                         }
                     }
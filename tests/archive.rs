@@ -0,0 +1,283 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct ItemV1 {
+    #[primary_key]
+    id: u32,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 2, from = ItemV1)]
+#[native_db]
+struct ItemV2 {
+    #[primary_key]
+    id: u64,
+    #[secondary_key]
+    name: String,
+}
+
+impl From<ItemV1> for ItemV2 {
+    fn from(item: ItemV1) -> Self {
+        ItemV2 {
+            id: item.id as u64,
+            name: item.name,
+        }
+    }
+}
+
+impl From<ItemV2> for ItemV1 {
+    fn from(item: ItemV2) -> Self {
+        ItemV1 {
+            id: item.id as u32,
+            name: item.name,
+        }
+    }
+}
+
+fn export_v1(ids: std::ops::Range<u32>) -> Vec<u8> {
+    let mut models = Models::new();
+    models.define::<ItemV1>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    for id in ids.clone() {
+        rw.insert(ItemV1 {
+            id,
+            name: format!("name_{}", id),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let mut archive = vec![];
+    assert_eq!(
+        db.export_model_archive::<ItemV1>(&mut archive).unwrap(),
+        ids.len()
+    );
+    archive
+}
+
+#[test]
+fn test_archive_round_trip() {
+    let archive = export_v1(0..10);
+
+    let mut models = Models::new();
+    models.define::<ItemV1>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+    assert_eq!(
+        db.import_model_archive::<ItemV1>(archive.as_slice())
+            .unwrap(),
+        10
+    );
+
+    let r = db.r_transaction().unwrap();
+    let items: Vec<ItemV1> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(items.len(), 10);
+    assert_eq!(
+        items[3],
+        ItemV1 {
+            id: 3,
+            name: "name_3".to_string(),
+        }
+    );
+    drop(r);
+
+    // Nothing is imported if a value can't be inserted
+    let archive = export_v1(5..15);
+    assert!(matches!(
+        db.import_model_archive::<ItemV1>(archive.as_slice()),
+        Err(db_type::Error::DuplicateKey { .. })
+    ));
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<ItemV1>().unwrap(), 10);
+    drop(r);
+
+    // A truncated archive is refused
+    let archive = export_v1(20..22);
+    assert!(matches!(
+        db.import_model_archive::<ItemV1>(&archive[..archive.len() - 1]),
+        Err(db_type::Error::Io(error)) if error.kind() == std::io::ErrorKind::UnexpectedEof
+    ));
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<ItemV1>().unwrap(), 10);
+}
+
+#[test]
+fn test_archive_import_older_version() {
+    let archive = export_v1(0..3);
+
+    let mut models = Models::new();
+    models.define::<ItemV2>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+    assert_eq!(
+        db.import_model_archive::<ItemV2>(archive.as_slice())
+            .unwrap(),
+        3
+    );
+
+    // The values are upgraded and indexed with the keys of the new version
+    let r = db.r_transaction().unwrap();
+    let item: Option<ItemV2> = r.get().primary(1u64).unwrap();
+    assert_eq!(
+        item,
+        Some(ItemV2 {
+            id: 1,
+            name: "name_1".to_string(),
+        })
+    );
+    let items: Vec<ItemV2> = r
+        .scan()
+        .secondary(ItemV2Key::name)
+        .unwrap()
+        .start_with("name_")
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(items.len(), 3);
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 2, version = 1)]
+#[native_db(blob)]
+struct Attachment {
+    #[primary_key]
+    id: u32,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 2, version = 1)]
+#[native_db]
+struct AttachmentWithoutBlob {
+    #[primary_key]
+    id: u32,
+}
+
+#[test]
+fn test_archive_blobs() {
+    let mut models = Models::new();
+    models.define::<Attachment>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert_with_blob(Attachment { id: 1 }, [7u8; 100_000].as_slice())
+        .unwrap();
+    rw.insert(Attachment { id: 2 }).unwrap();
+    rw.insert_with_blob(Attachment { id: 3 }, [].as_slice())
+        .unwrap();
+    rw.commit().unwrap();
+
+    let mut archive = vec![];
+    assert_eq!(
+        db.export_model_archive::<Attachment>(&mut archive).unwrap(),
+        3
+    );
+
+    // The blobs are imported with their values
+    let other_db = Builder::new().create_in_memory(&models).unwrap();
+    assert_eq!(
+        other_db
+            .import_model_archive::<Attachment>(archive.as_slice())
+            .unwrap(),
+        3
+    );
+    let r = other_db.r_transaction().unwrap();
+    let mut blob = vec![];
+    r.open_blob::<Attachment>(1u32)
+        .unwrap()
+        .unwrap()
+        .read_to_end(&mut blob)
+        .unwrap();
+    assert_eq!(blob, vec![7u8; 100_000]);
+    assert!(r.open_blob::<Attachment>(2u32).unwrap().is_none());
+    let mut blob = vec![];
+    r.open_blob::<Attachment>(3u32)
+        .unwrap()
+        .unwrap()
+        .read_to_end(&mut blob)
+        .unwrap();
+    assert!(blob.is_empty());
+    drop(r);
+
+    // A truncated blob is refused
+    let other_db = Builder::new().create_in_memory(&models).unwrap();
+    assert!(matches!(
+        other_db.import_model_archive::<Attachment>(&archive[..1000]),
+        Err(db_type::Error::Io(error)) if error.kind() == std::io::ErrorKind::UnexpectedEof
+    ));
+
+    // The blobs can't be imported in a model without blob
+    let mut models = Models::new();
+    models.define::<AttachmentWithoutBlob>().unwrap();
+    let other_db = Builder::new().create_in_memory(&models).unwrap();
+    assert!(matches!(
+        other_db.import_model_archive::<AttachmentWithoutBlob>(archive.as_slice()),
+        Err(db_type::Error::BlobNotDefined { .. })
+    ));
+}
+
+#[cfg(feature = "zstd")]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 3, version = 1)]
+#[native_db(compress = "zstd")]
+struct CompressedItem {
+    #[primary_key]
+    id: u32,
+    name: String,
+}
+
+#[cfg(feature = "zstd")]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 3, version = 1)]
+#[native_db]
+struct UncompressedItem {
+    #[primary_key]
+    id: u32,
+    name: String,
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_archive_compressed_values() {
+    let mut models = Models::new();
+    models.define::<CompressedItem>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(CompressedItem {
+        id: 1,
+        name: "name".repeat(100),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    let mut archive = vec![];
+    assert_eq!(
+        db.export_model_archive::<CompressedItem>(&mut archive)
+            .unwrap(),
+        1
+    );
+
+    // The values are archived with native_model, not as they are stored
+    let mut models = Models::new();
+    models.define::<UncompressedItem>().unwrap();
+    let other_db = Builder::new().create_in_memory(&models).unwrap();
+    assert_eq!(
+        other_db
+            .import_model_archive::<UncompressedItem>(archive.as_slice())
+            .unwrap(),
+        1
+    );
+    let r = other_db.r_transaction().unwrap();
+    let item: UncompressedItem = r.get().primary(1u32).unwrap().unwrap();
+    assert_eq!(item.name, "name".repeat(100));
+}
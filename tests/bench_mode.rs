@@ -0,0 +1,22 @@
+// The modes of the benchmarks, see `benches/setup.rs`.
+#[path = "../benches/setup/mode.rs"]
+mod mode;
+
+use mode::Mode;
+use native_db::*;
+
+#[test]
+fn test_bench_mode_sets_redb_flags() {
+    let models = Models::new();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+    for (mode, two_phase_commit, quick_repair) in [
+        (Mode::Default, false, false),
+        (Mode::TwoPhaseCommit, true, false),
+        (Mode::QuickRepair, false, true),
+    ] {
+        let mut rw = db.rw_transaction().unwrap();
+        mode.apply(&mut rw);
+        assert_eq!(rw.two_phase_commit(), two_phase_commit);
+        assert_eq!(rw.quick_repair(), quick_repair);
+    }
+}
@@ -0,0 +1,190 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use shortcut_assert_fs::TmpFs;
+use std::io::Read;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 1)]
+#[native_db(blob)]
+struct Attachment {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    file_name: String,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 2, version = 1)]
+#[native_db]
+struct Item {
+    #[primary_key]
+    id: u32,
+}
+
+fn content(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+#[test]
+fn test_blob_stream_in_out() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<Attachment>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let large = content(5 * 1024 * 1024 + 17);
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert_with_blob(
+        Attachment {
+            id: 1,
+            file_name: "large.bin".to_string(),
+        },
+        large.as_slice(),
+    )
+    .unwrap();
+    rw.insert_with_blob(
+        Attachment {
+            id: 2,
+            file_name: "empty.bin".to_string(),
+        },
+        std::io::empty(),
+    )
+    .unwrap();
+    rw.insert(Attachment {
+        id: 3,
+        file_name: "none".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+
+    // Stream with a small buffer
+    let mut blob = r.open_blob::<Attachment>(1u32).unwrap().unwrap();
+    let mut out = Vec::new();
+    let mut buffer = [0; 1000];
+    loop {
+        let len = blob.read(&mut buffer).unwrap();
+        if len == 0 {
+            break;
+        }
+        out.extend_from_slice(&buffer[..len]);
+    }
+    assert_eq!(out.len(), large.len());
+    assert!(out == large);
+
+    let mut out = Vec::new();
+    r.open_blob::<Attachment>(2u32)
+        .unwrap()
+        .unwrap()
+        .read_to_end(&mut out)
+        .unwrap();
+    assert!(out.is_empty());
+
+    assert!(r.open_blob::<Attachment>(3u32).unwrap().is_none());
+    assert!(r.open_blob::<Attachment>(4u32).unwrap().is_none());
+}
+
+#[test]
+fn test_blob_replace_and_remove() {
+    let mut models = Models::new();
+    models.define::<Attachment>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let attachment = Attachment {
+        id: 1,
+        file_name: "a.bin".to_string(),
+    };
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert_with_blob(attachment.clone(), content(200 * 1024).as_slice())
+        .unwrap();
+    rw.commit().unwrap();
+
+    // Replace the blob with a shorter one
+    let rw = db.rw_transaction().unwrap();
+    assert!(rw.remove_blob::<Attachment>(1u32).unwrap());
+    assert!(!rw.remove_blob::<Attachment>(1u32).unwrap());
+    rw.remove(attachment.clone()).unwrap();
+    rw.insert_with_blob(attachment.clone(), b"short".as_slice())
+        .unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let mut out = Vec::new();
+    r.open_blob::<Attachment>(1u32)
+        .unwrap()
+        .unwrap()
+        .read_to_end(&mut out)
+        .unwrap();
+    assert_eq!(out, b"short");
+    drop(r);
+
+    // Removing the value removes its blob
+    let rw = db.rw_transaction().unwrap();
+    rw.remove(attachment).unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    assert!(r.open_blob::<Attachment>(1u32).unwrap().is_none());
+}
+
+#[test]
+fn test_blob_follows_primary_key_update() {
+    let mut models = Models::new();
+    models.define::<Attachment>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let attachment = Attachment {
+        id: 1,
+        file_name: "a.bin".to_string(),
+    };
+    let rw = db.rw_transaction().unwrap();
+    rw.insert_with_blob(attachment.clone(), content(200 * 1024).as_slice())
+        .unwrap();
+    rw.commit().unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.update(
+        attachment.clone(),
+        Attachment {
+            id: 2,
+            ..attachment
+        },
+    )
+    .unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    assert!(r.open_blob::<Attachment>(1u32).unwrap().is_none());
+    let mut out = Vec::new();
+    r.open_blob::<Attachment>(2u32)
+        .unwrap()
+        .unwrap()
+        .read_to_end(&mut out)
+        .unwrap();
+    assert_eq!(out, content(200 * 1024));
+}
+
+#[test]
+fn test_blob_not_defined() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    let result = rw.insert_with_blob(Item { id: 1 }, b"data".as_slice());
+    assert!(matches!(result, Err(db_type::Error::BlobNotDefined { .. })));
+    drop(rw);
+
+    let r = db.r_transaction().unwrap();
+    assert!(matches!(
+        r.open_blob::<Item>(1u32),
+        Err(db_type::Error::BlobNotDefined { .. })
+    ));
+}
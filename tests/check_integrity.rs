@@ -33,3 +33,235 @@ fn test_check_integrity() {
     let out = db.check_integrity().unwrap();
     assert!(out);
 }
+
+#[test]
+fn test_open_integrity_check() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    {
+        let db = Builder::new().create(&models, db_path.clone()).unwrap();
+        let rw = db.rw_transaction().unwrap();
+        for id in 0..1000 {
+            rw.insert(Item {
+                id,
+                name: "x".repeat(100),
+            })
+            .unwrap();
+        }
+        rw.commit().unwrap();
+    }
+
+    // Corrupt a data page of the cleanly closed file.
+    let mut bytes = std::fs::read(&db_path).unwrap();
+    let offset = bytes.len() / 5;
+    bytes[offset..offset + 4096].fill(0xAB);
+    std::fs::write(&db_path, &bytes).unwrap();
+
+    // Strict mode reports the corruption at open time.
+    let result = Builder::new()
+        .set_open_integrity_check(true)
+        .open(&models, db_path.clone());
+    assert!(matches!(
+        result,
+        Err(db_type::Error::DatabaseCorrupted { .. })
+    ));
+
+    // Fast mode trusts the cleanly closed file and opens it.
+    let db = Builder::new().open(&models, db_path.clone());
+    assert!(db.is_ok());
+}
+
+fn create_items(models: &Models, db_path: &std::path::Path) {
+    let db = Builder::new().create(models, db_path).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..1000 {
+        rw.insert(Item {
+            id,
+            name: "x".repeat(100),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+}
+
+#[test]
+fn test_repair_on_open() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+    let crash_path = tf.path("crash");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create(&models, db_path.clone()).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..1000 {
+        rw.insert(Item {
+            id,
+            name: "x".repeat(100),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+    // The file of a database still open is the one left by a crash.
+    std::fs::copy(&db_path, &crash_path).unwrap();
+    drop(db);
+
+    let result = Builder::new()
+        .set_repair_on_open(false)
+        .open(&models, crash_path.clone());
+    assert!(matches!(
+        result,
+        Err(db_type::Error::DatabaseCorrupted { .. })
+    ));
+
+    // The repair keeps the committed values.
+    let db = Builder::new()
+        .set_repair_on_open(true)
+        .open(&models, crash_path.clone())
+        .unwrap();
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item>().unwrap(), 1000);
+}
+
+#[test]
+fn test_open_torn_file() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    create_items(&models, db_path.as_std_path());
+    let bytes = std::fs::read(&db_path).unwrap();
+
+    // Truncated file
+    std::fs::write(&db_path, &bytes[..bytes.len() / 2]).unwrap();
+    let result = Builder::new().open(&models, db_path.clone());
+    assert!(matches!(
+        result,
+        Err(db_type::Error::DatabaseCorrupted { .. })
+    ));
+    std::fs::write(&db_path, &bytes[..100]).unwrap();
+    let result = Builder::new().open(&models, db_path.clone());
+    assert!(matches!(
+        result,
+        Err(db_type::Error::DatabaseCorrupted { .. })
+    ));
+
+    // Both commit slots of the header overwritten
+    let mut torn = bytes.clone();
+    torn[64..320].fill(0xAB);
+    std::fs::write(&db_path, &torn).unwrap();
+    let result = Builder::new().open(&models, db_path.clone());
+    assert!(matches!(
+        result,
+        Err(db_type::Error::DatabaseCorrupted { .. })
+    ));
+}
+
+#[test]
+fn test_health_check() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create(&models, db_path.clone()).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..3 {
+        rw.insert(Item {
+            id,
+            name: "test".to_string(),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let report = db.health_check().unwrap();
+    assert!(report.is_healthy, "{:?}", report.issues);
+    assert!(report.issues.is_empty());
+    assert_eq!(report.stale_upgrade_file, None);
+    assert_eq!(report.stored_version, report.current_version);
+    assert_eq!(report.row_counts, vec![("1_1_id".to_string(), 3)]);
+}
+
+#[test]
+fn test_health_check_full() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    create_items(&models, db_path.as_std_path());
+
+    let mut db = Builder::new().open(&models, db_path.clone()).unwrap();
+    assert_eq!(db.health_check().unwrap().integrity, None);
+    let report = db.health_check_full().unwrap();
+    assert!(report.is_healthy, "{:?}", report.issues);
+    assert_eq!(report.integrity, Some(true));
+    drop(db);
+
+    // Corrupt a data page of the cleanly closed file, opened without checking it.
+    let mut bytes = std::fs::read(&db_path).unwrap();
+    let offset = bytes.len() / 5;
+    bytes[offset..offset + 4096].fill(0xAB);
+    std::fs::write(&db_path, &bytes).unwrap();
+
+    let mut db = Builder::new().open(&models, db_path.clone()).unwrap();
+    let report = db.health_check_full().unwrap();
+    assert!(!report.is_healthy);
+    assert_eq!(report.integrity, Some(false));
+    assert!(report.issues[0].contains("integrity"));
+    // redb panics when dropping a database it failed to repair.
+    std::mem::forget(db);
+}
+
+#[test]
+fn test_health_check_version_mismatch() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create(&models, db_path.clone()).unwrap();
+
+    // Written by a newer version of native_db
+    let metadata: redb::TableDefinition<&str, &str> = redb::TableDefinition::new("metadata");
+    let rw = db.redb_database().unwrap().begin_write().unwrap();
+    rw.open_table(metadata)
+        .unwrap()
+        .insert("version_native_db", "99.0.0")
+        .unwrap();
+    rw.commit().unwrap();
+
+    let report = db.health_check().unwrap();
+    assert!(!report.is_healthy);
+    assert_eq!(report.stored_version, "99.0.0");
+    assert_eq!(report.issues.len(), 1);
+    assert!(report.issues[0].contains("99.0.0"));
+}
+
+#[test]
+#[cfg(feature = "upgrade_0_5_x")]
+fn test_health_check_interrupted_upgrade() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create(&models, db_path.clone()).unwrap();
+
+    // Left behind by an interrupted upgrade
+    let upgrade_file = tf.path("test_redb2");
+    std::fs::write(&upgrade_file, b"").unwrap();
+
+    let report = db.health_check().unwrap();
+    assert!(!report.is_healthy);
+    assert_eq!(
+        report.stale_upgrade_file.as_deref(),
+        Some(upgrade_file.as_std_path())
+    );
+}
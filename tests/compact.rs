@@ -2,6 +2,7 @@ use native_db::*;
 use native_model::{native_model, Model};
 use serde::{Deserialize, Serialize};
 use shortcut_assert_fs::TmpFs;
+use std::time::Duration;
 
 #[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
 #[native_model(id = 1, version = 1)]
@@ -46,3 +47,472 @@ fn test_compact() {
     let file_size = metadata.len();
     assert_eq!(file_size, 876544);
 }
+
+#[test]
+fn test_compact_with_open_reader() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let mut db = Builder::new().create(&models, db_path.clone()).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Item {
+        id: 1,
+        name: "test".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    // Leak a read transaction to keep it open while compacting.
+    let r = db.r_transaction().unwrap();
+    std::mem::forget(r);
+
+    let result = db.compact();
+    assert!(matches!(
+        result,
+        Err(db_type::Error::CompactionBlockedByReaders { reader_count: 1 })
+    ));
+}
+
+#[test]
+fn test_compact_after_reader_dropped() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let mut db = Builder::new().create(&models, db_path.clone()).unwrap();
+
+    let r = db.r_transaction().unwrap();
+    drop(r);
+
+    assert!(db.compact().unwrap());
+}
+
+#[test]
+fn test_compact_with_open_writer() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let mut db = Builder::new().create(&models, db_path.clone()).unwrap();
+
+    // Leak a read-write transaction, compacting must fail instead of waiting for it.
+    let rw = db.rw_transaction().unwrap();
+    std::mem::forget(rw);
+
+    let result = db.compact();
+    let error = result.err().unwrap();
+    assert!(matches!(error, db_type::Error::CompactionBlockedByWriter));
+    assert!(error.is_retryable());
+
+    // Dropping redb waits for the write lock too, so leak the database as well.
+    std::mem::forget(db);
+}
+
+#[test]
+fn test_compact_with_concurrent_writer() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create(&models, db_path.clone()).unwrap();
+    let db = std::sync::RwLock::new(db);
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            for i in 0..100 {
+                let db = db.read().unwrap();
+                let rw = db.rw_transaction().unwrap();
+                rw.insert(Item {
+                    id: i,
+                    name: format!("test_{}", i),
+                })
+                .unwrap();
+                rw.commit().unwrap();
+            }
+        });
+        scope.spawn(|| {
+            let result = db.write().unwrap().compact();
+            sender.send(result).unwrap();
+        });
+        let result = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert!(result.unwrap());
+    });
+
+    let db = db.into_inner().unwrap();
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item>().unwrap(), 100);
+}
+
+#[test]
+fn test_compact_safe() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+    let backup_path = tf.path("test.pre-compact");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let mut db = Builder::new().create(&models, db_path.clone()).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for i in 0..999 {
+        rw.insert(Item {
+            id: i,
+            name: format!("test_{}", i),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+    let file_size_before = std::fs::metadata(db_path.clone()).unwrap().len();
+
+    db.compact_safe().unwrap();
+
+    // The backup is removed once the compaction succeeded
+    assert!(!backup_path.exists());
+    let file_size_after = std::fs::metadata(db_path.clone()).unwrap().len();
+    assert!(file_size_after < file_size_before);
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item>().unwrap(), 999);
+}
+
+#[test]
+fn test_compact_safe_backup() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+    let backup_path = tf.path("test.pre-compact");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let mut db = Builder::new().create(&models, db_path.clone()).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Item {
+        id: 1,
+        name: "test".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    // The compaction doesn't start if the backup can't be created
+    std::fs::create_dir(&backup_path).unwrap();
+    assert!(matches!(db.compact_safe(), Err(db_type::Error::Io(_))));
+    std::fs::remove_dir(&backup_path).unwrap();
+
+    // The backup is removed when the compaction is blocked
+    let r = db.r_transaction().unwrap();
+    std::mem::forget(r);
+    assert!(matches!(
+        db.compact_safe(),
+        Err(db_type::Error::CompactionBlockedByReaders { reader_count: 1 })
+    ));
+    assert!(!backup_path.exists());
+
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item>().unwrap(), 1);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_compact_safe_backup_non_utf8_path() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let tf = TmpFs::new().unwrap();
+    let db_path =
+        std::path::PathBuf::from(tf.path("test")).with_file_name(OsStr::from_bytes(b"test_\xff"));
+    let backup_path = db_path.with_file_name(OsStr::from_bytes(b"test_\xff.pre-compact"));
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let mut db = Builder::new().create(&models, &db_path).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Item {
+        id: 1,
+        name: "test".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    // The backup keeps the raw file name of the database
+    std::fs::create_dir(&backup_path).unwrap();
+    assert!(matches!(db.compact_safe(), Err(db_type::Error::Io(_))));
+    std::fs::remove_dir(&backup_path).unwrap();
+
+    db.compact_safe().unwrap();
+    assert!(!backup_path.exists());
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item>().unwrap(), 1);
+}
+
+#[test]
+fn test_compact_safe_with_savepoint() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+    let backup_path = tf.path("test.pre-compact");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create(&models, db_path.clone()).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Item {
+        id: 1,
+        name: "test".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+    db.create_savepoint("export").unwrap();
+    drop(db);
+
+    // The savepoint blocks the compaction: the file is not restored from the copy
+    let mut db = Builder::new().open(&models, db_path.clone()).unwrap();
+    let result = db.compact_safe();
+    assert!(matches!(
+        result,
+        Err(db_type::Error::CompactionBlockedByReaders { .. })
+            | Err(db_type::Error::RedbCompactionError(_))
+    ));
+    assert!(!backup_path.exists());
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item>().unwrap(), 1);
+}
+
+#[test]
+fn test_compact_fully() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let mut db = Builder::new().create(&models, db_path.clone()).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for i in 0..10_000 {
+        rw.insert(Item {
+            id: i,
+            name: format!("test_{}", i),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    // Remove most of the items
+    let rw = db.rw_transaction().unwrap();
+    for i in 100..10_000 {
+        rw.remove(Item {
+            id: i,
+            name: format!("test_{}", i),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let file_size_before = std::fs::metadata(db_path.clone()).unwrap().len();
+
+    let report = db.compact_fully().unwrap();
+    assert!(report.iterations >= 1);
+
+    let file_size_after = std::fs::metadata(db_path.clone()).unwrap().len();
+    assert!(file_size_after * 2 < file_size_before);
+    assert_eq!(report.reclaimed_bytes, file_size_before - file_size_after);
+
+    // The data is still there
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item>().unwrap(), 100);
+}
+
+#[test]
+fn test_compact_timeboxed() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let mut db = Builder::new().create(&models, db_path.clone()).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for i in 0..10_000 {
+        rw.insert(Item {
+            id: i,
+            name: format!("test_{}", i),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    // Remove most of the items
+    let rw = db.rw_transaction().unwrap();
+    for i in 100..10_000 {
+        rw.remove(Item {
+            id: i,
+            name: format!("test_{}", i),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let file_size_before = std::fs::metadata(db_path.clone()).unwrap().len();
+
+    // The budget is too small for a single pass
+    let report = db.compact_timeboxed(Duration::from_micros(1)).unwrap();
+    assert!(!report.completed);
+    assert_eq!(report.iterations, 0);
+    assert_eq!(report.reclaimed_bytes, 0);
+    let file_size_after = std::fs::metadata(db_path.clone()).unwrap().len();
+    assert_eq!(file_size_after, file_size_before);
+
+    let report = db.compact_timeboxed(Duration::from_secs(60)).unwrap();
+    assert!(report.completed);
+    assert!(report.iterations >= 1);
+    let file_size_after = std::fs::metadata(db_path.clone()).unwrap().len();
+    assert!(file_size_after < file_size_before);
+}
+
+#[test]
+fn test_auto_compact_on_open() {
+    let tf = TmpFs::new().unwrap();
+    let bloated_path = tf.path("bloated");
+    let tight_path = tf.path("tight");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+
+    // Bloated: most of the items are removed
+    let db = Builder::new()
+        .create(&models, bloated_path.clone())
+        .unwrap();
+    let rw = db.rw_transaction().unwrap();
+    for i in 0..10_000 {
+        rw.insert(Item {
+            id: i,
+            name: format!("test_{}", i),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+    let rw = db.rw_transaction().unwrap();
+    for i in 100..10_000 {
+        rw.remove(Item {
+            id: i,
+            name: format!("test_{}", i),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+    drop(db);
+
+    // Tight: already compacted
+    let mut db = Builder::new().create(&models, tight_path.clone()).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    for i in 0..100 {
+        rw.insert(Item {
+            id: i,
+            name: format!("test_{}", i),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+    db.compact_fully().unwrap();
+    drop(db);
+
+    let bloated_size_before = std::fs::metadata(bloated_path.clone()).unwrap().len();
+    let tight_size_before = std::fs::metadata(tight_path.clone()).unwrap().len();
+
+    let db = Builder::new()
+        .set_auto_compact_on_open(0.5)
+        .open(&models, bloated_path.clone())
+        .unwrap();
+    let bloated_size_after = std::fs::metadata(bloated_path.clone()).unwrap().len();
+    assert!(bloated_size_after * 2 < bloated_size_before);
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item>().unwrap(), 100);
+    drop(r);
+    drop(db);
+
+    let _db = Builder::new()
+        .set_auto_compact_on_open(0.5)
+        .open(&models, tight_path.clone())
+        .unwrap();
+    let tight_size_after = std::fs::metadata(tight_path.clone()).unwrap().len();
+    assert_eq!(tight_size_after, tight_size_before);
+}
+
+#[test]
+fn test_auto_compact_on_open_with_savepoint() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create(&models, db_path.clone()).unwrap();
+    db.create_savepoint("export").unwrap();
+    let rw = db.rw_transaction().unwrap();
+    for i in 0..10_000 {
+        rw.insert(Item {
+            id: i,
+            name: format!("test_{}", i),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+    let rw = db.rw_transaction().unwrap();
+    for i in 100..10_000 {
+        rw.remove(Item {
+            id: i,
+            name: format!("test_{}", i),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+    drop(db);
+
+    // The savepoint prevents the compaction, the database is opened anyway
+    let db = Builder::new()
+        .set_auto_compact_on_open(0.0)
+        .open(&models, db_path.clone())
+        .unwrap();
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item>().unwrap(), 100);
+}
+
+#[test]
+fn test_compact_with_progress() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let mut db = Builder::new().create(&models, db_path.clone()).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for i in 0..999 {
+        rw.insert(Item {
+            id: i,
+            name: format!("test_{}", i),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    // The start and the end of the compaction are reported
+    let mut progress = vec![];
+    db.compact_with_progress(|value| progress.push(value))
+        .unwrap();
+    assert_eq!(progress, vec![0.0, 1.0]);
+
+    // A blocked compaction only reports its start
+    let r = db.r_transaction().unwrap();
+    std::mem::forget(r);
+    let mut progress = vec![];
+    let result = db.compact_with_progress(|value| progress.push(value));
+    assert!(matches!(
+        result,
+        Err(db_type::Error::CompactionBlockedByReaders { .. })
+    ));
+    assert_eq!(progress, vec![0.0]);
+}
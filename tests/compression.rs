@@ -0,0 +1,300 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use shortcut_assert_fs::TmpFs;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 1)]
+#[native_db(compress = "zstd")]
+struct Document {
+    #[primary_key]
+    id: u32,
+    #[secondary_key(unique)]
+    title: String,
+    content: String,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 2, version = 1)]
+#[native_db]
+struct Note {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    title: String,
+    content: String,
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_compressed_and_uncompressed_models() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<Document>().unwrap();
+    models.define::<Note>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let content = "lorem ipsum dolor sit amet ".repeat(100);
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..10 {
+        rw.insert(Document {
+            id,
+            title: format!("document {}", id),
+            content: content.clone(),
+        })
+        .unwrap();
+        rw.insert(Note {
+            id,
+            title: format!("note {}", id),
+            content: content.clone(),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    // Both round-trip
+    let r = db.r_transaction().unwrap();
+    let document: Document = r.get().primary(3u32).unwrap().unwrap();
+    assert_eq!(document.content, content);
+    let document: Document = r
+        .get()
+        .secondary(DocumentKey::title, "document 4")
+        .unwrap()
+        .unwrap();
+    assert_eq!(document.id, 4);
+    let documents: Vec<Document> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(documents.len(), 10);
+    let note: Note = r.get().primary(3u32).unwrap().unwrap();
+    assert_eq!(note.content, content);
+    drop(r);
+
+    // Only the values of the compressed model are smaller
+    assert!(stored_size(&db, "1_1_id", 3) * 10 < stored_size(&db, "2_1_id", 3));
+
+    // The database opens again with both models
+    drop(db);
+    let db = Builder::new()
+        .open(&models, tf.path("test").as_std_path())
+        .unwrap();
+    let r = db.r_transaction().unwrap();
+    let document: Document = r.get().primary(9u32).unwrap().unwrap();
+    assert_eq!(document.content, content);
+    let note: Note = r.get().primary(9u32).unwrap().unwrap();
+    assert_eq!(note.content, content);
+}
+
+mod uncompressed {
+    use super::*;
+
+    // Same table as `Document`, without the compression.
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+    #[native_model(id = 1, version = 1)]
+    #[native_db]
+    pub struct Document {
+        #[primary_key]
+        pub id: u32,
+        #[secondary_key(unique)]
+        pub title: String,
+        pub content: String,
+    }
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_compression_mismatch() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<uncompressed::Document>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(uncompressed::Document {
+        id: 1,
+        title: "document".to_string(),
+        content: "content".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+    drop(db);
+
+    let mut models = Models::new();
+    models.define::<Document>().unwrap();
+    let result = Builder::new().open(&models, tf.path("test").as_std_path());
+    assert!(matches!(
+        result,
+        Err(db_type::Error::CompressionMismatch { table, stored, current })
+            if table == "1_1_id" && stored == "none" && current == "zstd"
+    ));
+}
+
+#[cfg(not(feature = "zstd"))]
+#[test]
+fn test_compression_not_enabled() {
+    let mut models = Models::new();
+    assert!(matches!(
+        models.define::<Document>(),
+        Err(db_type::Error::CompressionNotEnabled { compression }) if compression == "zstd"
+    ));
+    models.define::<Note>().unwrap();
+}
+
+/// Length of the value stored in the primary table `table` for `key`.
+fn stored_size(db: &Database, table: &str, key: u32) -> usize {
+    let table: redb::TableDefinition<Key, &[u8]> = redb::TableDefinition::new(table);
+    let r = db.redb_database().unwrap().begin_read().unwrap();
+    let table = r.open_table(table).unwrap();
+    let value = table.get(key.to_key()).unwrap().unwrap();
+    value.value().len()
+}
+
+#[test]
+fn test_encoded_size() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<Note>().unwrap();
+    #[cfg(feature = "zstd")]
+    models.define::<Document>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let content = "lorem ipsum dolor sit amet ".repeat(100);
+    let note = Note {
+        id: 1,
+        title: "note".to_string(),
+        content: content.clone(),
+    };
+    let note_size = note.encoded_size().unwrap();
+    assert!(note_size > content.len());
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(note).unwrap();
+    rw.commit().unwrap();
+    assert_eq!(stored_size(&db, "2_1_id", 1), note_size);
+
+    // The compressed size
+    #[cfg(feature = "zstd")]
+    {
+        let document = Document {
+            id: 1,
+            title: "document".to_string(),
+            content,
+        };
+        let document_size = document.encoded_size().unwrap();
+        assert!(document_size < note_size);
+        let rw = db.rw_transaction().unwrap();
+        rw.insert(document).unwrap();
+        rw.commit().unwrap();
+        assert_eq!(stored_size(&db, "1_1_id", 1), document_size);
+    }
+}
+
+#[cfg(feature = "zstd")]
+// The two leading fields of `Document`
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+#[native_model(id = 1, version = 1)]
+struct DocumentView {
+    id: u32,
+    title: String,
+}
+
+#[cfg(feature = "zstd")]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 2, from = Document)]
+#[native_db(compress = "zstd")]
+struct DocumentV2 {
+    #[primary_key]
+    id: u32,
+    #[secondary_key(unique)]
+    title: String,
+    content: String,
+}
+
+#[cfg(feature = "zstd")]
+impl From<Document> for DocumentV2 {
+    fn from(document: Document) -> Self {
+        DocumentV2 {
+            id: document.id,
+            title: document.title,
+            content: document.content,
+        }
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl From<DocumentV2> for Document {
+    fn from(document: DocumentV2) -> Self {
+        Document {
+            id: document.id,
+            title: document.title,
+            content: document.content,
+        }
+    }
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_max_value_size_decompressed() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Document>().unwrap();
+    let db = Builder::new().create(&models, db_path.clone()).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Document {
+        id: 1,
+        title: "document".to_string(),
+        content: "lorem ipsum dolor sit amet ".repeat(100),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+    assert!(stored_size(&db, "1_1_id", 1) < 500);
+    drop(db);
+
+    // The stored value is under the limit, not the decompressed one
+    let db = Builder::new()
+        .set_max_value_size(500)
+        .open(&models, db_path.clone())
+        .unwrap();
+    let r = db.r_transaction().unwrap();
+    assert!(matches!(
+        r.get().primary::<Document>(1u32),
+        Err(db_type::Error::ValueTooLarge { limit: 500, .. })
+    ));
+    let scan = r.scan().primary::<Document>().unwrap();
+    assert!(matches!(
+        scan.all().unwrap().next(),
+        Some(Err(db_type::Error::ValueTooLarge { limit: 500, .. }))
+    ));
+    assert!(matches!(
+        r.get().primary_as::<Document, DocumentView>(1u32),
+        Err(db_type::Error::ValueTooLarge { limit: 500, .. })
+    ));
+    drop(scan);
+    drop(r);
+    drop(db);
+
+    models.define::<DocumentV2>().unwrap();
+    let db = Builder::new()
+        .set_max_value_size(500)
+        .open(&models, db_path.clone())
+        .unwrap();
+    let rw = db.rw_transaction().unwrap();
+    assert!(matches!(
+        rw.migrate::<DocumentV2>(),
+        Err(db_type::Error::ValueTooLarge { limit: 500, .. })
+    ));
+}
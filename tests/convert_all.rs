@@ -89,3 +89,41 @@ fn convert_all() {
         }
     );
 }
+
+#[test]
+fn convert_all_bulk_events() {
+    let mut models = Models::new();
+    models.define::<ItemV0>().unwrap();
+    models.define::<ItemV1>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(ItemV0 { id: 42 }).unwrap();
+    rw.insert(ItemV0 { id: 43 }).unwrap();
+    rw.commit().unwrap();
+
+    #[allow(unused_mut)]
+    let (mut recv_old, _) = db.watch().get().primary::<ItemV0>(42u32).unwrap();
+    #[allow(unused_mut)]
+    let (mut recv_other, _) = db.watch().get().primary::<ItemV0>(7u32).unwrap();
+    #[allow(unused_mut)]
+    let (mut recv_new, _) = db.watch().get().primary::<ItemV1>("43").unwrap();
+
+    let mut rw = db.rw_transaction().unwrap();
+    rw.set_bulk_events(true);
+    rw.convert_all::<ItemV0, ItemV1>().unwrap();
+    rw.commit().unwrap();
+
+    assert!(matches!(
+        recv_old.try_recv(),
+        Ok(watch::Event::BulkDelete { count: 2, .. })
+    ));
+    assert!(recv_old.try_recv().is_err());
+    assert!(matches!(
+        recv_new.try_recv(),
+        Ok(watch::Event::BulkInsert { count: 2, .. })
+    ));
+    assert!(recv_new.try_recv().is_err());
+    // None of the converted values matches its key filter
+    assert!(recv_other.try_recv().is_err());
+}
@@ -0,0 +1,103 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use shortcut_assert_fs::TmpFs;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Invoice {
+    #[primary_key]
+    number: i64,
+}
+
+#[test]
+fn test_counters() {
+    let models = Models::new();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    assert_eq!(db.counters().get("visits").unwrap(), 0);
+    assert_eq!(db.counters().incr("visits", 1).unwrap(), 1);
+    assert_eq!(db.counters().incr("visits", 5).unwrap(), 6);
+    assert_eq!(db.counters().incr("visits", -2).unwrap(), 4);
+    assert_eq!(db.counters().get("visits").unwrap(), 4);
+
+    db.counters().set("visits", 100).unwrap();
+    assert_eq!(db.counters().get("visits").unwrap(), 100);
+    assert_eq!(db.counters().get("other").unwrap(), 0);
+}
+
+#[test]
+fn test_counters_overflow() {
+    let models = Models::new();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    db.counters().set("max", i64::MAX).unwrap();
+    let result = db.counters().incr("max", 1);
+    assert!(matches!(
+        result,
+        Err(db_type::Error::CounterOverflow { name }) if name == "max"
+    ));
+    assert_eq!(db.counters().get("max").unwrap(), i64::MAX);
+}
+
+#[test]
+fn test_counters_in_transaction() {
+    let mut models = Models::new();
+    models.define::<Invoice>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    let number = rw.counters().incr("invoice", 1).unwrap();
+    rw.insert(Invoice { number }).unwrap();
+    assert_eq!(rw.counters().get("invoice").unwrap(), 1);
+    rw.commit().unwrap();
+
+    // An aborted transaction doesn't change the counter.
+    let rw = db.rw_transaction().unwrap();
+    let number = rw.counters().incr("invoice", 1).unwrap();
+    assert_eq!(number, 2);
+    rw.insert(Invoice { number }).unwrap();
+    rw.abort().unwrap();
+
+    assert_eq!(db.counters().get("invoice").unwrap(), 1);
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Invoice>().unwrap(), 1);
+}
+
+#[test]
+fn test_counters_concurrent_incr() {
+    let models = Models::new();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    std::thread::scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|| {
+                for _ in 0..50 {
+                    db.counters().incr("hits", 1).unwrap();
+                }
+            });
+        }
+    });
+
+    assert_eq!(db.counters().get("hits").unwrap(), 400);
+}
+
+#[test]
+fn test_counters_snapshot_and_reopen() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+    let snapshot_path = tf.path("snapshot");
+
+    let models = Models::new();
+    let db = Builder::new().create(&models, db_path.clone()).unwrap();
+    db.counters().incr("hits", 3).unwrap();
+
+    let snapshot = db.snapshot(&models, snapshot_path.as_std_path()).unwrap();
+    assert_eq!(snapshot.counters().get("hits").unwrap(), 3);
+    drop(snapshot);
+    drop(db);
+
+    let db = Builder::new().open(&models, db_path.clone()).unwrap();
+    assert_eq!(db.counters().get("hits").unwrap(), 3);
+}
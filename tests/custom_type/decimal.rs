@@ -0,0 +1,143 @@
+use itertools::Itertools;
+use native_db::*;
+use native_model::{native_model, Model};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Order {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    #[serde(with = "rust_decimal::serde::str")]
+    price: Decimal,
+}
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+#[test]
+fn decimal_key_order() {
+    // Sorted by value, with various scales
+    let sorted = [
+        "-79228162514264337593543950335",
+        "-1000",
+        "-10.5",
+        "-10.25",
+        "-0.0000000000000000000000000001",
+        "0",
+        "0.0000000000000000000000000001",
+        "0.1",
+        "0.25",
+        "1.0000001",
+        "10.25",
+        "10.5",
+        "1000",
+        "79228162514264337593543950335",
+    ];
+
+    let mut models = Models::new();
+    models.define::<Order>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    // Inserted in reverse order
+    for (id, price) in sorted.iter().enumerate().rev() {
+        rw.insert(Order {
+            id: id as u32,
+            price: dec(price),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let orders: Vec<Order> = r
+        .scan()
+        .secondary(OrderKey::price)
+        .unwrap()
+        .all()
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(
+        orders.into_iter().map(|order| order.price).collect_vec(),
+        sorted.iter().map(|price| dec(price)).collect_vec()
+    );
+
+    // Same value, different scales
+    assert_eq!(dec("1.5").to_key(), dec("1.50000").to_key());
+    assert_eq!(dec("-0").to_key(), dec("0.000").to_key());
+}
+
+#[test]
+fn decimal_secondary_key_range() {
+    let mut models = Models::new();
+    models.define::<Order>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for (id, price) in [
+        (1, "10.5"),
+        (2, "-3.75"),
+        (3, "0"),
+        (4, "2.125"),
+        (5, "-12"),
+        (6, "10.50"),
+        (7, "100"),
+    ] {
+        rw.insert(Order {
+            id,
+            price: dec(price),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let ids = |orders: Vec<Order>| orders.into_iter().map(|order| order.id).collect_vec();
+
+    let orders: Vec<Order> = r
+        .scan()
+        .secondary(OrderKey::price)
+        .unwrap()
+        .all()
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(ids(orders), vec![5, 2, 3, 4, 1, 6, 7]);
+
+    // Across zero, bounds with a different scale than the values
+    let orders: Vec<Order> = r
+        .scan()
+        .secondary(OrderKey::price)
+        .unwrap()
+        .range(dec("-3.750")..dec("10.5"))
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(ids(orders), vec![2, 3, 4]);
+
+    let orders: Vec<Order> = r
+        .scan()
+        .secondary(OrderKey::price)
+        .unwrap()
+        .range(dec("-100")..=dec("10.500"))
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(ids(orders), vec![5, 2, 3, 4, 1, 6]);
+
+    let orders: Vec<Order> = r
+        .scan()
+        .secondary(OrderKey::price)
+        .unwrap()
+        .range(dec("-12.0001")..dec("-3.7"))
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(ids(orders), vec![5, 2]);
+}
@@ -0,0 +1,70 @@
+use itertools::Itertools;
+use native_db::db_type::Descending;
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Post {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    timestamp: Descending<u64>,
+}
+
+#[test]
+fn descending_secondary_key() {
+    let mut models = Models::new();
+    models.define::<Post>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for (id, timestamp) in [(1, 300), (2, 100), (3, 500), (4, 200), (5, 400)] {
+        rw.insert(Post {
+            id,
+            timestamp: Descending(timestamp),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+
+    // Forward scan, newest first
+    let posts: Vec<Post> = r
+        .scan()
+        .secondary(PostKey::timestamp)
+        .unwrap()
+        .all()
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(
+        posts.iter().map(|p| p.timestamp.0).collect_vec(),
+        vec![500, 400, 300, 200, 100]
+    );
+
+    // Forward range scan: the bounds are in descending order too
+    let posts: Vec<Post> = r
+        .scan()
+        .secondary(PostKey::timestamp)
+        .unwrap()
+        .range(Descending(400u64)..=Descending(200u64))
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(
+        posts.iter().map(|p| p.timestamp.0).collect_vec(),
+        vec![400, 300, 200]
+    );
+
+    // The inner type is not accepted
+    let scan = r.scan().secondary::<Post>(PostKey::timestamp).unwrap();
+    let result = scan.range(200u64..400u64);
+    assert!(matches!(
+        result,
+        Err(db_type::Error::MissmatchedKeyType { .. })
+    ));
+}
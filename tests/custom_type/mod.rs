@@ -1 +1,5 @@
 mod custom;
+#[cfg(feature = "rust_decimal")]
+mod decimal;
+mod descending;
+mod path;
@@ -0,0 +1,88 @@
+use itertools::Itertools;
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct File {
+    #[primary_key]
+    path: PathBuf,
+    #[secondary_key]
+    name: OsString,
+}
+
+#[test]
+fn path_primary_key_start_with() {
+    let mut models = Models::new();
+    models.define::<File>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for path in [
+        "/home/user/notes.txt",
+        "/home/other/notes.txt",
+        "/home/user/docs/report.pdf",
+        "/home/username/todo.txt",
+        "/etc/hosts",
+    ] {
+        let path = PathBuf::from(path);
+        rw.insert(File {
+            name: path.file_name().unwrap().to_os_string(),
+            path,
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let file: File = r
+        .get()
+        .primary(Path::new("/etc/hosts"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(file.name, "hosts");
+
+    // The paths sort and prefix-scan like strings
+    let files: Vec<File> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .start_with(Path::new("/home/user/"))
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(
+        files.iter().map(|f| f.path.to_str().unwrap()).collect_vec(),
+        vec!["/home/user/docs/report.pdf", "/home/user/notes.txt"]
+    );
+
+    let files: Vec<File> = r
+        .scan()
+        .secondary(FileKey::name)
+        .unwrap()
+        .start_with(OsString::from("notes"))
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(files.len(), 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn path_not_utf8_key() {
+    use std::os::unix::ffi::OsStrExt;
+
+    // Both would be `/tmp/\u{FFFD}` with a lossy conversion
+    let a = Path::new(std::ffi::OsStr::from_bytes(b"/tmp/\xFF"));
+    let b = Path::new(std::ffi::OsStr::from_bytes(b"/tmp/\xFE"));
+    assert_ne!(a.to_key(), b.to_key());
+    assert_eq!(a.to_key(), Key::new(b"/tmp/\xFF".to_vec()));
+    assert_eq!(a.as_os_str().to_key(), a.to_key());
+
+    // A valid UTF-8 path is keyed like a string
+    assert_eq!(Path::new("/tmp/a").to_key(), "/tmp/a".to_key());
+}
@@ -39,3 +39,65 @@ fn create_local_database_for_tests() {
         Err(Error::ModelError(ModelError::DecodeBodyError(_)))
     ));
 }
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 2, version = 1)]
+#[native_db]
+struct Item2 {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    data: Vec<u8>,
+}
+
+#[test]
+fn test_max_value_size() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<Item2>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Item2 {
+        id: 1,
+        data: vec![],
+    })
+    .unwrap();
+    rw.insert(Item2 {
+        id: 2,
+        data: vec![0; 1024],
+    })
+    .unwrap();
+    rw.commit().unwrap();
+    drop(db);
+
+    let db = Builder::new()
+        .set_max_value_size(512)
+        .open(&models, tf.path("test").as_std_path())
+        .unwrap();
+    let r = db.r_transaction().unwrap();
+
+    // The small value is still readable
+    let item: Item2 = r.get().primary(1u32).unwrap().unwrap();
+    assert!(item.data.is_empty());
+
+    let result: Result<Option<Item2>> = r.get().primary(2u32);
+    assert!(matches!(
+        result,
+        Err(Error::ValueTooLarge { size, limit: 512 }) if size > 1024
+    ));
+
+    let result: Result<Vec<Item2>> = r.scan().primary().unwrap().all().unwrap().try_collect();
+    assert!(matches!(result, Err(Error::ValueTooLarge { .. })));
+
+    let result: Result<Vec<Item2>> = r
+        .scan()
+        .secondary(Item2Key::data)
+        .unwrap()
+        .all()
+        .unwrap()
+        .try_collect();
+    assert!(matches!(result, Err(Error::ValueTooLarge { .. })));
+}
@@ -0,0 +1,85 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Item {
+    #[primary_key]
+    id: u32,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 2, version = 1)]
+#[native_db]
+struct Other {
+    #[primary_key]
+    id: u32,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 3, version = 1)]
+#[native_db(hashed_primary_key)]
+struct Page {
+    #[primary_key]
+    url: String,
+}
+
+#[test]
+fn test_dump_table() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    models.define::<Other>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in [3, 1, 2] {
+        rw.insert(Item {
+            id,
+            name: format!("item {id}"),
+        })
+        .unwrap();
+    }
+    rw.insert(Other { id: 1 }).unwrap();
+    rw.commit().unwrap();
+
+    let mut dump = vec![];
+    db.dump_table::<Item>(&mut dump).unwrap();
+    let dump = String::from_utf8(dump).unwrap();
+    let lines: Vec<&str> = dump.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "00000001\tItem { id: 1, name: \"item 1\" }");
+    assert_eq!(lines[2], "00000003\tItem { id: 3, name: \"item 3\" }");
+
+    // Empty table
+    let rw = db.rw_transaction().unwrap();
+    rw.remove(Other { id: 1 }).unwrap();
+    rw.commit().unwrap();
+    let mut dump = vec![];
+    db.dump_table::<Other>(&mut dump).unwrap();
+    assert!(dump.is_empty());
+}
+
+#[test]
+fn test_dump_table_hashed_primary_key() {
+    let mut models = Models::new();
+    models.define::<Page>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Page {
+        url: "ab".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    // The primary key of the value, not its hash
+    let mut dump = vec![];
+    db.dump_table::<Page>(&mut dump).unwrap();
+    assert_eq!(
+        String::from_utf8(dump).unwrap(),
+        "6162\tPage { url: \"ab\" }\n"
+    );
+}
@@ -0,0 +1,121 @@
+use native_db::*;
+use native_model::Model;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_db]
+struct Cache<T> {
+    #[primary_key]
+    key: u32,
+    #[secondary_key(unique)]
+    name: String,
+    value: T,
+}
+
+native_model_impl!(
+    Cache<String>,
+    id = 1,
+    version = 1,
+    with = native_model::bincode_1_3::Bincode
+);
+native_model_impl!(
+    Cache<u64>,
+    id = 2,
+    version = 1,
+    with = native_model::bincode_1_3::Bincode
+);
+// Same id as `Cache<String>`
+native_model_impl!(
+    Cache<bool>,
+    id = 1,
+    version = 1,
+    with = native_model::bincode_1_3::Bincode
+);
+
+#[test]
+fn test_generic_model() {
+    assert_ne!(
+        Cache::<String>::secondary_key_name(),
+        Cache::<u64>::secondary_key_name()
+    );
+
+    let mut models = Models::new();
+    models.define::<Cache<String>>().unwrap();
+    models.define::<Cache<u64>>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Cache {
+        key: 1,
+        name: "a".to_string(),
+        value: "one".to_string(),
+    })
+    .unwrap();
+    rw.insert(Cache {
+        key: 1,
+        name: "a".to_string(),
+        value: 1u64,
+    })
+    .unwrap();
+    rw.insert(Cache {
+        key: 2,
+        name: "b".to_string(),
+        value: 2u64,
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Cache<String>>().unwrap(), 1);
+    assert_eq!(r.len().primary::<Cache<u64>>().unwrap(), 2);
+
+    let item: Cache<String> = r.get().primary(1u32).unwrap().unwrap();
+    assert_eq!(item.value, "one");
+    let item: Cache<u64> = r
+        .get()
+        .secondary(Cache::<u64>::secondary_key_name(), "b")
+        .unwrap()
+        .unwrap();
+    assert_eq!(item.value, 2);
+}
+
+#[test]
+fn test_generic_model_table_collision() {
+    let mut models = Models::new();
+    models.define::<Cache<String>>().unwrap();
+    let result = models.define::<Cache<bool>>();
+    assert!(matches!(
+        result,
+        Err(db_type::Error::ModelTableCollision { table, first, second })
+            if table == "1_1_key" && first.ends_with("Cache<alloc::string::String>") && second.ends_with("Cache<bool>")
+    ));
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model::native_model(id = 3, version = 1)]
+#[native_db(blob)]
+struct Attachment {
+    #[primary_key]
+    id: u32,
+}
+
+// Its primary table is the blob table of `Attachment`
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model::native_model(id = 3, version = 1)]
+#[native_db]
+struct AttachmentBlob {
+    #[primary_key]
+    id_blob: u32,
+}
+
+#[test]
+fn test_side_table_collision() {
+    let mut models = Models::new();
+    models.define::<Attachment>().unwrap();
+    let result = models.define::<AttachmentBlob>();
+    assert!(matches!(
+        result,
+        Err(db_type::Error::ModelTableCollision { table, first, second })
+            if table == "3_1_id_blob" && first == "Attachment" && second == "AttachmentBlob"
+    ));
+}
@@ -0,0 +1,129 @@
+use itertools::Itertools;
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 1)]
+#[native_db(hashed_primary_key)]
+struct Page {
+    #[primary_key]
+    url: String,
+    #[secondary_key(unique)]
+    title: String,
+    #[secondary_key]
+    domain: String,
+}
+
+impl Page {
+    fn new(path: &str, title: &str) -> Self {
+        Self {
+            url: format!("https://example.com/{}", path.repeat(100)),
+            title: title.to_string(),
+            domain: "example.com".to_string(),
+        }
+    }
+}
+
+#[test]
+fn test_hashed_primary_key() {
+    assert!(Page::native_db_model().hashed_primary_key);
+
+    let mut models = Models::new();
+    models.define::<Page>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let a = Page::new("a", "A");
+    let b = Page::new("b", "B");
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(a.clone()).unwrap();
+    rw.insert(b.clone()).unwrap();
+    assert!(matches!(
+        rw.insert(a.clone()),
+        Err(db_type::Error::DuplicateKey { .. })
+    ));
+    rw.commit().unwrap();
+
+    // Get by primary key and by secondary key
+    let r = db.r_transaction().unwrap();
+    let result: Page = r.get().primary(a.url.as_str()).unwrap().unwrap();
+    assert_eq!(result, a);
+    let result: Option<Page> = r.get().primary("https://example.com/c").unwrap();
+    assert_eq!(result, None);
+    let result: Page = r.get().secondary(PageKey::title, "B").unwrap().unwrap();
+    assert_eq!(result, b);
+    // The original primary key, not the hash
+    let result = r
+        .get()
+        .secondary_primary_key::<Page>(PageKey::title, "B")
+        .unwrap();
+    assert_eq!(result, Some(b.url.to_key()));
+
+    // Scan
+    let result: Vec<Page> = r
+        .scan()
+        .secondary(PageKey::domain)
+        .unwrap()
+        .start_with("example.com")
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(result.len(), 2);
+    let result: Vec<Page> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(r.len().primary::<Page>().unwrap(), 2);
+    drop(r);
+
+    // Update, upsert and remove
+    let mut updated_a = a.clone();
+    updated_a.title = "A2".to_string();
+    let rw = db.rw_transaction().unwrap();
+    rw.update(a.clone(), updated_a.clone()).unwrap();
+    let old_b = rw.upsert(b.clone()).unwrap();
+    assert_eq!(old_b, Some(b.clone()));
+    let removed_b = rw.remove(b.clone()).unwrap();
+    assert_eq!(removed_b, b);
+    assert!(matches!(
+        rw.remove(b.clone()),
+        Err(db_type::Error::KeyNotFound { .. })
+    ));
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let result: Page = r.get().primary(a.url.as_str()).unwrap().unwrap();
+    assert_eq!(result, updated_a);
+    let result: Option<Page> = r.get().primary(b.url.as_str()).unwrap();
+    assert_eq!(result, None);
+    let result: Option<Page> = r.get().secondary(PageKey::title, "A").unwrap();
+    assert_eq!(result, None);
+    assert_eq!(r.len().primary::<Page>().unwrap(), 1);
+}
+
+#[test]
+fn test_hashed_primary_key_drain() {
+    let mut models = Models::new();
+    models.define::<Page>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let a = Page::new("a", "A");
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(a.clone()).unwrap();
+    let drained = rw.drain().primary::<Page>().unwrap();
+    assert_eq!(drained, vec![a.clone()]);
+
+    // The primary key can be inserted again after the drain
+    rw.insert(a.clone()).unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let result: Page = r.get().primary(a.url.as_str()).unwrap().unwrap();
+    assert_eq!(result, a);
+}
@@ -4,3 +4,12 @@ mod secondary_key;
 mod secondary_key_attribute;
 mod secondary_key_mix;
 mod export_keys_attribute;
+mod hashed_primary_key_attribute;
+mod version_field_attribute;
+mod secondary_key_accessor;
+mod generic_model;
+mod validate_attribute;
+mod secondary_key_collation;
+mod secondary_key_store_value;
+mod whole_primary_key_attribute;
+mod secondary_key_nested;
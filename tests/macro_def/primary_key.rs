@@ -26,3 +26,115 @@ fn test_insert_my_item() {
     let key = item.native_db_primary_key();
     assert_eq!(key, "1-test".to_key());
 }
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+#[native_model(id = 2, version = 1)]
+#[native_db(primary_key(composite_pk -> (u32, u32)))]
+struct ItemComposite {
+    tenant_id: u32,
+    user_id: u32,
+}
+
+impl ItemComposite {
+    pub fn composite_pk(&self) -> (u32, u32) {
+        (self.tenant_id, self.user_id)
+    }
+}
+
+#[test]
+fn test_composite_primary_key() {
+    let mut models = Models::new();
+    models.define::<ItemComposite>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for (tenant_id, user_id) in [(1, 1), (1, 2), (2, 1), (2, 2), (2, 3)] {
+        rw.insert(ItemComposite { tenant_id, user_id }).unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+
+    // Exact get by the pair
+    let item: ItemComposite = r.get().primary((2u32, 1u32)).unwrap().unwrap();
+    assert_eq!(
+        item,
+        ItemComposite {
+            tenant_id: 2,
+            user_id: 1
+        }
+    );
+    let item: Option<ItemComposite> = r.get().primary((3u32, 1u32)).unwrap();
+    assert!(item.is_none());
+
+    // Scan by the first component only
+    let items: Vec<ItemComposite> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .start_with_component(2u32)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(items.len(), 3);
+    assert!(items.iter().all(|item| item.tenant_id == 2));
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+#[native_model(id = 3, version = 1)]
+#[native_db(primary_key(composite_pk -> (String, String)))]
+struct ItemCompositeString {
+    first: String,
+    second: String,
+}
+
+impl ItemCompositeString {
+    pub fn composite_pk(&self) -> (String, String) {
+        (self.first.clone(), self.second.clone())
+    }
+}
+
+#[test]
+fn test_composite_primary_key_delimited() {
+    let mut models = Models::new();
+    models.define::<ItemCompositeString>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for (first, second) in [("ab", "c"), ("a", "bc")] {
+        rw.insert(ItemCompositeString {
+            first: first.to_string(),
+            second: second.to_string(),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<ItemCompositeString>().unwrap(), 2);
+    let item: ItemCompositeString = r
+        .get()
+        .primary(("a".to_string(), "bc".to_string()))
+        .unwrap()
+        .unwrap();
+    assert_eq!(item.second, "bc");
+
+    // The first component is delimited: "a" doesn't match "ab"
+    let items: Vec<ItemCompositeString> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .start_with_component("a".to_string())
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].second, "bc");
+
+    // The type of the component is checked
+    let scan = r.scan().primary::<ItemCompositeString>().unwrap();
+    assert!(matches!(
+        scan.start_with_component(1u32),
+        Err(db_type::Error::MissmatchedKeyType { .. })
+    ));
+}
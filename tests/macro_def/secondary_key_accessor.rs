@@ -0,0 +1,74 @@
+use itertools::Itertools;
+use native_db::db_type::ToKeyDefinition;
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 1)]
+#[native_db(secondary_key(flag_id -> String, unique))]
+struct ItemAccessor {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    flag: String,
+    #[secondary_key(optional)]
+    r#type: Option<String>,
+}
+
+impl ItemAccessor {
+    fn flag_id(&self) -> String {
+        format!("{}:{}", self.flag, self.id)
+    }
+}
+
+#[test]
+fn test_secondary_key_accessor() {
+    assert_eq!(
+        ItemAccessor::secondary_key_flag(),
+        ItemAccessorKey::flag.key_definition()
+    );
+    assert_eq!(
+        ItemAccessor::secondary_key_flag_id(),
+        ItemAccessorKey::flag_id.key_definition()
+    );
+    assert_eq!(
+        ItemAccessor::secondary_key_type(),
+        ItemAccessorKey::r#type.key_definition()
+    );
+
+    let mut models = Models::new();
+    models.define::<ItemAccessor>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(ItemAccessor {
+        id: 1,
+        flag: "red".to_string(),
+        r#type: None,
+    })
+    .unwrap();
+    rw.insert(ItemAccessor {
+        id: 2,
+        flag: "blue".to_string(),
+        r#type: Some("a".to_string()),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let scan = r
+        .scan()
+        .secondary(ItemAccessor::secondary_key_flag())
+        .unwrap();
+    let result: Vec<ItemAccessor> = scan.start_with("red").unwrap().try_collect().unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, 1);
+
+    let item: ItemAccessor = r
+        .get()
+        .secondary(ItemAccessor::secondary_key_flag_id(), "blue:2")
+        .unwrap()
+        .unwrap();
+    assert_eq!(item.id, 2);
+}
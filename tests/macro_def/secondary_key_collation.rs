@@ -0,0 +1,120 @@
+use itertools::Itertools;
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+// A simple collation: compare the names ignoring the case.
+fn case_folding(name: &str) -> Vec<u8> {
+    name.to_lowercase().into_bytes()
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Fruit {
+    #[primary_key]
+    id: u32,
+    #[secondary_key(unique, collation = case_folding)]
+    name: String,
+    #[secondary_key(optional, collation = case_folding)]
+    nickname: Option<String>,
+}
+
+fn fruit(id: u32, name: &str, nickname: Option<&str>) -> Fruit {
+    Fruit {
+        id,
+        name: name.to_string(),
+        nickname: nickname.map(|nickname| nickname.to_string()),
+    }
+}
+
+fn names(fruits: Vec<Fruit>) -> Vec<String> {
+    fruits.into_iter().map(|fruit| fruit.name).collect()
+}
+
+#[test]
+fn secondary_key_collation_order() {
+    let mut models = Models::new();
+    models.define::<Fruit>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(fruit(1, "banana", None)).unwrap();
+    rw.insert(fruit(2, "Apple", Some("Pink Lady"))).unwrap();
+    rw.insert(fruit(3, "Cherry", None)).unwrap();
+    rw.insert(fruit(4, "avocado", Some("butter"))).unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+
+    // Sorted by bytes, "Cherry" would come before "avocado".
+    let fruits: Vec<Fruit> = r
+        .scan()
+        .secondary(FruitKey::name)
+        .unwrap()
+        .all()
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(names(fruits), vec!["Apple", "avocado", "banana", "Cherry"]);
+
+    // The queries take the collation key.
+    let fruits: Vec<Fruit> = r
+        .scan()
+        .secondary(FruitKey::name)
+        .unwrap()
+        .range(case_folding("B")..)
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(names(fruits), vec!["banana", "Cherry"]);
+
+    let fruit: Option<Fruit> = r
+        .get()
+        .secondary(FruitKey::name, case_folding("CHERRY"))
+        .unwrap();
+    assert_eq!(fruit.unwrap().id, 3);
+
+    let fruits: Vec<Fruit> = r
+        .scan()
+        .secondary(FruitKey::nickname)
+        .unwrap()
+        .all()
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(names(fruits), vec!["avocado", "Apple"]);
+}
+
+#[test]
+fn secondary_key_collation_update() {
+    let mut models = Models::new();
+    models.define::<Fruit>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(fruit(1, "banana", None)).unwrap();
+    rw.insert(fruit(2, "Apple", None)).unwrap();
+    rw.commit().unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.update(fruit(1, "banana", None), fruit(1, "Zucchini", None))
+        .unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let fruits: Vec<Fruit> = r
+        .scan()
+        .secondary(FruitKey::name)
+        .unwrap()
+        .all()
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(names(fruits), vec!["Apple", "Zucchini"]);
+    let fruit: Option<Fruit> = r
+        .get()
+        .secondary(FruitKey::name, case_folding("banana"))
+        .unwrap();
+    assert_eq!(fruit, None);
+}
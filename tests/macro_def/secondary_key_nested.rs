@@ -0,0 +1,91 @@
+use itertools::Itertools;
+use native_db::db_type::{KeyDefinition, KeyOptions, ToKeyDefinition};
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+struct Customer {
+    id: u32,
+    email: String,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Order {
+    #[primary_key]
+    id: u32,
+    #[secondary_key(customer.id)]
+    #[secondary_key(customer.email, unique)]
+    customer: Customer,
+}
+
+fn order(id: u32, customer_id: u32) -> Order {
+    Order {
+        id,
+        customer: Customer {
+            id: customer_id,
+            email: format!("{}-{}@test", customer_id, id),
+        },
+    }
+}
+
+#[test]
+fn test_secondary_key_nested() {
+    assert_eq!(
+        OrderKey::customer_id.key_definition(),
+        KeyDefinition::new(1, 1, "customer_id", vec![], KeyOptions::default())
+    );
+    assert!(!OrderKey::customer_id.key_definition().options().unique);
+    assert_eq!(
+        Order::secondary_key_customer_email(),
+        OrderKey::customer_email.key_definition()
+    );
+    assert!(Order::secondary_key_customer_email().options().unique);
+
+    // The type of the nested field is checked
+    let scan_with_str = || -> db_type::Result<()> {
+        let mut models = Models::new();
+        models.define::<Order>()?;
+        let db = Builder::new().create_in_memory(&models)?;
+        let r = db.r_transaction()?;
+        let _: Vec<Order> = r
+            .scan()
+            .secondary(OrderKey::customer_id)?
+            .range("a".."b")?
+            .try_collect()?;
+        Ok(())
+    };
+    assert!(matches!(
+        scan_with_str(),
+        Err(db_type::Error::MissmatchedKeyType { .. })
+    ));
+
+    let mut models = Models::new();
+    models.define::<Order>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(order(1, 10)).unwrap();
+    rw.insert(order(2, 20)).unwrap();
+    rw.insert(order(3, 10)).unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let orders: Vec<Order> = r
+        .scan()
+        .secondary(OrderKey::customer_id)
+        .unwrap()
+        .range(10u32..=10u32)
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(orders, vec![order(1, 10), order(3, 10)]);
+
+    let found: Option<Order> = r
+        .get()
+        .secondary(OrderKey::customer_email, "20-2@test")
+        .unwrap();
+    assert_eq!(found, Some(order(2, 20)));
+}
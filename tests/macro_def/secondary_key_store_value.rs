@@ -0,0 +1,191 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use shortcut_assert_fs::TmpFs;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct User {
+    #[primary_key]
+    id: u32,
+    #[secondary_key(unique, store_value)]
+    email: String,
+    name: String,
+}
+
+fn user(id: u32, email: &str, name: &str) -> User {
+    User {
+        id,
+        email: email.to_string(),
+        name: name.to_string(),
+    }
+}
+
+const VALUE_TABLE: redb::TableDefinition<Key, &[u8]> =
+    redb::TableDefinition::new("1_1_email_value");
+
+fn stored_values_len(db: &Database) -> u64 {
+    use redb::ReadableTableMetadata;
+    let r = db.redb_database().unwrap().begin_read().unwrap();
+    r.open_table(VALUE_TABLE).unwrap().len().unwrap()
+}
+
+fn get_by_email(db: &Database, email: &str) -> Option<User> {
+    let r = db.r_transaction().unwrap();
+    r.get().secondary(UserKey::email, email).unwrap()
+}
+
+#[test]
+fn store_value_insert_update_remove() {
+    let mut models = Models::new();
+    models.define::<User>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(user(1, "a@test", "A")).unwrap();
+    rw.insert(user(2, "b@test", "B")).unwrap();
+    rw.commit().unwrap();
+    assert_eq!(get_by_email(&db, "a@test"), Some(user(1, "a@test", "A")));
+    assert_eq!(stored_values_len(&db), 2);
+
+    // Same key, the stored value is rewritten
+    let rw = db.rw_transaction().unwrap();
+    rw.update(user(1, "a@test", "A"), user(1, "a@test", "A2"))
+        .unwrap();
+    rw.commit().unwrap();
+    assert_eq!(get_by_email(&db, "a@test"), Some(user(1, "a@test", "A2")));
+
+    // New key
+    let rw = db.rw_transaction().unwrap();
+    rw.update(user(1, "a@test", "A2"), user(1, "c@test", "A2"))
+        .unwrap();
+    rw.commit().unwrap();
+    assert_eq!(get_by_email(&db, "a@test"), None);
+    assert_eq!(get_by_email(&db, "c@test"), Some(user(1, "c@test", "A2")));
+
+    // New primary key
+    let rw = db.rw_transaction().unwrap();
+    rw.upsert(user(2, "b@test", "B2")).unwrap();
+    rw.update(user(2, "b@test", "B2"), user(3, "b@test", "B3"))
+        .unwrap();
+    rw.commit().unwrap();
+    assert_eq!(get_by_email(&db, "b@test"), Some(user(3, "b@test", "B3")));
+
+    let rw = db.rw_transaction().unwrap();
+    rw.remove(user(1, "c@test", "A2")).unwrap();
+    rw.commit().unwrap();
+    assert_eq!(get_by_email(&db, "c@test"), None);
+
+    let rw = db.rw_transaction().unwrap();
+    rw.remove_many_by_keys::<User, _>([3u32]).unwrap();
+    rw.commit().unwrap();
+    assert_eq!(get_by_email(&db, "b@test"), None);
+    assert_eq!(stored_values_len(&db), 0);
+}
+
+#[test]
+fn store_value_duplicate_key() {
+    let mut models = Models::new();
+    models.define::<User>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(user(1, "a@test", "A")).unwrap();
+    rw.insert(user(2, "b@test", "B")).unwrap();
+    rw.commit().unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    let result = rw.update(user(2, "b@test", "B"), user(2, "a@test", "B"));
+    assert!(matches!(result, Err(db_type::Error::DuplicateKey { .. })));
+    rw.abort().unwrap();
+    assert_eq!(get_by_email(&db, "a@test"), Some(user(1, "a@test", "A")));
+}
+
+#[test]
+fn store_value_drain_and_snapshot() {
+    let tf = TmpFs::new().unwrap();
+    let mut models = Models::new();
+    models.define::<User>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(user(1, "a@test", "A")).unwrap();
+    rw.commit().unwrap();
+
+    let snapshot = db
+        .snapshot(&models, tf.path("snapshot").as_std_path())
+        .unwrap();
+    assert_eq!(
+        get_by_email(&snapshot, "a@test"),
+        Some(user(1, "a@test", "A"))
+    );
+
+    let rw = db.rw_transaction().unwrap();
+    let drained: Vec<User> = rw.drain().primary().unwrap();
+    assert_eq!(drained.len(), 1);
+    rw.commit().unwrap();
+    assert_eq!(get_by_email(&db, "a@test"), None);
+}
+
+// The same model, without `store_value`
+mod without_store_value {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+    #[native_model(id = 1, version = 1)]
+    #[native_db]
+    pub struct User {
+        #[primary_key]
+        pub id: u32,
+        #[secondary_key(unique)]
+        pub email: String,
+        pub name: String,
+    }
+}
+
+#[test]
+fn store_value_set_on_existing_database() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<without_store_value::User>().unwrap();
+    let db = Builder::new().create(&models, db_path.clone()).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(without_store_value::User {
+        id: 1,
+        email: "a@test".to_string(),
+        name: "A".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+    drop(db);
+
+    // The values of the existing items are stored when the database is opened
+    let mut models_store_value = Models::new();
+    models_store_value.define::<User>().unwrap();
+    let db = Builder::new()
+        .open(&models_store_value, db_path.clone())
+        .unwrap();
+    assert_eq!(get_by_email(&db, "a@test"), Some(user(1, "a@test", "A")));
+    drop(db);
+
+    // Unset it and update the item
+    let db = Builder::new().open(&models, db_path.clone()).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.upsert(without_store_value::User {
+        id: 1,
+        email: "a@test".to_string(),
+        name: "A2".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+    drop(db);
+
+    // Set it again, the stale values are not used
+    let db = Builder::new()
+        .open(&models_store_value, db_path.clone())
+        .unwrap();
+    assert_eq!(get_by_email(&db, "a@test"), Some(user(1, "a@test", "A2")));
+}
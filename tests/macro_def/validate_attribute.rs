@@ -0,0 +1,70 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 1)]
+#[native_db(validate = validate_payment)]
+struct Payment {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    amount: i64,
+}
+
+fn validate_payment(payment: &Payment) -> Result<(), String> {
+    if payment.amount < 0 {
+        return Err(format!("negative amount: {}", payment.amount));
+    }
+    Ok(())
+}
+
+fn assert_validation_failed<T: std::fmt::Debug>(result: Result<T, db_type::Error>) {
+    match result {
+        Err(db_type::Error::ValidationFailed { message }) => {
+            assert_eq!(message, "negative amount: -5")
+        }
+        result => panic!("expected ValidationFailed, got {:?}", result),
+    }
+}
+
+#[test]
+fn test_validate() {
+    let mut models = Models::new();
+    models.define::<Payment>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let valid = Payment { id: 1, amount: 10 };
+    let invalid = Payment { id: 1, amount: -5 };
+
+    let rw = db.rw_transaction().unwrap();
+    assert_validation_failed(rw.insert(invalid.clone()));
+    assert_validation_failed(rw.upsert(invalid.clone()));
+    // Nothing was written
+    assert_eq!(rw.len().primary::<Payment>().unwrap(), 0);
+    assert_eq!(
+        rw.len().secondary::<Payment>(PaymentKey::amount).unwrap(),
+        0
+    );
+
+    rw.insert(valid.clone()).unwrap();
+    assert_validation_failed(rw.auto_update(invalid.clone()));
+    #[allow(deprecated)]
+    let result = rw.update(valid.clone(), invalid.clone());
+    assert_validation_failed(result);
+    rw.commit().unwrap();
+
+    // The valid value is unchanged
+    let r = db.r_transaction().unwrap();
+    let payment: Payment = r.get().primary(1u32).unwrap().unwrap();
+    assert_eq!(payment, valid);
+    let payments: Vec<Payment> = r
+        .scan()
+        .secondary(PaymentKey::amount)
+        .unwrap()
+        .range(..0i64)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert!(payments.is_empty());
+}
@@ -0,0 +1,131 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 1)]
+#[native_db(version_field = rev)]
+struct Document {
+    #[primary_key]
+    id: u32,
+    content: String,
+    rev: u64,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 2, version = 1)]
+#[native_db]
+struct Other {
+    #[primary_key]
+    id: u32,
+}
+
+#[test]
+fn test_update_checked() {
+    let mut models = Models::new();
+    models.define::<Document>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Document {
+        id: 1,
+        content: "v0".to_string(),
+        rev: 0,
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    // Two clients read the same version
+    let r = db.r_transaction().unwrap();
+    let mut client_a: Document = r.get().primary(1u32).unwrap().unwrap();
+    let mut client_b: Document = r.get().primary(1u32).unwrap().unwrap();
+    drop(r);
+
+    // The first update succeeds and increments the version
+    client_a.content = "from a".to_string();
+    let rw = db.rw_transaction().unwrap();
+    rw.update_checked(client_a).unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let stored: Document = r.get().primary(1u32).unwrap().unwrap();
+    assert_eq!(
+        stored,
+        Document {
+            id: 1,
+            content: "from a".to_string(),
+            rev: 1,
+        }
+    );
+    drop(r);
+
+    // The second update is stale and conflicts
+    client_b.content = "from b".to_string();
+    let rw = db.rw_transaction().unwrap();
+    let result = rw.update_checked(client_b.clone());
+    assert!(matches!(
+        result,
+        Err(db_type::Error::VersionConflict {
+            expected: 0,
+            actual: 1
+        })
+    ));
+    rw.abort().unwrap();
+
+    // After reading the current version again, the update succeeds
+    client_b.rev = stored.rev;
+    let rw = db.rw_transaction().unwrap();
+    rw.update_checked(client_b).unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let stored: Document = r.get().primary(1u32).unwrap().unwrap();
+    assert_eq!(stored.content, "from b");
+    assert_eq!(stored.rev, 2);
+
+    // Not found
+    let rw = db.rw_transaction().unwrap();
+    let result = rw.update_checked(Document {
+        id: 2,
+        content: "missing".to_string(),
+        rev: 0,
+    });
+    assert!(matches!(result, Err(db_type::Error::KeyNotFound { .. })));
+}
+
+#[test]
+fn test_update_checked_without_version_field() {
+    let mut models = Models::new();
+    models.define::<Other>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Other { id: 1 }).unwrap();
+    let result = rw.update_checked(Other { id: 1 });
+    assert!(matches!(
+        result,
+        Err(db_type::Error::VersionFieldNotDefined { .. })
+    ));
+}
+
+#[test]
+fn test_update_checked_version_overflow() {
+    let mut models = Models::new();
+    models.define::<Document>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let document = Document {
+        id: 1,
+        content: "v0".to_string(),
+        rev: u64::MAX,
+    };
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(document.clone()).unwrap();
+    let result = rw.update_checked(document.clone());
+    assert!(matches!(
+        result,
+        Err(db_type::Error::VersionOverflow { table }) if table == "1_1_id"
+    ));
+    let stored: Document = rw.get().primary(1u32).unwrap().unwrap();
+    assert_eq!(stored, document);
+}
@@ -0,0 +1,121 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 1)]
+#[native_db(primary_key(whole))]
+struct Tag {
+    item: u32,
+    label: String,
+    #[secondary_key]
+    color: String,
+}
+
+impl Tag {
+    fn new(item: u32, label: &str) -> Self {
+        Self {
+            item,
+            label: label.to_string(),
+            color: "red".to_string(),
+        }
+    }
+}
+
+#[test]
+fn test_whole_primary_key() {
+    assert!(Tag::native_db_model().whole_primary_key);
+
+    let mut models = Models::new();
+    models.define::<Tag>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Tag::new(1, "urgent")).unwrap();
+    // Inserting the same value again is a no-op
+    rw.insert(Tag::new(1, "urgent")).unwrap();
+    assert_eq!(rw.len().primary::<Tag>().unwrap(), 1);
+    rw.insert(Tag::new(1, "later")).unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Tag>().unwrap(), 2);
+    let tag: Tag = r.get().primary(Tag::new(1, "later")).unwrap().unwrap();
+    assert_eq!(tag, Tag::new(1, "later"));
+    assert!(r
+        .get()
+        .primary::<Tag>(Tag::new(2, "later"))
+        .unwrap()
+        .is_none());
+    assert_eq!(r.len().secondary::<Tag>(TagKey::color).unwrap(), 2);
+    drop(r);
+
+    // An update is a remove followed by an insert
+    let rw = db.rw_transaction().unwrap();
+    rw.remove(Tag::new(1, "later")).unwrap();
+    rw.insert(Tag::new(1, "done")).unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Tag>().unwrap(), 2);
+    assert!(r
+        .get()
+        .primary::<Tag>(Tag::new(1, "done"))
+        .unwrap()
+        .is_some());
+}
+
+// A label that can't be encoded when it is empty.
+#[derive(Deserialize, Eq, PartialEq, Debug, Clone)]
+struct Label(String);
+
+impl Serialize for Label {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0.is_empty() {
+            return Err(serde::ser::Error::custom("empty label"));
+        }
+        serializer.serialize_str(&self.0)
+    }
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 2, version = 1)]
+#[native_db(primary_key(whole))]
+struct LabeledTag {
+    item: u32,
+    label: Label,
+}
+
+#[test]
+fn test_whole_primary_key_encode_error() {
+    let mut models = Models::new();
+    models.define::<LabeledTag>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let unencodable = LabeledTag {
+        item: 1,
+        label: Label(String::new()),
+    };
+    let rw = db.rw_transaction().unwrap();
+    assert!(rw.insert(unencodable.clone()).is_err());
+    assert!(rw.upsert(unencodable.clone()).is_err());
+    assert!(rw
+        .get()
+        .primary::<LabeledTag>(unencodable.clone())
+        .unwrap()
+        .is_none());
+    rw.insert(LabeledTag {
+        item: 1,
+        label: Label("urgent".to_string()),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<LabeledTag>().unwrap(), 1);
+    assert!(r
+        .get()
+        .primary::<LabeledTag>(unencodable)
+        .unwrap()
+        .is_none());
+}
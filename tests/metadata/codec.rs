@@ -0,0 +1,56 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use shortcut_assert_fs::TmpFs;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Item {
+    #[primary_key]
+    id: u32,
+}
+
+#[test]
+fn test_codec_mismatch() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create(&models, db_path.clone()).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Item { id: 1 }).unwrap();
+    rw.commit().unwrap();
+
+    let metadata: redb::TableDefinition<&str, &str> = redb::TableDefinition::new("metadata");
+    let r = db.redb_database().unwrap().begin_read().unwrap();
+    let stamped = r
+        .open_table(metadata)
+        .unwrap()
+        .get("codec:1_1_id")
+        .unwrap()
+        .is_some();
+    assert!(stamped);
+    drop(r);
+
+    // The same codec, it opens
+    drop(db);
+    let db = Builder::new().open(&models, db_path.clone()).unwrap();
+
+    // Filled by a version of native_db using another configuration of bincode
+    let rw = db.redb_database().unwrap().begin_write().unwrap();
+    rw.open_table(metadata)
+        .unwrap()
+        .insert("codec:1_1_id", "bincode-2/legacy/big-endian/fixint")
+        .unwrap();
+    rw.commit().unwrap();
+    drop(db);
+
+    let result = Builder::new().open(&models, db_path.clone());
+    assert!(matches!(
+        result,
+        Err(db_type::Error::CodecMismatch { table, stored, .. })
+            if table == "1_1_id" && stored == "bincode-2/legacy/big-endian/fixint"
+    ));
+}
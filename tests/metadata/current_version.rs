@@ -48,6 +48,9 @@ fn test_current_version() {
     let mut models = Models::new();
     models.define::<Item1>().unwrap();
     models.define::<Item2>().unwrap();
+    Builder::new()
+        .upgrade_key_encoding(&models, &tmp_database_path)
+        .unwrap();
     let db = Builder::new().open(&models, &tmp_database_path).unwrap();
 
     let metadata = db.metadata();
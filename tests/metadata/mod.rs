@@ -1 +1,2 @@
+mod codec;
 mod current_version;
@@ -0,0 +1,100 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use shortcut_assert_fs::TmpFs;
+use std::num::TryFromIntError;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct ItemV1 {
+    #[primary_key]
+    id: u32,
+    level: u32,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+#[native_model(id = 1, version = 2, try_from = (ItemV1, TryFromIntError))]
+#[native_db]
+struct ItemV2 {
+    #[primary_key]
+    id: u32,
+    level: u8,
+}
+
+impl TryFrom<ItemV1> for ItemV2 {
+    type Error = TryFromIntError;
+
+    fn try_from(item: ItemV1) -> Result<Self, Self::Error> {
+        Ok(ItemV2 {
+            id: item.id,
+            level: item.level.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<ItemV2> for ItemV1 {
+    type Error = TryFromIntError;
+
+    fn try_from(item: ItemV2) -> Result<Self, Self::Error> {
+        Ok(ItemV1 {
+            id: item.id,
+            level: item.level.into(),
+        })
+    }
+}
+
+#[test]
+fn test_migrate_dry_run() {
+    let tf = TmpFs::new().unwrap();
+    let mut models = Models::new();
+    models.define::<ItemV1>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(ItemV1 { id: 1, level: 10 }).unwrap();
+    rw.insert(ItemV1 { id: 2, level: 1000 }).unwrap();
+    rw.insert(ItemV1 { id: 3, level: 255 }).unwrap();
+    rw.commit().unwrap();
+    drop(db);
+
+    let mut models = Models::new();
+    models.define::<ItemV1>().unwrap();
+    models.define::<ItemV2>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    let report = rw.migrate_dry_run::<ItemV2>().unwrap();
+    assert!(!report.is_ok());
+    assert_eq!(report.convertible, 2);
+    assert_eq!(report.failing(), 1);
+    assert_eq!(report.failures[0].primary_key, 2u32.to_key());
+    assert!(matches!(
+        report.failures[0].error,
+        db_type::Error::ModelError(_)
+    ));
+    rw.commit().unwrap();
+
+    // Nothing has been migrated
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<ItemV1>().unwrap(), 3);
+    assert_eq!(r.len().primary::<ItemV2>().unwrap(), 0);
+    drop(r);
+
+    // Nothing to migrate
+    let rw = db.rw_transaction().unwrap();
+    rw.remove(ItemV1 { id: 2, level: 1000 }).unwrap();
+    rw.migrate::<ItemV2>().unwrap();
+    let report = rw.migrate_dry_run::<ItemV2>().unwrap();
+    assert!(report.is_ok());
+    assert_eq!(report.convertible, 0);
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let item: ItemV2 = r.get().primary(3u32).unwrap().unwrap();
+    assert_eq!(item, ItemV2 { id: 3, level: 255 });
+}
@@ -1,4 +1,7 @@
+mod dry_run;
 mod only_primary_key;
 mod with_multiple_versions;
 mod with_other_model;
+mod with_closure;
 mod with_secondary_keys;
+mod rename_table;
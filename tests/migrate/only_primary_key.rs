@@ -115,3 +115,37 @@ fn test_migrate() {
     assert_eq!(stats.primary_tables[1].n_entries, Some(1));
     assert_eq!(stats.secondary_tables.len(), 0);
 }
+
+#[test]
+fn test_open_with_forward_version() {
+    let tf = TmpFs::new().unwrap();
+
+    // A newer application writes with the version 2.
+    let mut models = Models::new();
+    models.define::<ItemV1>().unwrap();
+    models.define::<ItemV2>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(ItemV2 {
+        id: 1,
+        name: "test".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+    drop(db);
+
+    // An older application only knows the version 1.
+    let mut models = Models::new();
+    models.define::<ItemV1>().unwrap();
+    let result = Builder::new().open(&models, tf.path("test").as_std_path());
+    assert!(matches!(
+        result,
+        Err(db_type::Error::ForwardVersionUnsupported {
+            id: 1,
+            stored_version: 2,
+            max_known: 1,
+        })
+    ));
+}
@@ -0,0 +1,311 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use shortcut_assert_fs::TmpFs;
+
+mod before {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+    #[native_model(id = 1, version = 1)]
+    #[native_db]
+    pub struct Item {
+        #[primary_key]
+        pub id: u32,
+        #[secondary_key(unique)]
+        pub name: String,
+    }
+}
+
+mod after {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+    #[native_model(id = 1, version = 1)]
+    #[native_db]
+    pub struct Item {
+        #[primary_key]
+        pub key: u32,
+        #[secondary_key(unique)]
+        pub title: String,
+    }
+}
+
+#[test]
+fn test_rename_table() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<before::Item>().unwrap();
+    let db = Builder::new().create(&models, db_path.clone()).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    for id in 1..=3 {
+        rw.insert(before::Item {
+            id,
+            name: format!("item_{}", id),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+    drop(db);
+
+    // Open the database with the renamed keys
+    let mut models = Models::new();
+    models.define::<after::Item>().unwrap();
+    let db = Builder::new().open(&models, db_path.clone()).unwrap();
+
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<after::Item>().unwrap(), 0);
+    drop(r);
+
+    db.rename_table("1_1_id", "1_1_key").unwrap();
+    db.rename_table("1_1_name", "1_1_title").unwrap();
+
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<after::Item>().unwrap(), 3);
+    let item: after::Item = r.get().primary(2u32).unwrap().unwrap();
+    assert_eq!(item.title, "item_2");
+    let item: after::Item = r
+        .get()
+        .secondary(after::ItemKey::title, "item_3")
+        .unwrap()
+        .unwrap();
+    assert_eq!(item.key, 3);
+    drop(r);
+
+    // The old tables are deleted
+    assert!(matches!(
+        db.rename_table("1_1_id", "1_1_other"),
+        Err(db_type::Error::TableDefinitionNotFound { .. })
+    ));
+    assert!(matches!(
+        db.rename_table("1_1_name", "1_1_other"),
+        Err(db_type::Error::TableDefinitionNotFound { .. })
+    ));
+
+    // The new table already exists
+    assert!(matches!(
+        db.rename_table("1_1_title", "1_1_key"),
+        Err(db_type::Error::TableAlreadyExists { .. })
+    ));
+}
+
+mod computed_before {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+    #[native_model(id = 2, version = 1)]
+    #[native_db(primary_key(id -> u32))]
+    pub struct Item {
+        pub id: u32,
+        pub name: String,
+    }
+
+    impl Item {
+        pub fn id(&self) -> u32 {
+            self.id
+        }
+    }
+}
+
+mod computed_after {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+    #[native_model(id = 2, version = 1)]
+    #[native_db(primary_key(key -> u32))]
+    pub struct Item {
+        pub id: u32,
+        pub name: String,
+    }
+
+    impl Item {
+        pub fn key(&self) -> u32 {
+            self.id
+        }
+    }
+}
+
+#[test]
+fn test_define_alias() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<computed_before::Item>().unwrap();
+    let db = Builder::new().create(&models, db_path.clone()).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    for id in 1..=3 {
+        rw.insert(computed_before::Item {
+            id,
+            name: format!("item_{}", id),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+    drop(db);
+
+    // Open the database with the renamed key and its former table as an alias
+    let mut models = Models::new();
+    assert!(matches!(
+        models.define_alias::<computed_after::Item>("2_1_id"),
+        Err(db_type::Error::ModelNotDefined { .. })
+    ));
+    models.define::<computed_after::Item>().unwrap();
+    models
+        .define_alias::<computed_after::Item>("2_1_id")
+        .unwrap();
+    for alias in ["2_1_id", "2_1_key"] {
+        assert!(matches!(
+            models.define_alias::<computed_after::Item>(alias),
+            Err(db_type::Error::TableAlreadyExists { table }) if table == alias
+        ));
+    }
+    let db = Builder::new().open(&models, db_path.clone()).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(computed_after::Item {
+        id: 4,
+        name: "item_4".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    // The same model is read via its table and via the alias
+    let r = db.r_transaction().unwrap();
+    let item: computed_after::Item = r.get().primary(4u32).unwrap().unwrap();
+    assert_eq!(item.name, "item_4");
+    let item: Option<computed_after::Item> = r.get().primary(2u32).unwrap();
+    assert!(item.is_none());
+    let item: computed_after::Item = r.get().primary_alias("2_1_id", 2u32).unwrap().unwrap();
+    assert_eq!(item.name, "item_2");
+    let item: Option<computed_after::Item> = r.get().primary_alias("2_1_id", 4u32).unwrap();
+    assert!(item.is_none());
+    let result: Result<Option<computed_after::Item>, _> = r.get().primary_alias("2_1_other", 2u32);
+    assert!(matches!(
+        result,
+        Err(db_type::Error::TableDefinitionNotFound { .. })
+    ));
+    drop(r);
+    drop(db);
+
+    // The alias table does not exist in a new database
+    let db = Builder::new().create_in_memory(&models).unwrap();
+    let r = db.r_transaction().unwrap();
+    let item: Option<computed_after::Item> = r.get().primary_alias("2_1_id", 2u32).unwrap();
+    assert!(item.is_none());
+}
+
+#[test]
+fn test_define_alias_collision() {
+    // An alias naming a table of another model
+    let mut models = Models::new();
+    models.define::<before::Item>().unwrap();
+    models.define::<computed_after::Item>().unwrap();
+    for alias in ["1_1_id", "1_1_name"] {
+        assert!(matches!(
+            models.define_alias::<computed_after::Item>(alias),
+            Err(db_type::Error::TableAlreadyExists { table }) if table == alias
+        ));
+    }
+
+    // A model defined after an alias naming its table
+    let mut models = Models::new();
+    models.define::<computed_after::Item>().unwrap();
+    models
+        .define_alias::<computed_after::Item>("1_1_id")
+        .unwrap();
+    assert!(matches!(
+        models.define::<before::Item>(),
+        Err(db_type::Error::TableAlreadyExists { table }) if table == "1_1_id"
+    ));
+}
+
+#[cfg(feature = "zstd")]
+mod compressed_before {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+    #[native_model(id = 3, version = 1)]
+    #[native_db(primary_key(id -> u32), compress = "zstd")]
+    pub struct Item {
+        pub id: u32,
+        pub content: String,
+    }
+
+    impl Item {
+        pub fn id(&self) -> u32 {
+            self.id
+        }
+    }
+}
+
+#[cfg(feature = "zstd")]
+mod compressed_after {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+    #[native_model(id = 3, version = 1)]
+    #[native_db(primary_key(key -> u32), compress = "zstd")]
+    pub struct Item {
+        pub id: u32,
+        pub content: String,
+    }
+
+    impl Item {
+        pub fn key(&self) -> u32 {
+            self.id
+        }
+    }
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_rename_compressed_table() {
+    use redb::ReadableTable;
+
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<compressed_before::Item>().unwrap();
+    let db = Builder::new().create(&models, db_path.clone()).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    for id in 1..=3 {
+        rw.insert(compressed_before::Item {
+            id,
+            content: format!("content {}", id).repeat(100),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+    drop(db);
+
+    let mut models = Models::new();
+    models.define::<compressed_after::Item>().unwrap();
+    let db = Builder::new().open(&models, db_path.clone()).unwrap();
+    db.rename_table("3_1_id", "3_1_key").unwrap();
+
+    // The metadata of the old table is moved to the new one
+    let metadata: Vec<String> = {
+        let r = db.redb_database().unwrap().begin_read().unwrap();
+        let table = r
+            .open_table(redb::TableDefinition::<&str, &str>::new("metadata"))
+            .unwrap();
+        table
+            .iter()
+            .unwrap()
+            .map(|result| result.unwrap().0.value().to_string())
+            .collect()
+    };
+    assert!(metadata.contains(&"compression:3_1_key".to_string()));
+    assert!(!metadata.iter().any(|name| name.ends_with(":3_1_id")));
+    drop(db);
+
+    // The rows are read back once the database is opened again
+    let db = Builder::new().open(&models, db_path.clone()).unwrap();
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<compressed_after::Item>().unwrap(), 3);
+    let item: compressed_after::Item = r.get().primary(2u32).unwrap().unwrap();
+    assert_eq!(item.content, "content 2".repeat(100));
+}
@@ -0,0 +1,140 @@
+use itertools::Itertools;
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use shortcut_assert_fs::TmpFs;
+use std::error::Error as _;
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct ItemV1 {
+    #[primary_key]
+    id: u32,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+#[native_model(id = 1, version = 2, from = ItemV1)]
+#[native_db]
+struct ItemV2 {
+    #[primary_key]
+    id: u32,
+    name: String,
+}
+
+impl From<ItemV1> for ItemV2 {
+    fn from(item: ItemV1) -> Self {
+        ItemV2 {
+            id: item.id,
+            name: String::new(),
+        }
+    }
+}
+
+impl From<ItemV2> for ItemV1 {
+    fn from(item: ItemV2) -> Self {
+        ItemV1 { id: item.id }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct InvalidItem(u32);
+
+impl fmt::Display for InvalidItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid item {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidItem {}
+
+fn create_v1_database(tf: &TmpFs) {
+    let mut models = Models::new();
+    models.define::<ItemV1>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(ItemV1 { id: 1 }).unwrap();
+    rw.insert(ItemV1 { id: 2 }).unwrap();
+    rw.commit().unwrap();
+}
+
+#[test]
+fn test_migrate_with() {
+    let tf = TmpFs::new().unwrap();
+    create_v1_database(&tf);
+
+    let mut models = Models::new();
+    models.define::<ItemV1>().unwrap();
+    models.define::<ItemV2>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.migrate_with::<ItemV2, _>(|item| {
+        item.name = format!("item_{}", item.id);
+        Ok(())
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let items: Vec<ItemV2> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(
+        items,
+        vec![
+            ItemV2 {
+                id: 1,
+                name: "item_1".to_string()
+            },
+            ItemV2 {
+                id: 2,
+                name: "item_2".to_string()
+            },
+        ]
+    );
+    assert_eq!(r.len().primary::<ItemV1>().unwrap(), 0);
+}
+
+#[test]
+fn test_migrate_with_custom_error() {
+    let tf = TmpFs::new().unwrap();
+    create_v1_database(&tf);
+
+    let mut models = Models::new();
+    models.define::<ItemV1>().unwrap();
+    models.define::<ItemV2>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    let error = rw
+        .migrate_with::<ItemV2, _>(|item| {
+            if item.id == 2 {
+                return Err(Box::new(InvalidItem(item.id)));
+            }
+            Ok(())
+        })
+        .unwrap_err();
+    assert!(matches!(error, db_type::Error::Migration(_)));
+    let source = error.source().unwrap();
+    assert_eq!(source.downcast_ref::<InvalidItem>(), Some(&InvalidItem(2)));
+    rw.abort().unwrap();
+
+    // Nothing was migrated
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<ItemV1>().unwrap(), 2);
+    assert_eq!(r.len().primary::<ItemV2>().unwrap(), 0);
+}
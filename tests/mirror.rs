@@ -0,0 +1,237 @@
+use itertools::Itertools;
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use shortcut_assert_fs::TmpFs;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Item {
+    #[primary_key]
+    id: u32,
+    #[secondary_key(unique)]
+    name: String,
+}
+
+fn item(id: u32, name: &str) -> Item {
+    Item {
+        id,
+        name: name.to_string(),
+    }
+}
+
+fn all_items(db: &Database) -> Vec<Item> {
+    let r = db.r_transaction().unwrap();
+    r.scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .try_collect()
+        .unwrap()
+}
+
+#[test]
+fn test_mirror() {
+    let tf = TmpFs::new().unwrap();
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+
+    let db = Builder::new()
+        .with_mirror(tf.path("mirror").as_std_path())
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for (id, name) in [(1, "a"), (2, "b"), (3, "c"), (4, "d")] {
+        rw.insert(item(id, name)).unwrap();
+    }
+    rw.commit().unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    // Same primary key
+    rw.upsert(item(1, "a2")).unwrap();
+    // Other primary key
+    #[allow(deprecated)]
+    rw.update(item(2, "b"), item(20, "b2")).unwrap();
+    rw.remove(item(3, "c")).unwrap();
+    rw.remove_many_by_keys::<Item, _>([4u32]).unwrap();
+    rw.insert(item(5, "e")).unwrap();
+    rw.commit().unwrap();
+
+    let expected = all_items(&db);
+    assert_eq!(expected, vec![item(1, "a2"), item(5, "e"), item(20, "b2")]);
+    drop(db);
+
+    let mirror = Builder::new()
+        .open(&models, tf.path("mirror").as_std_path())
+        .unwrap();
+    assert_eq!(all_items(&mirror), expected);
+    let r = mirror.r_transaction().unwrap();
+    let found: Item = r.get().secondary(ItemKey::name, "b2").unwrap().unwrap();
+    assert_eq!(found, item(20, "b2"));
+    let found: Option<Item> = r.get().secondary(ItemKey::name, "b").unwrap();
+    assert_eq!(found, None);
+}
+
+#[test]
+fn test_mirror_swap() {
+    let tf = TmpFs::new().unwrap();
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+
+    let db = Builder::new()
+        .with_mirror(tf.path("mirror").as_std_path())
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for (id, name) in [(1, "a"), (2, "b"), (3, "c")] {
+        rw.insert(item(id, name)).unwrap();
+    }
+    rw.commit().unwrap();
+
+    // The unique secondary keys are exchanged too
+    let rw = db.rw_transaction().unwrap();
+    rw.swap::<Item>(1u32, 2u32).unwrap();
+    rw.upsert(item(3, "c2")).unwrap();
+    rw.commit().unwrap();
+
+    let expected = all_items(&db);
+    assert_eq!(expected, vec![item(1, "b"), item(2, "a"), item(3, "c2")]);
+    drop(db);
+
+    let mirror = Builder::new()
+        .open(&models, tf.path("mirror").as_std_path())
+        .unwrap();
+    assert_eq!(all_items(&mirror), expected);
+    let r = mirror.r_transaction().unwrap();
+    let found: Item = r.get().secondary(ItemKey::name, "a").unwrap().unwrap();
+    assert_eq!(found, item(2, "a"));
+}
+
+#[test]
+fn test_mirror_copied_on_open() {
+    let tf = TmpFs::new().unwrap();
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+
+    // Written without mirror
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(item(1, "a")).unwrap();
+    rw.commit().unwrap();
+    drop(db);
+
+    let db = Builder::new()
+        .with_mirror(tf.path("mirror").as_std_path())
+        .open(&models, tf.path("test").as_std_path())
+        .unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(item(2, "b")).unwrap();
+    rw.commit().unwrap();
+    drop(db);
+
+    let mirror = Builder::new()
+        .open(&models, tf.path("mirror").as_std_path())
+        .unwrap();
+    assert_eq!(all_items(&mirror), vec![item(1, "a"), item(2, "b")]);
+}
+
+#[test]
+fn test_mirror_counters() {
+    let tf = TmpFs::new().unwrap();
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+
+    let db = Builder::new()
+        .with_mirror(tf.path("mirror").as_std_path())
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+    db.counters().incr("visits", 2).unwrap();
+    db.counters().incr("visits", 3).unwrap();
+    db.counters().set("limit", 10).unwrap();
+
+    // With other changes
+    let rw = db.rw_transaction().unwrap();
+    rw.counters().incr("visits", 1).unwrap();
+    rw.insert(item(1, "a")).unwrap();
+    rw.commit().unwrap();
+    drop(db);
+
+    let mirror = Builder::new()
+        .open(&models, tf.path("mirror").as_std_path())
+        .unwrap();
+    assert_eq!(mirror.counters().get("visits").unwrap(), 6);
+    assert_eq!(mirror.counters().get("limit").unwrap(), 10);
+    assert_eq!(all_items(&mirror), vec![item(1, "a")]);
+}
+
+#[test]
+fn test_mirror_same_path() {
+    let tf = TmpFs::new().unwrap();
+    let models = Models::new();
+    let result = Builder::new()
+        .with_mirror(tf.path("test").as_std_path())
+        .create(&models, tf.path("test").as_std_path());
+    let error = result.err().unwrap();
+    assert!(matches!(
+        error,
+        db_type::Error::MirrorPathIsDatabasePath { .. }
+    ));
+    assert!(!error.is_retryable());
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 2, version = 1)]
+#[native_db(blob)]
+struct Attachment {
+    #[primary_key]
+    id: u32,
+}
+
+#[test]
+fn test_mirror_refuses_unmirrored_operations() {
+    let tf = TmpFs::new().unwrap();
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    models.define::<Attachment>().unwrap();
+
+    let db = Builder::new()
+        .with_mirror(tf.path("mirror").as_std_path())
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+    let is_not_mirrored = |result: Result<_, db_type::Error>, operation: &str| matches!(result, Err(db_type::Error::NotMirrored { operation: o }) if o == operation);
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(item(1, "a")).unwrap();
+    assert!(is_not_mirrored(
+        rw.insert_with_blob(Attachment { id: 1 }, b"hello".as_slice()),
+        "insert_with_blob"
+    ));
+    assert!(is_not_mirrored(
+        rw.remove_blob::<Attachment>(1u32).map(|_| ()),
+        "remove_blob"
+    ));
+    assert!(is_not_mirrored(
+        rw.drain().primary::<Item>().map(|_| ()),
+        "drain"
+    ));
+    assert!(is_not_mirrored(rw.refresh::<Item>(), "refresh"));
+    assert!(is_not_mirrored(rw.migrate::<Item>(), "migrate"));
+    rw.commit().unwrap();
+    assert!(is_not_mirrored(
+        db.rename_table("1_1_id", "1_1_key"),
+        "rename_table"
+    ));
+
+    // The operations which were refused left the database and its mirror equal
+    drop(db);
+    let mirror = Builder::new()
+        .open(&models, tf.path("mirror").as_std_path())
+        .unwrap();
+    assert_eq!(all_items(&mirror), vec![item(1, "a")]);
+}
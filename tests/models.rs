@@ -0,0 +1,116 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 2)]
+#[native_db]
+struct Item {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    name: String,
+}
+
+#[test]
+fn test_define_and_describe() {
+    let mut models = Models::new();
+    let descriptor = models.define_and_describe::<Item>().unwrap();
+    assert_eq!(descriptor.table_name(), "1_2_id");
+    assert_eq!(descriptor.native_model_id(), 1);
+    assert_eq!(descriptor.native_model_version(), 2);
+    assert_eq!(descriptor.model().secondary_keys.len(), 1);
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 2, version = 1)]
+#[native_db]
+struct Other {
+    #[primary_key]
+    id: u32,
+}
+
+mod with_code {
+    use super::*;
+
+    // Same id and version as `Item`, with one more secondary key.
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+    #[native_model(id = 1, version = 2)]
+    #[native_db]
+    pub struct Item {
+        #[primary_key]
+        pub id: u32,
+        #[secondary_key]
+        pub name: String,
+        #[secondary_key]
+        pub code: String,
+    }
+}
+
+#[test]
+fn test_schema_fingerprint_stable() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    models.define::<Other>().unwrap();
+
+    let mut models_reordered = Models::new();
+    models_reordered.define::<Other>().unwrap();
+    models_reordered.define::<Item>().unwrap();
+
+    assert_eq!(models.schema_fingerprint(), models.schema_fingerprint());
+    assert_eq!(
+        models.schema_fingerprint(),
+        models_reordered.schema_fingerprint()
+    );
+}
+
+#[test]
+fn test_schema_fingerprint_secondary_key_added() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+
+    let mut models_with_code = Models::new();
+    models_with_code.define::<with_code::Item>().unwrap();
+
+    assert_ne!(
+        models.schema_fingerprint(),
+        models_with_code.schema_fingerprint()
+    );
+}
+
+#[test]
+fn test_schema_fingerprint_unchanged() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    models.define::<Other>().unwrap();
+
+    // Computed before `store_value` was added, the options left to their default don't change
+    // the fingerprint of an existing schema.
+    assert_eq!(models.schema_fingerprint(), "24308bcea8c9e409");
+}
+
+#[test]
+fn test_define_twice() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    assert!(matches!(
+        models.define::<Item>(),
+        Err(db_type::Error::ModelAlreadyDefined { table }) if table == "1_2_id"
+    ));
+
+    // Another type with the same table is a collision, not a re-define
+    assert!(matches!(
+        models.define::<with_code::Item>(),
+        Err(db_type::Error::ModelTableCollision { .. })
+    ));
+
+    // The models are unchanged
+    let db = Builder::new().create_in_memory(&models).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Item {
+        id: 1,
+        name: "test".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+}
@@ -0,0 +1,114 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use shortcut_assert_fs::TmpFs;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Item {
+    #[primary_key]
+    id: u32,
+    #[secondary_key(unique)]
+    name: String,
+    #[secondary_key]
+    group: u32,
+}
+
+fn item(id: u32, name: &str) -> Item {
+    Item {
+        id,
+        name: name.to_string(),
+        group: 0,
+    }
+}
+
+fn get(db: &Database, name: &str) -> Option<Item> {
+    let r = db.r_transaction().unwrap();
+    r.get().secondary(ItemKey::name, name).unwrap()
+}
+
+#[test]
+fn cache_unique_secondary_consistency() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+
+    let db = Builder::new()
+        .cache_unique_secondary::<Item>(ItemKey::name)
+        .create(&models, db_path.as_std_path())
+        .unwrap();
+
+    // Insert
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(item(1, "a")).unwrap();
+    rw.insert(item(2, "b")).unwrap();
+    rw.commit().unwrap();
+    assert_eq!(get(&db, "a"), Some(item(1, "a")));
+    assert_eq!(get(&db, "b"), Some(item(2, "b")));
+    assert_eq!(get(&db, "c"), None);
+
+    // A read transaction opened before a commit keeps its snapshot
+    let old_r = db.r_transaction().unwrap();
+
+    // Update the secondary key
+    let rw = db.rw_transaction().unwrap();
+    rw.auto_update(item(1, "c")).unwrap();
+    rw.commit().unwrap();
+    assert_eq!(get(&db, "a"), None);
+    assert_eq!(get(&db, "c"), Some(item(1, "c")));
+    let old_item: Option<Item> = old_r.get().secondary(ItemKey::name, "a").unwrap();
+    assert_eq!(old_item, Some(item(1, "a")));
+    let old_item: Option<Item> = old_r.get().secondary(ItemKey::name, "c").unwrap();
+    assert_eq!(old_item, None);
+    drop(old_r);
+
+    // Remove
+    let rw = db.rw_transaction().unwrap();
+    rw.remove(item(2, "b")).unwrap();
+    rw.commit().unwrap();
+    assert_eq!(get(&db, "b"), None);
+
+    // Abort: the cache is left untouched
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(item(3, "d")).unwrap();
+    rw.auto_update(item(1, "e")).unwrap();
+    let in_txn: Option<Item> = rw.get().secondary(ItemKey::name, "e").unwrap();
+    assert_eq!(in_txn, Some(item(1, "e")));
+    rw.abort().unwrap();
+    assert_eq!(get(&db, "d"), None);
+    assert_eq!(get(&db, "e"), None);
+    assert_eq!(get(&db, "c"), Some(item(1, "c")));
+
+    // Dropped without commit
+    {
+        let rw = db.rw_transaction().unwrap();
+        rw.remove(item(1, "c")).unwrap();
+    }
+    assert_eq!(get(&db, "c"), Some(item(1, "c")));
+
+    // The cache is loaded from the database when it is opened
+    drop(db);
+    let db = Builder::new()
+        .cache_unique_secondary::<Item>(ItemKey::name)
+        .open(&models, db_path.as_std_path())
+        .unwrap();
+    assert_eq!(get(&db, "c"), Some(item(1, "c")));
+    assert_eq!(get(&db, "a"), None);
+}
+
+#[test]
+fn cache_unique_secondary_not_unique() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+
+    let result = Builder::new()
+        .cache_unique_secondary::<Item>(ItemKey::group)
+        .create_in_memory(&models);
+    assert!(matches!(
+        result,
+        Err(db_type::Error::SecondaryKeyConstraintMismatch { .. })
+    ));
+}
@@ -0,0 +1,59 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Measure {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    timestamp: u64,
+}
+
+#[test]
+fn estimate_secondary_count() {
+    let mut models = Models::new();
+    models.define::<Measure>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    // Two values per timestamp: 0, 10, 20, ..., 99_990
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..20_000 {
+        rw.insert(Measure {
+            id,
+            timestamp: (id / 2) as u64 * 10,
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let estimate = |range: std::ops::Range<u64>| {
+        r.estimate_secondary_count::<Measure, u64>(MeasureKey::timestamp, range)
+            .unwrap()
+    };
+
+    // Few keys in the range: exact
+    assert_eq!(estimate(1_000..2_000), 200);
+    assert_eq!(estimate(200_000..300_000), 0);
+
+    // Few keys out of the range: exact
+    assert_eq!(estimate(5_000..100_000), 19_000);
+
+    // Many keys in and out of the range: estimated
+    let count = estimate(20_000..50_000);
+    assert!((5_700..=6_300).contains(&count), "{}", count);
+
+    // Unbounded
+    let count = r
+        .estimate_secondary_count::<Measure, u64>(MeasureKey::timestamp, 60_000..)
+        .unwrap();
+    assert!((7_600..=8_400).contains(&count), "{}", count);
+
+    // The key type is checked
+    assert!(r
+        .estimate_secondary_count::<Measure, u32>(MeasureKey::timestamp, 0..1)
+        .is_err());
+}
@@ -0,0 +1,65 @@
+use native_db::transaction::query::GetResult;
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Item {
+    #[primary_key]
+    id: u32,
+    name: String,
+}
+
+const PRIMARY_TABLE: redb::TableDefinition<Key, &[u8]> = redb::TableDefinition::new("1_1_id");
+
+#[test]
+fn get_primary_checked() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Item {
+        id: 1,
+        name: "test".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    // Store a value that can't be decoded: a valid native_model header (id 1, version 1)
+    // followed by a truncated body.
+    let corrupt_value = [1, 0, 0, 0, 1, 0, 0, 0, 0xff];
+    let rw = db.redb_database().unwrap().begin_write().unwrap();
+    rw.open_table(PRIMARY_TABLE)
+        .unwrap()
+        .insert(2u32.to_key(), corrupt_value.as_slice())
+        .unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    assert!(matches!(
+        r.get().primary_checked::<Item>(1u32).unwrap(),
+        GetResult::Present(Item { id: 1, .. })
+    ));
+    assert!(matches!(
+        r.get().primary_checked::<Item>(3u32).unwrap(),
+        GetResult::Absent
+    ));
+    match r.get().primary_checked::<Item>(2u32).unwrap() {
+        GetResult::Corrupt(key, error) => {
+            assert_eq!(key, 2u32.to_key());
+            assert!(matches!(*error, db_type::Error::ModelError(_)));
+        }
+        result => panic!("expected a corrupt value, got {:?}", result),
+    }
+    // `primary` fails on the corrupt value.
+    assert!(r.get().primary::<Item>(2u32).is_err());
+
+    let rw = db.rw_transaction().unwrap();
+    assert!(matches!(
+        rw.get().primary_checked::<Item>(2u32).unwrap(),
+        GetResult::Corrupt(..)
+    ));
+}
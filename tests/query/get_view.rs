@@ -0,0 +1,78 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Wide {
+    #[primary_key]
+    id: u32,
+    name: String,
+    score: u64,
+    tags: Vec<String>,
+    payload: Vec<u8>,
+}
+
+// The two leading fields of `Wide`
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+#[native_model(id = 1, version = 1)]
+struct WideView {
+    id: u32,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+#[native_model(id = 2, version = 1)]
+struct OtherView {
+    id: u32,
+}
+
+#[test]
+fn get_primary_as_view() {
+    let mut models = Models::new();
+    models.define::<Wide>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Wide {
+        id: 1,
+        name: "test".to_string(),
+        score: 42,
+        tags: vec!["a".to_string(), "b".to_string()],
+        payload: vec![7; 4096],
+    })
+    .unwrap();
+    let view: Option<WideView> = rw.get().primary_as::<Wide, _>(1u32).unwrap();
+    assert_eq!(
+        view,
+        Some(WideView {
+            id: 1,
+            name: "test".to_string()
+        })
+    );
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let view: Option<WideView> = r.get().primary_as::<Wide, _>(1u32).unwrap();
+    assert_eq!(
+        view,
+        Some(WideView {
+            id: 1,
+            name: "test".to_string()
+        })
+    );
+    let view: Option<WideView> = r.get().primary_as::<Wide, _>(2u32).unwrap();
+    assert_eq!(view, None);
+
+    // The view must share the id of the model
+    let result = r.get().primary_as::<Wide, OtherView>(1u32);
+    assert!(matches!(
+        result,
+        Err(db_type::Error::IncompatibleView {
+            id: 1,
+            version: 1,
+            ..
+        })
+    ));
+}
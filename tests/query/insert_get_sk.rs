@@ -164,3 +164,40 @@ fn test_insert_optional() {
         .unwrap();
     assert_eq!(item_1, result_item);
 }
+
+#[test]
+fn get_secondary_primary_key() {
+    let tf = TmpFs::new().unwrap();
+
+    let item = Item {
+        id: 1,
+        name: "test".to_string(),
+    };
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(item.clone()).unwrap();
+    let result = rw
+        .get()
+        .secondary_primary_key::<Item>(ItemKey::gk_1, "test-1")
+        .unwrap();
+    assert_eq!(result, Some("1".to_key()));
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let result = r
+        .get()
+        .secondary_primary_key::<Item>(ItemKey::gk_1, "test-1")
+        .unwrap();
+    assert_eq!(result, Some(item.pk().to_key()));
+    let result = r
+        .get()
+        .secondary_primary_key::<Item>(ItemKey::gk_1, "unknown")
+        .unwrap();
+    assert_eq!(result, None);
+}
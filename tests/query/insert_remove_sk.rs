@@ -266,3 +266,61 @@ fn insert_remove_unique_optional_with_invalid_secondary_key() {
     assert_eq!(stats.secondary_tables[0].name, "1_1_name");
     assert_eq!(stats.secondary_tables[0].n_entries, Some(1));
 }
+
+#[test]
+fn remove_many_by_keys() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let item = |id: u32| Item {
+        id,
+        name: format!("test-{}", id),
+    };
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 1..=4 {
+        rw.insert(item(id)).unwrap();
+    }
+    rw.commit().unwrap();
+
+    #[allow(unused_mut)]
+    let (mut recv, _) = db.watch().scan().primary().all::<Item>().unwrap();
+
+    // 5 and 6 are absent
+    let rw = db.rw_transaction().unwrap();
+    let removed: Vec<Item> = rw.remove_many_by_keys([3u32, 5, 1, 6]).unwrap();
+    assert_eq!(removed, vec![item(3), item(1)]);
+    rw.commit().unwrap();
+
+    let mut deleted = vec![];
+    for _ in 0..2 {
+        let watch::Event::Delete(event) = recv.try_recv().unwrap() else {
+            panic!("wrong event")
+        };
+        deleted.push(event.inner::<Item>().unwrap().id);
+    }
+    deleted.sort();
+    assert_eq!(deleted, vec![1, 3]);
+    assert!(recv.try_recv().is_err());
+
+    let r = db.r_transaction().unwrap();
+    let items: Vec<Item> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(items, vec![item(2), item(4)]);
+    let items: Vec<Item> = r
+        .scan()
+        .secondary(ItemKey::name)
+        .unwrap()
+        .all()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(items, vec![item(2), item(4)]);
+}
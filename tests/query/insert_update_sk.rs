@@ -78,3 +78,137 @@ fn insert_update_sk() {
     let length = r.len().primary::<Item>().unwrap();
     assert_eq!(length, 1);
 }
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 2, version = 1)]
+#[native_db]
+struct ItemManyKeys {
+    #[primary_key]
+    id: u32,
+    #[secondary_key(unique)]
+    name: String,
+    #[secondary_key]
+    group: u32,
+    #[secondary_key(optional)]
+    tag: Option<String>,
+    value: u32,
+}
+
+#[test]
+fn update_same_primary_key() {
+    let mut models = Models::new();
+    models.define::<ItemManyKeys>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let item = ItemManyKeys {
+        id: 1,
+        name: "a".to_string(),
+        group: 1,
+        tag: Some("x".to_string()),
+        value: 1,
+    };
+    let other = ItemManyKeys {
+        id: 2,
+        name: "b".to_string(),
+        group: 1,
+        tag: None,
+        value: 1,
+    };
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(item.clone()).unwrap();
+    rw.insert(other.clone()).unwrap();
+    rw.commit().unwrap();
+
+    // Only the non-indexed field changes
+    let item_v2 = ItemManyKeys {
+        value: 2,
+        ..item.clone()
+    };
+    let rw = db.rw_transaction().unwrap();
+    rw.update(item.clone(), item_v2.clone()).unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let result: ItemManyKeys = r
+        .get()
+        .secondary(ItemManyKeysKey::name, "a")
+        .unwrap()
+        .unwrap();
+    assert_eq!(result, item_v2);
+    assert_eq!(
+        r.len()
+            .secondary::<ItemManyKeys>(ItemManyKeysKey::group)
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        r.len()
+            .secondary::<ItemManyKeys>(ItemManyKeysKey::tag)
+            .unwrap(),
+        1
+    );
+    drop(r);
+
+    // Some secondary keys change
+    let item_v3 = ItemManyKeys {
+        name: "c".to_string(),
+        tag: None,
+        ..item_v2.clone()
+    };
+    let rw = db.rw_transaction().unwrap();
+    rw.update(item_v2.clone(), item_v3.clone()).unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let result: Option<ItemManyKeys> = r.get().secondary(ItemManyKeysKey::name, "a").unwrap();
+    assert_eq!(result, None);
+    let result: ItemManyKeys = r
+        .get()
+        .secondary(ItemManyKeysKey::name, "c")
+        .unwrap()
+        .unwrap();
+    assert_eq!(result, item_v3);
+    assert_eq!(
+        r.len()
+            .secondary::<ItemManyKeys>(ItemManyKeysKey::name)
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        r.len()
+            .secondary::<ItemManyKeys>(ItemManyKeysKey::group)
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        r.len()
+            .secondary::<ItemManyKeys>(ItemManyKeysKey::tag)
+            .unwrap(),
+        0
+    );
+    drop(r);
+
+    // The unique secondary key is already used
+    let item_v4 = ItemManyKeys {
+        name: "b".to_string(),
+        ..item_v3.clone()
+    };
+    let rw = db.rw_transaction().unwrap();
+    let result = rw.update(item_v3.clone(), item_v4);
+    assert!(matches!(result, Err(db_type::Error::DuplicateKey { .. })));
+    rw.abort().unwrap();
+
+    // The old value does not match the stored one
+    let rw = db.rw_transaction().unwrap();
+    let result = rw.update(item_v2, item_v3.clone());
+    assert!(matches!(
+        result,
+        Err(db_type::Error::IncorrectInputData { .. })
+    ));
+    rw.abort().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let result: ItemManyKeys = r.get().primary(1u32).unwrap().unwrap();
+    assert_eq!(result, item_v3);
+}
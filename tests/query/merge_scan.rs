@@ -0,0 +1,130 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Login {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 2, version = 1)]
+#[native_db]
+struct Logout {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    timestamp: u64,
+    #[secondary_key]
+    reason: String,
+}
+
+#[derive(Eq, PartialEq, Debug)]
+enum Timeline {
+    Login(Login),
+    Logout(Logout),
+}
+
+impl Timeline {
+    fn timestamp(&self) -> u64 {
+        match self {
+            Timeline::Login(login) => login.timestamp,
+            Timeline::Logout(logout) => logout.timestamp,
+        }
+    }
+}
+
+fn logout(id: u32, timestamp: u64) -> Logout {
+    Logout {
+        id,
+        timestamp,
+        reason: "user".to_string(),
+    }
+}
+
+#[test]
+fn merge_scan_all_and_range() {
+    let mut models = Models::new();
+    models.define::<Login>().unwrap();
+    models.define::<Logout>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    // Primary keys are not in timestamp order
+    rw.insert(Login {
+        id: 1,
+        timestamp: 300,
+    })
+    .unwrap();
+    rw.insert(Login {
+        id: 2,
+        timestamp: 10,
+    })
+    .unwrap();
+    rw.insert(Login {
+        id: 3,
+        timestamp: 250,
+    })
+    .unwrap();
+    rw.insert(logout(1, 400)).unwrap();
+    rw.insert(logout(2, 20)).unwrap();
+    rw.insert(logout(3, 250)).unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let merge = r
+        .merge_scan()
+        .secondary(LoginKey::timestamp, Timeline::Login)
+        .unwrap()
+        .secondary(LogoutKey::timestamp, Timeline::Logout)
+        .unwrap();
+
+    let timeline: Vec<Timeline> = merge.all().unwrap().collect::<Result<_, _>>().unwrap();
+    assert_eq!(
+        timeline.iter().map(Timeline::timestamp).collect::<Vec<_>>(),
+        vec![10, 20, 250, 250, 300, 400]
+    );
+    // Same timestamp: the order the models were added
+    assert_eq!(
+        timeline[2],
+        Timeline::Login(Login {
+            id: 3,
+            timestamp: 250
+        })
+    );
+    assert_eq!(timeline[3], Timeline::Logout(logout(3, 250)));
+
+    let timeline: Vec<Timeline> = merge
+        .range(20u64..300u64)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        timeline.iter().map(Timeline::timestamp).collect::<Vec<_>>(),
+        vec![20, 250, 250]
+    );
+}
+
+#[test]
+fn merge_scan_mismatched_key_type() {
+    let mut models = Models::new();
+    models.define::<Login>().unwrap();
+    models.define::<Logout>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let result = r
+        .merge_scan()
+        .secondary(LoginKey::timestamp, Timeline::Login)
+        .unwrap()
+        .secondary(LogoutKey::reason, Timeline::Logout);
+    assert!(matches!(
+        result,
+        Err(db_type::Error::MissmatchedKeyType { .. })
+    ));
+}
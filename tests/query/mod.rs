@@ -15,3 +15,21 @@ mod upsert_get_sk;
 // Auto Update
 mod auto_update_pk;
 mod auto_update_sk;
+
+// Cache
+mod cache_unique_secondary;
+
+// Multi
+mod multi_sk;
+
+// View
+mod get_view;
+
+// Merge
+mod merge_scan;
+
+// Checked
+mod get_checked;
+
+// Estimate
+mod estimate_sk;
@@ -0,0 +1,117 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Article {
+    #[primary_key]
+    id: u32,
+    #[secondary_key(multi)]
+    tags: Vec<String>,
+}
+
+fn article(id: u32, tags: &[&str]) -> Article {
+    Article {
+        id,
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+    }
+}
+
+fn ids_by_tag(db: &Database, tag: &str) -> Vec<u32> {
+    let r = db.r_transaction().unwrap();
+    let articles: Vec<Article> = r
+        .scan()
+        .secondary(ArticleKey::tags)
+        .unwrap()
+        .range(tag..=tag)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    articles.into_iter().map(|article| article.id).collect()
+}
+
+#[test]
+fn multi_insert_update_remove() {
+    let mut models = Models::new();
+    models.define::<Article>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    // The duplicated tag is stored once
+    rw.insert(article(1, &["rust", "db", "rust"])).unwrap();
+    rw.insert(article(2, &["rust"])).unwrap();
+    rw.insert(article(3, &[])).unwrap();
+    rw.commit().unwrap();
+
+    assert_eq!(ids_by_tag(&db, "rust"), vec![1, 2]);
+    assert_eq!(ids_by_tag(&db, "db"), vec![1]);
+    assert_eq!(ids_by_tag(&db, "go"), Vec::<u32>::new());
+
+    // Add and remove tags
+    let rw = db.rw_transaction().unwrap();
+    rw.auto_update(article(1, &["db", "go"])).unwrap();
+    rw.auto_update(article(3, &["rust"])).unwrap();
+    rw.commit().unwrap();
+
+    assert_eq!(ids_by_tag(&db, "rust"), vec![2, 3]);
+    assert_eq!(ids_by_tag(&db, "db"), vec![1]);
+    assert_eq!(ids_by_tag(&db, "go"), vec![1]);
+
+    // Remove all the tags of an article
+    let rw = db.rw_transaction().unwrap();
+    rw.remove(article(1, &["db", "go"])).unwrap();
+    rw.commit().unwrap();
+
+    assert_eq!(ids_by_tag(&db, "db"), Vec::<u32>::new());
+    assert_eq!(ids_by_tag(&db, "go"), Vec::<u32>::new());
+    assert_eq!(ids_by_tag(&db, "rust"), vec![2, 3]);
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 2, version = 1)]
+#[native_db(
+    primary_key(pk -> u32),
+    secondary_key(lowercase_tags -> Vec<String>, multi)
+)]
+struct Post {
+    id: u32,
+    tags: Vec<String>,
+}
+
+impl Post {
+    fn pk(&self) -> u32 {
+        self.id
+    }
+
+    fn lowercase_tags(&self) -> Vec<String> {
+        self.tags.iter().map(|tag| tag.to_lowercase()).collect()
+    }
+}
+
+#[test]
+fn multi_function_key() {
+    let mut models = Models::new();
+    models.define::<Post>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Post {
+        id: 1,
+        tags: vec!["Rust".to_string(), "rust".to_string()],
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let posts: Vec<Post> = r
+        .scan()
+        .secondary(PostKey::lowercase_tags)
+        .unwrap()
+        .start_with("ru")
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(posts.len(), 1);
+}
@@ -49,6 +49,9 @@ fn test_refresh() {
     let mut models = Models::new();
     models.define::<Item1>().unwrap();
     models.define::<Item2>().unwrap();
+    Builder::new()
+        .upgrade_key_encoding(&models, &tmp_database_path)
+        .unwrap();
     let db = Builder::new().open(&models, &tmp_database_path).unwrap();
     // TODO: during open, the database must be upgraded to the latest version.
 
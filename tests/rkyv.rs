@@ -0,0 +1,186 @@
+#![cfg(feature = "rkyv")]
+
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use shortcut_assert_fs::TmpFs;
+
+#[derive(
+    Serialize,
+    Deserialize,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    Eq,
+    PartialEq,
+    Debug,
+    Clone,
+)]
+#[rkyv(crate = native_db::rkyv)]
+#[native_model(id = 1, version = 1)]
+#[native_db(rkyv)]
+struct Document {
+    #[primary_key]
+    id: u32,
+    #[secondary_key(unique)]
+    title: String,
+    pages: Vec<u32>,
+}
+
+// Derives the rkyv traits, but is stored with `native_model`.
+#[derive(
+    Serialize,
+    Deserialize,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    Eq,
+    PartialEq,
+    Debug,
+    Clone,
+)]
+#[rkyv(crate = native_db::rkyv)]
+#[native_model(id = 2, version = 1)]
+#[native_db]
+struct Note {
+    #[primary_key]
+    id: u32,
+    content: String,
+}
+
+#[test]
+fn test_get_archived() {
+    let mut models = Models::new();
+    models.define::<Document>().unwrap();
+    models.define::<Note>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let document = Document {
+        id: 1,
+        title: "document".to_string(),
+        pages: vec![10, 20, 30],
+    };
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(document.clone()).unwrap();
+    rw.insert(Note {
+        id: 1,
+        content: "note".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+
+    // The fields are read from the archive
+    let archived = r.get_archived::<Document>(1u32).unwrap().unwrap();
+    assert_eq!(archived.title.as_str(), "document");
+    assert_eq!(archived.pages.len(), 3);
+    assert_eq!(archived.pages[2].to_native(), 30);
+    assert_eq!(archived.deserialize().unwrap(), document);
+    assert!(r.get_archived::<Document>(2u32).unwrap().is_none());
+
+    // The other reads still deserialize the whole value
+    let value: Document = r.get().primary(1u32).unwrap().unwrap();
+    assert_eq!(value, document);
+    let value: Document = r
+        .get()
+        .secondary(DocumentKey::title, "document")
+        .unwrap()
+        .unwrap();
+    assert_eq!(value, document);
+
+    assert!(matches!(
+        r.get_archived::<Note>(1u32),
+        Err(db_type::Error::RkyvNotDefined { table }) if table == "2_1_id"
+    ));
+}
+
+// The leading fields of `Document`
+#[derive(Serialize, Deserialize, Debug)]
+#[native_model(id = 1, version = 1)]
+struct DocumentTitle {
+    id: u32,
+    title: String,
+}
+
+#[test]
+fn test_primary_as_rkyv() {
+    let mut models = Models::new();
+    models.define::<Document>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Document {
+        id: 1,
+        title: "document".to_string(),
+        pages: vec![],
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let result = r.get().primary_as::<Document, DocumentTitle>(1u32);
+    assert!(matches!(
+        result,
+        Err(db_type::Error::ViewNotSupported { table }) if table == "1_1_id"
+    ));
+}
+
+mod native_model_encoded {
+    use super::*;
+
+    // Same table as `Document`, encoded with `native_model`.
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+    #[native_model(id = 1, version = 1)]
+    #[native_db]
+    pub struct Document {
+        #[primary_key]
+        pub id: u32,
+        #[secondary_key(unique)]
+        pub title: String,
+        pub pages: Vec<u32>,
+    }
+}
+
+#[test]
+fn test_encoding_mismatch() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<native_model_encoded::Document>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(native_model_encoded::Document {
+        id: 1,
+        title: "document".to_string(),
+        pages: vec![],
+    })
+    .unwrap();
+    rw.commit().unwrap();
+    drop(db);
+
+    let mut models = Models::new();
+    models.define::<Document>().unwrap();
+    let result = Builder::new().open(&models, tf.path("test").as_std_path());
+    assert!(matches!(
+        result,
+        Err(db_type::Error::EncodingMismatch { table, stored, current })
+            if table == "1_1_id" && stored == "native_model" && current == "rkyv"
+    ));
+}
+
+#[test]
+fn test_schema_fingerprint_rkyv() {
+    let mut models = Models::new();
+    models.define::<Document>().unwrap();
+    let mut models_native_model = Models::new();
+    models_native_model
+        .define::<native_model_encoded::Document>()
+        .unwrap();
+    assert_ne!(
+        models.schema_fingerprint(),
+        models_native_model.schema_fingerprint()
+    );
+}
@@ -0,0 +1,142 @@
+use native_db::watch::Event;
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use shortcut_assert_fs::TmpFs;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Item {
+    #[primary_key]
+    id: u32,
+    name: String,
+}
+
+#[test]
+fn test_changes_since() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for (id, name) in [(1, "removed"), (2, "updated"), (3, "unchanged")] {
+        rw.insert(Item {
+            id,
+            name: name.to_string(),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    db.create_savepoint("export").unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.remove(Item {
+        id: 1,
+        name: "removed".to_string(),
+    })
+    .unwrap();
+    rw.upsert(Item {
+        id: 2,
+        name: "updated 2".to_string(),
+    })
+    .unwrap();
+    rw.insert(Item {
+        id: 4,
+        name: "inserted".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    let changes = db.changes_since("export").unwrap();
+    assert_eq!(changes.len(), 3);
+
+    // Added and modified values first, in the order of their primary keys
+    assert_eq!(changes[0].primary_key, 2u32.to_key());
+    let Event::Update(update) = &changes[0].event else {
+        panic!("expected an update");
+    };
+    assert_eq!(update.inner_old::<Item>().unwrap().name, "updated");
+    assert_eq!(update.inner_new::<Item>().unwrap().name, "updated 2");
+
+    assert_eq!(changes[1].primary_key, 4u32.to_key());
+    let Event::Insert(insert) = &changes[1].event else {
+        panic!("expected an insert");
+    };
+    assert_eq!(insert.inner::<Item>().unwrap().name, "inserted");
+
+    // Then the removed values
+    assert_eq!(changes[2].primary_key, 1u32.to_key());
+    let Event::Delete(delete) = &changes[2].event else {
+        panic!("expected a delete");
+    };
+    assert_eq!(delete.inner::<Item>().unwrap().name, "removed");
+
+    // Computing the changes does not alter the database
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item>().unwrap(), 3);
+    drop(r);
+
+    // The savepoint survives reopening the database
+    drop(db);
+    let db = Builder::new()
+        .open(&models, tf.path("test").as_std_path())
+        .unwrap();
+    assert_eq!(db.changes_since("export").unwrap().len(), 3);
+
+    // A savepoint created again starts from the current state
+    db.create_savepoint("export").unwrap();
+    assert!(db.changes_since("export").unwrap().is_empty());
+
+    assert!(db.delete_savepoint("export").unwrap());
+    assert!(!db.delete_savepoint("export").unwrap());
+    assert!(matches!(
+        db.changes_since("export"),
+        Err(db_type::Error::SavepointNotFound { name }) if name == "export"
+    ));
+}
+
+#[test]
+fn test_changes_since_two_savepoints() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let insert = |id: u32| {
+        let rw = db.rw_transaction().unwrap();
+        rw.insert(Item {
+            id,
+            name: format!("item {}", id),
+        })
+        .unwrap();
+        rw.commit().unwrap();
+    };
+
+    db.create_savepoint("a").unwrap();
+    insert(1);
+    db.create_savepoint("b").unwrap();
+    insert(2);
+
+    // Reading the older savepoint would invalidate the newer one
+    assert!(matches!(
+        db.changes_since("a"),
+        Err(db_type::Error::SavepointNotLatest { name }) if name == "a"
+    ));
+    let changes = db.changes_since("b").unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].primary_key, 2u32.to_key());
+    assert_eq!(db.changes_since("b").unwrap().len(), 1);
+
+    // The older savepoint is the latest once the newer one is deleted
+    assert!(db.delete_savepoint("b").unwrap());
+    assert_eq!(db.changes_since("a").unwrap().len(), 2);
+}
@@ -5,6 +5,8 @@ use native_db::*;
 use native_model::{native_model, Model};
 use serde::{Deserialize, Serialize};
 use shortcut_assert_fs::TmpFs;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
 #[native_model(id = 1, version = 1)]
@@ -521,6 +523,63 @@ fn test_start_with_by_key_scenario_readonly_txn() {
     }
 }
 
+#[test]
+fn test_double_ended_start_with() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<ItemFlag>().unwrap();
+    models.define::<ItemIdFlag>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(ItemFlag::new("blue:1")).unwrap();
+    rw.insert(ItemFlag::new("red:1")).unwrap();
+    rw.insert(ItemFlag::new("red:2")).unwrap();
+    rw.insert(ItemFlag::new("red:3")).unwrap();
+    rw.insert(ItemFlag::new("red;1")).unwrap();
+    rw.insert(ItemIdFlag::new("1", "red")).unwrap();
+    rw.insert(ItemIdFlag::new("2", "blue")).unwrap();
+    rw.insert(ItemIdFlag::new("3", "red")).unwrap();
+    rw.insert(ItemIdFlag::new("4", "green")).unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+
+    // Primary key
+    let scan = r.scan().primary().unwrap();
+    let iter = scan.start_with("red:").unwrap();
+    let result: Vec<ItemFlag> = iter.rev().try_collect().unwrap();
+    assert_eq!(
+        result,
+        vec![
+            ItemFlag::new("red:3"),
+            ItemFlag::new("red:2"),
+            ItemFlag::new("red:1")
+        ]
+    );
+
+    // Both ends
+    let mut iter = scan.start_with("red:").unwrap();
+    let first: ItemFlag = iter.next().unwrap().unwrap();
+    let last: ItemFlag = iter.next_back().unwrap().unwrap();
+    assert_eq!(first.name, "red:1");
+    assert_eq!(last.name, "red:3");
+    let middle: Vec<ItemFlag> = iter.try_collect().unwrap();
+    assert_eq!(middle, vec![ItemFlag::new("red:2")]);
+
+    // Secondary key
+    let scan = r.scan().secondary(ItemIdFlagKey::flag).unwrap();
+    let iter = scan.start_with("red:").unwrap();
+    let result: Vec<ItemIdFlag> = iter.rev().try_collect().unwrap();
+    assert_eq!(
+        result,
+        vec![ItemIdFlag::new("3", "red"), ItemIdFlag::new("1", "red")]
+    );
+}
+
 #[test]
 fn test_txn_write_iter() {
     let tf = TmpFs::new().unwrap();
@@ -891,3 +950,840 @@ fn test_low_level_scan_range() {
     // And detect colision, use the return value of https://docs.rs/redb/latest/redb/struct.Table.html#method.insert
     // and re-insert the item if the return value is not null, recompute the primary key hash with a timestamp.
 }
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 5, version = 1)]
+#[native_db]
+struct ItemCategory {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    category: String,
+}
+
+#[test]
+fn test_distinct_keys() {
+    let mut models = Models::new();
+    models.define::<ItemCategory>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let categories = ["apple", "apricot", "banana", "cherry"];
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..100 {
+        rw.insert(ItemCategory {
+            id,
+            category: categories[id as usize % categories.len()].to_string(),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let scan = r
+        .scan()
+        .secondary::<ItemCategory>(ItemCategoryKey::category)
+        .unwrap();
+
+    let keys = scan.distinct_keys().unwrap();
+    let expected: Vec<Key> = categories.iter().map(|c| c.to_key()).collect();
+    assert_eq!(keys, expected);
+
+    let keys = scan.distinct_keys_start_with("ap").unwrap();
+    assert_eq!(keys, vec!["apple".to_key(), "apricot".to_key()]);
+
+    let keys = scan.distinct_keys_range("b"..).unwrap();
+    assert_eq!(keys, vec!["banana".to_key(), "cherry".to_key()]);
+}
+
+#[test]
+fn test_skip_take() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..10 {
+        rw.insert(Item::new(id, &format!("name_{}", 9 - id)))
+            .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+
+    // Primary key
+    let result: Vec<Item> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .skip_take(3, 4)
+        .try_collect()
+        .unwrap();
+    assert_eq!(result.iter().map(|i| i.id).collect_vec(), vec![3, 4, 5, 6]);
+
+    // Offset beyond the end
+    let result: Vec<Item> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .skip_take(20, 4)
+        .try_collect()
+        .unwrap();
+    assert!(result.is_empty());
+
+    // Secondary key, ordered by name
+    let result: Vec<Item> = r
+        .scan()
+        .secondary(ItemKey::secondary_key_2)
+        .unwrap()
+        .all()
+        .unwrap()
+        .skip_take(8, 4)
+        .try_collect()
+        .unwrap();
+    assert_eq!(result.iter().map(|i| i.id).collect_vec(), vec![1, 0]);
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 6, version = 1)]
+#[native_db]
+struct Ticket {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    status: String,
+    #[secondary_key]
+    region: String,
+}
+
+impl Ticket {
+    fn new(id: u32, status: &str, region: &str) -> Self {
+        Self {
+            id,
+            status: status.to_string(),
+            region: region.to_string(),
+        }
+    }
+}
+
+#[test]
+fn test_scan_intersect() {
+    let mut models = Models::new();
+    models.define::<Ticket>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Ticket::new(1, "Open", "EU")).unwrap();
+    rw.insert(Ticket::new(2, "Closed", "EU")).unwrap();
+    rw.insert(Ticket::new(3, "Open", "US")).unwrap();
+    rw.insert(Ticket::new(4, "Open", "EU")).unwrap();
+    rw.insert(Ticket::new(5, "Closed", "US")).unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+
+    // Overlapping sets
+    let result: Vec<Ticket> = r
+        .scan_intersect((TicketKey::status, "Open"), (TicketKey::region, "EU"))
+        .unwrap();
+    assert_eq!(result.iter().map(|t| t.id).collect_vec(), vec![1, 4]);
+
+    // The order of the keys does not matter
+    let result: Vec<Ticket> = r
+        .scan_intersect((TicketKey::region, "EU"), (TicketKey::status, "Open"))
+        .unwrap();
+    assert_eq!(result.iter().map(|t| t.id).collect_vec(), vec![1, 4]);
+
+    // Disjoint sets
+    let result: Vec<Ticket> = r
+        .scan_intersect((TicketKey::status, "Closed"), (TicketKey::status, "Open"))
+        .unwrap();
+    assert!(result.is_empty());
+
+    // Unknown value
+    let result: Vec<Ticket> = r
+        .scan_intersect((TicketKey::status, "Open"), (TicketKey::region, "ASIA"))
+        .unwrap();
+    assert!(result.is_empty());
+
+    // Wrong key type
+    let result: Result<Vec<Ticket>, _> =
+        r.scan_intersect((TicketKey::status, 1u32), (TicketKey::region, "EU"));
+    assert!(matches!(
+        result,
+        Err(db_type::Error::MissmatchedKeyType { .. })
+    ));
+}
+
+#[test]
+fn test_filter_decode() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..10 {
+        rw.insert(Item::new(id, &format!("name_{}", id))).unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+
+    // Primary key
+    let result: Vec<Item> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .filter_decode(|item: &Item| item.id % 3 == 0)
+        .try_collect()
+        .unwrap();
+    assert_eq!(result.iter().map(|i| i.id).collect_vec(), vec![0, 3, 6, 9]);
+
+    // Reversed
+    let result: Vec<Item> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .filter_decode(|item: &Item| item.id % 3 == 0)
+        .rev()
+        .try_collect()
+        .unwrap();
+    assert_eq!(result.iter().map(|i| i.id).collect_vec(), vec![9, 6, 3, 0]);
+
+    // Primary key start with
+    let result: Vec<Item> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .start_with(4u32)
+        .unwrap()
+        .filter_decode(|item: &Item| item.name.ends_with('4'))
+        .try_collect()
+        .unwrap();
+    assert_eq!(result.iter().map(|i| i.id).collect_vec(), vec![4]);
+
+    // Secondary key range
+    let result: Vec<Item> = r
+        .scan()
+        .secondary(ItemKey::secondary_key_2)
+        .unwrap()
+        .range("name_2".."name_8")
+        .unwrap()
+        .filter_decode(|item: &Item| item.id % 2 == 1)
+        .try_collect()
+        .unwrap();
+    assert_eq!(result.iter().map(|i| i.id).collect_vec(), vec![3, 5, 7]);
+
+    // No match
+    let result: Vec<Item> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .filter_decode(|item: &Item| item.id > 100)
+        .try_collect()
+        .unwrap();
+    assert!(result.is_empty());
+}
+
+#[cfg(feature = "diagnostics")]
+#[test]
+fn test_explain() {
+    use std::ops::Bound;
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..10 {
+        rw.insert(Item::new(id, &format!("name_{}", id))).unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let scan = r
+        .scan()
+        .secondary::<Item>(ItemKey::secondary_key_2)
+        .unwrap();
+
+    // Bounded range
+    let plan = scan.range("name_2".."name_5").unwrap().explain();
+    assert_eq!(plan.table, "1_1_secondary_key_2");
+    assert_eq!(plan.start, Bound::Included("name_2".to_key()));
+    assert_eq!(plan.end, Bound::Excluded("name_5".to_key()));
+    assert_eq!(plan.prefix, None);
+    assert_eq!(plan.primary_keys, 3);
+    assert!(!plan.is_full_scan());
+
+    // Prefix
+    let plan = scan.start_with("name_1").unwrap().explain();
+    assert_eq!(plan.start, Bound::Included("name_1".to_key()));
+    assert_eq!(plan.end, Bound::Unbounded);
+    assert_eq!(plan.prefix, Some("name_1".to_key()));
+    assert_eq!(plan.primary_keys, 1);
+    assert!(!plan.is_full_scan());
+
+    // Full scan
+    let plan = scan.all().unwrap().explain();
+    assert_eq!(plan.primary_keys, 10);
+    assert!(plan.is_full_scan());
+}
+
+#[test]
+fn test_chunks() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..10 {
+        rw.insert(Item::new(id, &format!("name_{}", id))).unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+
+    // Primary key
+    let chunks: Vec<Vec<Item>> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .chunks(3)
+        .try_collect()
+        .unwrap();
+    assert_eq!(
+        chunks.iter().map(|c| c.len()).collect_vec(),
+        vec![3, 3, 3, 1]
+    );
+    assert_eq!(
+        chunks.concat().iter().map(|i| i.id).collect_vec(),
+        (0..10).collect_vec()
+    );
+
+    // Primary key start with
+    let chunks: Vec<Vec<Item>> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .start_with(4u32)
+        .unwrap()
+        .chunks(3)
+        .try_collect()
+        .unwrap();
+    assert_eq!(chunks.iter().map(|c| c.len()).collect_vec(), vec![1]);
+
+    // Secondary key
+    let chunks: Vec<Vec<Item>> = r
+        .scan()
+        .secondary(ItemKey::secondary_key_2)
+        .unwrap()
+        .all()
+        .unwrap()
+        .chunks(5)
+        .try_collect()
+        .unwrap();
+    assert_eq!(chunks.iter().map(|c| c.len()).collect_vec(), vec![5, 5]);
+
+    // No value
+    let chunks: Vec<Vec<Item>> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .range(100u32..200u32)
+        .unwrap()
+        .chunks(3)
+        .try_collect()
+        .unwrap();
+    assert!(chunks.is_empty());
+}
+
+#[test]
+fn test_collect_map() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..10 {
+        rw.insert(Item::new(id, &format!("name_{}", id))).unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+
+    // Primary key, keyed by name
+    let items: HashMap<String, Item> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .collect_map(|item: &Item| item.name.clone())
+        .unwrap();
+    assert_eq!(items.len(), 10);
+    assert_eq!(items["name_4"], Item::new(4, "name_4"));
+
+    // Secondary key, ordered by the key
+    let items: BTreeMap<u32, Item> = r
+        .scan()
+        .secondary(ItemKey::secondary_key_2)
+        .unwrap()
+        .range("name_2".."name_5")
+        .unwrap()
+        .collect_btreemap(|item: &Item| item.id)
+        .unwrap();
+    assert_eq!(items.keys().copied().collect_vec(), vec![2, 3, 4]);
+
+    // The last value is kept for a duplicate key
+    let items: HashMap<u32, Item> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .start_with(4u32)
+        .unwrap()
+        .collect_map(|item: &Item| item.id % 2)
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[&0], Item::new(4, "name_4"));
+
+    let items: HashMap<u32, Item> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .collect_map(|item: &Item| item.id % 2)
+        .unwrap();
+    assert_eq!(items[&0], Item::new(8, "name_8"));
+    assert_eq!(items[&1], Item::new(9, "name_9"));
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 10, version = 1)]
+#[native_db]
+struct SignedItem {
+    #[primary_key]
+    id: i64,
+    #[secondary_key]
+    small: i32,
+    #[secondary_key]
+    large: i64,
+}
+
+#[test]
+fn test_scan_range_signed() {
+    let mut models = Models::new();
+    models.define::<SignedItem>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in [3i64, -1, 0, i64::MIN, -300, 1, i64::MAX] {
+        rw.insert(SignedItem {
+            id,
+            small: (id % 1000) as i32 * 1000,
+            large: id.saturating_mul(1 << 20),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+
+    // Primary key
+    let ids = |items: Vec<SignedItem>| items.into_iter().map(|item| item.id).collect_vec();
+    let items: Vec<SignedItem> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(ids(items), vec![i64::MIN, -300, -1, 0, 1, 3, i64::MAX]);
+    let items: Vec<SignedItem> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .range(-300i64..=1)
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(ids(items), vec![-300, -1, 0, 1]);
+
+    // Secondary key i32
+    let items: Vec<SignedItem> = r
+        .scan()
+        .secondary(SignedItemKey::small)
+        .unwrap()
+        .range(-300_000i32..1000)
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(
+        items.iter().map(|item| item.small).collect_vec(),
+        vec![-300_000, -1000, 0]
+    );
+    let items: Vec<SignedItem> = r
+        .scan()
+        .secondary(SignedItemKey::small)
+        .unwrap()
+        .range(..0i32)
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(
+        items.iter().map(|item| item.small).collect_vec(),
+        vec![-808_000, -300_000, -1000]
+    );
+
+    // Secondary key i64, across zero
+    let items: Vec<SignedItem> = r
+        .scan()
+        .secondary(SignedItemKey::large)
+        .unwrap()
+        .range(-(1i64 << 20)..=(3 << 20))
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(ids(items), vec![-1, 0, 1, 3]);
+    let items: Vec<SignedItem> = r
+        .scan()
+        .secondary(SignedItemKey::large)
+        .unwrap()
+        .all()
+        .unwrap()
+        .rev()
+        .try_collect()
+        .unwrap();
+    assert_eq!(ids(items), vec![i64::MAX, 3, 1, 0, -1, -300, i64::MIN]);
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 7, version = 1)]
+#[native_db]
+struct Task {
+    #[primary_key]
+    id: u32,
+    #[secondary_key(unique)]
+    priority: Option<u32>,
+}
+
+#[test]
+fn test_option_key_order() {
+    let mut models = Models::new();
+    models.define::<Task>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Task {
+        id: 1,
+        priority: Some(2),
+    })
+    .unwrap();
+    rw.insert(Task {
+        id: 2,
+        priority: None,
+    })
+    .unwrap();
+    rw.insert(Task {
+        id: 3,
+        priority: Some(0),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let scan = r.scan().secondary(TaskKey::priority).unwrap();
+    let result: Vec<Task> = scan.all().unwrap().try_collect().unwrap();
+    let ids: Vec<u32> = result.iter().map(|task| task.id).collect();
+    assert_eq!(ids, vec![2, 3, 1]);
+
+    // Only the `Some` values
+    let result: Vec<Task> = scan.range(Some(0u32)..).unwrap().try_collect().unwrap();
+    let ids: Vec<u32> = result.iter().map(|task| task.id).collect();
+    assert_eq!(ids, vec![3, 1]);
+
+    let task: Task = r
+        .get()
+        .secondary(TaskKey::priority, None::<u32>)
+        .unwrap()
+        .unwrap();
+    assert_eq!(task.id, 2);
+}
+
+static DECODE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+fn decode_count() -> usize {
+    DECODE_COUNT.load(Ordering::SeqCst)
+}
+
+// Counts the number of decoded values
+#[derive(Serialize, Eq, PartialEq, Debug, Clone)]
+struct DecodeCounter;
+
+impl<'de> Deserialize<'de> for DecodeCounter {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <()>::deserialize(deserializer)?;
+        DECODE_COUNT.fetch_add(1, Ordering::SeqCst);
+        Ok(DecodeCounter)
+    }
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 8, version = 1)]
+#[native_db]
+struct Counted {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    name: String,
+    counter: DecodeCounter,
+}
+
+#[test]
+fn test_limit_first_n() {
+    let mut models = Models::new();
+    models.define::<Counted>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..10 {
+        rw.insert(Counted {
+            id,
+            name: format!("name_{}", 9 - id),
+            counter: DecodeCounter,
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+
+    // Primary key
+    let before = decode_count();
+    let result: Vec<Counted> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .first_n(3)
+        .unwrap();
+    assert_eq!(result.iter().map(|i| i.id).collect_vec(), vec![0, 1, 2]);
+    assert_eq!(decode_count() - before, 3);
+
+    let before = decode_count();
+    let result: Vec<Counted> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .limit(2)
+        .try_collect()
+        .unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(decode_count() - before, 2);
+
+    // More than the number of values
+    let result: Vec<Counted> = r
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .first_n(20)
+        .unwrap();
+    assert_eq!(result.len(), 10);
+
+    // Secondary key, ordered by name
+    let before = decode_count();
+    let scan = r.scan().secondary(CountedKey::name).unwrap();
+    let result: Vec<Counted> = scan.all().unwrap().first_n(3).unwrap();
+    assert_eq!(result.iter().map(|i| i.id).collect_vec(), vec![9, 8, 7]);
+    assert_eq!(decode_count() - before, 3);
+}
+
+#[test]
+fn test_prepare_secondary() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..10 {
+        rw.insert(Item::new(id, &format!("name_{}", id))).unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let scan = r
+        .prepare_secondary::<Item>(ItemKey::secondary_key_2)
+        .unwrap();
+    for id in 0..9 {
+        let items: Vec<Item> = scan
+            .range(format!("name_{}", id)..format!("name_{}", id + 1))
+            .unwrap()
+            .try_collect()
+            .unwrap();
+        assert_eq!(items, vec![Item::new(id, &format!("name_{}", id))]);
+    }
+
+    // The bounds are checked on each range
+    assert!(matches!(
+        scan.range(0u32..1u32),
+        Err(db_type::Error::MissmatchedKeyType { .. })
+    ));
+}
+
+#[test]
+fn test_prefix_range() {
+    let mut models = Models::new();
+    models.define::<ItemCategory>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let names = [
+        "alice", "bob", "carol", "m", "mallory", "mzz", "nadia", "oscar", "zoe",
+    ];
+    let rw = db.rw_transaction().unwrap();
+    for (id, name) in names.iter().enumerate() {
+        rw.insert(ItemCategory {
+            id: id as u32,
+            category: name.to_string(),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let scan = r
+        .scan()
+        .secondary::<ItemCategory>(ItemCategoryKey::category)
+        .unwrap();
+    let categories = |low: &str, high: &str| -> Vec<String> {
+        scan.prefix_range(low, high)
+            .unwrap()
+            .map(|item| item.unwrap().category)
+            .collect()
+    };
+
+    // Both buckets are included, up to the last name starting with "m"
+    assert_eq!(
+        categories("b", "m"),
+        vec!["bob", "carol", "m", "mallory", "mzz"]
+    );
+    assert_eq!(categories("m", "m"), vec!["m", "mallory", "mzz"]);
+    assert_eq!(categories("n", "z"), vec!["nadia", "oscar", "zoe"]);
+    assert!(categories("d", "l").is_empty());
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 11, version = 1)]
+#[native_db]
+struct ItemOptional {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    parent: Option<u32>,
+    #[secondary_key(optional)]
+    name: Option<String>,
+}
+
+#[test]
+fn test_scan_optional_ranges() {
+    use native_db::db_type::{none_range, present_range, some_range};
+
+    let mut models = Models::new();
+    models.define::<ItemOptional>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for (id, parent, name) in [
+        (1, None, Some("a")),
+        (2, Some(0), None),
+        (3, Some(1), Some("b")),
+        (4, None, Some("c")),
+        (5, Some(2), Some("d")),
+    ] {
+        rw.insert(ItemOptional {
+            id,
+            parent,
+            name: name.map(str::to_string),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let ids = |items: Vec<ItemOptional>| items.into_iter().map(|item| item.id).collect_vec();
+
+    let roots: Vec<ItemOptional> = r
+        .scan()
+        .secondary(ItemOptionalKey::parent)
+        .unwrap()
+        .range(none_range::<u32>())
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(ids(roots), vec![1, 4]);
+
+    let children: Vec<ItemOptional> = r
+        .scan()
+        .secondary(ItemOptionalKey::parent)
+        .unwrap()
+        .range(present_range::<u32>())
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(ids(children), vec![2, 3, 5]);
+
+    let children: Vec<ItemOptional> = r
+        .scan()
+        .secondary(ItemOptionalKey::parent)
+        .unwrap()
+        .range(some_range(1u32, 2))
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(ids(children), vec![3, 5]);
+
+    // The `None` values of an optional secondary key are not indexed
+    let named: Vec<ItemOptional> = r
+        .scan()
+        .secondary(ItemOptionalKey::name)
+        .unwrap()
+        .range(present_range::<String>())
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(ids(named), vec![1, 3, 4, 5]);
+    let named: Vec<ItemOptional> = r
+        .scan()
+        .secondary(ItemOptionalKey::name)
+        .unwrap()
+        .range(some_range("b", "c"))
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert_eq!(ids(named), vec![3, 4]);
+    let unnamed: Vec<ItemOptional> = r
+        .scan()
+        .secondary(ItemOptionalKey::name)
+        .unwrap()
+        .range(none_range::<String>())
+        .unwrap()
+        .try_collect()
+        .unwrap();
+    assert!(unnamed.is_empty());
+}
@@ -44,3 +44,68 @@ fn test_snapshot() {
 
     tf.display_dir_entries();
 }
+
+#[test]
+fn test_snapshot_reader() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..100 {
+        rw.insert(Item {
+            id,
+            name: format!("before_{}", id),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let snapshot = db.snapshot_reader().unwrap();
+
+    // Changes committed after the snapshot are not visible
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..100 {
+        rw.upsert(Item {
+            id,
+            name: format!("after_{}", id),
+        })
+        .unwrap();
+    }
+    rw.insert(Item {
+        id: 100,
+        name: "after_100".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    std::thread::scope(|s| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let snapshot = snapshot.clone();
+                s.spawn(move || {
+                    let items: Vec<Item> = snapshot
+                        .scan()
+                        .primary()
+                        .unwrap()
+                        .all()
+                        .unwrap()
+                        .map(|item| item.unwrap())
+                        .collect();
+                    let item: Item = snapshot.get().primary(50u32).unwrap().unwrap();
+                    (items, item)
+                })
+            })
+            .collect();
+        for handle in handles {
+            let (items, item) = handle.join().unwrap();
+            assert_eq!(items.len(), 100);
+            assert!(items.iter().all(|item| item.name.starts_with("before_")));
+            assert_eq!(item.name, "before_50");
+        }
+    });
+
+    // The current state is visible to a new transaction
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item>().unwrap(), 101);
+}
@@ -3,6 +3,7 @@ use native_model::{native_model, Model};
 use serde::{Deserialize, Serialize};
 use shortcut_assert_fs::TmpFs;
 use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
 
 #[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
 #[native_model(id = 1, version = 1)]
@@ -145,3 +146,686 @@ fn test_transaction_fail() {
     let result: Option<Item> = r.get().primary(2u32).unwrap();
     assert!(result.is_none());
 }
+
+#[test]
+fn test_commit_with_info() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let item = |id: u32, name: &str| Item {
+        id,
+        name: name.to_string(),
+    };
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(item(1, "a")).unwrap();
+    rw.insert(item(2, "b")).unwrap();
+    rw.insert(item(3, "c")).unwrap();
+    let info = rw.commit_with_info().unwrap();
+    assert_eq!(
+        info,
+        transaction::CommitInfo {
+            inserts: 3,
+            updates: 0,
+            removes: 0,
+            sequence: 1,
+        }
+    );
+
+    let rw = db.rw_transaction().unwrap();
+    rw.update(item(1, "a"), item(1, "a2")).unwrap();
+    rw.upsert(item(2, "b2")).unwrap();
+    rw.upsert(item(4, "d")).unwrap();
+    rw.remove(item(3, "c")).unwrap();
+    let info = rw.commit_with_info().unwrap();
+    assert_eq!(
+        info,
+        transaction::CommitInfo {
+            inserts: 1,
+            updates: 2,
+            removes: 1,
+            sequence: 2,
+        }
+    );
+    assert_eq!(info.changes(), 4);
+
+    // Empty transaction
+    let rw = db.rw_transaction().unwrap();
+    let info = rw.commit_with_info().unwrap();
+    assert_eq!(info.changes(), 0);
+    assert_eq!(info.sequence, 3);
+}
+
+#[test]
+fn test_abort_returning() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let item = |id: u32, name: &str| Item {
+        id,
+        name: name.to_string(),
+    };
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(item(1, "a")).unwrap();
+    rw.insert(item(2, "b")).unwrap();
+    rw.commit().unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(item(3, "c")).unwrap();
+    rw.update(item(1, "a"), item(1, "a2")).unwrap();
+    rw.remove(item(2, "b")).unwrap();
+    let staged = rw.abort_returning().unwrap();
+
+    assert_eq!(staged.len(), 3);
+    assert!(staged.iter().all(|change| change.table == "1_1_id"));
+    assert_eq!(
+        staged
+            .iter()
+            .map(|change| change.primary_key.clone())
+            .collect::<Vec<_>>(),
+        vec![3u32.to_key(), 1u32.to_key(), 2u32.to_key()]
+    );
+    match &staged[0].event {
+        watch::Event::Insert(insert) => assert_eq!(insert.inner::<Item>().unwrap(), item(3, "c")),
+        _ => panic!("wrong event"),
+    }
+    match &staged[1].event {
+        watch::Event::Update(update) => {
+            assert_eq!(update.inner_old::<Item>().unwrap(), item(1, "a"));
+            assert_eq!(update.inner_new::<Item>().unwrap(), item(1, "a2"));
+        }
+        _ => panic!("wrong event"),
+    }
+    match &staged[2].event {
+        watch::Event::Delete(delete) => assert_eq!(delete.inner::<Item>().unwrap(), item(2, "b")),
+        _ => panic!("wrong event"),
+    }
+
+    // Nothing was written
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item>().unwrap(), 2);
+    let item_1: Item = r.get().primary(1u32).unwrap().unwrap();
+    assert_eq!(item_1.name, "a");
+}
+
+#[test]
+fn test_on_commit() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+
+    let committed = Arc::new(Mutex::new(vec![]));
+    let committed_hook = Arc::clone(&committed);
+    let db = Builder::new()
+        .on_commit(Arc::new(move |events: &[watch::Event]| {
+            committed_hook.lock().unwrap().push(events.to_vec());
+        }))
+        .create_in_memory(&models)
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..3 {
+        rw.insert(Item {
+            id,
+            name: format!("test_{}", id),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    // Aborted and empty transactions are not reported
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Item {
+        id: 10,
+        name: "aborted".to_string(),
+    })
+    .unwrap();
+    rw.abort().unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.commit().unwrap();
+
+    let committed = committed.lock().unwrap();
+    assert_eq!(committed.len(), 1);
+    let ids = committed[0]
+        .iter()
+        .map(|event| match event {
+            watch::Event::Insert(insert) => insert.inner::<Item>().unwrap().id,
+            _ => panic!("expected an insert"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![0, 1, 2]);
+}
+
+#[test]
+fn test_commit_sequence() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+    assert_eq!(db.current_commit_sequence(), 0);
+
+    for id in 1..=3 {
+        let rw = db.rw_transaction().unwrap();
+        rw.insert(Item {
+            id,
+            name: "test".to_string(),
+        })
+        .unwrap();
+        rw.commit().unwrap();
+        assert_eq!(db.current_commit_sequence(), id as u64);
+    }
+
+    // Aborted transaction
+    let rw = db.rw_transaction().unwrap();
+    rw.abort().unwrap();
+    assert_eq!(db.current_commit_sequence(), 3);
+
+    // Empty transaction
+    let rw = db.rw_transaction().unwrap();
+    rw.commit().unwrap();
+    assert_eq!(db.current_commit_sequence(), 4);
+
+    let rw = db.rw_transaction().unwrap();
+    let info = rw.commit_with_info().unwrap();
+    assert_eq!(info.sequence, 5);
+    assert_eq!(db.current_commit_sequence(), info.sequence);
+}
+
+#[test]
+fn test_model_not_defined() {
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    let error = rw
+        .insert(Item2 {
+            id: 1,
+            name: "test".to_string(),
+        })
+        .unwrap_err();
+    assert!(matches!(error, db_type::Error::ModelNotDefined { .. }));
+    assert_eq!(
+        error.to_string(),
+        "The model Item2 (native_model id 2, version 1) is not defined, did you forget `models.define::<Item2>()`?"
+    );
+
+    let r = db.r_transaction().unwrap();
+    let error = r.get().primary::<Item2>(1u32).unwrap_err();
+    assert!(matches!(error, db_type::Error::ModelNotDefined { .. }));
+}
+
+#[test]
+fn test_two_phase_commit_and_quick_repair() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let mut rw = db.rw_transaction().unwrap();
+    assert!(!rw.two_phase_commit());
+    assert!(!rw.quick_repair());
+
+    rw.set_two_phase_commit(true);
+    assert!(rw.two_phase_commit());
+    assert!(!rw.quick_repair());
+
+    rw.set_two_phase_commit(false);
+    rw.set_quick_repair(true);
+    assert!(!rw.two_phase_commit());
+    assert!(rw.quick_repair());
+
+    rw.insert(Item {
+        id: 1,
+        name: "test".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    // The flags are per transaction
+    let rw = db.rw_transaction().unwrap();
+    assert!(!rw.two_phase_commit());
+    assert!(!rw.quick_repair());
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 2, version = 1)]
+#[native_db]
+struct Ordered {
+    #[primary_key]
+    position: u32,
+    #[secondary_key(unique)]
+    name: String,
+}
+
+#[test]
+fn test_swap() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<Ordered>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Ordered {
+        position: 1,
+        name: "first".to_string(),
+    })
+    .unwrap();
+    rw.insert(Ordered {
+        position: 2,
+        name: "second".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.swap::<Ordered>(1u32, 2u32).unwrap();
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let item: Ordered = r.get().primary(1u32).unwrap().unwrap();
+    assert_eq!(item.name, "second");
+    let item: Ordered = r.get().primary(2u32).unwrap().unwrap();
+    assert_eq!(item.name, "first");
+
+    // The unique secondary keys follow their value
+    let item: Ordered = r
+        .get()
+        .secondary(OrderedKey::name, "first")
+        .unwrap()
+        .unwrap();
+    assert_eq!(item.position, 2);
+    let item: Ordered = r
+        .get()
+        .secondary(OrderedKey::name, "second")
+        .unwrap()
+        .unwrap();
+    assert_eq!(item.position, 1);
+    assert_eq!(r.len().primary::<Ordered>().unwrap(), 2);
+
+    // A missing key fails without changing anything
+    let rw = db.rw_transaction().unwrap();
+    assert!(matches!(
+        rw.swap::<Ordered>(1u32, 3u32),
+        Err(db_type::Error::KeyNotFound { .. })
+    ));
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 3, version = 1)]
+#[native_db(blob)]
+struct OrderedAttachment {
+    #[primary_key]
+    position: u32,
+    file_name: String,
+}
+
+#[test]
+fn test_swap_blob() {
+    use std::io::Read;
+
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<OrderedAttachment>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for (position, file_name, content) in [(1, "a.txt", "a"), (2, "b.txt", "b")] {
+        rw.insert_with_blob(
+            OrderedAttachment {
+                position,
+                file_name: file_name.to_string(),
+            },
+            content.as_bytes(),
+        )
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.swap::<OrderedAttachment>(1u32, 2u32).unwrap();
+    rw.commit().unwrap();
+
+    // The blobs follow their value
+    let r = db.r_transaction().unwrap();
+    for position in [1u32, 2] {
+        let item: OrderedAttachment = r.get().primary(position).unwrap().unwrap();
+        let mut content = String::new();
+        r.open_blob::<OrderedAttachment>(position)
+            .unwrap()
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(item.file_name, format!("{}.txt", content));
+    }
+    let item: OrderedAttachment = r.get().primary(1u32).unwrap().unwrap();
+    assert_eq!(item.file_name, "b.txt");
+}
+
+#[test]
+fn test_read_own_writes() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<Ordered>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Ordered {
+        position: 1,
+        name: "first".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+
+    // Insert then scan
+    rw.insert(Ordered {
+        position: 2,
+        name: "second".to_string(),
+    })
+    .unwrap();
+    let items: Vec<Ordered> = rw
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(items.len(), 2);
+    let item: Option<Ordered> = rw.get().secondary(OrderedKey::name, "second").unwrap();
+    assert_eq!(item.unwrap().position, 2);
+
+    // Remove then get
+    rw.remove(Ordered {
+        position: 1,
+        name: "first".to_string(),
+    })
+    .unwrap();
+    let item: Option<Ordered> = rw.get().primary(1u32).unwrap();
+    assert!(item.is_none());
+    let item: Option<Ordered> = rw.get().secondary(OrderedKey::name, "first").unwrap();
+    assert!(item.is_none());
+    assert_eq!(rw.len().primary::<Ordered>().unwrap(), 1);
+
+    // Update then scan
+    rw.update(
+        Ordered {
+            position: 2,
+            name: "second".to_string(),
+        },
+        Ordered {
+            position: 2,
+            name: "renamed".to_string(),
+        },
+    )
+    .unwrap();
+    let items: Vec<Ordered> = rw
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        items,
+        vec![Ordered {
+            position: 2,
+            name: "renamed".to_string(),
+        }]
+    );
+    let names: Vec<Ordered> = rw
+        .scan()
+        .secondary(OrderedKey::name)
+        .unwrap()
+        .all()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(names, items);
+    rw.abort().unwrap();
+
+    // Nothing is visible outside of the aborted transaction
+    let r = db.r_transaction().unwrap();
+    let item: Ordered = r.get().primary(1u32).unwrap().unwrap();
+    assert_eq!(item.name, "first");
+    assert_eq!(r.len().primary::<Ordered>().unwrap(), 1);
+}
+
+/// In memory storage that fails with `StorageFull` once `full` is set.
+#[derive(Debug)]
+struct FullableBackend {
+    inner: Arc<redb::backends::InMemoryBackend>,
+    full: Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl FullableBackend {
+    fn check(&self) -> std::io::Result<()> {
+        if self.full.load(std::sync::atomic::Ordering::SeqCst) {
+            Err(std::io::ErrorKind::StorageFull.into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl redb::StorageBackend for FullableBackend {
+    fn len(&self) -> std::io::Result<u64> {
+        self.inner.len()
+    }
+
+    fn read(&self, offset: u64, len: usize) -> std::io::Result<Vec<u8>> {
+        self.inner.read(offset, len)
+    }
+
+    fn set_len(&self, len: u64) -> std::io::Result<()> {
+        self.check()?;
+        self.inner.set_len(len)
+    }
+
+    fn sync_data(&self, eventual: bool) -> std::io::Result<()> {
+        self.check()?;
+        self.inner.sync_data(eventual)
+    }
+
+    fn write(&self, offset: u64, data: &[u8]) -> std::io::Result<()> {
+        self.check()?;
+        self.inner.write(offset, data)
+    }
+}
+
+#[test]
+fn test_commit_storage_full() {
+    let inner = Arc::new(redb::backends::InMemoryBackend::new());
+    let full = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let backend = || FullableBackend {
+        inner: Arc::clone(&inner),
+        full: Arc::clone(&full),
+    };
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let redb_database = redb::Database::builder()
+        .create_with_backend(backend())
+        .unwrap();
+    let db = Builder::new().from_redb(&models, redb_database).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Item {
+        id: 1,
+        name: "test".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    #[allow(unused_mut)]
+    let (mut recv, _) = db.watch().scan().primary().all::<Item>().unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Item {
+        id: 2,
+        name: "test".to_string(),
+    })
+    .unwrap();
+    full.store(true, std::sync::atomic::Ordering::SeqCst);
+    let error = rw.commit().unwrap_err();
+    assert!(error.is_storage_full());
+    assert!(
+        matches!(&error, db_type::Error::Io(error) if error.kind() == std::io::ErrorKind::StorageFull)
+    );
+
+    // No event for the failed commit
+    assert!(recv.try_recv().is_err());
+    drop(db);
+
+    // Once reopened, the database is at its last successful commit
+    full.store(false, std::sync::atomic::Ordering::SeqCst);
+    let redb_database = redb::Database::builder()
+        .create_with_backend(backend())
+        .unwrap();
+    let db = Builder::new().from_redb(&models, redb_database).unwrap();
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item>().unwrap(), 1);
+    let item: Option<Item> = r.get().primary(2u32).unwrap();
+    assert!(item.is_none());
+}
+
+#[test]
+fn test_committed_view() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<Ordered>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Ordered {
+        position: 1,
+        name: "first".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Ordered {
+        position: 2,
+        name: "second".to_string(),
+    })
+    .unwrap();
+    rw.update(
+        Ordered {
+            position: 1,
+            name: "first".to_string(),
+        },
+        Ordered {
+            position: 1,
+            name: "renamed".to_string(),
+        },
+    )
+    .unwrap();
+
+    // The committed view doesn't show the changes of the transaction
+    let committed = rw.committed_view().unwrap();
+    let items: Vec<Ordered> = committed
+        .scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        items,
+        vec![Ordered {
+            position: 1,
+            name: "first".to_string(),
+        }]
+    );
+    let item: Option<Ordered> = committed.get().primary(2u32).unwrap();
+    assert!(item.is_none());
+    assert_eq!(rw.len().primary::<Ordered>().unwrap(), 2);
+
+    // It stays on the state at the start of the transaction after the commit
+    rw.commit().unwrap();
+    assert_eq!(committed.len().primary::<Ordered>().unwrap(), 1);
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Ordered>().unwrap(), 2);
+}
+
+#[test]
+fn test_max_db_size() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let limit = 2 * 1024 * 1024;
+    let mut db = Builder::new()
+        .set_max_db_size(limit)
+        .create(&models, db_path.as_std_path())
+        .unwrap();
+
+    // Insert until the quota trips
+    let name = "x".repeat(10 * 1024);
+    let mut id = 0;
+    let result = loop {
+        let rw = db.rw_transaction().unwrap();
+        for _ in 0..10 {
+            rw.insert(Item {
+                id,
+                name: name.clone(),
+            })
+            .unwrap();
+            id += 1;
+        }
+        if let Err(error) = rw.commit() {
+            break error;
+        }
+        assert!(id < 10_000, "the quota never tripped");
+    };
+    assert!(matches!(result, db_type::Error::QuotaExceeded { limit: l } if l == limit));
+
+    // The last transaction is rolled back
+    let r = db.r_transaction().unwrap();
+    let len = r.len().primary::<Item>().unwrap();
+    assert_eq!(len, id as u64 - 10);
+    assert!(len > 0 && len * name.len() as u64 <= limit);
+    drop(r);
+
+    // A transaction which only removes data is still committed
+    let rw = db.rw_transaction().unwrap();
+    for id in 0..(len as u32 / 2) {
+        rw.remove(Item {
+            id,
+            name: name.clone(),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item>().unwrap(), len - len / 2);
+    drop(r);
+
+    // The space is given back by a compaction
+    db.compact().unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Item { id, name }).unwrap();
+    rw.commit().unwrap();
+}
@@ -0,0 +1,22 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Customer {
+    id: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Order {
+    #[primary_key]
+    id: u32,
+    #[secondary_key(customer.id)]
+    customer: Customer,
+    #[secondary_key]
+    customer_id: u32,
+}
+
+fn main() {}
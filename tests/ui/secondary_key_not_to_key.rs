@@ -0,0 +1,20 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Address {
+    city: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Customer {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    address: Address,
+}
+
+fn main() {}
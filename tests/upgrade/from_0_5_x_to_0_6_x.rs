@@ -30,7 +30,7 @@ fn try_to_open_legacy_database_without_upgrade_feature() {
     let root_project_path = env!("CARGO_MANIFEST_DIR");
     let database_path = format!("{}/tests/data/db_0_5_x", root_project_path);
 
-    // Try to open the legacy database. This must fail with an UpgradeRequired error.
+    // Try to open the legacy database. This must fail with an actionable error.
     let mut models = Models::new();
     models.define::<Item1>().unwrap();
     let db_error: Result<Database<'_>, db_type::Error> =
@@ -38,10 +38,14 @@ fn try_to_open_legacy_database_without_upgrade_feature() {
     assert!(db_error.is_err());
     assert!(matches!(
         db_error,
-        Result::Err(db_type::Error::RedbDatabaseError(
-            redb::DatabaseError::UpgradeRequired(1)
-        ))
+        Result::Err(db_type::Error::RedbUpgradeAvailable { ref path })
+            if path == std::path::Path::new(&database_path)
     ));
+    assert!(db_error
+        .err()
+        .unwrap()
+        .to_string()
+        .contains("enable the feature `upgrade_0_5_x`"));
 }
 
 #[test]
@@ -68,6 +72,9 @@ fn try_to_open_legacy_database_with_upgrade_feature() {
     let mut models = Models::new();
     models.define::<Item1>().unwrap();
     models.define::<Item2>().unwrap();
+    Builder::new()
+        .upgrade_key_encoding(&models, &tmp_database_path)
+        .unwrap();
     let db = Builder::new().open(&models, &tmp_database_path).unwrap();
     // TODO: during open, the database must be upgraded to the latest version.
 
@@ -120,6 +127,9 @@ fn try_to_open_legacy_database_with_upgrade_feature_with_other_model() {
     models.define::<Item1>().unwrap();
     models.define::<Item2>().unwrap();
     models.define::<Item3>().unwrap();
+    Builder::new()
+        .upgrade_key_encoding(&models, &tmp_database_path)
+        .unwrap();
     let db = Builder::new().open(&models, &tmp_database_path).unwrap();
     // TODO: during open, the database must be upgraded to the latest version.
 
@@ -134,3 +144,102 @@ fn try_to_open_legacy_database_with_upgrade_feature_with_other_model() {
     let len = r_txn.len().primary::<Item2>().unwrap();
     assert_eq!(len, 1000);
 }
+
+#[test]
+#[cfg(feature = "upgrade_0_5_x")]
+fn recover_interrupted_upgrade() {
+    use std::path::PathBuf;
+    #[cfg(any(target_os = "android", target_os = "ios"))]
+    let database_path = { dinghy_test::test_project_path().join("tests/data/db_0_5_x") };
+
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    let database_path = {
+        let root_project_path = env!("CARGO_MANIFEST_DIR");
+        PathBuf::from(format!("{}/tests/data/db_0_5_x", root_project_path))
+    };
+
+    use shortcut_assert_fs::TmpFs;
+    let tmp = TmpFs::new().unwrap();
+    let tmp_database_path = tmp.path("db_0_5_x");
+    let tmp_redb2_path = tmp.path("db_0_5_x_redb2");
+    std::fs::copy(&database_path, &tmp_database_path).unwrap();
+
+    let mut models = Models::new();
+    models.define::<Item1>().unwrap();
+    models.define::<Item2>().unwrap();
+
+    // A running upgrade holds the lock of the copy: it is not touched
+    let running_upgrade = redb::Database::create(&tmp_redb2_path).unwrap();
+    let result = Builder::new().open(&models, &tmp_database_path);
+    assert!(matches!(result, Err(error) if error.is_conflict()));
+    assert!(tmp_redb2_path.exists());
+    drop(running_upgrade);
+
+    // Interrupted during the copy: the partial copy is removed and the upgrade starts over
+    Builder::new()
+        .upgrade_key_encoding(&models, &tmp_database_path)
+        .unwrap();
+    let db = Builder::new().open(&models, &tmp_database_path).unwrap();
+    assert!(!tmp_redb2_path.exists());
+    let r_txn = db.r_transaction().unwrap();
+    assert_eq!(r_txn.len().primary::<Item2>().unwrap(), 1000);
+    drop(r_txn);
+    drop(db);
+
+    // Interrupted after the removal of the legacy file: the complete copy replaces it
+    std::fs::rename(&tmp_database_path, &tmp_redb2_path).unwrap();
+    let db = Builder::new().open(&models, &tmp_database_path).unwrap();
+    assert!(!tmp_redb2_path.exists());
+    let r_txn = db.r_transaction().unwrap();
+    assert_eq!(r_txn.len().primary::<Item2>().unwrap(), 1000);
+}
+
+#[test]
+#[cfg(feature = "upgrade_0_5_x")]
+fn ignore_unrelated_redb2_file() {
+    use shortcut_assert_fs::TmpFs;
+    let tmp = TmpFs::new().unwrap();
+    let database_path = tmp.path("test");
+    let redb2_path = tmp.path("test_redb2");
+
+    let mut models = Models::new();
+    models.define::<Item1>().unwrap();
+
+    // No database yet: a file which is not a redb2 database doesn't replace it
+    std::fs::write(&redb2_path, b"not a database").unwrap();
+    let db = Builder::new().create(&models, &database_path).unwrap();
+    drop(db);
+    assert_eq!(std::fs::read(&redb2_path).unwrap(), b"not a database");
+
+    // The database doesn't require the upgrade: the file is not touched
+    let db = Builder::new().open(&models, &database_path).unwrap();
+    drop(db);
+    assert_eq!(std::fs::read(&redb2_path).unwrap(), b"not a database");
+}
+
+#[test]
+#[cfg(all(feature = "upgrade_0_5_x", target_os = "linux"))]
+fn upgrade_legacy_database_with_non_utf8_path() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let root_project_path = env!("CARGO_MANIFEST_DIR");
+    let database_path = format!("{}/tests/data/db_0_5_x", root_project_path);
+
+    use shortcut_assert_fs::TmpFs;
+    let tmp = TmpFs::new().unwrap();
+
+    let tmp_database_path = std::path::PathBuf::from(tmp.path("db_0_5_x"))
+        .with_file_name(OsStr::from_bytes(b"db_0_5_x_\xff"));
+    std::fs::copy(&database_path, &tmp_database_path).unwrap();
+
+    let mut models = Models::new();
+    models.define::<Item1>().unwrap();
+    models.define::<Item2>().unwrap();
+    Builder::new()
+        .upgrade_key_encoding(&models, &tmp_database_path)
+        .unwrap();
+    let db = Builder::new().open(&models, &tmp_database_path).unwrap();
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item2>().unwrap(), 1000);
+}
@@ -67,6 +67,9 @@ fn upgrade_from_0_7_x_to_0_8_x() {
     let mut models = Models::new();
     models.define::<Item1>().unwrap();
     models.define::<Item2>().unwrap();
+    Builder::new()
+        .upgrade_key_encoding(&models, &tmp_database_path)
+        .unwrap();
     let db = Builder::new().open(&models, &tmp_database_path).unwrap();
     drop(db);
 
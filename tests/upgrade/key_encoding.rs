@@ -0,0 +1,319 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use redb::{ReadableMultimapTable, ReadableTable};
+use serde::{Deserialize, Serialize};
+use shortcut_assert_fs::TmpFs;
+use std::io::Read;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 1, version = 1)]
+#[native_db(blob)]
+struct Item {
+    #[primary_key]
+    id: i32,
+    #[secondary_key]
+    score: i64,
+}
+
+const PRIMARY_TABLE: redb::TableDefinition<Key, &[u8]> = redb::TableDefinition::new("1_1_id");
+const BLOB_TABLE: redb::TableDefinition<Key, &[u8]> = redb::TableDefinition::new("1_1_id_blob");
+const SECONDARY_TABLE: redb::MultimapTableDefinition<Key, Key> =
+    redb::MultimapTableDefinition::new("1_1_score");
+const METADATA_TABLE: redb::TableDefinition<&str, &str> = redb::TableDefinition::new("metadata");
+
+// The first encoding of the signed integers, without the sign bit flipped.
+fn first_encoding(key: Key) -> Key {
+    let mut data = <Key as redb::Value>::as_bytes(&key).to_vec();
+    data[0] ^= 0x80;
+    Key::new(data)
+}
+
+// Rewrites the keys of the database with the first encoding and removes the record of the
+// encoding, like a database filled before the signed integers were sorted in numeric order.
+fn downgrade_keys(db: &Database) {
+    let rw = db.redb_database().unwrap().begin_write().unwrap();
+    {
+        for definition in [PRIMARY_TABLE, BLOB_TABLE] {
+            let mut table = rw.open_table(definition).unwrap();
+            let mut entries = vec![];
+            for result in table.iter().unwrap() {
+                let (key, value) = result.unwrap();
+                entries.push((key.value(), value.value().to_vec()));
+            }
+            table.retain(|_, _| false).unwrap();
+            for (key, value) in entries {
+                table.insert(first_encoding(key), value.as_slice()).unwrap();
+            }
+        }
+
+        let mut table = rw.open_multimap_table(SECONDARY_TABLE).unwrap();
+        let mut entries = vec![];
+        for result in table.iter().unwrap() {
+            let (secondary_key, primary_keys) = result.unwrap();
+            for primary_key in primary_keys {
+                entries.push((secondary_key.value(), primary_key.unwrap().value()));
+            }
+        }
+        for (secondary_key, primary_key) in entries {
+            table.remove(&secondary_key, &primary_key).unwrap();
+            table
+                .insert(first_encoding(secondary_key), first_encoding(primary_key))
+                .unwrap();
+        }
+
+        let mut table = rw.open_table(METADATA_TABLE).unwrap();
+        table.remove("key_encoding:1_1_id").unwrap();
+    }
+    rw.commit().unwrap();
+}
+
+#[test]
+fn upgrade_key_encoding() {
+    let tf = TmpFs::new().unwrap();
+    let database_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create(&models, &database_path).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    for id in -2..=2 {
+        let blob = format!("blob {}", id);
+        rw.insert_with_blob(
+            Item {
+                id,
+                score: -10 * id as i64,
+            },
+            blob.as_bytes(),
+        )
+        .unwrap();
+    }
+    rw.commit().unwrap();
+    downgrade_keys(&db);
+    drop(db);
+
+    // The keys must be upgraded before the database is opened
+    let result = Builder::new().open(&models, &database_path);
+    assert!(matches!(
+        result,
+        Err(db_type::Error::KeyEncodingUpgradeRequired { table }) if table == "1_1_id"
+    ));
+    let report = Builder::new()
+        .upgrade_key_encoding(&models, &database_path)
+        .unwrap();
+    assert_eq!(report.upgraded_tables, vec!["1_1_id".to_string()]);
+    assert!(report.skipped.is_empty());
+
+    let db = Builder::new().open(&models, &database_path).unwrap();
+    let r = db.r_transaction().unwrap();
+    let ids: Vec<i32> = r
+        .scan()
+        .primary::<Item>()
+        .unwrap()
+        .all()
+        .unwrap()
+        .map(|item| item.unwrap().id)
+        .collect();
+    assert_eq!(ids, vec![-2, -1, 0, 1, 2]);
+
+    let scores: Vec<i64> = r
+        .scan()
+        .secondary::<Item>(ItemKey::score)
+        .unwrap()
+        .range(-15i64..)
+        .unwrap()
+        .map(|item| item.unwrap().score)
+        .collect();
+    assert_eq!(scores, vec![-10, 0, 10, 20]);
+
+    let item: Item = r.get().primary(-1i32).unwrap().unwrap();
+    assert_eq!(item.score, 10);
+
+    let mut blob = String::new();
+    r.open_blob::<Item>(-2i32)
+        .unwrap()
+        .unwrap()
+        .read_to_string(&mut blob)
+        .unwrap();
+    assert_eq!(blob, "blob -2");
+    drop(r);
+    drop(db);
+
+    // Only once
+    let report = Builder::new()
+        .upgrade_key_encoding(&models, &database_path)
+        .unwrap();
+    assert_eq!(report, KeyEncodingReport::default());
+    let db = Builder::new().open(&models, &database_path).unwrap();
+    let r = db.r_transaction().unwrap();
+    let item: Item = r.get().primary(2i32).unwrap().unwrap();
+    assert_eq!(item.score, -20);
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 2, version = 1)]
+#[native_db]
+struct Tagged {
+    #[primary_key]
+    id: u32,
+    #[secondary_key(optional)]
+    tag: Option<String>,
+}
+
+#[test]
+fn upgrade_optional_key_encoding() {
+    let tf = TmpFs::new().unwrap();
+    let database_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Tagged>().unwrap();
+    let db = Builder::new().create(&models, &database_path).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Tagged {
+        id: 1,
+        tag: Some("b".to_string()),
+    })
+    .unwrap();
+    rw.insert(Tagged { id: 2, tag: None }).unwrap();
+    rw.insert(Tagged {
+        id: 3,
+        tag: Some("a".to_string()),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    // The first encoding of `Some(value)` is the encoding of `value`
+    let rw = db.redb_database().unwrap().begin_write().unwrap();
+    {
+        let mut table = rw
+            .open_multimap_table(redb::MultimapTableDefinition::<Key, Key>::new("2_1_tag"))
+            .unwrap();
+        let mut entries = vec![];
+        for result in table.iter().unwrap() {
+            let (secondary_key, primary_keys) = result.unwrap();
+            for primary_key in primary_keys {
+                entries.push((secondary_key.value(), primary_key.unwrap().value()));
+            }
+        }
+        for (secondary_key, primary_key) in entries {
+            table.remove(&secondary_key, &primary_key).unwrap();
+            let data = <Key as redb::Value>::as_bytes(&secondary_key)[1..].to_vec();
+            table.insert(Key::new(data), primary_key).unwrap();
+        }
+        let mut table = rw.open_table(METADATA_TABLE).unwrap();
+        table.remove("key_encoding:2_1_id").unwrap();
+    }
+    rw.commit().unwrap();
+    drop(db);
+
+    Builder::new()
+        .upgrade_key_encoding(&models, &database_path)
+        .unwrap();
+    let db = Builder::new().open(&models, &database_path).unwrap();
+    let r = db.r_transaction().unwrap();
+    let ids: Vec<u32> = r
+        .scan()
+        .secondary::<Tagged>(TaggedKey::tag)
+        .unwrap()
+        .range(Some("a")..=Some("a"))
+        .unwrap()
+        .map(|tagged| tagged.unwrap().id)
+        .collect();
+    assert_eq!(ids, vec![3]);
+    let ids: Vec<u32> = r
+        .scan()
+        .secondary::<Tagged>(TaggedKey::tag)
+        .unwrap()
+        .all()
+        .unwrap()
+        .map(|tagged| tagged.unwrap().id)
+        .collect();
+    assert_eq!(ids, vec![3, 1]);
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[native_model(id = 3, version = 1)]
+#[native_db]
+struct Named {
+    #[primary_key]
+    name: String,
+}
+
+#[test]
+fn open_unchanged_key_encoding() {
+    let tf = TmpFs::new().unwrap();
+    let database_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Named>().unwrap();
+    let db = Builder::new().create(&models, &database_path).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Named {
+        name: "a".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+    let rw = db.redb_database().unwrap().begin_write().unwrap();
+    {
+        // A value which can not be decoded is not read by the open
+        let mut table = rw
+            .open_table(redb::TableDefinition::<Key, &[u8]>::new("3_1_name"))
+            .unwrap();
+        table.insert("b".to_key(), [0xffu8; 4].as_slice()).unwrap();
+        let mut table = rw.open_table(METADATA_TABLE).unwrap();
+        table.remove("key_encoding:3_1_name").unwrap();
+    }
+    rw.commit().unwrap();
+    drop(db);
+
+    // The keys of a `String` are the same in all the encodings
+    let db = Builder::new().open(&models, &database_path).unwrap();
+    let r = db.r_transaction().unwrap();
+    let named: Named = r.get().primary("a").unwrap().unwrap();
+    assert_eq!(named.name, "a");
+}
+
+#[test]
+fn upgrade_key_encoding_skips_undecodable_values() {
+    let tf = TmpFs::new().unwrap();
+    let database_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = Builder::new().create(&models, &database_path).unwrap();
+    let rw = db.rw_transaction().unwrap();
+    for id in [-1, 1] {
+        rw.insert(Item { id, score: 0 }).unwrap();
+    }
+    rw.commit().unwrap();
+    downgrade_keys(&db);
+    let rw = db.redb_database().unwrap().begin_write().unwrap();
+    {
+        // The value of another model can not be decoded as an `Item`
+        let value = Named {
+            name: "a".to_string(),
+        }
+        .encode()
+        .unwrap();
+        let mut table = rw.open_table(PRIMARY_TABLE).unwrap();
+        table
+            .insert(first_encoding(5i32.to_key()), value.as_slice())
+            .unwrap();
+    }
+    rw.commit().unwrap();
+    drop(db);
+
+    let report = Builder::new()
+        .upgrade_key_encoding(&models, &database_path)
+        .unwrap();
+    assert_eq!(
+        report.skipped,
+        vec![("1_1_id".to_string(), first_encoding(5i32.to_key()))]
+    );
+
+    // The other values are upgraded
+    let db = Builder::new().open(&models, &database_path).unwrap();
+    let r = db.r_transaction().unwrap();
+    let item: Item = r.get().primary(-1i32).unwrap().unwrap();
+    assert_eq!(item.id, -1);
+    assert_eq!(r.len().primary::<Item>().unwrap(), 3);
+}
@@ -2,3 +2,5 @@ mod from_0_5_x_to_0_6_x;
 
 #[cfg(feature = "upgrade_0_7_x")]
 mod from_0_7_x_to_0_8_x;
+
+mod key_encoding;
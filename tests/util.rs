@@ -44,6 +44,45 @@ fn test_open_existing_database() {
     let _db = Builder::new().open(&models, tf.path("test")).unwrap();
 }
 
+#[test]
+fn test_path() {
+    let tf = TmpFs::new().unwrap();
+
+    let models = Models::new();
+    let db = Builder::new().create(&models, tf.path("test")).unwrap();
+    assert_eq!(db.path(), Some(tf.path("test").as_std_path()));
+    assert!(!db.is_in_memory());
+    drop(db);
+
+    let db = Builder::new().open(&models, tf.path("test")).unwrap();
+    assert_eq!(db.path(), Some(tf.path("test").as_std_path()));
+
+    let db = Builder::new().create_in_memory(&models).unwrap();
+    assert_eq!(db.path(), None);
+    assert!(db.is_in_memory());
+}
+
+#[test]
+fn test_open_already_opened_database_is_conflict() {
+    let tf = TmpFs::new().unwrap();
+
+    let models = Models::new();
+    let _db = Builder::new().create(&models, tf.path("test")).unwrap();
+
+    // The database file is locked by the first instance
+    let error = Builder::new().open(&models, tf.path("test")).err().unwrap();
+    assert!(matches!(
+        error,
+        db_type::Error::RedbDatabaseError(redb::DatabaseError::DatabaseAlreadyOpen)
+    ));
+    assert!(error.is_conflict());
+    assert!(error.is_retryable());
+
+    let error = db_type::Error::KeyNotFound { key: vec![1] };
+    assert!(!error.is_conflict());
+    assert!(!error.is_retryable());
+}
+
 use native_model::{native_model, Model};
 use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
@@ -113,3 +152,283 @@ fn create_local_database_for_tests() {
     let len = ro.len().primary::<Item2>().unwrap();
     assert_eq!(len, 1000);
 }
+
+#[test]
+fn test_total_len() {
+    let mut models = Models::new();
+    models.define::<Item1>().unwrap();
+    models.define::<Item2>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+    assert_eq!(db.total_len().unwrap(), 0);
+
+    let rw = db.rw_transaction().unwrap();
+    for i in 0..3 {
+        rw.insert(Item1 {
+            id: i,
+            name: format!("item1_{}", i),
+        })
+        .unwrap();
+    }
+    for i in 0..5 {
+        rw.insert(Item2 {
+            id: i,
+            id2: None,
+            name: format!("item2_{}", i),
+        })
+        .unwrap();
+    }
+    rw.commit().unwrap();
+
+    let r = db.r_transaction().unwrap();
+    let len = r.len().primary::<Item1>().unwrap() + r.len().primary::<Item2>().unwrap();
+    drop(r);
+    assert_eq!(len, 8);
+    assert_eq!(db.total_len().unwrap(), len);
+}
+
+#[test]
+fn test_from_redb() {
+    const RAW_TABLE: redb::TableDefinition<&str, u64> = redb::TableDefinition::new("raw");
+
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    // Create a redb database with a raw table
+    let redb_database = redb::Database::create(&db_path).unwrap();
+    let wx = redb_database.begin_write().unwrap();
+    {
+        let mut table = wx.open_table(RAW_TABLE).unwrap();
+        table.insert("counter", 1).unwrap();
+    }
+    wx.commit().unwrap();
+
+    // Wrap it with native_db
+    let mut models = Models::new();
+    models.define::<Item1>().unwrap();
+    let db = Builder::new().from_redb(&models, redb_database).unwrap();
+    assert!(db.path().is_none());
+    assert!(!db.is_in_memory());
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Item1 {
+        id: 1,
+        name: "item1".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    // Use the raw table alongside the native_db tables
+    let wx = db.redb_database().unwrap().begin_write().unwrap();
+    {
+        let mut table = wx.open_table(RAW_TABLE).unwrap();
+        table.insert("counter", 2).unwrap();
+    }
+    wx.commit().unwrap();
+    drop(db);
+
+    // Both are persisted in the same file
+    let redb_database = redb::Database::open(&db_path).unwrap();
+    let db = Builder::new().from_redb(&models, redb_database).unwrap();
+    let r = db.r_transaction().unwrap();
+    let item: Item1 = r.get().primary(1u32).unwrap().unwrap();
+    assert_eq!(item.name, "item1");
+
+    let rx = db.redb_database().unwrap().begin_read().unwrap();
+    let table = rx.open_table(RAW_TABLE).unwrap();
+    assert_eq!(table.get("counter").unwrap().unwrap().value(), 2);
+}
+
+#[test]
+fn test_metadata_table_name() {
+    use redb::ReadableTableMetadata;
+
+    // A raw table using the default name of the metadata table
+    const RAW_TABLE: redb::TableDefinition<&str, u64> = redb::TableDefinition::new("metadata");
+    const METADATA_TABLE: redb::TableDefinition<&str, &str> =
+        redb::TableDefinition::new("native_db_metadata");
+
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let redb_database = redb::Database::create(&db_path).unwrap();
+    let wx = redb_database.begin_write().unwrap();
+    {
+        let mut table = wx.open_table(RAW_TABLE).unwrap();
+        table.insert("counter", 1).unwrap();
+    }
+    wx.commit().unwrap();
+
+    let mut models = Models::new();
+    models.define::<Item1>().unwrap();
+    let db = Builder::new()
+        .set_metadata_table_name("native_db_metadata")
+        .from_redb(&models, redb_database)
+        .unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Item1 {
+        id: 1,
+        name: "item1".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+
+    // The metadata are in the custom table, the raw table is untouched
+    {
+        let rx = db.redb_database().unwrap().begin_read().unwrap();
+        let table = rx.open_table(METADATA_TABLE).unwrap();
+        assert_eq!(
+            table.get("version_native_db").unwrap().unwrap().value(),
+            db.metadata().current_version()
+        );
+        let table = rx.open_table(RAW_TABLE).unwrap();
+        assert_eq!(table.len().unwrap(), 1);
+        assert_eq!(table.get("counter").unwrap().unwrap().value(), 1);
+    }
+    drop(db);
+
+    // Reopen with the same name
+    let redb_database = redb::Database::open(&db_path).unwrap();
+    let db = Builder::new()
+        .set_metadata_table_name("native_db_metadata")
+        .from_redb(&models, redb_database)
+        .unwrap();
+    assert_eq!(
+        db.metadata().previous_version(),
+        Some(db.metadata().current_version())
+    );
+    let r = db.r_transaction().unwrap();
+    let item: Item1 = r.get().primary(1u32).unwrap().unwrap();
+    assert_eq!(item.name, "item1");
+}
+
+#[test]
+fn test_metadata_table_name_collision() {
+    let mut models = Models::new();
+    models.define::<Item1>().unwrap();
+
+    for name in ["1_1_id", "2_1_name", "native_db_counters"] {
+        let result = Builder::new()
+            .set_metadata_table_name(name)
+            .create_in_memory(&models);
+        assert!(matches!(
+            result,
+            Err(db_type::Error::MetadataTableNameCollision { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_create_with_seed() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item1>().unwrap();
+
+    // A failing seed leaves no database behind
+    let result = Builder::new().create_with_seed(&models, db_path.as_std_path(), |rw| {
+        rw.insert(Item1 {
+            id: 1,
+            name: "default".to_string(),
+        })?;
+        Err(db_type::Error::PrimaryKeyNotFound)
+    });
+    assert!(matches!(result, Err(db_type::Error::PrimaryKeyNotFound)));
+    assert!(!db_path.exists());
+
+    let mut seed_runs = 0;
+    let db = Builder::new()
+        .create_with_seed(&models, db_path.as_std_path(), |rw| {
+            seed_runs += 1;
+            rw.insert(Item1 {
+                id: 1,
+                name: "default".to_string(),
+            })
+        })
+        .unwrap();
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item1>().unwrap(), 1);
+    drop(r);
+    drop(db);
+
+    // The database exists: it is opened without seeding
+    let db = Builder::new()
+        .create_with_seed(&models, db_path.as_std_path(), |_| {
+            seed_runs += 1;
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(seed_runs, 1);
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item1>().unwrap(), 1);
+}
+
+#[test]
+fn test_create_with_seed_existing_empty_file() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+    drop(redb::Database::create(db_path.as_std_path()).unwrap());
+
+    let mut models = Models::new();
+    models.define::<Item1>().unwrap();
+
+    // The file exists but is not initialized by native_db: it is seeded, and kept on failure
+    let result = Builder::new().create_with_seed(&models, db_path.as_std_path(), |_| {
+        Err(db_type::Error::PrimaryKeyNotFound)
+    });
+    assert!(matches!(result, Err(db_type::Error::PrimaryKeyNotFound)));
+    assert!(db_path.exists());
+
+    let db = Builder::new()
+        .create_with_seed(&models, db_path.as_std_path(), |rw| {
+            rw.insert(Item1 {
+                id: 1,
+                name: "default".to_string(),
+            })
+        })
+        .unwrap();
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item1>().unwrap(), 1);
+}
+
+#[test]
+fn test_open_reporting() {
+    let tf = TmpFs::new().unwrap();
+    let db_path = tf.path("test");
+
+    let mut models = Models::new();
+    models.define::<Item1>().unwrap();
+
+    let (db, open_kind) = Builder::new()
+        .open_reporting(&models, db_path.as_std_path())
+        .unwrap();
+    assert_eq!(open_kind, OpenKind::CreatedNew);
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(Item1 {
+        id: 1,
+        name: "default".to_string(),
+    })
+    .unwrap();
+    rw.commit().unwrap();
+    drop(db);
+
+    let (db, open_kind) = Builder::new()
+        .open_reporting(&models, db_path.as_std_path())
+        .unwrap();
+    assert_eq!(open_kind, OpenKind::OpenedExisting);
+    let r = db.r_transaction().unwrap();
+    assert_eq!(r.len().primary::<Item1>().unwrap(), 1);
+    drop(r);
+    drop(db);
+
+    // A database created with `create` is reported as existing
+    let db_path = tf.path("test_create");
+    let db = Builder::new()
+        .create(&models, db_path.as_std_path())
+        .unwrap();
+    drop(db);
+    let (_db, open_kind) = Builder::new()
+        .open_reporting(&models, db_path.as_std_path())
+        .unwrap();
+    assert_eq!(open_kind, OpenKind::OpenedExisting);
+}
@@ -354,6 +354,52 @@ fn unwatch_by_deleted_receiver() {
     assert!(!db.unwatch(recv_id).unwrap());
 }
 
+#[test]
+fn unwatch_by_dropped_guard() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<ItemA>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let item_a = ItemA { id: 1 };
+
+    let (recv, guard) = db.watch_scoped().get().primary::<ItemA>(item_a.id).unwrap();
+    let (recv_all, guard_all) = db.watch_scoped().scan().primary().all::<ItemA>().unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(item_a.clone()).unwrap();
+    rw.commit().unwrap();
+    assert!(matches!(
+        recv.recv_timeout(TIMEOUT).unwrap(),
+        Event::Insert(_)
+    ));
+    assert!(matches!(
+        recv_all.recv_timeout(TIMEOUT).unwrap(),
+        Event::Insert(_)
+    ));
+
+    let recv_id = guard.id();
+    drop(guard);
+
+    // The watcher is removed when the guard is dropped, the other one is kept
+    assert!(!db.unwatch(recv_id).unwrap());
+    let rw = db.rw_transaction().unwrap();
+    rw.upsert(item_a.clone()).unwrap();
+    rw.commit().unwrap();
+    assert!(recv.try_recv().is_err());
+    assert!(matches!(
+        recv_all.recv_timeout(TIMEOUT).unwrap(),
+        Event::Update(_)
+    ));
+
+    // Unwatched explicitly before the guard is dropped
+    assert!(db.unwatch(guard_all.id()).unwrap());
+    drop(guard_all);
+}
+
 #[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
 #[native_model(id = 4, version = 1)]
 #[native_db]
@@ -406,6 +452,49 @@ fn watch_start_with() {
     assert!(recv.try_recv().is_err());
 }
 
+#[test]
+fn watch_range() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<ItemA>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let (recv, _) = db
+        .watch()
+        .scan()
+        .primary()
+        .range::<ItemA, _>(1000u32..2000u32)
+        .unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in [999, 1000, 1500, 1999, 2000, 3000] {
+        rw.insert(ItemA { id }).unwrap();
+    }
+    rw.commit().unwrap();
+
+    let mut ids = vec![];
+    for _ in 0..3 {
+        let inner_event: ItemA = if let Event::Insert(event) = recv.recv_timeout(TIMEOUT).unwrap() {
+            event.inner().unwrap()
+        } else {
+            panic!("wrong event")
+        };
+        ids.push(inner_event.id);
+    }
+    ids.sort();
+    assert_eq!(ids, vec![1000, 1500, 1999]);
+    assert!(recv.try_recv().is_err());
+
+    // Outside of the range
+    let rw = db.rw_transaction().unwrap();
+    rw.remove(ItemA { id: 2000 }).unwrap();
+    rw.commit().unwrap();
+    assert!(recv.try_recv().is_err());
+}
+
 #[test]
 fn watch_start_with_by_key() {
     let tf = TmpFs::new().unwrap();
@@ -454,6 +543,80 @@ fn watch_start_with_by_key() {
     assert!(recv.try_recv().is_err());
 }
 
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 5, version = 1)]
+#[native_db]
+struct Book {
+    #[primary_key]
+    id: u32,
+    #[secondary_key]
+    category_title: (String, String),
+}
+
+#[test]
+fn watch_start_with_component() {
+    let tf = TmpFs::new().unwrap();
+
+    let mut models = Models::new();
+    models.define::<Book>().unwrap();
+    let db = Builder::new()
+        .create(&models, tf.path("test").as_std_path())
+        .unwrap();
+
+    let (recv, _) = db
+        .watch()
+        .scan()
+        .secondary(BookKey::category_title)
+        .start_with_component::<Book>("novel")
+        .unwrap();
+
+    let book = |id: u32, category: &str, title: &str| Book {
+        id,
+        category_title: (category.to_string(), title.to_string()),
+    };
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(book(1, "novel", "a")).unwrap();
+    rw.insert(book(2, "poetry", "b")).unwrap();
+    rw.insert(book(3, "novel", "c")).unwrap();
+    rw.insert(book(4, "history", "novel")).unwrap();
+    rw.insert(book(5, "novella", "d")).unwrap();
+    rw.insert(book(6, "novel\0", "e")).unwrap();
+    rw.commit().unwrap();
+
+    let mut ids = vec![];
+    for _ in 0..2 {
+        let inner_event: Book = if let Event::Insert(event) = recv.recv_timeout(TIMEOUT).unwrap() {
+            event.inner().unwrap()
+        } else {
+            panic!("wrong event")
+        };
+        ids.push(inner_event.id);
+    }
+    ids.sort();
+    assert_eq!(ids, vec![1, 3]);
+    assert!(recv.try_recv().is_err());
+
+    // The component must be the first component of the key
+    let result = db
+        .watch()
+        .scan()
+        .secondary(BookKey::category_title)
+        .start_with_component::<Book>(("novel".to_string(), "a".to_string()));
+    assert!(matches!(
+        result,
+        Err(db_type::Error::MissmatchedKeyType { .. })
+    ));
+    let result = db
+        .watch()
+        .scan()
+        .secondary(BookKey::category_title)
+        .start_with_component::<Book>(1u32);
+    assert!(matches!(
+        result,
+        Err(db_type::Error::MissmatchedKeyType { .. })
+    ));
+}
+
 #[test]
 fn watch_all_delete() {
     let tf = TmpFs::new().unwrap();
@@ -527,3 +690,75 @@ fn watch_all_update() {
     }
     assert!(recv.try_recv().is_err());
 }
+
+#[test]
+fn watch_database_drop() {
+    let mut models = Models::new();
+    models.define::<ItemA>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let (recv, _) = db.watch().scan().primary().all::<ItemA>().unwrap();
+    drop(db);
+
+    assert!(matches!(
+        recv.recv_timeout(TIMEOUT),
+        Err(std::sync::mpsc::RecvTimeoutError::Disconnected)
+    ));
+}
+
+#[test]
+fn watch_bulk_delete() {
+    let mut models = Models::new();
+    models.define::<ItemA>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 1..=10 {
+        rw.insert(ItemA { id }).unwrap();
+    }
+    rw.commit().unwrap();
+
+    let (recv_all, _) = db.watch().scan().primary().all::<ItemA>().unwrap();
+    let (recv_one, _) = db.watch().get().primary::<ItemA>(2u32).unwrap();
+    let (recv_other, _) = db.watch().get().primary::<ItemA>(3u32).unwrap();
+
+    let mut rw = db.rw_transaction().unwrap();
+    rw.set_bulk_events(true);
+    let removed: Vec<ItemA> = rw.remove_many_by_keys([4u32, 2, 8, 11]).unwrap();
+    assert_eq!(removed.len(), 3);
+    // The values are still counted one by one
+    assert_eq!(rw.commit_with_info().unwrap().removes, 3);
+
+    // None of the removed values matches its key filter
+    assert!(recv_other.recv_timeout(TIMEOUT).is_err());
+
+    // A single summary, sent to the watchers matching one of the values
+    for recv in [&recv_all, &recv_one] {
+        let event = recv.recv_timeout(TIMEOUT).unwrap();
+        let Event::BulkDelete {
+            table,
+            count,
+            range,
+        } = &event
+        else {
+            panic!("wrong event")
+        };
+        assert_eq!(table, "1_1_id");
+        assert_eq!(*count, 3);
+        assert_eq!(range.clone().unwrap(), 2u32.to_key()..=8u32.to_key());
+        assert!(event.inner::<ItemA>().is_err());
+        assert!(recv.try_recv().is_err());
+    }
+
+    // Disabled by default
+    let rw = db.rw_transaction().unwrap();
+    let _: Vec<ItemA> = rw.remove_many_by_keys([1u32, 3]).unwrap();
+    rw.commit().unwrap();
+    for _ in 0..2 {
+        assert!(matches!(
+            recv_all.recv_timeout(TIMEOUT).unwrap(),
+            Event::Delete(_)
+        ));
+    }
+    assert!(recv_all.try_recv().is_err());
+}
@@ -5,6 +5,7 @@ use native_db::{watch::Event, Models};
 use native_model::{native_model, Model};
 use serde::{Deserialize, Serialize};
 use shortcut_assert_fs::TmpFs;
+use std::time::Duration;
 
 #[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
 #[native_model(id = 1, version = 1)]
@@ -14,6 +15,15 @@ struct ItemA {
     id: u32,
 }
 
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 2, version = 1)]
+#[native_db]
+struct ItemB {
+    #[primary_key]
+    id: u32,
+    text: String,
+}
+
 #[tokio::test]
 async fn watch_one_primary_key() {
     let tf = TmpFs::new().unwrap();
@@ -44,3 +54,132 @@ async fn watch_one_primary_key() {
 }
 
 // TODO: maybe do others tests but it should the same as a std::sync::mpsc::channel.
+
+#[tokio::test]
+async fn watch_database_drop() {
+    let mut models = Models::new();
+    models.define::<ItemA>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let (mut recv, _) = db.watch().scan().primary().all::<ItemA>().unwrap();
+    drop(db);
+
+    assert!(recv.recv().await.is_none());
+}
+
+#[tokio::test]
+async fn watch_recv_batch() {
+    let mut models = Models::new();
+    models.define::<ItemA>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let (mut recv, _) = db.watch().scan().primary().all::<ItemA>().unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    for id in 1..=5 {
+        rw.insert(ItemA { id }).unwrap();
+    }
+    rw.commit().unwrap();
+
+    // The available events are received in one call, up to `max`
+    let mut events = watch::recv_batch(&mut recv, 3).await;
+    assert_eq!(events.len(), 3);
+    events.extend(watch::recv_batch(&mut recv, 10).await);
+    assert_eq!(events.len(), 5);
+    let mut ids: Vec<u32> = events
+        .iter()
+        .map(|event| event.inner::<ItemA>().unwrap().id)
+        .collect();
+    ids.sort();
+    assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+    assert!(recv.try_recv().is_err());
+
+    drop(db);
+    assert!(watch::recv_batch(&mut recv, 10).await.is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn watch_debounce() {
+    let mut models = Models::new();
+    models.define::<ItemB>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let item = |id: u32, text: &str| ItemB {
+        id,
+        text: text.to_string(),
+    };
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(item(3, "x")).unwrap();
+    rw.commit().unwrap();
+
+    let (mut recv, _) = db
+        .watch()
+        .debounce(Duration::from_millis(100))
+        .scan()
+        .primary()
+        .all::<ItemB>()
+        .unwrap();
+
+    // Rapid writes, one transaction each
+    let write = |f: &dyn Fn(&transaction::RwTransaction)| {
+        let rw = db.rw_transaction().unwrap();
+        f(&rw);
+        rw.commit().unwrap();
+    };
+    write(&|rw| rw.insert(item(1, "a")).unwrap());
+    write(&|rw| rw.insert(item(2, "b")).unwrap());
+    write(&|rw| rw.update(item(3, "x"), item(3, "y")).unwrap());
+    write(&|rw| rw.update(item(1, "a"), item(1, "ab")).unwrap());
+    write(&|rw| drop(rw.remove(item(2, "b")).unwrap()));
+    write(&|rw| rw.update(item(3, "y"), item(3, "z")).unwrap());
+    write(&|rw| rw.update(item(1, "ab"), item(1, "abc")).unwrap());
+
+    // Nothing is sent before the end of the window
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(recv.try_recv().is_err());
+
+    // One event per key, from the value before the window to the latest value
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    let events = watch::recv_batch(&mut recv, 10).await;
+    assert_eq!(events.len(), 2);
+    let Event::Insert(insert) = &events[0] else {
+        panic!("wrong event {:?}", events[0]);
+    };
+    assert_eq!(insert.inner::<ItemB>().unwrap(), item(1, "abc"));
+    let Event::Update(update) = &events[1] else {
+        panic!("wrong event {:?}", events[1]);
+    };
+    assert_eq!(update.inner_old::<ItemB>().unwrap(), item(3, "x"));
+    assert_eq!(update.inner_new::<ItemB>().unwrap(), item(3, "z"));
+    assert!(recv.try_recv().is_err());
+
+    // The next window starts with the next event
+    let rw = db.rw_transaction().unwrap();
+    rw.remove(item(3, "z")).unwrap();
+    rw.commit().unwrap();
+    tokio::time::sleep(Duration::from_millis(110)).await;
+    assert!(matches!(recv.try_recv(), Ok(Event::Delete(_))));
+
+    // The pending events are sent when the database is dropped
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(item(4, "d")).unwrap();
+    rw.commit().unwrap();
+    drop(db);
+    assert!(matches!(recv.recv().await, Some(Event::Insert(_))));
+    assert!(recv.recv().await.is_none());
+}
+
+#[test]
+fn watch_debounce_without_runtime() {
+    let mut models = Models::new();
+    models.define::<ItemB>().unwrap();
+    let db = Builder::new().create_in_memory(&models).unwrap();
+
+    let result = db
+        .watch()
+        .debounce(Duration::from_millis(100))
+        .scan()
+        .primary()
+        .all::<ItemB>();
+    assert!(matches!(result, Err(db_type::Error::TokioRuntimeNotFound)));
+}
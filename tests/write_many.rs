@@ -0,0 +1,192 @@
+use native_db::transaction::{WriteManyReport, WritePolicy};
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use shortcut_assert_fs::TmpFs;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[native_model(id = 1, version = 1)]
+#[native_db]
+struct Item {
+    #[primary_key]
+    id: u32,
+    #[secondary_key(unique)]
+    name: String,
+}
+
+fn item(id: u32, name: &str) -> Item {
+    Item {
+        id,
+        name: name.to_string(),
+    }
+}
+
+fn items(db: &Database) -> Vec<Item> {
+    let r = db.r_transaction().unwrap();
+    r.scan()
+        .primary()
+        .unwrap()
+        .all()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn create_db<'a>(tf: &TmpFs, models: &'a Models) -> Database<'a> {
+    let db = Builder::new()
+        .create(models, tf.path("test").as_std_path())
+        .unwrap();
+    let rw = db.rw_transaction().unwrap();
+    rw.insert(item(1, "one")).unwrap();
+    rw.insert(item(2, "two")).unwrap();
+    rw.commit().unwrap();
+    db
+}
+
+#[test]
+fn test_write_many_insert_fail_on_dup() {
+    let tf = TmpFs::new().unwrap();
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = create_db(&tf, &models);
+
+    let rw = db.rw_transaction().unwrap();
+    let report = rw
+        .write_many(
+            vec![item(3, "three"), item(4, "four")],
+            WritePolicy::InsertFailOnDup,
+        )
+        .unwrap();
+    assert_eq!(
+        report,
+        WriteManyReport {
+            inserted: 2,
+            updated: 0,
+            skipped: 0,
+        }
+    );
+    rw.commit().unwrap();
+    assert_eq!(items(&db).len(), 4);
+
+    // A duplicate primary key fails
+    let rw = db.rw_transaction().unwrap();
+    let result = rw.write_many(
+        vec![item(5, "five"), item(1, "one bis")],
+        WritePolicy::InsertFailOnDup,
+    );
+    assert!(matches!(result, Err(db_type::Error::DuplicateKey { .. })));
+    rw.abort().unwrap();
+    assert_eq!(items(&db).len(), 4);
+}
+
+#[test]
+fn test_write_many_upsert() {
+    let tf = TmpFs::new().unwrap();
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = create_db(&tf, &models);
+
+    #[allow(unused_mut)]
+    let (mut recv, _) = db.watch().scan().primary().all::<Item>().unwrap();
+
+    let rw = db.rw_transaction().unwrap();
+    let report = rw
+        .upsert_many(vec![item(1, "one bis"), item(3, "three")])
+        .unwrap();
+    assert_eq!(
+        report,
+        WriteManyReport {
+            inserted: 1,
+            updated: 1,
+            skipped: 0,
+        }
+    );
+    rw.commit().unwrap();
+
+    assert_eq!(
+        items(&db),
+        vec![item(1, "one bis"), item(2, "two"), item(3, "three")]
+    );
+
+    // The secondary keys follow the updated values
+    let r = db.r_transaction().unwrap();
+    let updated: Option<Item> = r.get().secondary(ItemKey::name, "one bis").unwrap();
+    assert_eq!(updated, Some(item(1, "one bis")));
+    let old: Option<Item> = r.get().secondary(ItemKey::name, "one").unwrap();
+    assert_eq!(old, None);
+    drop(r);
+
+    // The update is sent with its old value
+    let events = [recv.try_recv().unwrap(), recv.try_recv().unwrap()];
+    assert!(recv.try_recv().is_err());
+    let update = events
+        .iter()
+        .find_map(|event| match event {
+            watch::Event::Update(update) => Some(update),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(update.inner_old::<Item>().unwrap(), item(1, "one"));
+    assert_eq!(update.inner_new::<Item>().unwrap(), item(1, "one bis"));
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, watch::Event::Insert(_))));
+
+    // A conflict on a unique secondary key still fails
+    let rw = db.rw_transaction().unwrap();
+    let result = rw.upsert_many(vec![item(2, "three")]);
+    assert!(matches!(result, Err(db_type::Error::DuplicateKey { .. })));
+}
+
+#[test]
+fn test_write_many_insert_or_ignore() {
+    let tf = TmpFs::new().unwrap();
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = create_db(&tf, &models);
+
+    let rw = db.rw_transaction().unwrap();
+    let report = rw
+        .write_many(
+            vec![item(1, "one bis"), item(3, "three"), item(2, "two bis")],
+            WritePolicy::InsertOrIgnore,
+        )
+        .unwrap();
+    assert_eq!(
+        report,
+        WriteManyReport {
+            inserted: 1,
+            updated: 0,
+            skipped: 2,
+        }
+    );
+    rw.commit().unwrap();
+
+    assert_eq!(
+        items(&db),
+        vec![item(1, "one"), item(2, "two"), item(3, "three")]
+    );
+}
+
+#[test]
+fn test_write_many_bulk_events() {
+    let tf = TmpFs::new().unwrap();
+    let mut models = Models::new();
+    models.define::<Item>().unwrap();
+    let db = create_db(&tf, &models);
+
+    #[allow(unused_mut)]
+    let (mut recv, _) = db.watch().scan().primary().all::<Item>().unwrap();
+
+    let mut rw = db.rw_transaction().unwrap();
+    rw.set_bulk_events(true);
+    rw.upsert_many(vec![item(1, "one bis"), item(3, "three")])
+        .unwrap();
+    let info = rw.commit_with_info().unwrap();
+    assert_eq!((info.inserts, info.updates), (1, 1));
+
+    // A single event summarizes the inserted and updated values
+    let event = recv.try_recv().unwrap();
+    assert!(matches!(event, watch::Event::BulkInsert { ref table, count: 2 } if table == "1_1_id"));
+    assert!(recv.try_recv().is_err());
+}